@@ -2,104 +2,128 @@
 /// that is completed by the scheduler in 1 second.
 ///
 /// While this is a trivial example, it's easy to see how
-/// `Future`s integrate nicely with Actors.
-///
-/// For those coming from the Akka/Scala ecosystem, a oneshot
-/// is akin to a `scala.concurrent.Promise`.
+/// `Future`s integrate nicely with Actors: futures are spawned
+/// onto a dispatcher, and their output is converted into a
+/// message by watching the returned `JoinHandle`.
 ///
 /// At the core of the ActorSystem is a dispatcher that implements
 /// a work-stealing scheduler that runs futures and does messaging.
-extern crate futures;
 extern crate pantomime;
 
-use futures::*;
-use pantomime::pattern::{Ask, PipeTo};
 use pantomime::prelude::*;
-use std::{io, process, time};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::{io, time};
 
 const DELAY_MS: u64 = 1000;
 
-fn slow_double(
-    context: &ActorContext<Msg>,
-    value: usize,
-) -> impl Future<Item = usize, Error = Canceled> {
-    let (c, p) = oneshot::<usize>();
+#[derive(Default)]
+struct DelayedState {
+    value: Option<usize>,
+    waker: Option<Waker>,
+}
+
+struct Delayed {
+    state: Arc<Mutex<DelayedState>>,
+}
+
+impl Future for Delayed {
+    type Output = usize;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<usize> {
+        let mut state = self.state.lock().unwrap();
+
+        match state.value.take() {
+            Some(value) => Poll::Ready(value),
 
-    context.schedule_thunk(time::Duration::from_millis(DELAY_MS), move || {
-        let _ = c.send(value * 2);
-    });
+            None => {
+                state.waker = Some(cx.waker().clone());
 
-    p
+                Poll::Pending
+            }
+        }
+    }
+}
+
+fn slow_double(context: &ActorContext<Msg>, value: usize) -> Delayed {
+    let state = Arc::new(Mutex::new(DelayedState::default()));
+
+    {
+        let state = state.clone();
+
+        context.schedule_thunk(time::Duration::from_millis(DELAY_MS), move || {
+            let mut state = state.lock().unwrap();
+
+            state.value = Some(value * 2);
+
+            if let Some(waker) = state.waker.take() {
+                waker.wake();
+            }
+        });
+    }
+
+    Delayed { state }
 }
 
 enum Msg {
     Double(usize),
-    SendDouble(usize, ActorRef<usize>),
-    ReceivedDouble(usize),
+    Failed,
 }
 
 struct MyActor;
 
-impl Actor<Msg> for MyActor {
+impl Actor for MyActor {
+    type Msg = Msg;
+
     fn receive(&mut self, message: Msg, context: &mut ActorContext<Msg>) {
         match message {
             Msg::Double(value) => {
                 println!("result: {}", value);
 
                 if value > 4096 {
-                    process::exit(0);
-                }
+                    context.stop();
+                } else {
+                    let doubled = slow_double(context, value);
 
-                context.dispatcher().spawn_future(
-                    slow_double(&context, value)
-                        .then(|r| future::ok(Msg::Double(r.ok().unwrap_or(0))))
-                        .pipe_to(context.actor_ref().clone()),
-                );
-            }
+                    let handle = context
+                        .dispatcher()
+                        .spawn_future(async move { doubled.await });
 
-            Msg::SendDouble(value, reply_to) => {
-                println!("send a double!");
-                reply_to.tell(value * 2);
+                    context.watch(handle, |result: Result<usize, FailureReason>| {
+                        result.map(Msg::Double).unwrap_or(Msg::Failed)
+                    });
+                }
             }
 
-            Msg::ReceivedDouble(value) => {
-                println!("received a doubling of {}", value);
+            Msg::Failed => {
+                context.stop();
             }
         }
     }
 
     fn receive_signal(&mut self, signal: Signal, context: &mut ActorContext<Msg>) {
         if let Signal::Started = signal {
-            context.dispatcher().clone().spawn_future(
-                context
-                    .actor_ref()
-                    .clone()
-                    .ask(
-                        context,
-                        |reply_to| Msg::SendDouble(42, reply_to),
-                        time::Duration::from_secs(10),
-                    )
-                    .then(|r| future::ok(Msg::ReceivedDouble(r.ok().unwrap_or(0))))
-                    .pipe_to(context.actor_ref().clone()),
-            );
+            context.actor_ref().tell(Msg::Double(1));
         }
     }
 }
 
-struct MyReaper;
+struct Reaper;
+
+impl Actor for Reaper {
+    type Msg = ();
 
-impl Actor<()> for MyReaper {
     fn receive(&mut self, _: (), _: &mut ActorContext<()>) {}
 
-    fn receive_signal(&mut self, signal: Signal, ctx: &mut ActorContext<()>) {
+    fn receive_signal(&mut self, signal: Signal, context: &mut ActorContext<()>) {
         if let Signal::Started = signal {
-            let my_actor = ctx.spawn(MyActor);
-
-            my_actor.tell(Msg::Double(2));
+            context.spawn(MyActor);
         }
     }
 }
 
 fn main() -> io::Result<()> {
-    ActorSystem::new().spawn(MyReaper)
+    ActorSystem::new().spawn(Reaper)
 }
@@ -7,6 +7,8 @@ use downcast_rs::Downcast;
 use std::cell::RefCell;
 use std::collections::{HashMap, VecDeque};
 use std::error::Error;
+use std::fmt;
+use std::future::Future;
 use std::mem;
 use std::panic;
 use std::rc::Rc;
@@ -25,6 +27,7 @@ pub enum SystemMsg {
     ActorStopped(usize, StopReason),
     Watch(SystemActorRef),
     SendDelivery(String, usize),
+    ReceiveCompleted(bool, Option<FailureReason>),
 
     #[cfg(feature = "posix-signals-support")]
     PosixSignal(i32),
@@ -66,11 +69,29 @@ impl FailureError {
     }
 }
 
+impl fmt::Display for FailureError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.error {
+            Some(ref error) => error.fmt(f),
+            None => write!(f, "unknown error"),
+        }
+    }
+}
+
 pub enum FailureReason {
     Panicked,
     Errored(FailureError),
 }
 
+impl fmt::Display for FailureReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FailureReason::Panicked => write!(f, "panicked"),
+            FailureReason::Errored(error) => error.fmt(f),
+        }
+    }
+}
+
 pub enum StopReason {
     /// Signifies that the actor was stopped normally.
     Stopped,
@@ -149,6 +170,7 @@ where
     Msg: Send,
 {
     pub(in crate::actor) actor_ref: ActorRef<Msg>,
+    pub(in crate::actor) awaiting: bool,
     pub(in crate::actor) children: HashMap<usize, SystemActorRef>,
     pub(in crate::actor) deliveries: HashMap<String, Delivery<Msg>>,
     pub(in crate::actor) dispatcher: Dispatcher,
@@ -164,7 +186,7 @@ where
 
     #[allow(clippy::type_complexity)]
     pub(in crate::actor) watching:
-        HashMap<usize, Vec<Box<dyn Fn(StopReason) -> Msg + 'static + Send>>>,
+        HashMap<usize, Vec<Box<dyn Fn(StopReason) -> Option<Msg> + 'static + Send>>>,
 
     #[cfg(feature = "posix-signals-support")]
     pub(in crate::actor) watching_posix_signals:
@@ -229,6 +251,7 @@ impl<'a> ActorSpawnContext<'a> {
             actor,
             context: ActorContext {
                 actor_ref: empty_ref,
+                awaiting: false,
                 children: HashMap::new(),
                 deliveries: HashMap::new(),
                 dispatcher: dispatcher.clone(),
@@ -391,7 +414,13 @@ where
     where
         E: 'static + Send,
     {
-        self.pending_stop = Some(Some(FailureReason::Errored(FailureError::new(reason))));
+        self.fail_with_reason(FailureReason::Errored(FailureError::new(reason)));
+    }
+
+    /// Asynchronously fail this actor with the supplied reason, as
+    /// with `fail`.
+    pub(crate) fn fail_with_reason(&mut self, reason: FailureReason) {
+        self.pending_stop = Some(Some(reason));
     }
 
     /// Asynchronously stop this actor. No other messages will
@@ -544,10 +573,8 @@ where
     /// This is typically used to watch the following:
     ///
     /// - Actors (`ActorRef`)
-    /// - Streams (`StreamResult`)
-    ///
-    /// When the std::future modules are fully stable, this will
-    /// also be used to watch futures.
+    /// - Streams (`StreamComplete`)
+    /// - Futures (`JoinHandle`, via `Dispatcher::spawn_future`)
     pub fn watch<W, R, F: Fn(R) -> Msg>(&mut self, watchable: W, convert: F)
     where
         Self: Watchable<W, R, Msg, F>,
@@ -555,6 +582,41 @@ where
         self.perform_watch(watchable, convert);
     }
 
+    /// Run the supplied future on this actor's dispatcher, converting
+    /// its output (or `FailureReason::Panicked` if it panics) into a
+    /// message that is sent to this actor.
+    ///
+    /// If the actor has stopped by the time the future completes,
+    /// the message is discarded.
+    pub fn pipe_to_self<F, C>(&self, future: F, convert: C)
+    where
+        F: 'static + Future + Send,
+        F::Output: 'static + Send,
+        C: 'static + FnOnce(Result<F::Output, FailureReason>) -> Msg + Send,
+    {
+        self.pipe_to(future, &self.actor_ref, convert);
+    }
+
+    /// Run the supplied future on this actor's dispatcher, converting
+    /// its output (or `FailureReason::Panicked` if it panics) into a
+    /// message that is sent to the supplied actor.
+    ///
+    /// If the recipient has stopped by the time the future completes,
+    /// the message is discarded.
+    pub fn pipe_to<F, N, C>(&self, future: F, actor_ref: &ActorRef<N>, convert: C)
+    where
+        F: 'static + Future + Send,
+        F::Output: 'static + Send,
+        N: 'static + Send,
+        C: 'static + FnOnce(Result<F::Output, FailureReason>) -> N + Send,
+    {
+        let actor_ref = actor_ref.clone();
+
+        self.dispatcher
+            .spawn_future(future)
+            .on_complete(move |result| actor_ref.tell(convert(result)));
+    }
+
     pub(crate) fn system_context(&self) -> &ActorSystemContext {
         &self.system_context
     }
@@ -587,6 +649,19 @@ where
     where
         N: 'static + Send,
         F: 'static + Send,
+    {
+        self.watch_with_option(actor_ref, move |reason| Some(msg(reason)));
+    }
+
+    /// Watch the supplied actor, receiving the message that the supplied
+    /// function returns when it stops, if any.
+    pub(crate) fn watch_with_option<N, F: Fn(StopReason) -> Option<Msg>>(
+        &mut self,
+        actor_ref: &ActorRef<N>,
+        msg: F,
+    ) where
+        N: 'static + Send,
+        F: 'static + Send,
     {
         let actor_id = actor_ref.id();
         let new = !self.watching.contains_key(&actor_id);
@@ -611,7 +686,7 @@ where
         self.watching
             .entry(actor_id)
             .or_insert_with(Vec::new)
-            .push(Box::new(msg));
+            .push(Box::new(move |reason| Some(msg(reason))));
 
         if new {
             system_ref.tell_system(SystemMsg::Watch(self.actor_ref().system_ref()));
@@ -858,12 +933,12 @@ where
 
     fn receive(&mut self, msg: Msg) {
         match self.context.state {
-            SpawnedActorState::Active => {
+            SpawnedActorState::Active if !self.context.awaiting => {
                 self.actor.receive(msg, &mut self.context);
                 self.check_pending_stop();
             }
 
-            SpawnedActorState::WaitingForStop => {
+            SpawnedActorState::Active | SpawnedActorState::WaitingForStop => {
                 self.stash.push_back(Envelope::Msg(msg));
             }
 
@@ -990,6 +1065,40 @@ where
 
             (SpawnedActorState::Stopped, _) => {}
 
+            (SpawnedActorState::WaitingForStop, SystemMsg::ReceiveCompleted(awaited, failure)) => {
+                if awaited {
+                    self.context.awaiting = false;
+                }
+
+                // we're already failing, but the actor may resume, in which
+                // case this failure must still be handled
+
+                if let Some(reason) = failure {
+                    self.stash
+                        .push_front(Envelope::SystemMsg(SystemMsg::ReceiveCompleted(
+                            false,
+                            Some(reason),
+                        )));
+                }
+            }
+
+            (SpawnedActorState::Active, SystemMsg::ReceiveCompleted(awaited, failure)) => {
+                if awaited {
+                    self.context.awaiting = false;
+                }
+
+                match failure {
+                    Some(reason) => {
+                        self.context.pending_stop = Some(Some(reason));
+                        self.check_pending_stop();
+                    }
+
+                    None => {
+                        self.unstash_all();
+                    }
+                }
+            }
+
             (_, SystemMsg::Watch(watcher)) => {
                 self.watchers.push(watcher);
             }
@@ -1027,11 +1136,15 @@ where
                     for msg in msgs.drain(..) {
                         match reason {
                             StopReason::Failed => {
-                                self.receive(msg(StopReason::Failed));
+                                if let Some(msg) = msg(StopReason::Failed) {
+                                    self.receive(msg);
+                                }
                             }
 
                             StopReason::Stopped => {
-                                self.receive(msg(StopReason::Stopped));
+                                if let Some(msg) = msg(StopReason::Stopped) {
+                                    self.receive(msg);
+                                }
                             }
                         }
                     }
@@ -1045,17 +1158,11 @@ where
 
             #[cfg(feature = "posix-signals-support")]
             (_, SystemMsg::PosixSignal(signal)) => {
-                // @FIXME should we support numeric signals?
-
                 let mut messages = Vec::new();
 
-                for converter in self.context.watching_posix_signals.iter() {
-                    if signal == PosixSignal::SIGHUP as i32 {
-                        messages.push(converter(PosixSignal::SIGHUP));
-                    } else if signal == PosixSignal::SIGINT as i32 {
-                        messages.push(converter(PosixSignal::SIGINT));
-                    } else if signal == PosixSignal::SIGTERM as i32 {
-                        messages.push(converter(PosixSignal::SIGTERM));
+                if let Some(signal) = PosixSignal::from_i32(signal) {
+                    for converter in self.context.watching_posix_signals.iter() {
+                        messages.push(converter(signal));
                     }
                 }
 
@@ -1081,6 +1188,10 @@ where
 
                 // @TODO log this? shouldnt happen
             }
+
+            (_, SystemMsg::ReceiveCompleted(..)) => {
+                // we're stopping, so the outcome is no longer relevant
+            }
         }
     }
 
@@ -1456,6 +1567,10 @@ where
                 SpawnedActorState::WaitingForStop => {
                     return;
                 }*/
+                SpawnedActorState::Active if self.context.awaiting => {
+                    return;
+                }
+
                 _ => match self.stash.pop_front() {
                     Some(Envelope::Msg(msg)) => {
                         self.receive(msg);
@@ -0,0 +1,152 @@
+use super::actor_ref::{Envelope, SystemMsg};
+use crate::actor::*;
+use crate::dispatcher::Dispatcher;
+use crate::mailbox::Mailbox;
+use std::future::Future;
+use std::pin::Pin;
+
+/// The future returned by `AsyncActor::receive`.
+pub type ReceiveFuture = Pin<Box<dyn Future<Output = Result<(), FailureError>> + Send>>;
+
+/// An actor whose message handler is asynchronous.
+///
+/// The future that is returned by `receive` is driven on the actor's
+/// dispatcher. By default, the next message is not delivered until it
+/// has completed, preserving the guarantee that an actor processes a
+/// single message at a time. Actors that can handle messages while
+/// earlier futures are still running can opt into this by returning
+/// `true` from `reentrant`.
+///
+/// The future is `'static`, so it cannot borrow the actor or the
+/// context. Any state that it needs must be cloned or moved into it
+/// (e.g. behind an `Arc`), and results that should update the actor
+/// are sent back to it as messages via a cloned `ActorRef`.
+///
+/// If the future resolves to an error or panics, the actor fails and
+/// `handle_failure` is invoked, as is the case with synchronous actors.
+///
+/// Any type that implements `AsyncActor` is also an `Actor`, via a
+/// blanket implementation, so it is spawned and messaged like any other
+/// actor. Consequently, a type implements one of `Actor` or `AsyncActor`,
+/// never both. Its dispatcher, mailbox and throughput are configured via
+/// the same hooks as an `Actor`, which the blanket implementation forwards.
+pub trait AsyncActor: Send
+where
+    Self::Msg: Send,
+{
+    type Msg;
+
+    fn config_dispatcher(&self, ctx: &ActorSystemContext) -> Option<Dispatcher> {
+        {
+            let _ = ctx;
+        }
+
+        None
+    }
+
+    fn config_mailbox(&self, ctx: &ActorSystemContext) -> Option<Mailbox<Envelope<Self::Msg>>> {
+        {
+            let _ = ctx;
+        }
+
+        None
+    }
+
+    fn config_throughput(&self, ctx: &ActorSystemContext) -> Option<usize> {
+        {
+            let _ = ctx;
+        }
+
+        None
+    }
+
+    fn reentrant(&self) -> bool {
+        false
+    }
+
+    fn handle_failure(
+        &mut self,
+        reason: FailureReason,
+        ctx: &mut ActorContext<Self::Msg>,
+    ) -> FailureAction {
+        {
+            let _ = ctx;
+        }
+
+        FailureAction::Fail(reason)
+    }
+
+    fn receive_signal(&mut self, sig: Signal, ctx: &mut ActorContext<Self::Msg>) {
+        {
+            let _ = sig;
+        }
+        {
+            let _ = ctx;
+        }
+    }
+
+    fn receive(&mut self, msg: Self::Msg, context: &mut ActorContext<Self::Msg>) -> ReceiveFuture;
+}
+
+impl<A: AsyncActor> Actor for A
+where
+    A::Msg: 'static + Send,
+{
+    type Msg = A::Msg;
+
+    fn config_dispatcher(&self, ctx: &ActorSystemContext) -> Option<Dispatcher> {
+        AsyncActor::config_dispatcher(self, ctx)
+    }
+
+    fn config_mailbox(&self, ctx: &ActorSystemContext) -> Option<Mailbox<Envelope<Self::Msg>>> {
+        AsyncActor::config_mailbox(self, ctx)
+    }
+
+    fn config_throughput(&self, ctx: &ActorSystemContext) -> Option<usize> {
+        AsyncActor::config_throughput(self, ctx)
+    }
+
+    fn handle_failure(
+        &mut self,
+        reason: FailureReason,
+        ctx: &mut ActorContext<Self::Msg>,
+    ) -> FailureAction {
+        AsyncActor::handle_failure(self, reason, ctx)
+    }
+
+    fn receive_signal(&mut self, sig: Signal, ctx: &mut ActorContext<Self::Msg>) {
+        AsyncActor::receive_signal(self, sig, ctx);
+    }
+
+    fn receive(&mut self, msg: Self::Msg, context: &mut ActorContext<Self::Msg>) {
+        let future = AsyncActor::receive(self, msg, context);
+
+        context.await_receive(future, self.reentrant());
+    }
+}
+
+impl<Msg> ActorContext<Msg>
+where
+    Msg: 'static + Send,
+{
+    fn await_receive(&mut self, future: ReceiveFuture, reentrant: bool) {
+        let system_ref = self.actor_ref.system_ref();
+        let awaited = !reentrant;
+
+        if awaited {
+            self.awaiting = true;
+        }
+
+        self.dispatcher
+            .spawn_future(future)
+            .on_complete(move |result| {
+                let failure = match result {
+                    Ok(Ok(())) => None,
+                    Ok(Err(error)) => Some(FailureReason::Errored(error)),
+                    Err(reason) => Some(reason),
+                };
+
+                system_ref.tell_system(SystemMsg::ReceiveCompleted(awaited, failure));
+            });
+    }
+}
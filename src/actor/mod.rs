@@ -1,6 +1,7 @@
 //! Core messaging
 
 mod actor_ref;
+mod async_actor;
 mod probe;
 mod system;
 
@@ -15,6 +16,7 @@ pub use self::actor_ref::{
     Actor, ActorContext, ActorRef, ActorSpawnContext, FailureAction, FailureError, FailureReason,
     Signal, Spawnable, StopReason, SystemActorRef, Watchable,
 };
+pub use self::async_actor::{AsyncActor, ReceiveFuture};
 pub use self::probe::{Probe, SpawnProbe};
 pub use self::system::{ActiveActorSystem, ActorSystem, ActorSystemContext, SubscriptionEvent};
 
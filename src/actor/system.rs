@@ -1,6 +1,7 @@
 use super::*;
 use crate::dispatcher::{
-    Dispatcher, DispatcherLogic, SingleThreadedDispatcher, WorkStealingDispatcher,
+    Dispatcher, DispatcherLogic, PinnedDispatcher, RejectionPolicy, SingleThreadedDispatcher,
+    ThreadPoolDispatcher, WorkStealingDispatcher,
 };
 use crate::mailbox::{
     CrossbeamChannelMailboxLogic, CrossbeamSegQueueMailboxLogic, VecDequeMailboxLogic,
@@ -58,6 +59,25 @@ impl ActorSystemContext {
         &self.inner.dispatcher
     }
 
+    /// Obtain a dispatcher that was declared in the configuration
+    /// (via `PANTOMIME_DISPATCHERS`) by its name.
+    ///
+    /// Actors can be assigned to a named dispatcher by overriding
+    /// `Actor::config_dispatcher`, which is useful for isolating
+    /// blocking or compute heavy work from the default dispatcher.
+    ///
+    /// If the dispatcher is configured with the pinned logic, a new
+    /// dispatcher with its own thread is returned each time.
+    pub fn named_dispatcher(&self, name: &str) -> Option<Dispatcher> {
+        self.inner
+            .dispatchers
+            .get(name)
+            .map(|dispatcher| match dispatcher {
+                NamedDispatcher::Shared(dispatcher) => dispatcher.clone(),
+                NamedDispatcher::Pinned => Dispatcher::new(PinnedDispatcher::new()),
+            })
+    }
+
     pub fn stop(&self) {
         self.tell_reaper_monitor(ReaperMsg::Stop);
     }
@@ -113,6 +133,7 @@ impl ActorSystemContext {
             actor,
             context: ActorContext {
                 actor_ref: empty_ref.clone(),
+                awaiting: false,
                 children: HashMap::new(),
                 deliveries: HashMap::new(),
                 dispatcher: dispatcher.clone(),
@@ -166,7 +187,11 @@ impl ActorSystemContext {
     }
 
     pub(in crate::actor) fn new_actor_dispatcher(&self) -> Dispatcher {
-        self.inner.dispatcher.clone()
+        if self.inner.config.default_dispatcher_logic == "pinned" {
+            Dispatcher::new(PinnedDispatcher::new())
+        } else {
+            self.inner.dispatcher.clone()
+        }
     }
 
     pub(in crate::actor) fn new_actor_mailbox<Msg>(&self) -> Mailbox<Msg>
@@ -225,12 +250,18 @@ impl Clone for ActorSystemContext {
 struct ActorSystemContextInner {
     config: ActorSystemConfig,
     dispatcher: Dispatcher,
+    dispatchers: HashMap<String, NamedDispatcher>,
     next_actor_id: AtomicUsize,
     sender: channel::Sender<ActorSystemMsg>,
     sender_readiness: Option<SetReadiness>,
     ticker: Option<ActiveTicker>,
 }
 
+enum NamedDispatcher {
+    Shared(Dispatcher),
+    Pinned,
+}
+
 pub struct ActiveActorSystem {
     context: ActorSystemContext,
     receiver: channel::Receiver<ActorSystemMsg>,
@@ -365,6 +396,12 @@ impl ActiveActorSystem {
                             ticker.stop();
                         }
 
+                        for dispatcher in self.context.inner.dispatchers.values() {
+                            if let NamedDispatcher::Shared(ref dispatcher) = dispatcher {
+                                dispatcher.clone().shutdown();
+                            }
+                        }
+
                         if failed.load(Ordering::Acquire) && exit_code == 0 {
                             exit_code = 1;
                         }
@@ -580,37 +617,30 @@ impl ActorSystem {
         });
 
         let config = ActorSystemConfig::new(&self.config.take().unwrap_or_default())?;
-        let _ = self.validate_default_dispatcher_logic(&config);
-        let _ = self.validate_default_mailbox_logic(&config);
-        let failed = Arc::new(AtomicBool::new(false));
+        self.validate_dispatcher_logic(&config.default_dispatcher())?;
 
-        let dispatcher_logic: Box<dyn DispatcherLogic + Sync + Send> =
-            match config.default_dispatcher_logic.as_ref() {
-                "work-stealing" => {
-                    let default_dispatcher_parallelism = cmp::min(
-                        config.default_dispatcher_logic_work_stealing_parallelism_max,
-                        cmp::max(
-                            config.default_dispatcher_logic_work_stealing_parallelism_min,
-                            (config.num_cpus as f32
-                                * config.default_dispatcher_logic_work_stealing_parallelism_factor)
-                                as usize,
-                        ),
-                    );
+        for dispatcher_config in config.dispatchers.iter() {
+            self.validate_dispatcher_logic(dispatcher_config)?;
+        }
 
-                    Box::new(WorkStealingDispatcher::new(
-                        default_dispatcher_parallelism,
-                        config.default_dispatcher_logic_work_stealing_task_queue_fifo,
-                    ))
-                }
+        let _ = self.validate_default_mailbox_logic(&config);
+        let failed = Arc::new(AtomicBool::new(false));
 
-                "single-threaded" => Box::new(SingleThreadedDispatcher::new()),
+        let dispatcher = Self::new_dispatcher(&config, &config.default_dispatcher());
 
-                other => {
-                    panic!(format!("pantomime bug: unknown dispatcher logic {}", other));
-                }
-            };
+        let dispatchers = config
+            .dispatchers
+            .iter()
+            .map(|dispatcher_config| {
+                let dispatcher = if dispatcher_config.logic == "pinned" {
+                    NamedDispatcher::Pinned
+                } else {
+                    NamedDispatcher::Shared(Self::new_dispatcher(&config, dispatcher_config))
+                };
 
-        let dispatcher = Dispatcher::new_boxed(dispatcher_logic);
+                (dispatcher_config.name.clone(), dispatcher)
+            })
+            .collect();
 
         let (sender, receiver) = channel::unbounded();
 
@@ -624,6 +654,7 @@ impl ActorSystem {
             inner: Arc::new(ActorSystemContextInner {
                 config,
                 dispatcher,
+                dispatchers,
                 next_actor_id: AtomicUsize::new(100), // we reserve < 100 as an internal id, i.e. special. in practice, we currently only need 2
                 sender,
                 sender_readiness: Some(sender_readiness),
@@ -648,13 +679,101 @@ impl ActorSystem {
         }
     }
 
-    fn validate_default_dispatcher_logic(&self, config: &ActorSystemConfig) -> Result<(), Error> {
-        match config.default_dispatcher_logic.as_str() {
+    fn new_dispatcher(
+        config: &ActorSystemConfig,
+        dispatcher_config: &DispatcherConfig,
+    ) -> Dispatcher {
+        let dispatcher_logic: Box<dyn DispatcherLogic + Sync + Send> =
+            match dispatcher_config.logic.as_ref() {
+                "work-stealing" if dispatcher_config.work_stealing_elastic => {
+                    Box::new(WorkStealingDispatcher::elastic(
+                        dispatcher_config.work_stealing_parallelism_min,
+                        dispatcher_config.work_stealing_parallelism_max,
+                        time::Duration::from_millis(dispatcher_config.work_stealing_keep_alive_ms),
+                        dispatcher_config.work_stealing_task_queue_fifo,
+                    ))
+                }
+
+                "work-stealing" => {
+                    let parallelism = cmp::min(
+                        dispatcher_config.work_stealing_parallelism_max,
+                        cmp::max(
+                            dispatcher_config.work_stealing_parallelism_min,
+                            (config.num_cpus as f32
+                                * dispatcher_config.work_stealing_parallelism_factor)
+                                as usize,
+                        ),
+                    );
+
+                    Box::new(WorkStealingDispatcher::new(
+                        parallelism,
+                        dispatcher_config.work_stealing_task_queue_fifo,
+                    ))
+                }
+
+                "single-threaded" => Box::new(SingleThreadedDispatcher::new()),
+
+                "thread-pool" => {
+                    if dispatcher_config.thread_pool_queue_capacity == 0 {
+                        Box::new(ThreadPoolDispatcher::new(
+                            dispatcher_config.thread_pool_size,
+                        ))
+                    } else {
+                        Box::new(ThreadPoolDispatcher::with_bounded_queue(
+                            dispatcher_config.thread_pool_size,
+                            dispatcher_config.thread_pool_queue_capacity,
+                            Self::rejection_policy(dispatcher_config),
+                        ))
+                    }
+                }
+
+                "pinned" => Box::new(PinnedDispatcher::new()),
+
+                other => {
+                    panic!(format!("pantomime bug: unknown dispatcher logic {}", other));
+                }
+            };
+
+        Dispatcher::new_boxed(dispatcher_logic)
+    }
+
+    fn rejection_policy(dispatcher_config: &DispatcherConfig) -> RejectionPolicy {
+        match dispatcher_config.thread_pool_rejection_policy.as_str() {
+            "block" => RejectionPolicy::Block,
+            "caller-runs" => RejectionPolicy::CallerRuns,
+            other => {
+                panic!("pantomime bug: unknown rejection policy {}", other);
+            }
+        }
+    }
+
+    fn validate_dispatcher_logic(&self, config: &DispatcherConfig) -> Result<(), Error> {
+        match config.logic.as_str() {
             "work-stealing" => Ok(()),
             "single-threaded" => Ok(()),
+            "pinned" => Ok(()),
+            "thread-pool" => match config.thread_pool_rejection_policy.as_str() {
+                "block" => Ok(()),
+                "caller-runs" => Ok(()),
+
+                // configured dispatchers execute actors, whose work must
+                // never be discarded
+                "discard" => Err(Error::new(
+                    ErrorKind::Other,
+                    format!(
+                        "discard rejection policy cannot be used for {}, as it executes actors",
+                        config.name
+                    ),
+                )),
+
+                other => Err(Error::new(
+                    ErrorKind::Other,
+                    format!("unknown rejection policy for {}: {}", config.name, other),
+                )),
+            },
             other => Err(Error::new(
                 ErrorKind::Other,
-                format!("unknown dispatcher logic: {}", other),
+                format!("unknown dispatcher logic for {}: {}", config.name, other),
             )),
         }
     }
@@ -0,0 +1,248 @@
+use crate::actor::*;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::thread;
+use std::time::Duration;
+
+/// A future that completes after it has been woken from another thread.
+struct Delay(bool);
+
+impl Future for Delay {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+
+            let waker = cx.waker().clone();
+
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(10));
+
+                waker.wake();
+            });
+
+            Poll::Pending
+        }
+    }
+}
+
+enum MyMsg {
+    Work(usize),
+    Fail,
+    Panic,
+}
+
+struct MyActor {
+    reentrant: bool,
+    running: Arc<AtomicUsize>,
+    max_running: Arc<AtomicUsize>,
+    actor_ref: ActorRef<usize>,
+}
+
+impl AsyncActor for MyActor {
+    type Msg = MyMsg;
+
+    fn reentrant(&self) -> bool {
+        self.reentrant
+    }
+
+    fn handle_failure(&mut self, _: FailureReason, _: &mut ActorContext<MyMsg>) -> FailureAction {
+        self.actor_ref.tell(0);
+
+        FailureAction::Resume
+    }
+
+    fn receive(&mut self, msg: MyMsg, _: &mut ActorContext<MyMsg>) -> ReceiveFuture {
+        let running = self.running.clone();
+        let max_running = self.max_running.clone();
+        let actor_ref = self.actor_ref.clone();
+
+        Box::pin(async move {
+            match msg {
+                MyMsg::Work(n) => {
+                    let now_running = running.fetch_add(1, Ordering::SeqCst) + 1;
+
+                    max_running.fetch_max(now_running, Ordering::SeqCst);
+
+                    Delay(false).await;
+
+                    running.fetch_sub(1, Ordering::SeqCst);
+
+                    actor_ref.tell(n);
+
+                    Ok(())
+                }
+
+                MyMsg::Fail => {
+                    Delay(false).await;
+
+                    Err(FailureError::new(io::Error::new(
+                        io::ErrorKind::Other,
+                        "failed",
+                    )))
+                }
+
+                MyMsg::Panic => {
+                    Delay(false).await;
+
+                    panic!("testing");
+                }
+            }
+        })
+    }
+}
+
+#[test]
+fn test() {
+    struct TestReaper;
+
+    impl Actor for TestReaper {
+        type Msg = ();
+
+        fn receive_signal(&mut self, signal: Signal, ctx: &mut ActorContext<()>) {
+            if let Signal::Started = signal {
+                let mut probe = ctx.spawn_probe::<usize>();
+
+                // messages are processed one at a time, in order
+
+                let max_running = Arc::new(AtomicUsize::new(0));
+
+                let serial = ctx.spawn(MyActor {
+                    reentrant: false,
+                    running: Arc::new(AtomicUsize::new(0)),
+                    max_running: max_running.clone(),
+                    actor_ref: probe.actor_ref().clone(),
+                });
+
+                for n in 1..=5 {
+                    serial.tell(MyMsg::Work(n));
+                }
+
+                for n in 1..=5 {
+                    assert_eq!(probe.receive(Duration::from_secs(10)), n);
+                }
+
+                assert_eq!(max_running.load(Ordering::SeqCst), 1);
+
+                // failures and panics are handled by handle_failure,
+                // and messages that arrive in the meantime are kept
+
+                serial.tell(MyMsg::Fail);
+                serial.tell(MyMsg::Work(6));
+                serial.tell(MyMsg::Panic);
+                serial.tell(MyMsg::Work(7));
+
+                assert_eq!(probe.receive(Duration::from_secs(10)), 0);
+                assert_eq!(probe.receive(Duration::from_secs(10)), 6);
+                assert_eq!(probe.receive(Duration::from_secs(10)), 0);
+                assert_eq!(probe.receive(Duration::from_secs(10)), 7);
+
+                // reentrant actors receive messages while futures are running
+
+                let max_running = Arc::new(AtomicUsize::new(0));
+
+                let reentrant = ctx.spawn(MyActor {
+                    reentrant: true,
+                    running: Arc::new(AtomicUsize::new(0)),
+                    max_running: max_running.clone(),
+                    actor_ref: probe.actor_ref().clone(),
+                });
+
+                for n in 1..=5 {
+                    reentrant.tell(MyMsg::Work(n));
+                }
+
+                let mut sum = 0;
+
+                for _ in 1..=5 {
+                    sum += probe.receive(Duration::from_secs(10));
+                }
+
+                assert_eq!(sum, 15);
+                assert!(max_running.load(Ordering::SeqCst) > 1);
+
+                ctx.actor_ref().stop();
+            }
+        }
+
+        fn receive(&mut self, _: (), _: &mut ActorContext<()>) {}
+    }
+
+    assert!(ActorSystem::new().spawn(TestReaper).is_ok());
+}
+
+#[test]
+fn test_named_dispatcher() {
+    use crate::cfg::Config;
+    use crate::dispatcher::Dispatcher;
+    use std::thread::ThreadId;
+
+    struct PinnedActor {
+        actor_ref: ActorRef<ThreadId>,
+    }
+
+    impl AsyncActor for PinnedActor {
+        type Msg = ();
+
+        fn config_dispatcher(&self, ctx: &ActorSystemContext) -> Option<Dispatcher> {
+            ctx.named_dispatcher("single")
+        }
+
+        fn receive(&mut self, _: (), _: &mut ActorContext<()>) -> ReceiveFuture {
+            self.actor_ref.tell(thread::current().id());
+
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    struct TestReaper;
+
+    impl Actor for TestReaper {
+        type Msg = ();
+
+        fn receive_signal(&mut self, signal: Signal, ctx: &mut ActorContext<()>) {
+            if let Signal::Started = signal {
+                let mut probe = ctx.spawn_probe::<ThreadId>();
+
+                {
+                    let actor_ref = probe.actor_ref().clone();
+
+                    ctx.system_context()
+                        .named_dispatcher("single")
+                        .unwrap()
+                        .execute(move || actor_ref.tell(thread::current().id()));
+                }
+
+                let dispatcher_thread = probe.receive(Duration::from_secs(10));
+
+                // the actor runs on the single thread of the named dispatcher
+
+                ctx.spawn(PinnedActor {
+                    actor_ref: probe.actor_ref().clone(),
+                })
+                .tell(());
+
+                assert_eq!(probe.receive(Duration::from_secs(10)), dispatcher_thread);
+
+                ctx.actor_ref().stop();
+            }
+        }
+
+        fn receive(&mut self, _: (), _: &mut ActorContext<()>) {}
+    }
+
+    assert!(ActorSystem::new()
+        .with_config(&Config::new(&[
+            ("PANTOMIME_DISPATCHERS", "single"),
+            ("PANTOMIME_DISPATCHER_SINGLE_LOGIC", "single-threaded"),
+        ]))
+        .spawn(TestReaper)
+        .is_ok());
+}
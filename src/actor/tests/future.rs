@@ -0,0 +1,152 @@
+use crate::actor::*;
+
+#[test]
+fn test_watch_future() {
+    enum TestReaperMsg {
+        Completed(usize),
+        Panicked,
+    }
+
+    struct TestReaper {
+        completed: usize,
+        panicked: bool,
+    }
+
+    impl Actor for TestReaper {
+        type Msg = TestReaperMsg;
+
+        fn receive(&mut self, msg: TestReaperMsg, ctx: &mut ActorContext<TestReaperMsg>) {
+            match msg {
+                TestReaperMsg::Completed(n) => {
+                    self.completed += n;
+                }
+
+                TestReaperMsg::Panicked => {
+                    self.panicked = true;
+                }
+            }
+
+            if self.completed == 3 && self.panicked {
+                ctx.stop();
+            }
+        }
+
+        fn receive_signal(&mut self, signal: Signal, ctx: &mut ActorContext<TestReaperMsg>) {
+            if let Signal::Started = signal {
+                let first = ctx.dispatcher().spawn_future(async { 1 });
+
+                let second = ctx
+                    .dispatcher()
+                    .spawn_future(async move { first.await.ok().unwrap_or(0) + 1 });
+
+                let third = ctx.dispatcher().spawn_future(async {
+                    if true {
+                        panic!("testing");
+                    }
+
+                    0
+                });
+
+                ctx.watch(second, |result: Result<usize, FailureReason>| {
+                    TestReaperMsg::Completed(result.ok().unwrap_or(0))
+                });
+
+                ctx.watch(ctx.dispatcher().spawn_future(async { 1 }), |result| {
+                    TestReaperMsg::Completed(result.ok().unwrap_or(0))
+                });
+
+                ctx.watch(third, |result: Result<usize, FailureReason>| match result {
+                    Ok(n) => TestReaperMsg::Completed(n),
+                    Err(_) => TestReaperMsg::Panicked,
+                });
+            }
+        }
+    }
+
+    assert!(ActorSystem::new()
+        .spawn(TestReaper {
+            completed: 0,
+            panicked: false
+        })
+        .is_ok());
+}
+
+#[test]
+fn test_pipe_to_self() {
+    enum TestReaperMsg {
+        Completed(usize),
+        Panicked,
+    }
+
+    struct TestReaper {
+        completed: usize,
+        panicked: bool,
+    }
+
+    impl Actor for TestReaper {
+        type Msg = TestReaperMsg;
+
+        fn receive(&mut self, msg: TestReaperMsg, ctx: &mut ActorContext<TestReaperMsg>) {
+            match msg {
+                TestReaperMsg::Completed(n) => {
+                    self.completed += n;
+                }
+
+                TestReaperMsg::Panicked => {
+                    self.panicked = true;
+                }
+            }
+
+            if self.completed == 3 && self.panicked {
+                ctx.stop();
+            }
+        }
+
+        fn receive_signal(&mut self, signal: Signal, ctx: &mut ActorContext<TestReaperMsg>) {
+            if let Signal::Started = signal {
+                let forwarder = ctx.spawn(Forwarder {
+                    reaper: ctx.actor_ref().clone(),
+                });
+
+                ctx.pipe_to_self(async { 1 }, |result| {
+                    TestReaperMsg::Completed(result.ok().unwrap_or(0))
+                });
+
+                ctx.pipe_to(async { 2 }, &forwarder, |result| result.ok().unwrap_or(0));
+
+                ctx.pipe_to_self(
+                    async {
+                        if true {
+                            panic!("testing");
+                        }
+
+                        0
+                    },
+                    |result| match result {
+                        Ok(n) => TestReaperMsg::Completed(n),
+                        Err(_) => TestReaperMsg::Panicked,
+                    },
+                );
+            }
+        }
+    }
+
+    struct Forwarder {
+        reaper: ActorRef<TestReaperMsg>,
+    }
+
+    impl Actor for Forwarder {
+        type Msg = usize;
+
+        fn receive(&mut self, msg: usize, _: &mut ActorContext<usize>) {
+            self.reaper.tell(TestReaperMsg::Completed(msg));
+        }
+    }
+
+    assert!(ActorSystem::new()
+        .spawn(TestReaper {
+            completed: 0,
+            panicked: false
+        })
+        .is_ok());
+}
@@ -1,8 +1,11 @@
+mod async_actor;
 mod convert;
 mod delivery;
 mod drain;
 mod fail;
 mod failure_policy;
+mod future;
+mod named_dispatcher;
 mod simple;
 mod watch;
 
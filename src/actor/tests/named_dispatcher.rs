@@ -0,0 +1,241 @@
+use crate::actor::*;
+use crate::cfg::Config;
+use crate::dispatcher::Dispatcher;
+
+fn config() -> Config {
+    // both dispatchers have a single thread, so the thread that an actor
+    // runs on identifies its dispatcher
+
+    Config::new(&[
+        ("PANTOMIME_DISPATCHERS", "blocking-io,cpu"),
+        (
+            "PANTOMIME_DISPATCHER_BLOCKING_IO_WORK_STEALING_PARALLELISM_MIN",
+            "1",
+        ),
+        (
+            "PANTOMIME_DISPATCHER_BLOCKING_IO_WORK_STEALING_PARALLELISM_MAX",
+            "1",
+        ),
+        ("PANTOMIME_DISPATCHER_CPU_LOGIC", "single-threaded"),
+    ])
+}
+
+#[test]
+fn test_named_dispatcher() {
+    use std::collections::HashMap;
+    use std::thread::{self, ThreadId};
+
+    enum TestMsg {
+        Probed(&'static str, ThreadId),
+        Replied(&'static str, ThreadId),
+    }
+
+    struct MyActor {
+        dispatcher: &'static str,
+    }
+
+    struct TestReaper {
+        probes: HashMap<&'static str, ThreadId>,
+        replies: HashMap<&'static str, ThreadId>,
+    }
+
+    impl Actor for MyActor {
+        type Msg = ActorRef<TestMsg>;
+
+        fn config_dispatcher(&self, ctx: &ActorSystemContext) -> Option<Dispatcher> {
+            ctx.named_dispatcher(self.dispatcher)
+        }
+
+        fn receive(&mut self, msg: Self::Msg, _: &mut ActorContext<Self::Msg>) {
+            msg.tell(TestMsg::Replied(self.dispatcher, thread::current().id()));
+        }
+    }
+
+    impl Actor for TestReaper {
+        type Msg = TestMsg;
+
+        fn receive(&mut self, msg: Self::Msg, ctx: &mut ActorContext<Self::Msg>) {
+            match msg {
+                TestMsg::Probed(dispatcher, thread_id) => {
+                    self.probes.insert(dispatcher, thread_id);
+                }
+
+                TestMsg::Replied(dispatcher, thread_id) => {
+                    self.replies.insert(dispatcher, thread_id);
+                }
+            }
+
+            if self.probes.len() == 2 && self.replies.len() == 2 {
+                // each actor ran on the thread of its dispatcher
+
+                assert!(self.probes["blocking-io"] != self.probes["cpu"]);
+                assert!(self.replies["blocking-io"] == self.probes["blocking-io"]);
+                assert!(self.replies["cpu"] == self.probes["cpu"]);
+
+                ctx.stop();
+            }
+        }
+
+        fn receive_signal(&mut self, signal: Signal, ctx: &mut ActorContext<Self::Msg>) {
+            if let Signal::Started = signal {
+                assert!(ctx.system_context().named_dispatcher("unknown").is_none());
+
+                for dispatcher in &["blocking-io", "cpu"] {
+                    let actor_ref = ctx.actor_ref().clone();
+
+                    ctx.system_context()
+                        .named_dispatcher(dispatcher)
+                        .expect("named dispatcher is missing")
+                        .execute(move || {
+                            actor_ref.tell(TestMsg::Probed(dispatcher, thread::current().id()));
+                        });
+
+                    ctx.spawn(MyActor { dispatcher })
+                        .tell(ctx.actor_ref().clone());
+                }
+            }
+        }
+    }
+
+    assert!(ActorSystem::new()
+        .with_config(&config())
+        .spawn(TestReaper {
+            probes: HashMap::new(),
+            replies: HashMap::new(),
+        })
+        .is_ok());
+}
+
+#[test]
+fn test_unknown_named_dispatcher_logic() {
+    struct TestReaper;
+
+    impl Actor for TestReaper {
+        type Msg = ();
+
+        fn receive(&mut self, _: Self::Msg, _: &mut ActorContext<Self::Msg>) {}
+
+        fn receive_signal(&mut self, signal: Signal, ctx: &mut ActorContext<Self::Msg>) {
+            if let Signal::Started = signal {
+                ctx.stop();
+            }
+        }
+    }
+
+    assert!(ActorSystem::new()
+        .with_config(&Config::new(&[
+            ("PANTOMIME_DISPATCHERS", "blocking-io"),
+            ("PANTOMIME_DISPATCHER_BLOCKING_IO_LOGIC", "unknown"),
+        ]))
+        .spawn(TestReaper)
+        .is_err());
+}
+
+#[test]
+fn test_discard_rejection_policy() {
+    struct TestReaper;
+
+    impl Actor for TestReaper {
+        type Msg = ();
+
+        fn receive(&mut self, _: Self::Msg, _: &mut ActorContext<Self::Msg>) {}
+
+        fn receive_signal(&mut self, signal: Signal, ctx: &mut ActorContext<Self::Msg>) {
+            if let Signal::Started = signal {
+                ctx.stop();
+            }
+        }
+    }
+
+    // discarding work would silently drop actor messages
+
+    assert!(ActorSystem::new()
+        .with_config(&Config::new(&[
+            ("PANTOMIME_DISPATCHERS", "blocking-io"),
+            ("PANTOMIME_DISPATCHER_BLOCKING_IO_LOGIC", "thread-pool"),
+            (
+                "PANTOMIME_DISPATCHER_BLOCKING_IO_THREAD_POOL_QUEUE_CAPACITY",
+                "16"
+            ),
+            (
+                "PANTOMIME_DISPATCHER_BLOCKING_IO_THREAD_POOL_REJECTION_POLICY",
+                "discard"
+            ),
+        ]))
+        .spawn(TestReaper)
+        .is_err());
+}
+
+#[test]
+fn test_thread_pool_and_pinned_dispatchers() {
+    use std::thread::{self, ThreadId};
+
+    struct MyActor {
+        dispatcher: &'static str,
+    }
+
+    struct TestReaper {
+        replies: Vec<(&'static str, ThreadId)>,
+    }
+
+    impl Actor for MyActor {
+        type Msg = ActorRef<(&'static str, ThreadId)>;
+
+        fn config_dispatcher(&self, ctx: &ActorSystemContext) -> Option<Dispatcher> {
+            ctx.named_dispatcher(self.dispatcher)
+        }
+
+        fn receive(&mut self, msg: Self::Msg, _: &mut ActorContext<Self::Msg>) {
+            msg.tell((self.dispatcher, thread::current().id()));
+        }
+    }
+
+    impl Actor for TestReaper {
+        type Msg = (&'static str, ThreadId);
+
+        fn receive(&mut self, msg: Self::Msg, ctx: &mut ActorContext<Self::Msg>) {
+            self.replies.push(msg);
+
+            if self.replies.len() == 4 {
+                let pinned: Vec<ThreadId> = self
+                    .replies
+                    .iter()
+                    .filter(|(dispatcher, _)| *dispatcher == "dedicated")
+                    .map(|(_, thread_id)| *thread_id)
+                    .collect();
+
+                // each pinned actor has its own thread
+
+                assert_eq!(pinned.len(), 2);
+                assert!(pinned[0] != pinned[1]);
+
+                ctx.stop();
+            }
+        }
+
+        fn receive_signal(&mut self, signal: Signal, ctx: &mut ActorContext<Self::Msg>) {
+            if let Signal::Started = signal {
+                for dispatcher in &["blocking-io", "blocking-io", "dedicated", "dedicated"] {
+                    ctx.spawn(MyActor { dispatcher })
+                        .tell(ctx.actor_ref().clone());
+                }
+            }
+        }
+    }
+
+    assert!(ActorSystem::new()
+        .with_config(&Config::new(&[
+            ("PANTOMIME_DISPATCHERS", "blocking-io,dedicated"),
+            ("PANTOMIME_DISPATCHER_BLOCKING_IO_LOGIC", "thread-pool"),
+            ("PANTOMIME_DISPATCHER_BLOCKING_IO_THREAD_POOL_SIZE", "4"),
+            (
+                "PANTOMIME_DISPATCHER_BLOCKING_IO_THREAD_POOL_QUEUE_CAPACITY",
+                "16"
+            ),
+            ("PANTOMIME_DISPATCHER_DEDICATED_LOGIC", "pinned"),
+        ]))
+        .spawn(TestReaper {
+            replies: Vec::new()
+        })
+        .is_ok());
+}
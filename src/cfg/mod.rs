@@ -139,16 +139,121 @@ impl Default for Config {
     }
 }
 
+/// The name of the dispatcher that blocking stream stages, such as those
+/// performing file I/O, execute on.
+///
+/// It is always created, even if it isn't declared via `PANTOMIME_DISPATCHERS`,
+/// in which case it defaults to an elastic `work-stealing` dispatcher that
+/// starts with a single thread and grows to at most 32 as its threads block,
+/// rather than to the settings of the default dispatcher. Its settings can be
+/// specified like any other named dispatcher, e.g. via
+/// `PANTOMIME_DISPATCHER_BLOCKING_IO_WORK_STEALING_PARALLELISM_MAX`.
+pub const BLOCKING_IO_DISPATCHER: &str = "blocking-io";
+
+/// Configuration for a named dispatcher, i.e. one that is declared
+/// via `PANTOMIME_DISPATCHERS` and created when the system starts.
+///
+/// Each setting is read from a key that is derived from the dispatcher's
+/// name, which is uppercased and has dashes replaced with underscores. For
+/// instance, the logic of a dispatcher named `blocking-io` is read from
+/// `PANTOMIME_DISPATCHER_BLOCKING_IO_LOGIC`. Settings that aren't specified
+/// fall back to those of the default dispatcher.
+///
+/// The logic is one of `work-stealing`, `single-threaded`, `thread-pool`
+/// or `pinned`. A pinned dispatcher gives each actor its own thread, so
+/// a new one is created every time it is looked up.
+#[derive(Clone, Debug)]
+pub struct DispatcherConfig {
+    pub name: String,
+    pub logic: String,
+    pub thread_pool_size: usize,
+    pub thread_pool_queue_capacity: usize,
+    pub thread_pool_rejection_policy: String,
+    pub work_stealing_elastic: bool,
+    pub work_stealing_keep_alive_ms: u64,
+    pub work_stealing_parallelism_min: usize,
+    pub work_stealing_parallelism_max: usize,
+    pub work_stealing_parallelism_factor: f32,
+    pub work_stealing_task_queue_fifo: bool,
+}
+
+impl DispatcherConfig {
+    #[rustfmt::skip]
+    fn new(cfg: &Config, name: String, default: &DispatcherConfig) -> io::Result<Self> {
+        let prefix = format!("PANTOMIME_DISPATCHER_{}", name.to_uppercase().replace('-', "_"));
+
+        let logic_key = format!("{}_LOGIC", prefix);
+        let pool_size_key = format!("{}_THREAD_POOL_SIZE", prefix);
+        let pool_queue_capacity_key = format!("{}_THREAD_POOL_QUEUE_CAPACITY", prefix);
+        let pool_rejection_policy_key = format!("{}_THREAD_POOL_REJECTION_POLICY", prefix);
+        let elastic_key = format!("{}_WORK_STEALING_ELASTIC", prefix);
+        let keep_alive_ms_key = format!("{}_WORK_STEALING_KEEP_ALIVE_MS", prefix);
+        let parallelism_min_key = format!("{}_WORK_STEALING_PARALLELISM_MIN", prefix);
+        let parallelism_max_key = format!("{}_WORK_STEALING_PARALLELISM_MAX", prefix);
+        let parallelism_factor_key = format!("{}_WORK_STEALING_PARALLELISM_FACTOR", prefix);
+        let task_queue_fifo_key = format!("{}_WORK_STEALING_TASK_QUEUE_FIFO", prefix);
+
+        let pool_size = default.thread_pool_size.to_string();
+        let pool_queue_capacity = default.thread_pool_queue_capacity.to_string();
+        let elastic = default.work_stealing_elastic.to_string();
+        let keep_alive_ms = default.work_stealing_keep_alive_ms.to_string();
+        let parallelism_min = default.work_stealing_parallelism_min.to_string();
+        let parallelism_max = default.work_stealing_parallelism_max.to_string();
+        let parallelism_factor = default.work_stealing_parallelism_factor.to_string();
+        let task_queue_fifo = default.work_stealing_task_queue_fifo.to_string();
+
+        let cfg = cfg.with_fallback(&[
+            (logic_key.as_str(),                  default.logic.as_str()),
+            (pool_size_key.as_str(),              pool_size.as_str()),
+            (pool_queue_capacity_key.as_str(),    pool_queue_capacity.as_str()),
+            (pool_rejection_policy_key.as_str(),  default.thread_pool_rejection_policy.as_str()),
+            (elastic_key.as_str(),                elastic.as_str()),
+            (keep_alive_ms_key.as_str(),          keep_alive_ms.as_str()),
+            (parallelism_min_key.as_str(),        parallelism_min.as_str()),
+            (parallelism_max_key.as_str(),        parallelism_max.as_str()),
+            (parallelism_factor_key.as_str(),     parallelism_factor.as_str()),
+            (task_queue_fifo_key.as_str(),        task_queue_fifo.as_str()),
+        ]);
+
+        Ok(Self {
+            name,
+            logic:                                cfg.parsed(&logic_key)?,
+            thread_pool_size:                     cfg.parsed(&pool_size_key)?,
+            thread_pool_queue_capacity:           cfg.parsed(&pool_queue_capacity_key)?,
+            thread_pool_rejection_policy:         cfg.parsed(&pool_rejection_policy_key)?,
+            work_stealing_elastic:                cfg.parsed(&elastic_key)?,
+            work_stealing_keep_alive_ms:          cfg.parsed(&keep_alive_ms_key)?,
+            work_stealing_parallelism_min:        cfg.parsed(&parallelism_min_key)?,
+            work_stealing_parallelism_max:        cfg.parsed(&parallelism_max_key)?,
+            work_stealing_parallelism_factor:     cfg.parsed(&parallelism_factor_key)?,
+            work_stealing_task_queue_fifo:        cfg.parsed(&task_queue_fifo_key)?,
+        })
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct ActorSystemConfig {
     pub default_actor_throughput: usize,
     pub default_dispatcher_logic: String,
+    pub default_dispatcher_logic_thread_pool_size: usize,
+    pub default_dispatcher_logic_thread_pool_queue_capacity: usize,
+    pub default_dispatcher_logic_thread_pool_rejection_policy: String,
+    pub default_dispatcher_logic_work_stealing_elastic: bool,
+    pub default_dispatcher_logic_work_stealing_keep_alive_ms: u64,
     pub default_dispatcher_logic_work_stealing_parallelism_min: usize,
     pub default_dispatcher_logic_work_stealing_parallelism_max: usize,
     pub default_dispatcher_logic_work_stealing_parallelism_factor: f32,
     pub default_dispatcher_logic_work_stealing_task_queue_fifo: bool,
     pub default_mailbox_logic: String,
     pub default_streams_buffer_size: usize,
+
+    /// Additional dispatchers that are created when the system starts,
+    /// declared via a comma separated list of names in `PANTOMIME_DISPATCHERS`,
+    /// along with the `BLOCKING_IO_DISPATCHER`.
+    ///
+    /// These can be looked up by name via `ActorSystemContext::named_dispatcher`.
+    pub dispatchers: Vec<DispatcherConfig>,
+
     pub log_config_on_start: bool,
     pub mio_event_capacity: usize,
     pub mio_poll_error_delay_ms: u64,
@@ -161,6 +266,8 @@ pub struct ActorSystemConfig {
     ///
     /// These should be comma separated and are parsed to a reasonable
     /// degree, i.e. whitespace is trimmed, the value is uppercased.
+    /// Signals can be specified by name (with or without the SIG
+    /// prefix) or by number. Unknown signals are ignored.
     #[cfg(feature = "posix-signals-support")]
     pub posix_signals: Vec<i32>,
 
@@ -183,24 +290,26 @@ impl ActorSystemConfig {
     pub fn new(cfg: &Config) -> io::Result<Self> {
         #[cfg(feature = "posix-signals-support")]
         fn posix_signal(sig: String) -> i32 {
-            // @FIXME support numeric specification
-            match sig.as_str() {
-                "SIGHUP"  => crate::posix_signals::PosixSignal::SIGHUP as i32,
-                "SIGINT"  => crate::posix_signals::PosixSignal::SIGINT as i32,
-                "SIGTERM" => crate::posix_signals::PosixSignal::SIGTERM as i32,
-                _         => 0,
-            }
+            crate::posix_signals::PosixSignal::parse(&sig)
+                .map(|s| s as i32)
+                .unwrap_or(0)
         }
 
         let cfg = cfg.with_fallback(&[
             ("PANTOMIME_DEFAULT_ACTOR_THROUGHPUT",                                  "10"),
             ("PANTOMIME_DEFAULT_DISPATCHER_LOGIC",                                  "work-stealing"),
+            ("PANTOMIME_DEFAULT_DISPATCHER_LOGIC_THREAD_POOL_SIZE",                 "32"),
+            ("PANTOMIME_DEFAULT_DISPATCHER_LOGIC_THREAD_POOL_QUEUE_CAPACITY",       "0"),
+            ("PANTOMIME_DEFAULT_DISPATCHER_LOGIC_THREAD_POOL_REJECTION_POLICY",     "caller-runs"),
+            ("PANTOMIME_DEFAULT_DISPATCHER_LOGIC_WORK_STEALING_ELASTIC",            "false"),
+            ("PANTOMIME_DEFAULT_DISPATCHER_LOGIC_WORK_STEALING_KEEP_ALIVE_MS",      "60000"),
             ("PANTOMIME_DEFAULT_DISPATCHER_LOGIC_WORK_STEALING_PARALLELISM_MIN",    "4"),
             ("PANTOMIME_DEFAULT_DISPATCHER_LOGIC_WORK_STEALING_PARALLELISM_MAX",    "64"),
             ("PANTOMIME_DEFAULT_DISPATCHER_LOGIC_WORK_STEALING_PARALLELISM_FACTOR", "1.0"),
             ("PANTOMIME_DEFAULT_DISPATCHER_LOGIC_WORK_STEALING_TASK_QUEUE_FIFO",    "true"),
             ("PANTOMIME_DEFAULT_MAILBOX_LOGIC",                                     "conqueue"),
             ("PANTOMIME_DEFAULT_STREAMS_BUFFER_SIZE",                               "15"),
+            ("PANTOMIME_DISPATCHERS",                                               ""),
             ("PANTOMIME_LOG_CONFIG_ON_START",                                       "false"),
             ("PANTOMIME_MIO_EVENT_CAPACITY",                                        "1024"),
             ("PANTOMIME_MIO_POLL_ERROR_DELAY_MS",                                   "1000"),
@@ -211,15 +320,21 @@ impl ActorSystemConfig {
             ("PANTOMIME_POSIX_EXIT_SIGNALS",                                        "SIGINT,SIGTERM"),
         ]);
 
-        Ok(Self {
+        let mut config = Self {
             default_actor_throughput:                                   cfg.parsed("PANTOMIME_DEFAULT_ACTOR_THROUGHPUT")?,
             default_dispatcher_logic:                                   cfg.parsed("PANTOMIME_DEFAULT_DISPATCHER_LOGIC")?,
+            default_dispatcher_logic_thread_pool_size:                  cfg.parsed("PANTOMIME_DEFAULT_DISPATCHER_LOGIC_THREAD_POOL_SIZE")?,
+            default_dispatcher_logic_thread_pool_queue_capacity:        cfg.parsed("PANTOMIME_DEFAULT_DISPATCHER_LOGIC_THREAD_POOL_QUEUE_CAPACITY")?,
+            default_dispatcher_logic_thread_pool_rejection_policy:      cfg.parsed("PANTOMIME_DEFAULT_DISPATCHER_LOGIC_THREAD_POOL_REJECTION_POLICY")?,
+            default_dispatcher_logic_work_stealing_elastic:             cfg.parsed("PANTOMIME_DEFAULT_DISPATCHER_LOGIC_WORK_STEALING_ELASTIC")?,
+            default_dispatcher_logic_work_stealing_keep_alive_ms:       cfg.parsed("PANTOMIME_DEFAULT_DISPATCHER_LOGIC_WORK_STEALING_KEEP_ALIVE_MS")?,
             default_dispatcher_logic_work_stealing_parallelism_min:     cfg.parsed("PANTOMIME_DEFAULT_DISPATCHER_LOGIC_WORK_STEALING_PARALLELISM_MIN")?,
             default_dispatcher_logic_work_stealing_parallelism_max:     cfg.parsed("PANTOMIME_DEFAULT_DISPATCHER_LOGIC_WORK_STEALING_PARALLELISM_MAX")?,
             default_dispatcher_logic_work_stealing_parallelism_factor:  cfg.parsed("PANTOMIME_DEFAULT_DISPATCHER_LOGIC_WORK_STEALING_PARALLELISM_FACTOR")?,
             default_dispatcher_logic_work_stealing_task_queue_fifo:     cfg.parsed("PANTOMIME_DEFAULT_DISPATCHER_LOGIC_WORK_STEALING_TASK_QUEUE_FIFO")?,
             default_mailbox_logic:                                      cfg.parsed("PANTOMIME_DEFAULT_MAILBOX_LOGIC")?,
             default_streams_buffer_size:                                cfg.parsed("PANTOMIME_DEFAULT_STREAMS_BUFFER_SIZE")?,
+            dispatchers:                                                Vec::new(),
             log_config_on_start:                                        cfg.parsed("PANTOMIME_LOG_CONFIG_ON_START")?,
             mio_event_capacity:                                         cfg.parsed("PANTOMIME_MIO_EVENT_CAPACITY")?,
             mio_poll_error_delay_ms:                                    cfg.parsed("PANTOMIME_MIO_POLL_ERROR_DELAY_MS")?,
@@ -241,7 +356,59 @@ impl ActorSystemConfig {
                                                                           .map(posix_signal)
                                                                           .filter(|s| *s != 0)
                                                                           .collect(),
-        })
+        };
+
+        let default_dispatcher = config.default_dispatcher();
+
+        config.dispatchers = cfg
+            .string_vec("PANTOMIME_DISPATCHERS")?
+            .into_iter()
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .map(|name| DispatcherConfig::new(&cfg, name, &default_dispatcher))
+            .collect::<io::Result<_>>()?;
+
+        if !config.dispatchers.iter().any(|d| d.name == BLOCKING_IO_DISPATCHER) {
+            let blocking_io_default = DispatcherConfig {
+                logic: "work-stealing".to_string(),
+                work_stealing_elastic: true,
+                work_stealing_parallelism_min: 1,
+                work_stealing_parallelism_max: 32,
+                ..default_dispatcher
+            };
+
+            config.dispatchers.push(DispatcherConfig::new(
+                &cfg,
+                BLOCKING_IO_DISPATCHER.to_string(),
+                &blocking_io_default,
+            )?);
+        }
+
+        Ok(config)
+    }
+
+    /// The configuration of the default dispatcher, expressed as a
+    /// `DispatcherConfig`.
+    pub fn default_dispatcher(&self) -> DispatcherConfig {
+        DispatcherConfig {
+            name: "default".to_string(),
+            logic: self.default_dispatcher_logic.clone(),
+            thread_pool_size: self.default_dispatcher_logic_thread_pool_size,
+            thread_pool_queue_capacity: self.default_dispatcher_logic_thread_pool_queue_capacity,
+            thread_pool_rejection_policy: self
+                .default_dispatcher_logic_thread_pool_rejection_policy
+                .clone(),
+            work_stealing_elastic: self.default_dispatcher_logic_work_stealing_elastic,
+            work_stealing_keep_alive_ms: self.default_dispatcher_logic_work_stealing_keep_alive_ms,
+            work_stealing_parallelism_min: self
+                .default_dispatcher_logic_work_stealing_parallelism_min,
+            work_stealing_parallelism_max: self
+                .default_dispatcher_logic_work_stealing_parallelism_max,
+            work_stealing_parallelism_factor: self
+                .default_dispatcher_logic_work_stealing_parallelism_factor,
+            work_stealing_task_queue_fifo: self
+                .default_dispatcher_logic_work_stealing_task_queue_fifo,
+        }
     }
 }
 
@@ -298,4 +465,104 @@ mod tests {
     fn test_actor_config() {
         assert!(ActorSystemConfig::new(&Config::default()).is_ok());
     }
+
+    #[test]
+    #[cfg(feature = "posix-signals-support")]
+    fn test_posix_signals_config() -> io::Result<()> {
+        use crate::posix_signals::PosixSignal;
+
+        let config = ActorSystemConfig::new(&Config::new(&[
+            (
+                "PANTOMIME_POSIX_SIGNALS",
+                "sigint, USR1,SIGWINCH,15,SIGKILL,bogus",
+            ),
+            ("PANTOMIME_POSIX_EXIT_SIGNALS", "2"),
+        ]))?;
+
+        assert_eq!(
+            config.posix_signals,
+            vec![
+                PosixSignal::SIGINT as i32,
+                PosixSignal::SIGUSR1 as i32,
+                PosixSignal::SIGWINCH as i32,
+                PosixSignal::SIGTERM as i32
+            ]
+        );
+
+        assert_eq!(
+            config.posix_shutdown_signals,
+            vec![PosixSignal::SIGINT as i32]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_blocking_io_dispatcher_config() -> io::Result<()> {
+        let config = ActorSystemConfig::new(&Config::new(&[
+            ("PANTOMIME_DISPATCHERS", "cpu"),
+            (
+                "PANTOMIME_DISPATCHER_BLOCKING_IO_WORK_STEALING_PARALLELISM_MAX",
+                "4",
+            ),
+        ]))?;
+
+        assert_eq!(config.dispatchers.len(), 2);
+
+        assert_eq!(config.dispatchers[1].name, BLOCKING_IO_DISPATCHER);
+        assert_eq!(config.dispatchers[1].logic, "work-stealing");
+        assert!(config.dispatchers[1].work_stealing_elastic);
+        assert_eq!(config.dispatchers[1].work_stealing_parallelism_min, 1);
+        assert_eq!(config.dispatchers[1].work_stealing_parallelism_max, 4);
+
+        let config = ActorSystemConfig::new(&Config::new(&[
+            ("PANTOMIME_DISPATCHER_BLOCKING_IO_LOGIC", "thread-pool"),
+            ("PANTOMIME_DISPATCHER_BLOCKING_IO_THREAD_POOL_SIZE", "4"),
+        ]))?;
+
+        assert_eq!(config.dispatchers.len(), 1);
+
+        assert_eq!(config.dispatchers[0].logic, "thread-pool");
+        assert_eq!(config.dispatchers[0].thread_pool_size, 4);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_dispatchers_config() -> io::Result<()> {
+        let config = ActorSystemConfig::new(&Config::new(&[
+            ("PANTOMIME_DISPATCHERS", "blocking-io, cpu"),
+            (
+                "PANTOMIME_DISPATCHER_BLOCKING_IO_WORK_STEALING_PARALLELISM_MIN",
+                "32",
+            ),
+            (
+                "PANTOMIME_DISPATCHER_BLOCKING_IO_WORK_STEALING_PARALLELISM_MAX",
+                "32",
+            ),
+            (
+                "PANTOMIME_DISPATCHER_BLOCKING_IO_WORK_STEALING_ELASTIC",
+                "true",
+            ),
+            ("PANTOMIME_DISPATCHER_CPU_LOGIC", "single-threaded"),
+        ]))?;
+
+        assert_eq!(config.dispatchers.len(), 2);
+
+        assert_eq!(config.dispatchers[0].name, "blocking-io");
+        assert_eq!(config.dispatchers[0].logic, config.default_dispatcher_logic);
+        assert_eq!(config.dispatchers[0].work_stealing_parallelism_min, 32);
+        assert_eq!(config.dispatchers[0].work_stealing_parallelism_max, 32);
+        assert!(config.dispatchers[0].work_stealing_elastic);
+
+        assert_eq!(config.dispatchers[1].name, "cpu");
+        assert_eq!(config.dispatchers[1].logic, "single-threaded");
+        assert!(!config.dispatchers[1].work_stealing_elastic);
+        assert_eq!(
+            config.dispatchers[1].work_stealing_parallelism_max,
+            config.default_dispatcher_logic_work_stealing_parallelism_max
+        );
+
+        Ok(())
+    }
 }
@@ -0,0 +1,387 @@
+use super::Dispatcher;
+use crate::actor::{ActorContext, FailureReason, Watchable};
+use parking_lot::Mutex;
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+
+const TASK_IDLE: usize = 0;
+const TASK_SCHEDULED: usize = 1;
+const TASK_RUNNING: usize = 2;
+const TASK_NOTIFIED: usize = 3;
+const TASK_COMPLETE: usize = 4;
+
+impl Dispatcher {
+    /// Spawn the provided future, polling it on this dispatcher until
+    /// it has completed.
+    ///
+    /// The returned `JoinHandle` can be used to obtain the future's
+    /// output, either by awaiting it or by watching it from an actor.
+    /// Dropping the handle does not cancel the future.
+    ///
+    /// If polling the future panics, the handle completes with
+    /// `FailureReason::Panicked`.
+    pub fn spawn_future<F>(&self, future: F) -> JoinHandle<F::Output>
+    where
+        F: 'static + Future + Send,
+        F::Output: 'static + Send,
+    {
+        let join_state = Arc::new(Mutex::new(JoinState {
+            result: None,
+            waker: None,
+            callback: None,
+        }));
+
+        let task = Arc::new(Task {
+            future: Mutex::new(Some(Box::pin(TaskFuture {
+                future: Box::pin(future),
+                join_state: join_state.clone(),
+            }))),
+            state: AtomicUsize::new(TASK_SCHEDULED),
+            dispatcher: self.clone(),
+        });
+
+        self.execute(move || task.run());
+
+        JoinHandle { state: join_state }
+    }
+}
+
+type BoxedTaskFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// A future that has been spawned onto a dispatcher.
+///
+/// Tasks are scheduled whenever they're woken, and a task is never
+/// polled concurrently. If a task is woken while it is being polled,
+/// it is rescheduled once polling has finished.
+struct Task {
+    future: Mutex<Option<BoxedTaskFuture>>,
+    state: AtomicUsize,
+    dispatcher: Dispatcher,
+}
+
+impl Task {
+    fn run(self: Arc<Self>) {
+        self.state.store(TASK_RUNNING, Ordering::SeqCst);
+
+        let waker = Waker::from(self.clone());
+        let mut context = Context::from_waker(&waker);
+
+        let ready = {
+            let mut future = self.future.lock();
+
+            match future.as_mut() {
+                Some(f) => {
+                    let ready = f.as_mut().poll(&mut context).is_ready();
+
+                    if ready {
+                        future.take();
+                    }
+
+                    ready
+                }
+
+                None => true,
+            }
+        };
+
+        if ready {
+            self.state.store(TASK_COMPLETE, Ordering::SeqCst);
+        } else if self
+            .state
+            .compare_exchange(TASK_RUNNING, TASK_IDLE, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+        {
+            // we were woken while polling, so we need to go again
+
+            self.state.store(TASK_SCHEDULED, Ordering::SeqCst);
+
+            let dispatcher = self.dispatcher.clone();
+
+            dispatcher.execute(move || self.run());
+        }
+    }
+}
+
+impl Wake for Task {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        loop {
+            match self.state.load(Ordering::SeqCst) {
+                TASK_IDLE => {
+                    if self
+                        .state
+                        .compare_exchange(
+                            TASK_IDLE,
+                            TASK_SCHEDULED,
+                            Ordering::SeqCst,
+                            Ordering::SeqCst,
+                        )
+                        .is_ok()
+                    {
+                        let task = self.clone();
+
+                        self.dispatcher.execute(move || task.run());
+
+                        return;
+                    }
+                }
+
+                TASK_RUNNING => {
+                    if self
+                        .state
+                        .compare_exchange(
+                            TASK_RUNNING,
+                            TASK_NOTIFIED,
+                            Ordering::SeqCst,
+                            Ordering::SeqCst,
+                        )
+                        .is_ok()
+                    {
+                        return;
+                    }
+                }
+
+                _ => {
+                    // already scheduled, notified or complete
+
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// Wraps a spawned future, catching panics and publishing its
+/// output to the `JoinHandle`.
+struct TaskFuture<T> {
+    future: Pin<Box<dyn Future<Output = T> + Send>>,
+    join_state: Arc<Mutex<JoinState<T>>>,
+}
+
+impl<T> Future for TaskFuture<T> {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        let future = &mut self.future;
+
+        let result = match panic::catch_unwind(AssertUnwindSafe(|| future.as_mut().poll(cx))) {
+            Ok(Poll::Pending) => {
+                return Poll::Pending;
+            }
+
+            Ok(Poll::Ready(value)) => Ok(value),
+
+            Err(_) => Err(FailureReason::Panicked),
+        };
+
+        JoinState::complete(&self.join_state, result);
+
+        Poll::Ready(())
+    }
+}
+
+type JoinCallback<T> = Box<dyn FnOnce(Result<T, FailureReason>) + Send>;
+
+struct JoinState<T> {
+    result: Option<Result<T, FailureReason>>,
+    waker: Option<Waker>,
+    callback: Option<JoinCallback<T>>,
+}
+
+impl<T> JoinState<T> {
+    fn complete(state: &Mutex<Self>, result: Result<T, FailureReason>) {
+        let callback = {
+            let mut state = state.lock();
+
+            match state.callback.take() {
+                Some(callback) => callback,
+
+                None => {
+                    state.result = Some(result);
+
+                    if let Some(waker) = state.waker.take() {
+                        waker.wake();
+                    }
+
+                    return;
+                }
+            }
+        };
+
+        callback(result);
+    }
+}
+
+/// A handle to the output of a future that has been spawned via
+/// `Dispatcher::spawn_future`.
+///
+/// The handle itself is a future and can be awaited, or it can be
+/// watched by an actor via `ActorContext::watch`, which converts the
+/// output into a message that is sent to the actor.
+pub struct JoinHandle<T> {
+    state: Arc<Mutex<JoinState<T>>>,
+}
+
+impl<T> JoinHandle<T> {
+    /// Invoke the provided function with the output once it is
+    /// available, which may be immediately.
+    pub(crate) fn on_complete<F>(self, f: F)
+    where
+        F: 'static + FnOnce(Result<T, FailureReason>) + Send,
+    {
+        let result = {
+            let mut state = self.state.lock();
+
+            match state.result.take() {
+                Some(result) => result,
+
+                None => {
+                    state.callback = Some(Box::new(f));
+
+                    return;
+                }
+            }
+        };
+
+        f(result);
+    }
+}
+
+impl<T> Future for JoinHandle<T> {
+    type Output = Result<T, FailureReason>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let mut state = self.state.lock();
+
+        match state.result.take() {
+            Some(result) => Poll::Ready(result),
+
+            None => {
+                state.waker = Some(cx.waker().clone());
+
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<Msg, T, F: Fn(Result<T, FailureReason>) -> Msg>
+    Watchable<JoinHandle<T>, Result<T, FailureReason>, Msg, F> for ActorContext<Msg>
+where
+    Msg: 'static + Send,
+    T: 'static + Send,
+    F: 'static + Send,
+{
+    fn perform_watch(&mut self, subject: JoinHandle<T>, convert: F) {
+        let actor_ref = self.actor_ref().clone();
+
+        subject.on_complete(move |result| actor_ref.tell(convert(result)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dispatcher::{SingleThreadedDispatcher, WorkStealingDispatcher};
+    use crate::testkit::*;
+    use std::thread;
+    use std::time::Duration;
+
+    /// A future that completes after it has been polled a number of times,
+    /// waking itself from another thread each time.
+    struct Countdown(usize);
+
+    impl Future for Countdown {
+        type Output = usize;
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<usize> {
+            if self.0 == 0 {
+                Poll::Ready(42)
+            } else {
+                self.0 -= 1;
+
+                let waker = cx.waker().clone();
+
+                thread::spawn(move || {
+                    thread::sleep(Duration::from_millis(1));
+
+                    waker.wake();
+                });
+
+                Poll::Pending
+            }
+        }
+    }
+
+    #[test]
+    fn test_spawn_future() {
+        let dispatcher = Dispatcher::new(WorkStealingDispatcher::new(4, true));
+
+        let handle = dispatcher.spawn_future(Countdown(10));
+
+        assert_eq!(block_on(handle).ok(), Some(42));
+    }
+
+    #[test]
+    fn test_spawn_async() {
+        let dispatcher = Dispatcher::new(SingleThreadedDispatcher::new());
+
+        let first = dispatcher.spawn_future(async { 1 });
+
+        let second = dispatcher.spawn_future(async move {
+            let first = first.await.ok().unwrap_or(0);
+
+            first + Countdown(3).await
+        });
+
+        assert_eq!(block_on(second).ok(), Some(43));
+    }
+
+    #[test]
+    fn test_panic() {
+        let dispatcher = Dispatcher::new(WorkStealingDispatcher::new(1, true));
+
+        let failed = dispatcher.spawn_future(async {
+            if true {
+                panic!("testing");
+            }
+        });
+
+        match block_on(failed) {
+            Err(FailureReason::Panicked) => {}
+            _ => panic!("expected the future to have panicked"),
+        }
+
+        // the dispatcher is still usable
+
+        assert_eq!(block_on(dispatcher.spawn_future(async { 1 })).ok(), Some(1));
+    }
+
+    #[test]
+    fn test_on_complete() {
+        let dispatcher = Dispatcher::new(WorkStealingDispatcher::new(2, true));
+
+        let result = Arc::new(AtomicUsize::new(0));
+
+        {
+            let result = result.clone();
+
+            dispatcher
+                .spawn_future(Countdown(2))
+                .on_complete(move |value| {
+                    result.store(value.ok().unwrap_or(0), Ordering::SeqCst);
+                });
+        }
+
+        eventually(Duration::from_millis(3000), move || {
+            result.load(Ordering::SeqCst) == 42
+        });
+    }
+}
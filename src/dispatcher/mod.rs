@@ -1,23 +1,23 @@
 //! Dispatchers schedule and execute work
 
+mod future;
+mod pinned;
 mod single_threaded;
+mod thread_pool;
 mod work_stealing;
 
-#[cfg(feature = "futures-support")]
-mod fs;
-
 #[cfg(feature = "tokio-support")]
 mod tokio;
 
 use crossbeam::channel::{unbounded, Receiver, RecvError, Sender};
 use std::thread;
 
+pub use self::future::JoinHandle;
+pub use self::pinned::PinnedDispatcher;
 pub use self::single_threaded::SingleThreadedDispatcher;
+pub use self::thread_pool::{RejectionPolicy, ThreadPoolDispatcher};
 pub use self::work_stealing::WorkStealingDispatcher;
 
-#[cfg(feature = "futures-support")]
-pub use self::fs::FutureDispatcherBridge;
-
 #[cfg(feature = "tokio-support")]
 pub use self::tokio::RunTokioFuture;
 
@@ -54,6 +54,13 @@ impl Dispatcher {
     pub fn execute_trampoline(&self, trampoline: Trampoline) {
         self.inner.execute_trampoline(trampoline);
     }
+
+    /// Shutdown this dispatcher, signaling its threads to stop.
+    ///
+    /// This affects all clones of this dispatcher.
+    pub fn shutdown(self) {
+        self.inner.shutdown();
+    }
 }
 
 impl Clone for Dispatcher {
@@ -78,7 +85,8 @@ impl Clone for Dispatcher {
 ///
 /// For some workloads, in particular those that make heavy
 /// use of synchronous I/O or are particular compute bound,
-/// separate dispatchers should be used.
+/// separate dispatchers should be used, e.g. a `ThreadPoolDispatcher`
+/// or a `PinnedDispatcher`.
 ///
 /// An actor can be pinned to a particular dispatcher by overriding
 /// the `config_dispatcher` method.
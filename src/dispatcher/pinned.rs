@@ -0,0 +1,164 @@
+use super::*;
+
+enum PinnedDispatcherMessage {
+    Execute(Thunk),
+    ExecuteTrampoline(Trampoline),
+    Shutdown,
+}
+
+/// A dispatcher that owns a single dedicated thread, which is intended
+/// to be used by a single actor.
+///
+/// This is useful for actors that block for long periods of time, e.g.
+/// wrapping a synchronous database driver, as they cannot affect the
+/// execution of other actors.
+///
+/// The thread is stopped once the dispatcher (including all of its clones)
+/// has been dropped, so a new `PinnedDispatcher` should be created for
+/// each actor that is to be pinned.
+///
+/// Trampolines are executed to completion on the thread.
+pub struct PinnedDispatcher {
+    sender: Sender<PinnedDispatcherMessage>,
+}
+
+impl PinnedDispatcher {
+    pub fn new() -> Self {
+        let (sender, receiver) = unbounded::<PinnedDispatcherMessage>();
+
+        Self::spawn(receiver);
+
+        Self { sender }
+    }
+
+    fn spawn(receiver: Receiver<PinnedDispatcherMessage>) {
+        struct Panicking {
+            receiver: Receiver<PinnedDispatcherMessage>,
+        }
+
+        impl Drop for Panicking {
+            fn drop(&mut self) {
+                if thread::panicking() {
+                    PinnedDispatcher::spawn(self.receiver.clone());
+                }
+            }
+        }
+
+        thread::spawn(move || {
+            let p = Panicking {
+                receiver: receiver.clone(),
+            };
+
+            loop {
+                match receiver.recv() {
+                    Ok(PinnedDispatcherMessage::Execute(work)) => {
+                        work.apply();
+                    }
+
+                    Ok(PinnedDispatcherMessage::ExecuteTrampoline(trampoline)) => {
+                        let mut step = trampoline.step;
+
+                        while let TrampolineStep::Bounce(next_step) = step {
+                            step = next_step.apply().step;
+                        }
+                    }
+
+                    Ok(PinnedDispatcherMessage::Shutdown) | Err(RecvError) => {
+                        break;
+                    }
+                }
+            }
+
+            drop(receiver);
+            drop(p);
+        });
+    }
+}
+
+impl Default for PinnedDispatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DispatcherLogic for PinnedDispatcher {
+    fn clone_box(&self) -> Box<dyn DispatcherLogic + 'static + Send + Sync> {
+        Box::new(Self {
+            sender: self.sender.clone(),
+        })
+    }
+
+    fn execute(&self, thunk: Thunk) {
+        let _ = self.sender.send(PinnedDispatcherMessage::Execute(thunk));
+    }
+
+    fn execute_trampoline(&self, trampoline: Trampoline) {
+        let _ = self
+            .sender
+            .send(PinnedDispatcherMessage::ExecuteTrampoline(trampoline));
+    }
+
+    fn shutdown(self: Box<Self>) {
+        let _ = self.sender.send(PinnedDispatcherMessage::Shutdown);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testkit::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    #[test]
+    fn simple_test() {
+        let thread_ids = Arc::new(Mutex::new(Vec::new()));
+
+        let dispatcher = PinnedDispatcher::new();
+
+        for _ in 0..100 {
+            let thread_ids = thread_ids.clone();
+
+            dispatcher.execute(Box::new(move || {
+                thread_ids.lock().unwrap().push(thread::current().id());
+            }));
+        }
+
+        {
+            let thread_ids = thread_ids.clone();
+
+            eventually(Duration::from_millis(3000), move || {
+                thread_ids.lock().unwrap().len() == 100
+            });
+        }
+
+        let thread_ids = thread_ids.lock().unwrap();
+
+        assert!(thread_ids.iter().all(|id| *id == thread_ids[0]));
+        assert!(thread_ids[0] != thread::current().id());
+    }
+
+    #[test]
+    fn test_panic() {
+        let counter = Arc::new(AtomicUsize::new(0));
+
+        let dispatcher = PinnedDispatcher::new();
+
+        dispatcher.execute(Box::new(move || {
+            panic!("testing");
+        }));
+
+        {
+            let counter = counter.clone();
+
+            dispatcher.execute(Box::new(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+            }));
+        }
+
+        eventually(Duration::from_millis(10000), move || {
+            counter.load(Ordering::SeqCst) == 1
+        });
+    }
+}
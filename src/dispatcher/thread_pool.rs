@@ -0,0 +1,414 @@
+use super::*;
+use crossbeam::channel::{bounded, select, TryRecvError, TrySendError};
+use parking_lot::Mutex;
+use std::sync::Arc;
+
+enum ThreadPoolDispatcherMessage {
+    Execute(Thunk),
+    ExecuteTrampoline(Trampoline),
+}
+
+/// Defines what happens when work is submitted to a `ThreadPoolDispatcher`
+/// whose queue is full.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RejectionPolicy {
+    /// Block the submitting thread until there is room in the queue.
+    Block,
+
+    /// Execute the work on the submitting thread.
+    CallerRuns,
+
+    /// Discard the work, logging an error.
+    ///
+    /// Actors are scheduled by submitting work to their dispatcher, so
+    /// this must not be used for dispatchers that are executing actors,
+    /// and it cannot be used for dispatchers declared in configuration.
+    Discard,
+}
+
+/// A fixed-size thread pool that is backed by crossbeam_channel.
+///
+/// Unlike the `WorkStealingDispatcher`, all threads consume from a single
+/// shared queue and no attempt is made to keep work on the thread that
+/// submitted it. This makes it well suited for blocking work, e.g. file
+/// or database I/O, as a blocked thread does not prevent any other work
+/// from being executed.
+///
+/// The queue is unbounded by default, but a bounded queue can be used
+/// along with a `RejectionPolicy` that defines what happens when it is full.
+///
+/// Trampolines are executed to completion by the thread that receives
+/// them.
+///
+/// If a thread panics while executing, a new thread is spawned to take
+/// its place.
+///
+/// Upon shutdown, threads exit once they have finished their current work,
+/// and any work that is still queued is discarded.
+pub struct ThreadPoolDispatcher {
+    sender: Sender<ThreadPoolDispatcherMessage>,
+    shutdown: Arc<Mutex<Option<Sender<()>>>>,
+    rejection_policy: RejectionPolicy,
+}
+
+impl ThreadPoolDispatcher {
+    /// Creates a new dispatcher with `size` threads and an unbounded queue.
+    pub fn new(size: usize) -> Self {
+        let (sender, receiver) = unbounded();
+
+        Self::start(sender, receiver, RejectionPolicy::Block, size)
+    }
+
+    /// Creates a new dispatcher with `size` threads and a queue that holds
+    /// at most `capacity` pending tasks. When the queue is full, the
+    /// provided `RejectionPolicy` is applied.
+    pub fn with_bounded_queue(
+        size: usize,
+        capacity: usize,
+        rejection_policy: RejectionPolicy,
+    ) -> Self {
+        let (sender, receiver) = bounded(capacity);
+
+        Self::start(sender, receiver, rejection_policy, size)
+    }
+
+    fn start(
+        sender: Sender<ThreadPoolDispatcherMessage>,
+        receiver: Receiver<ThreadPoolDispatcherMessage>,
+        rejection_policy: RejectionPolicy,
+        size: usize,
+    ) -> Self {
+        // nothing is ever sent on this channel, it is disconnected to
+        // notify every thread of shutdown without blocking
+
+        let (shutdown_sender, shutdown_receiver) = bounded(0);
+
+        for _ in 0..size {
+            Self::spawn(receiver.clone(), shutdown_receiver.clone());
+        }
+
+        Self {
+            sender,
+            shutdown: Arc::new(Mutex::new(Some(shutdown_sender))),
+            rejection_policy,
+        }
+    }
+
+    fn spawn(receiver: Receiver<ThreadPoolDispatcherMessage>, shutdown: Receiver<()>) {
+        struct Panicking {
+            receiver: Receiver<ThreadPoolDispatcherMessage>,
+            shutdown: Receiver<()>,
+        }
+
+        impl Drop for Panicking {
+            fn drop(&mut self) {
+                if thread::panicking() {
+                    ThreadPoolDispatcher::spawn(self.receiver.clone(), self.shutdown.clone());
+                }
+            }
+        }
+
+        thread::spawn(move || {
+            let p = Panicking {
+                receiver: receiver.clone(),
+                shutdown: shutdown.clone(),
+            };
+
+            loop {
+                // shutdown is checked first so that queued work doesn't
+                // delay it
+
+                if let Err(TryRecvError::Disconnected) = shutdown.try_recv() {
+                    break;
+                }
+
+                select! {
+                    recv(receiver) -> msg => match msg {
+                        Ok(msg) => Self::run(msg),
+                        Err(RecvError) => break,
+                    },
+
+                    recv(shutdown) -> _ => break,
+                }
+            }
+
+            drop(receiver);
+            drop(p);
+        });
+    }
+
+    fn run(msg: ThreadPoolDispatcherMessage) {
+        match msg {
+            ThreadPoolDispatcherMessage::Execute(work) => {
+                work.apply();
+            }
+
+            ThreadPoolDispatcherMessage::ExecuteTrampoline(trampoline) => {
+                let mut step = trampoline.step;
+
+                while let TrampolineStep::Bounce(next_step) = step {
+                    step = next_step.apply().step;
+                }
+            }
+        }
+    }
+
+    fn submit(&self, msg: ThreadPoolDispatcherMessage) {
+        if self.shutdown.lock().is_none() {
+            // no threads remain to execute it, and blocking on a full
+            // queue would never return
+
+            return;
+        }
+
+        match self.rejection_policy {
+            RejectionPolicy::Block => {
+                let _ = self.sender.send(msg);
+            }
+
+            RejectionPolicy::CallerRuns => {
+                if let Err(TrySendError::Full(msg)) = self.sender.try_send(msg) {
+                    Self::run(msg);
+                }
+            }
+
+            RejectionPolicy::Discard => {
+                if let Err(TrySendError::Full(_)) = self.sender.try_send(msg) {
+                    error!("ThreadPoolDispatcher queue is full, discarding work");
+                }
+            }
+        }
+    }
+}
+
+impl DispatcherLogic for ThreadPoolDispatcher {
+    fn clone_box(&self) -> Box<dyn DispatcherLogic + 'static + Send + Sync> {
+        Box::new(Self {
+            sender: self.sender.clone(),
+            shutdown: self.shutdown.clone(),
+            rejection_policy: self.rejection_policy,
+        })
+    }
+
+    fn execute(&self, thunk: Thunk) {
+        self.submit(ThreadPoolDispatcherMessage::Execute(thunk));
+    }
+
+    fn execute_trampoline(&self, trampoline: Trampoline) {
+        self.submit(ThreadPoolDispatcherMessage::ExecuteTrampoline(trampoline));
+    }
+
+    fn shutdown(self: Box<Self>) {
+        self.shutdown.lock().take();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testkit::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Barrier};
+    use std::time::Duration;
+
+    #[test]
+    fn simple_test() {
+        let counter = Arc::new(AtomicUsize::new(0));
+
+        let dispatcher = ThreadPoolDispatcher::new(4);
+
+        for _ in 0..100 {
+            let counter = counter.clone();
+
+            dispatcher.execute(Box::new(move || {
+                counter.fetch_add(10, Ordering::SeqCst);
+            }));
+        }
+
+        eventually(Duration::from_millis(3000), move || {
+            counter.load(Ordering::SeqCst) == 1000
+        });
+    }
+
+    #[test]
+    fn test_blocking() {
+        // all threads are blocked until every task has started, which
+        // can only succeed if each task is running on its own thread
+
+        let counter = Arc::new(AtomicUsize::new(0));
+        let barrier = Arc::new(Barrier::new(8));
+
+        let dispatcher = ThreadPoolDispatcher::new(8);
+
+        for _ in 0..8 {
+            let barrier = barrier.clone();
+            let counter = counter.clone();
+
+            dispatcher.execute(Box::new(move || {
+                barrier.wait();
+                counter.fetch_add(1, Ordering::SeqCst);
+            }));
+        }
+
+        eventually(Duration::from_millis(3000), move || {
+            counter.load(Ordering::SeqCst) == 8
+        });
+    }
+
+    #[test]
+    fn test_caller_runs() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let barrier = Arc::new(Barrier::new(2));
+
+        let dispatcher =
+            ThreadPoolDispatcher::with_bounded_queue(1, 1, RejectionPolicy::CallerRuns);
+
+        {
+            // occupy the only thread
+
+            let barrier = barrier.clone();
+
+            dispatcher.execute(Box::new(move || {
+                barrier.wait();
+            }));
+        }
+
+        for _ in 0..10 {
+            let counter = counter.clone();
+
+            dispatcher.execute(Box::new(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+            }));
+        }
+
+        // at most two tasks are held by the pool (one executing, one queued)
+        // so the rest must have been executed by this thread
+
+        assert!(counter.load(Ordering::SeqCst) >= 9);
+
+        barrier.wait();
+
+        eventually(Duration::from_millis(3000), move || {
+            counter.load(Ordering::SeqCst) == 10
+        });
+    }
+
+    #[test]
+    fn test_discard() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let barrier = Arc::new(Barrier::new(2));
+
+        let dispatcher = ThreadPoolDispatcher::with_bounded_queue(1, 1, RejectionPolicy::Discard);
+
+        {
+            let barrier = barrier.clone();
+
+            dispatcher.execute(Box::new(move || {
+                barrier.wait();
+            }));
+        }
+
+        // wait for the blocking task to be received so that the
+        // queue is empty
+
+        eventually(Duration::from_millis(3000), || dispatcher.sender.is_empty());
+
+        for _ in 0..10 {
+            let counter = counter.clone();
+
+            dispatcher.execute(Box::new(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+            }));
+        }
+
+        barrier.wait();
+
+        {
+            let counter = counter.clone();
+
+            eventually(Duration::from_millis(3000), move || {
+                counter.load(Ordering::SeqCst) == 1
+            });
+        }
+
+        std::thread::sleep(Duration::from_millis(100));
+
+        assert_eq!(counter.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_shutdown() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let barrier = Arc::new(Barrier::new(2));
+
+        let dispatcher = ThreadPoolDispatcher::with_bounded_queue(1, 1, RejectionPolicy::Block);
+
+        {
+            let barrier = barrier.clone();
+
+            dispatcher.execute(Box::new(move || {
+                barrier.wait();
+            }));
+        }
+
+        eventually(Duration::from_millis(3000), || dispatcher.sender.is_empty());
+
+        // the only thread is busy and the queue is full, so shutting
+        // down must not wait for either
+
+        {
+            let counter = counter.clone();
+
+            dispatcher.execute(Box::new(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+            }));
+        }
+
+        dispatcher.clone_box().shutdown();
+
+        // work that is submitted after shutdown is discarded rather
+        // than blocking on the full queue
+
+        {
+            let counter = counter.clone();
+
+            dispatcher.execute(Box::new(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+            }));
+        }
+
+        barrier.wait();
+
+        std::thread::sleep(Duration::from_millis(100));
+
+        // the queued work is discarded, as the thread exits once it
+        // has finished its current work
+
+        assert_eq!(counter.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn test_panic() {
+        let counter = Arc::new(AtomicUsize::new(0));
+
+        let dispatcher = ThreadPoolDispatcher::new(2);
+
+        for _ in 0..4 {
+            dispatcher.execute(Box::new(move || {
+                panic!("testing");
+            }));
+        }
+
+        for _ in 0..4 {
+            let counter = counter.clone();
+
+            dispatcher.execute(Box::new(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+            }));
+        }
+
+        eventually(Duration::from_millis(10000), move || {
+            counter.load(Ordering::SeqCst) == 4
+        });
+    }
+}
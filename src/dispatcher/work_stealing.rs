@@ -1,19 +1,27 @@
 use super::{DispatcherLogic, Thunk, Trampoline, TrampolineStep};
 use crossbeam::deque::{self as deque, Injector, Steal, Stealer, Worker};
+use parking_lot::{Condvar, Mutex, RwLock};
 use rand::rngs::SmallRng;
 use rand::{FromEntropy, Rng};
 use std::cell::RefCell;
+use std::sync::atomic::{self, AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
-use std::thread;
-use std::time;
-
-#[derive(Debug)]
-enum PollSchedule {
-    Aggressive(usize),
-    Moderate(usize),
-    Conservative(usize),
-    UltraConservative,
-}
+use std::thread::{self, Thread};
+use std::time::{Duration, Instant};
+
+/// How long work must be waiting without any idle workers before
+/// an elastic dispatcher spawns an additional worker.
+const GROW_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Number of times an idle worker yields before parking.
+const SPIN_ATTEMPTS: usize = 100;
+
+/// Number of trampoline steps to execute before rescheduling it.
+const TRAMPOLINE_LIMIT: usize = 10;
+
+/// A worker occasionally pulls in work from the injector while it
+/// still has work of its own, for fairness.
+const INLINE_STEAL_EVERY: usize = 1000;
 
 /// A work stealing dispatcher that is backed by crossbeam_deque.
 ///
@@ -29,16 +37,81 @@ enum PollSchedule {
 /// If an external thread submits tasks to the dispatcher, it is placed in an
 /// injector queue that is checked by the workers.
 ///
+/// Workers that cannot find any work are parked until work is submitted,
+/// rather than polling for it.
+///
+/// An elastic dispatcher (see `WorkStealingDispatcher::elastic`) additionally
+/// spawns workers when work is waiting and no workers are idle, e.g. when
+/// workers are blocked, and stops workers that have been idle for some time.
+///
 /// If a thread panics while executing, a new thread is spawned to take
 /// its place.
 pub struct WorkStealingDispatcher {
-    injector: Arc<Injector<WorkStealingDispatcherMessage>>,
-    num_workers: usize,
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    injector: Injector<WorkStealingDispatcherMessage>,
+    stealers: RwLock<Vec<(usize, Stealer<WorkStealingDispatcherMessage>)>>,
+    stealers_epoch: AtomicUsize,
+    next_worker_id: AtomicUsize,
+    workers: AtomicUsize,
+    sleeping: AtomicUsize,
+    sleep_lock: Mutex<()>,
+    sleep_condvar: Condvar,
+    supervisor: RwLock<Option<Thread>>,
+    elasticity: Option<Elasticity>,
+    task_queue_fifo: bool,
+    shutdown: AtomicBool,
+}
+
+/// A copy of the registered stealers, so that stealing and checking for
+/// work doesn't contend on the shared lock. It's refreshed whenever workers
+/// have been added or removed since it was taken.
+struct Stealers {
+    epoch: usize,
+    stealers: Vec<(usize, Stealer<WorkStealingDispatcherMessage>)>,
+}
+
+impl Stealers {
+    fn new(shared: &Shared) -> Self {
+        let mut stealers = Self {
+            epoch: 0,
+            stealers: Vec::new(),
+        };
+
+        stealers.take(shared);
+
+        stealers
+    }
+
+    fn refresh(&mut self, shared: &Shared) -> &[(usize, Stealer<WorkStealingDispatcherMessage>)] {
+        if shared.stealers_epoch.load(Ordering::SeqCst) != self.epoch {
+            self.take(shared);
+        }
+
+        &self.stealers
+    }
+
+    fn take(&mut self, shared: &Shared) {
+        // the epoch is only changed while the lock is held for writing
+
+        let stealers = shared.stealers.read();
+
+        self.epoch = shared.stealers_epoch.load(Ordering::SeqCst);
+        self.stealers = stealers.clone();
+    }
+}
+
+struct Elasticity {
+    parallelism_min: usize,
+    parallelism_max: usize,
+    keep_alive: Duration,
 }
 
 impl WorkStealingDispatcher {
     thread_local! {
-        static WORKER: RefCell<Option<Worker<WorkStealingDispatcherMessage>>> = RefCell::new(None);
+        static WORKER: RefCell<Option<(usize, Worker<WorkStealingDispatcherMessage>)>> = RefCell::new(None);
     }
 
     /// Creates a new dispatcher with the given parameters.
@@ -58,64 +131,108 @@ impl WorkStealingDispatcher {
     ///
     /// threads = min(parallelism_max, max(parallelism_min, cpus * parallelism_factor))
     pub fn new(parallelism: usize, task_queue_fifo: bool) -> Self {
-        let mut stealers = Vec::new();
-        let mut workers = Vec::new();
+        Self::start(parallelism, None, task_queue_fifo)
+    }
+
+    /// Creates a new elastic dispatcher with the given parameters.
+    ///
+    /// # Arguments
+    ///
+    /// * `parallelism_min` - Number of threads to start, and the minimum to keep
+    /// * `parallelism_max` - Maximum number of threads
+    /// * `keep_alive` - How long an idle thread is kept for when above `parallelism_min`
+    /// * `task_queue_fifo` - If true, execute tasks in FIFO order
+    ///
+    /// # Remarks
+    ///
+    /// A thread is added whenever work has been waiting for a short period of time
+    /// without any threads being idle, which is typically the case when the
+    /// dispatcher is overloaded or its threads are blocked.
+    pub fn elastic(
+        parallelism_min: usize,
+        parallelism_max: usize,
+        keep_alive: Duration,
+        task_queue_fifo: bool,
+    ) -> Self {
+        let parallelism_min = parallelism_min.max(1);
+        let parallelism_max = parallelism_max.max(parallelism_min);
+
+        let elasticity = Elasticity {
+            parallelism_min,
+            parallelism_max,
+            keep_alive,
+        };
+
+        Self::start(parallelism_min, Some(elasticity), task_queue_fifo)
+    }
+
+    fn start(parallelism: usize, elasticity: Option<Elasticity>, task_queue_fifo: bool) -> Self {
+        let elastic = elasticity.is_some();
+
+        let shared = Arc::new(Shared {
+            injector: Injector::new(),
+            stealers: RwLock::new(Vec::new()),
+            stealers_epoch: AtomicUsize::new(0),
+            next_worker_id: AtomicUsize::new(0),
+            workers: AtomicUsize::new(parallelism),
+            sleeping: AtomicUsize::new(0),
+            sleep_lock: Mutex::new(()),
+            sleep_condvar: Condvar::new(),
+            supervisor: RwLock::new(None),
+            elasticity,
+            task_queue_fifo,
+            shutdown: AtomicBool::new(false),
+        });
 
         for _ in 0..parallelism {
-            let w = if task_queue_fifo {
-                deque::Worker::new_fifo()
-            } else {
-                deque::Worker::new_lifo()
+            Self::spawn_worker(&shared);
+        }
+
+        if elastic {
+            let supervisor = {
+                let shared = shared.clone();
+
+                thread::spawn(move || Self::supervise(&shared))
             };
-            let s = w.stealer();
 
-            workers.push(w);
-            stealers.push(s);
+            *shared.supervisor.write() = Some(supervisor.thread().clone());
         }
 
-        let injector = Arc::new(Injector::new());
-
-        for i in (0..parallelism).rev() {
-            let injector = injector.clone();
+        Self { shared }
+    }
 
-            let mut stealers: Vec<Stealer<WorkStealingDispatcherMessage>> = stealers.to_vec();
+    /// Spawns a new worker, registering its stealer. The caller must
+    /// have accounted for it in the number of workers.
+    fn spawn_worker(shared: &Arc<Shared>) {
+        let worker = if shared.task_queue_fifo {
+            deque::Worker::new_fifo()
+        } else {
+            deque::Worker::new_lifo()
+        };
 
-            let w = workers.remove(i);
-            let _ = stealers.remove(i);
+        let id = shared.next_worker_id.fetch_add(1, Ordering::SeqCst);
 
-            Self::spawn_worker(w, stealers, injector);
-        }
+        shared.update_stealers(|stealers| stealers.push((id, worker.stealer())));
 
-        Self {
-            injector,
-            num_workers: parallelism,
-        }
+        Self::spawn_thread(shared.clone(), id, worker);
     }
 
-    fn spawn_worker(
-        worker: Worker<WorkStealingDispatcherMessage>,
-        stealers: Vec<Stealer<WorkStealingDispatcherMessage>>,
-        injector: Arc<Injector<WorkStealingDispatcherMessage>>,
-    ) {
+    fn spawn_thread(shared: Arc<Shared>, id: usize, worker: Worker<WorkStealingDispatcherMessage>) {
         struct Panicking {
-            stealers: Vec<Stealer<WorkStealingDispatcherMessage>>,
-            injector: Arc<Injector<WorkStealingDispatcherMessage>>,
+            shared: Arc<Shared>,
+            id: usize,
         }
 
         impl Drop for Panicking {
             fn drop(&mut self) {
                 if thread::panicking() {
                     WorkStealingDispatcher::WORKER.with(|w| {
-                        if let Some(worker) = w.replace(None) {
-                            let mut stealers = Vec::new();
-
-                            if let Some(stealer) = self.stealers.pop() {
-                                stealers.push(stealer);
-                            }
-
-                            let injector = self.injector.clone();
-
-                            WorkStealingDispatcher::spawn_worker(worker, stealers, injector);
+                        if let Some((_, worker)) = w.replace(None) {
+                            WorkStealingDispatcher::spawn_thread(
+                                self.shared.clone(),
+                                self.id,
+                                worker,
+                            );
                         }
                     });
                 }
@@ -124,330 +241,393 @@ impl WorkStealingDispatcher {
 
         thread::spawn(move || {
             let p = Panicking {
-                stealers: stealers.clone(),
-                injector: injector.clone(),
+                shared: shared.clone(),
+                id,
             };
 
+            let key = shared.key();
+
             Self::WORKER.with(|w| {
                 {
-                    *w.borrow_mut() = Some(worker);
+                    *w.borrow_mut() = Some((key, worker));
                 }
 
-                // we've chosen SmallRng because the main requirement
-                // is to be fast, not secure
-                let mut small_rng = SmallRng::from_entropy();
+                match *w.borrow() {
+                    Some((_, ref worker)) => {
+                        Self::work(&shared, id, worker);
+                    }
 
-                let mut schedule_state = PollSchedule::Aggressive(0);
+                    None => {
+                        panic!("pantomime bug: cannot initialize WorkStealingDispatcher thread");
+                    }
+                }
+            });
 
-                let inline_steal_every = 1000;
-                let inline_steal_injector_limit = 100;
-                let inline_steal_stealers_limit = 1;
+            Self::WORKER.with(|w| {
+                *w.borrow_mut() = None;
+            });
 
-                let trampoline_limit = 10;
+            shared.update_stealers(|stealers| stealers.retain(|(i, _)| *i != id));
 
-                let schedule_aggressive_attempts = 100;
-                let schedule_moderate_attempts = 100;
-                let schedule_conservative_attempts = 20;
+            drop(p);
+        });
+    }
 
-                let schedule_moderate = time::Duration::from_micros(50);
-                let schedule_conservative = time::Duration::from_millis(10);
-                let schedule_ultra_conservative = time::Duration::from_millis(100);
+    fn work(shared: &Shared, id: usize, worker: &Worker<WorkStealingDispatcherMessage>) {
+        // we've chosen SmallRng because the main requirement
+        // is to be fast, not secure
+        let mut small_rng = SmallRng::from_entropy();
 
-                let l = stealers.len();
+        let mut stealers = Stealers::new(shared);
+        let mut since_inline_steal: usize = 0;
+        let mut spins: usize = 0;
 
-                match *w.borrow() {
-                    Some(ref worker) => {
-                        loop {
-                            let mut work_available = true;
-
-                            if work_available {
-                                let mut i = 0;
-
-                                loop {
-                                    match worker.pop() {
-                                        Some(work) => {
-                                            // @TODO this is duplicated below
-                                            match work {
-                                                WorkStealingDispatcherMessage::Execute(thunk) => {
-                                                    thunk.apply();
-                                                }
-
-                                                WorkStealingDispatcherMessage::ExecuteTrampoline(trampoline) => {
-                                                    let mut i = 0;
-                                                    let mut step = trampoline.step;
-
-                                                    while let TrampolineStep::Bounce(next_step) = step {
-                                                        if i == trampoline_limit {
-                                                            worker.push(
-                                                                WorkStealingDispatcherMessage::ExecuteTrampoline(
-                                                                    Trampoline { step: TrampolineStep::Bounce(next_step) }
-                                                                )
-                                                            );
-
-                                                            break;
-                                                        } else {
-                                                            i += 1;
-                                                            step = next_step.apply().step;
-                                                        }
-                                                    }
-                                                }
-
-                                                WorkStealingDispatcherMessage::Shutdown => {
-                                                    return;
-                                                }
-                                            }
-
-                                            i += 1;
-
-                                            if i % inline_steal_every == 0 {
-                                                // we occasionally pull in some tasks that may
-                                                // be sitting there from the injector and add
-                                                // them to the queue. they may then be stolen
-                                                // by other idle workers
-                                                for _ in 0..inline_steal_injector_limit {
-                                                    let mut stolen = false;
-
-                                                    loop {
-                                                        match injector.steal() {
-                                                            Steal::Success(work) => {
-                                                                stolen = true;
-                                                                worker.push(work);
-                                                                break;
-                                                            }
-
-                                                            Steal::Empty => {
-                                                                break;
-                                                            }
-
-                                                            Steal::Retry => {}
-                                                        }
-                                                    }
-
-                                                    if !stolen {
-                                                        break;
-                                                    }
-                                                }
-
-                                                if l > 0 {
-                                                    for _ in 0..inline_steal_stealers_limit {
-                                                        let r: usize = small_rng.gen();
-                                                        let mut stolen = false;
-
-                                                        loop {
-                                                            match stealers[r % l].steal() {
-                                                                Steal::Success(work) => {
-                                                                    stolen = true;
-                                                                    worker.push(work);
-                                                                    break;
-                                                                }
-
-                                                                Steal::Empty => {
-                                                                    break;
-                                                                }
-
-                                                                Steal::Retry => {}
-                                                            }
-                                                        }
-
-                                                        if !stolen {
-                                                            break;
-                                                        }
-                                                    }
-                                                }
-
-                                                // as well as attempt to steal a bit before
-                                                // working on our tasks some more
-                                            }
-                                        }
-
-                                        None => {
-                                            // our worker is empty, and since only this thread
-                                            // can insert items into it, it's time to start
-                                            // stealing
-
-                                            work_available = false;
-                                            break;
-                                        }
-                                    }
-                                }
-                            }
-
-                            // next, steal as much as we can from the injector
-
-                            loop {
-                                match injector.steal() {
-                                    Steal::Success(work) => {
-                                        worker.push(work);
-                                        work_available = true;
-                                        break;
-                                    }
-
-                                    Steal::Empty => {
-                                        break;
-                                    }
-
-                                    Steal::Retry => {}
-                                }
-                            }
-
-                            // our worker is either empty, or reached max for fairness,
-                            // so now let's try to steal from people randomly
-
-                            if !work_available && l > 0 {
-                                let r: usize = small_rng.gen();
-
-                                loop {
-                                    match stealers[r % l].steal() {
-                                        Steal::Success(work) => {
-                                            // @TODO this is duplicated above
-                                            match work {
-                                                WorkStealingDispatcherMessage::Execute(thunk) => {
-                                                    thunk.apply();
-                                                }
-
-                                                WorkStealingDispatcherMessage::ExecuteTrampoline(trampoline) => {
-                                                    let mut i = 0;
-                                                    let mut step = trampoline.step;
-
-                                                    while let TrampolineStep::Bounce(next_step) = step {
-                                                        if i == trampoline_limit {
-                                                            worker.push(
-                                                                WorkStealingDispatcherMessage::ExecuteTrampoline(
-                                                                    Trampoline { step: TrampolineStep::Bounce(next_step) }
-                                                                )
-                                                            );
-
-                                                            break;
-                                                        } else {
-                                                            i += 1;
-                                                            step = next_step.apply().step;
-                                                        }
-                                                    }
-                                                }
-
-                                                WorkStealingDispatcherMessage::Shutdown => {
-                                                    return;
-                                                }
-                                            }
-
-                                            work_available = true;
-                                            break;
-                                        }
-
-                                        Steal::Empty => {
-                                            break;
-                                        }
-
-                                        Steal::Retry => {}
-                                    }
-                                }
-                            }
-
-                            // if any work was performed, immediately continue, otherwise'
-                            // we go into a backoff to avoid spinning
-
-                            if !work_available {
-                                match schedule_state {
-                                    PollSchedule::Aggressive(n)
-                                        if n < schedule_aggressive_attempts =>
-                                    {
-                                        schedule_state = PollSchedule::Aggressive(n + 1);
-                                        thread::yield_now();
-                                    }
-
-                                    PollSchedule::Aggressive(_) => {
-                                        schedule_state = PollSchedule::Moderate(1);
-                                        thread::park_timeout(schedule_moderate);
-                                    }
-
-                                    PollSchedule::Moderate(n) if n < schedule_moderate_attempts => {
-                                        schedule_state = PollSchedule::Moderate(n + 1);
-                                        thread::park_timeout(schedule_moderate);
-                                    }
-
-                                    PollSchedule::Moderate(_) => {
-                                        schedule_state = PollSchedule::Conservative(1);
-                                        thread::park_timeout(schedule_conservative);
-                                    }
-
-                                    PollSchedule::Conservative(n)
-                                        if n < schedule_conservative_attempts =>
-                                    {
-                                        schedule_state = PollSchedule::Conservative(n + 1);
-                                        thread::park_timeout(schedule_conservative);
-                                    }
-
-                                    PollSchedule::Conservative(_) => {
-                                        schedule_state = PollSchedule::UltraConservative;
-                                        thread::park_timeout(schedule_ultra_conservative);
-                                    }
-
-                                    PollSchedule::UltraConservative => {
-                                        thread::park_timeout(schedule_ultra_conservative);
-                                    }
-                                }
-                            } else {
-                                schedule_state = PollSchedule::Aggressive(0);
-                            }
-                        }
+        loop {
+            // first, work on our own tasks
+
+            if let Some(work) = worker.pop() {
+                Self::run(work, worker);
+
+                since_inline_steal += 1;
+                spins = 0;
+
+                if since_inline_steal == INLINE_STEAL_EVERY {
+                    since_inline_steal = 0;
+
+                    // we occasionally pull in some tasks that may
+                    // be sitting there from the injector and add
+                    // them to the queue. they may then be stolen
+                    // by other idle workers
+
+                    while let Steal::Retry = shared.injector.steal_batch(worker) {}
+                }
+
+                continue;
+            }
+
+            // next, steal as much as we can from the injector
+
+            if let Some(work) = Self::steal(|| shared.injector.steal_batch_and_pop(worker)) {
+                Self::run(work, worker);
+
+                spins = 0;
+
+                continue;
+            }
+
+            // our worker is empty, so now let's try to steal from others
+
+            if let Some(work) = shared.steal_from_others(id, &mut stealers, &mut small_rng) {
+                Self::run(work, worker);
+
+                spins = 0;
+
+                continue;
+            }
+
+            // no work is available, so we spin a little before parking
+
+            if spins < SPIN_ATTEMPTS {
+                spins += 1;
+
+                thread::yield_now();
+            } else {
+                spins = 0;
+
+                if shared.sleep(&mut stealers) {
+                    return;
+                }
+            }
+        }
+    }
+
+    fn run(work: WorkStealingDispatcherMessage, worker: &Worker<WorkStealingDispatcherMessage>) {
+        match work {
+            WorkStealingDispatcherMessage::Execute(thunk) => {
+                thunk.apply();
+            }
+
+            WorkStealingDispatcherMessage::ExecuteTrampoline(trampoline) => {
+                let mut i = 0;
+                let mut step = trampoline.step;
+
+                while let TrampolineStep::Bounce(next_step) = step {
+                    if i == TRAMPOLINE_LIMIT {
+                        worker.push(WorkStealingDispatcherMessage::ExecuteTrampoline(
+                            Trampoline {
+                                step: TrampolineStep::Bounce(next_step),
+                            },
+                        ));
+
+                        break;
+                    } else {
+                        i += 1;
+                        step = next_step.apply().step;
                     }
+                }
+            }
+        }
+    }
 
-                    None => {
-                        panic!("pantomime bug: cannot initialize WorkStealingDispatcher thread");
+    fn steal<F: FnMut() -> Steal<WorkStealingDispatcherMessage>>(
+        mut f: F,
+    ) -> Option<WorkStealingDispatcherMessage> {
+        loop {
+            match f() {
+                Steal::Success(work) => {
+                    return Some(work);
+                }
+
+                Steal::Empty => {
+                    return None;
+                }
+
+                Steal::Retry => {}
+            }
+        }
+    }
+
+    fn supervise(shared: &Arc<Shared>) {
+        let parallelism_max = match shared.elasticity {
+            Some(ref elasticity) => elasticity.parallelism_max,
+            None => return,
+        };
+
+        let mut stealers = Stealers::new(shared);
+
+        while !shared.shutdown.load(Ordering::SeqCst) {
+            if shared.is_saturated(&mut stealers)
+                && shared.workers.load(Ordering::SeqCst) < parallelism_max
+            {
+                // work is waiting and nobody is idle, so if that's still the
+                // case after a short period, we'll add another worker. note that
+                // we're frequently unparked by submitters in this state
+
+                let start = Instant::now();
+
+                while !shared.shutdown.load(Ordering::SeqCst) {
+                    let elapsed = start.elapsed();
+
+                    if elapsed >= GROW_INTERVAL {
+                        break;
                     }
+
+                    thread::park_timeout(GROW_INTERVAL - elapsed);
                 }
-            });
 
-            drop(p);
+                if shared.is_saturated(&mut stealers) && shared.try_grow(parallelism_max) {
+                    Self::spawn_worker(shared);
+                }
+            } else {
+                // a submit only unparks us when no workers are sleeping, but
+                // a worker that was just woken still counts as sleeping, so
+                // we check again periodically rather than relying on it
+
+                thread::park_timeout(GROW_INTERVAL);
+            }
+        }
+    }
+
+    fn submit(&self, work: WorkStealingDispatcherMessage) {
+        let key = self.shared.key();
+
+        let work = Self::WORKER.with(|w| match *w.borrow() {
+            Some((k, ref worker)) if k == key => {
+                worker.push(work);
+
+                None
+            }
+
+            _ => Some(work),
         });
 
-        // @TODO unset the thread local storage here
+        if let Some(work) = work {
+            self.shared.injector.push(work);
+        }
+
+        self.shared.notify();
+    }
+}
+
+impl Shared {
+    /// Identifies the dispatcher that a worker thread belongs to, as
+    /// worker threads may submit work to other dispatchers.
+    fn key(&self) -> usize {
+        self as *const Shared as usize
+    }
+
+    /// Adds or removes stealers, which causes workers to refresh their
+    /// copies of them.
+    fn update_stealers<F: FnOnce(&mut Vec<(usize, Stealer<WorkStealingDispatcherMessage>)>)>(
+        &self,
+        f: F,
+    ) {
+        let mut stealers = self.stealers.write();
+
+        f(&mut stealers);
+
+        self.stealers_epoch.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn has_work(&self, stealers: &mut Stealers) -> bool {
+        !self.injector.is_empty() || stealers.refresh(self).iter().any(|(_, s)| !s.is_empty())
+    }
+
+    fn is_saturated(&self, stealers: &mut Stealers) -> bool {
+        self.sleeping.load(Ordering::SeqCst) == 0 && self.has_work(stealers)
+    }
+
+    fn notify(&self) {
+        atomic::fence(Ordering::SeqCst);
+
+        if self.sleeping.load(Ordering::SeqCst) > 0 {
+            let _guard = self.sleep_lock.lock();
+
+            self.sleep_condvar.notify_one();
+        } else if let Some(ref elasticity) = self.elasticity {
+            if self.workers.load(Ordering::Relaxed) < elasticity.parallelism_max {
+                if let Some(ref supervisor) = *self.supervisor.read() {
+                    supervisor.unpark();
+                }
+            }
+        }
+    }
+
+    /// Parks the current worker until there's work available. Returns
+    /// true if the worker should stop.
+    fn sleep(&self, stealers: &mut Stealers) -> bool {
+        let mut guard = self.sleep_lock.lock();
+
+        self.sleeping.fetch_add(1, Ordering::SeqCst);
+
+        atomic::fence(Ordering::SeqCst);
+
+        let stop = if self.has_work(stealers) {
+            false
+        } else if self.shutdown.load(Ordering::SeqCst) {
+            self.workers.fetch_sub(1, Ordering::SeqCst);
+
+            true
+        } else {
+            match self.elasticity {
+                Some(ref elasticity) => {
+                    let timed_out = self
+                        .sleep_condvar
+                        .wait_for(&mut guard, elasticity.keep_alive)
+                        .timed_out();
+
+                    timed_out
+                        && !self.has_work(stealers)
+                        && self.try_shrink(elasticity.parallelism_min)
+                }
+
+                None => {
+                    self.sleep_condvar.wait(&mut guard);
+
+                    false
+                }
+            }
+        };
+
+        self.sleeping.fetch_sub(1, Ordering::SeqCst);
+
+        stop
+    }
+
+    fn steal_from_others(
+        &self,
+        id: usize,
+        stealers: &mut Stealers,
+        small_rng: &mut SmallRng,
+    ) -> Option<WorkStealingDispatcherMessage> {
+        let stealers = stealers.refresh(self);
+        let l = stealers.len();
+
+        if l > 1 {
+            let r: usize = small_rng.gen();
+
+            for i in 0..l {
+                let (stealer_id, ref stealer) = stealers[(r + i) % l];
+
+                if stealer_id != id {
+                    if let Some(work) = WorkStealingDispatcher::steal(|| stealer.steal()) {
+                        return Some(work);
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    fn try_grow(&self, parallelism_max: usize) -> bool {
+        let mut current = self.workers.load(Ordering::SeqCst);
+
+        while current < parallelism_max {
+            match self.workers.compare_exchange(
+                current,
+                current + 1,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            ) {
+                Ok(_) => return true,
+                Err(actual) => current = actual,
+            }
+        }
+
+        false
+    }
+
+    fn try_shrink(&self, parallelism_min: usize) -> bool {
+        let mut current = self.workers.load(Ordering::SeqCst);
+
+        while current > parallelism_min {
+            match self.workers.compare_exchange(
+                current,
+                current - 1,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            ) {
+                Ok(_) => return true,
+                Err(actual) => current = actual,
+            }
+        }
+
+        false
     }
 }
 
 enum WorkStealingDispatcherMessage {
     Execute(Thunk),
     ExecuteTrampoline(Trampoline),
-    Shutdown,
 }
 
 impl DispatcherLogic for WorkStealingDispatcher {
     fn clone_box(&self) -> Box<dyn DispatcherLogic + 'static + Send + Sync> {
         Box::new(Self {
-            injector: self.injector.clone(),
-            num_workers: self.num_workers,
+            shared: self.shared.clone(),
         })
     }
 
     fn execute(&self, thunk: Thunk) {
-        Self::WORKER.with(|w| match *w.borrow() {
-            Some(ref worker) => {
-                worker.push(WorkStealingDispatcherMessage::Execute(thunk));
-            }
-
-            None => {
-                self.injector
-                    .push(WorkStealingDispatcherMessage::Execute(thunk));
-            }
-        });
+        self.submit(WorkStealingDispatcherMessage::Execute(thunk));
     }
 
     fn execute_trampoline(&self, trampoline: Trampoline) {
-        Self::WORKER.with(|w| match *w.borrow() {
-            Some(ref worker) => {
-                worker.push(WorkStealingDispatcherMessage::ExecuteTrampoline(trampoline));
-            }
-
-            None => {
-                self.injector
-                    .push(WorkStealingDispatcherMessage::ExecuteTrampoline(trampoline));
-            }
-        });
+        self.submit(WorkStealingDispatcherMessage::ExecuteTrampoline(trampoline));
     }
 
     fn shutdown(self: Box<Self>) {
-        for _ in 0..self.num_workers {
-            self.injector.push(WorkStealingDispatcherMessage::Shutdown);
+        self.shared.shutdown.store(true, Ordering::SeqCst);
+
+        {
+            let _guard = self.shared.sleep_lock.lock();
+
+            self.shared.sleep_condvar.notify_all();
+        }
+
+        if let Some(ref supervisor) = *self.shared.supervisor.read() {
+            supervisor.unpark();
         }
     }
 }
@@ -456,8 +636,9 @@ impl DispatcherLogic for WorkStealingDispatcher {
 mod tests {
     use super::*;
     use crate::testkit::*;
+    use parking_lot::Mutex;
     use std::sync::atomic::{AtomicUsize, Ordering};
-    use std::sync::Arc;
+    use std::sync::{Arc, Barrier};
     use std::time::Duration;
 
     #[test]
@@ -522,4 +703,162 @@ mod tests {
             counter.load(Ordering::SeqCst) == 16
         });
     }
+
+    #[test]
+    fn test_nested_dispatchers() {
+        // work submitted to another dispatcher from a worker thread
+        // must not end up in the worker's own deque
+
+        let thread_ids = Arc::new(Mutex::new(Vec::new()));
+
+        let outer = WorkStealingDispatcher::new(1, false);
+        let inner = WorkStealingDispatcher::new(1, false);
+
+        for _ in 0..10 {
+            let inner = inner.clone_box();
+            let thread_ids = thread_ids.clone();
+
+            outer.execute(Box::new(move || {
+                let outer_thread_id = thread::current().id();
+
+                inner.execute(Box::new(move || {
+                    thread_ids
+                        .lock()
+                        .push(outer_thread_id != thread::current().id());
+                }));
+            }));
+        }
+
+        eventually(Duration::from_millis(3000), move || {
+            let thread_ids = thread_ids.lock();
+
+            thread_ids.len() == 10 && thread_ids.iter().all(|different| *different)
+        });
+    }
+
+    #[test]
+    fn test_elastic_grow() {
+        // all tasks block until every task has started, which requires
+        // the dispatcher to grow from one thread to four
+
+        let counter = Arc::new(AtomicUsize::new(0));
+        let barrier = Arc::new(Barrier::new(4));
+
+        let dispatcher = WorkStealingDispatcher::elastic(1, 4, Duration::from_secs(60), true);
+
+        for _ in 0..4 {
+            let barrier = barrier.clone();
+            let counter = counter.clone();
+
+            dispatcher.execute(Box::new(move || {
+                barrier.wait();
+                counter.fetch_add(1, Ordering::SeqCst);
+            }));
+        }
+
+        eventually(Duration::from_millis(3000), move || {
+            counter.load(Ordering::SeqCst) == 4
+        });
+
+        assert_eq!(dispatcher.shared.workers.load(Ordering::SeqCst), 4);
+    }
+
+    #[test]
+    fn test_elastic_grow_after_wakeup() {
+        // the first task blocks until the second one runs. the second is
+        // submitted while the only worker is still waking up for the first,
+        // so it still counts as sleeping and the submit doesn't unpark the
+        // supervisor
+
+        for _ in 0..10 {
+            let counter = Arc::new(AtomicUsize::new(0));
+            let (sender, receiver) = crossbeam::channel::bounded::<()>(1);
+
+            let dispatcher = WorkStealingDispatcher::elastic(1, 2, Duration::from_secs(60), true);
+
+            // wait for the worker to park
+
+            thread::sleep(Duration::from_millis(20));
+
+            {
+                let counter = counter.clone();
+
+                dispatcher.execute(Box::new(move || {
+                    receiver.recv().unwrap();
+                    counter.fetch_add(1, Ordering::SeqCst);
+                }));
+            }
+
+            dispatcher.execute(Box::new(move || {
+                sender.send(()).unwrap();
+            }));
+
+            eventually(Duration::from_millis(3000), move || {
+                counter.load(Ordering::SeqCst) == 1
+            });
+
+            Box::new(dispatcher).shutdown();
+        }
+    }
+
+    #[test]
+    fn test_elastic_shrink() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let barrier = Arc::new(Barrier::new(3));
+
+        let dispatcher = WorkStealingDispatcher::elastic(1, 3, Duration::from_millis(100), true);
+
+        for _ in 0..3 {
+            let barrier = barrier.clone();
+            let counter = counter.clone();
+
+            dispatcher.execute(Box::new(move || {
+                barrier.wait();
+                counter.fetch_add(1, Ordering::SeqCst);
+            }));
+        }
+
+        {
+            let counter = counter.clone();
+
+            eventually(Duration::from_millis(3000), move || {
+                counter.load(Ordering::SeqCst) == 3
+            });
+        }
+
+        {
+            let shared = dispatcher.shared.clone();
+
+            eventually(Duration::from_millis(3000), move || {
+                shared.workers.load(Ordering::SeqCst) == 1 && shared.stealers.read().len() == 1
+            });
+        }
+
+        // the remaining worker still executes work
+
+        for _ in 0..10 {
+            let counter = counter.clone();
+
+            dispatcher.execute(Box::new(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+            }));
+        }
+
+        eventually(Duration::from_millis(3000), move || {
+            counter.load(Ordering::SeqCst) == 13
+        });
+    }
+
+    #[test]
+    fn test_shutdown() {
+        let dispatcher = WorkStealingDispatcher::elastic(2, 4, Duration::from_secs(60), true);
+
+        let shared = dispatcher.shared.clone();
+
+        Box::new(dispatcher).shutdown();
+
+        eventually(Duration::from_millis(3000), move || {
+            shared.workers.load(Ordering::SeqCst) == 0 && shared.stealers.read().is_empty()
+        });
+    }
 }
@@ -0,0 +1,288 @@
+//! A minimal HTTP/1.1 server that is built on streams.
+//!
+//! Each connection is served by a stream that parses requests from the
+//! connection's flow with a `RequestParser`, passes them through a handler
+//! `Flow`, and writes them back to the connection's flow with a
+//! `ResponseSerializer`. Requests are only parsed once the handler is ready
+//! for them, so a slow handler backpressures the client.
+//!
+//! Connections are kept alive unless the request or response asks for them
+//! to be closed, and pipelined requests are answered in order. Request
+//! bodies may be delimited by `Content-Length` or use chunked transfer
+//! coding, and are made available in their entirety. A response is sent
+//! with chunked transfer coding if it has a `Transfer-Encoding: chunked`
+//! header, and otherwise with a `Content-Length`.
+//!
+//! To handle requests with an actor, use `Flow::ask` as the handler.
+
+use crate::actor::{Actor, ActorContext, Signal};
+use crate::stream::flow::Tcp;
+use crate::stream::sink::fan_out::Broadcast;
+use crate::stream::source::tcp::TcpConnection;
+use crate::stream::{FanOutCancellation, Flow, Graph, Sink, Source, Stream};
+use std::io;
+use std::net::SocketAddr;
+
+mod request;
+mod response;
+
+pub use self::request::RequestParser;
+pub use self::response::ResponseSerializer;
+
+#[cfg(test)]
+mod tests;
+
+/// The largest request head, i.e. request line and headers, that is accepted.
+pub const MAX_HEAD_SIZE: usize = 64 * 1024;
+
+/// The largest request body that is accepted.
+pub const MAX_BODY_SIZE: usize = 8 * 1024 * 1024;
+
+/// The maximum number of headers in a request.
+pub const MAX_HEADERS: usize = 64;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Version {
+    Http10,
+    Http11,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Header {
+    pub name: String,
+    pub value: Vec<u8>,
+}
+
+impl Header {
+    pub fn new<N: Into<String>, V: Into<Vec<u8>>>(name: N, value: V) -> Self {
+        Self {
+            name: name.into(),
+            value: value.into(),
+        }
+    }
+
+    /// Determine whether this header's value is a comma separated list that
+    /// contains the supplied token, ignoring case.
+    fn has_token(&self, token: &str) -> bool {
+        self.value
+            .split(|b| *b == b',')
+            .any(|t| trim(t).eq_ignore_ascii_case(token.as_bytes()))
+    }
+}
+
+pub struct Request {
+    method: String,
+    path: String,
+    version: Version,
+    headers: Vec<Header>,
+    body: Vec<u8>,
+}
+
+impl Request {
+    pub fn method(&self) -> &str {
+        &self.method
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    pub fn headers(&self) -> &[Header] {
+        &self.headers
+    }
+
+    /// Find the value of the first header with the supplied name, ignoring
+    /// case.
+    pub fn header(&self, name: &str) -> Option<&[u8]> {
+        find_header(&self.headers, name).map(|h| &h.value[..])
+    }
+
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    pub fn into_body(self) -> Vec<u8> {
+        self.body
+    }
+
+    /// Determine whether the client wishes to keep the connection open
+    /// after this request has been answered.
+    pub fn keep_alive(&self) -> bool {
+        match find_header(&self.headers, "connection") {
+            Some(header) if header.has_token("close") => false,
+            Some(header) if header.has_token("keep-alive") => true,
+            _ => self.version == Version::Http11,
+        }
+    }
+}
+
+pub struct Response {
+    status: u16,
+    headers: Vec<Header>,
+    body: Vec<u8>,
+}
+
+impl Response {
+    pub fn new(status: u16) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+
+    pub fn ok() -> Self {
+        Self::new(200)
+    }
+
+    pub fn with_header<N: Into<String>, V: Into<Vec<u8>>>(mut self, name: N, value: V) -> Self {
+        self.headers.push(Header::new(name, value));
+        self
+    }
+
+    pub fn with_body<B: Into<Vec<u8>>>(mut self, body: B) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    pub fn status(&self) -> u16 {
+        self.status
+    }
+
+    pub fn headers(&self) -> &[Header] {
+        &self.headers
+    }
+
+    /// Find the value of the first header with the supplied name, ignoring
+    /// case.
+    pub fn header(&self, name: &str) -> Option<&[u8]> {
+        find_header(&self.headers, name).map(|h| &h.value[..])
+    }
+
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+}
+
+/// Create a stream that serves the requests received on the supplied
+/// connection with the supplied handler. The stream completes when the
+/// connection has been closed.
+///
+/// The connection is closed once the client has closed its side, or once
+/// a response that closes the connection has been written.
+pub fn serve(connection: TcpConnection, handler: Flow<Request, Response>) -> Stream<Vec<()>> {
+    let mut graph = Graph::new();
+
+    let connection = Flow::from_logic(Tcp::new(connection.into_stream()).without_half_close());
+
+    let (connection_in, connection_out) = graph.add_flow(connection);
+    let (parser_in, parser_out) = graph.add_flow(Flow::from_logic(RequestParser::new()));
+    let (handler_in, handler_out) = graph.add_flow(handler);
+    let (serializer_in, serializer_out) =
+        graph.add_flow(Flow::from_logic(ResponseSerializer::new()));
+
+    // the data that is read is also broadcast to a sink, which completes
+    // the stream once the connection has been closed
+
+    let broadcast = graph.add_junction(Broadcast::new(2, FanOutCancellation::Lazy));
+    let closed = graph.add_sink(Sink::ignore());
+
+    graph.connect(connection_out, broadcast.inlet(0));
+    graph.connect(broadcast.outlet(0), parser_in);
+    graph.connect(broadcast.outlet(1), closed);
+    graph.connect(parser_out, handler_in);
+    graph.connect(handler_out, serializer_in);
+    graph.connect(serializer_out, connection_in);
+
+    graph.stream().expect("pantomime bug: invalid HTTP graph")
+}
+
+pub enum HttpServerMsg {
+    Connection(TcpConnection),
+    Unbound,
+}
+
+/// An actor that binds to an address and serves each connection with a
+/// handler that is created by the supplied function.
+///
+/// The address is bound when the server is created, so that failing to bind
+/// it can be handled by the caller.
+pub struct HttpServer<F> {
+    source: Option<Source<TcpConnection>>,
+    local_address: SocketAddr,
+    handler: F,
+}
+
+impl<F> HttpServer<F>
+where
+    F: Fn() -> Flow<Request, Response>,
+{
+    pub fn new(address: &SocketAddr, handler: F) -> io::Result<Self> {
+        let (source, local_address) = Source::tcp_bind(address)?;
+
+        Ok(Self {
+            source: Some(source),
+            local_address,
+            handler,
+        })
+    }
+
+    /// The address that the server is bound to.
+    pub fn local_address(&self) -> SocketAddr {
+        self.local_address
+    }
+}
+
+impl<F> Actor for HttpServer<F>
+where
+    F: 'static + Fn() -> Flow<Request, Response> + Send,
+{
+    type Msg = HttpServerMsg;
+
+    fn receive(&mut self, msg: HttpServerMsg, ctx: &mut ActorContext<HttpServerMsg>) {
+        match msg {
+            HttpServerMsg::Connection(connection) => {
+                ctx.spawn(serve(connection, (self.handler)()));
+            }
+
+            HttpServerMsg::Unbound => {
+                ctx.stop();
+            }
+        }
+    }
+
+    fn receive_signal(&mut self, signal: Signal, ctx: &mut ActorContext<HttpServerMsg>) {
+        if let Signal::Started = signal {
+            let actor_ref = ctx.actor_ref().clone();
+
+            let (_, result) = ctx.spawn(self.source.take().unwrap().to(Sink::for_each(
+                move |connection| actor_ref.tell(HttpServerMsg::Connection(connection)),
+            )));
+
+            ctx.watch_result(result, |_| HttpServerMsg::Unbound);
+        }
+    }
+}
+
+fn find_header<'a>(headers: &'a [Header], name: &str) -> Option<&'a Header> {
+    headers.iter().find(|h| h.name.eq_ignore_ascii_case(name))
+}
+
+fn trim(bytes: &[u8]) -> &[u8] {
+    let start = bytes
+        .iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .unwrap_or(bytes.len());
+
+    let end = bytes
+        .iter()
+        .rposition(|b| !b.is_ascii_whitespace())
+        .map(|i| i + 1)
+        .unwrap_or(start);
+
+    &bytes[start..end]
+}
@@ -0,0 +1,375 @@
+use super::{find_header, trim, Header, Request, Version};
+use super::{MAX_BODY_SIZE, MAX_HEADERS, MAX_HEAD_SIZE};
+use crate::actor::{FailureError, FailureReason};
+use crate::stream::{Action, Logic, LogicEvent, StreamContext};
+use std::io::{self, ErrorKind as IoErrorKind};
+use std::mem;
+use std::str;
+
+enum BodyLength {
+    Fixed(usize),
+    Chunked(ChunkedBody),
+}
+
+/// A request whose head has been parsed, awaiting its body.
+pub(super) struct Head {
+    request: Request,
+    length: usize,
+    body_length: BodyLength,
+}
+
+/// A flow that parses the requests in the data read from a connection.
+/// Data is only pulled once a request has been pulled and the buffered
+/// data doesn't contain one.
+///
+/// The flow completes once upstream completes, discarding any partial
+/// request, or after emitting a request that doesn't keep the connection
+/// alive, in which case it cancels upstream. It fails if a request is
+/// malformed.
+pub struct RequestParser {
+    buffer: Vec<u8>,
+    head: Option<Head>,
+    pulled: bool,
+    stopped: bool,
+}
+
+impl RequestParser {
+    pub fn new() -> Self {
+        Self {
+            buffer: Vec::new(),
+            head: None,
+            pulled: false,
+            stopped: false,
+        }
+    }
+
+    fn try_push(&mut self, ctx: &mut StreamContext<Vec<u8>, Request, ()>) -> Action<Request, ()> {
+        match self.parse() {
+            Ok(Some(request)) => {
+                self.pulled = false;
+
+                if request.keep_alive() {
+                    Action::Push(request)
+                } else {
+                    self.stop(Action::PushAndStop(request, None), ctx)
+                }
+            }
+
+            Ok(None) if !self.stopped => Action::Pull,
+
+            // any partial request is discarded
+            Ok(None) => Action::Stop(None),
+
+            Err(e) => {
+                let reason = Some(FailureReason::Errored(FailureError::new(e)));
+
+                self.stop(Action::Stop(reason), ctx)
+            }
+        }
+    }
+
+    /// Parse a request from the front of the buffer, if a complete one is
+    /// available.
+    fn parse(&mut self) -> io::Result<Option<Request>> {
+        if self.head.is_none() {
+            self.head = parse_head(&self.buffer)?;
+        }
+
+        let end = match self.head {
+            Some(ref mut head) => match head.body_length {
+                BodyLength::Fixed(length) if self.buffer.len() >= head.length + length => {
+                    let body = self.buffer[head.length..head.length + length].to_vec();
+
+                    Some((body, head.length + length))
+                }
+
+                BodyLength::Fixed(_) => None,
+
+                BodyLength::Chunked(ref mut chunked) => chunked.parse(&self.buffer)?,
+            },
+
+            None => None,
+        };
+
+        match end {
+            Some((body, end)) => {
+                let mut request = self
+                    .head
+                    .take()
+                    .expect("pantomime bug: RequestParser::head is None")
+                    .request;
+
+                request.body = body;
+
+                self.buffer.drain(..end);
+
+                Ok(Some(request))
+            }
+
+            None => Ok(None),
+        }
+    }
+
+    /// Perform the supplied action, which stops the flow, cancelling
+    /// upstream if it hasn't stopped.
+    fn stop(
+        &mut self,
+        action: Action<Request, ()>,
+        ctx: &mut StreamContext<Vec<u8>, Request, ()>,
+    ) -> Action<Request, ()> {
+        self.buffer.clear();
+        self.head = None;
+
+        if self.stopped {
+            action
+        } else {
+            self.stopped = true;
+
+            ctx.tell(action);
+
+            Action::Cancel
+        }
+    }
+}
+
+impl Default for RequestParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Logic<Vec<u8>, Request> for RequestParser {
+    type Ctl = ();
+
+    fn name(&self) -> &'static str {
+        "pantomime::http::RequestParser"
+    }
+
+    fn receive(
+        &mut self,
+        msg: LogicEvent<Vec<u8>, Self::Ctl>,
+        ctx: &mut StreamContext<Vec<u8>, Request, Self::Ctl>,
+    ) -> Action<Request, Self::Ctl> {
+        match msg {
+            LogicEvent::Pulled => {
+                self.pulled = true;
+
+                self.try_push(ctx)
+            }
+
+            LogicEvent::Pushed(data) => {
+                self.buffer.extend_from_slice(&data);
+
+                // we only pull once we've been pulled, so there is demand
+
+                self.try_push(ctx)
+            }
+
+            LogicEvent::Stopped if self.stopped => Action::None,
+
+            LogicEvent::Stopped => {
+                self.stopped = true;
+
+                if self.pulled {
+                    self.try_push(ctx)
+                } else {
+                    Action::None
+                }
+            }
+
+            LogicEvent::Cancelled => Action::Cancel,
+
+            LogicEvent::Started | LogicEvent::Forwarded(()) => Action::None,
+        }
+    }
+}
+
+fn invalid<E: ToString>(error: E) -> io::Error {
+    io::Error::new(IoErrorKind::InvalidData, error.to_string())
+}
+
+pub(super) fn parse_head(buffer: &[u8]) -> io::Result<Option<Head>> {
+    let mut headers = [httparse::EMPTY_HEADER; MAX_HEADERS];
+    let mut parsed = httparse::Request::new(&mut headers);
+
+    let length = match parsed.parse(buffer).map_err(invalid)? {
+        httparse::Status::Complete(length) => length,
+
+        httparse::Status::Partial if buffer.len() > MAX_HEAD_SIZE => {
+            return Err(invalid("request head is too large"));
+        }
+
+        httparse::Status::Partial => return Ok(None),
+    };
+
+    let version = match parsed.version {
+        Some(0) => Version::Http10,
+        _ => Version::Http11,
+    };
+
+    let headers = parsed
+        .headers
+        .iter()
+        .map(|h| Header::new(h.name, h.value))
+        .collect::<Vec<_>>();
+
+    let chunked = find_header(&headers, "transfer-encoding")
+        .map(|h| h.has_token("chunked"))
+        .unwrap_or(false);
+
+    let body_length = if chunked {
+        BodyLength::Chunked(ChunkedBody::new(length))
+    } else {
+        match find_header(&headers, "content-length") {
+            Some(header) => {
+                let value = str::from_utf8(trim(&header.value)).map_err(invalid)?;
+                let body_length = value.parse::<usize>().map_err(invalid)?;
+
+                if body_length > MAX_BODY_SIZE {
+                    return Err(invalid("request body is too large"));
+                }
+
+                BodyLength::Fixed(body_length)
+            }
+
+            None => BodyLength::Fixed(0),
+        }
+    };
+
+    let request = Request {
+        method: parsed.method.unwrap_or_default().to_string(),
+        path: parsed.path.unwrap_or_default().to_string(),
+        version,
+        headers,
+        body: Vec::new(),
+    };
+
+    Ok(Some(Head {
+        request,
+        length,
+        body_length,
+    }))
+}
+
+/// The longest chunk size line, including any chunk extensions and its
+/// CRLF, that is accepted.
+const MAX_CHUNK_LINE_SIZE: usize = 1024;
+
+/// Decodes a chunked body incrementally, so that the data which has
+/// already been decoded isn't parsed again as more is read. Chunk
+/// extensions and trailers are ignored.
+pub(super) struct ChunkedBody {
+    body: Vec<u8>,
+    position: usize,
+    remaining: Option<usize>,
+    trailers: Option<usize>,
+}
+
+impl ChunkedBody {
+    /// Create a decoder for a body that starts at `start` in the buffer.
+    pub(super) fn new(start: usize) -> Self {
+        Self {
+            body: Vec::new(),
+            position: start,
+            remaining: None,
+            trailers: None,
+        }
+    }
+
+    /// Continue decoding the body from the supplied buffer, which must
+    /// begin with the data that was previously supplied. Returns the body
+    /// and the position where it ends if it is complete.
+    pub(super) fn parse(&mut self, buffer: &[u8]) -> io::Result<Option<(Vec<u8>, usize)>> {
+        loop {
+            if let Some(start) = self.trailers {
+                // the body is followed by optional trailers and an empty line
+
+                let max = MAX_HEAD_SIZE - (self.position - start);
+
+                let line_end = match find_line(buffer, self.position, max)? {
+                    Some(line_end) => line_end,
+                    None => return Ok(None),
+                };
+
+                let empty = line_end == self.position;
+
+                self.position = line_end + 2;
+
+                if empty {
+                    return Ok(Some((mem::take(&mut self.body), self.position)));
+                }
+
+                continue;
+            }
+
+            match self.remaining {
+                Some(0) => {
+                    if buffer.len() < self.position + 2 {
+                        return Ok(None);
+                    }
+
+                    if &buffer[self.position..self.position + 2] != b"\r\n" {
+                        return Err(invalid("chunk is not terminated by CRLF"));
+                    }
+
+                    self.position += 2;
+                    self.remaining = None;
+                }
+
+                Some(remaining) => {
+                    let available = remaining.min(buffer.len() - self.position);
+
+                    if available == 0 {
+                        return Ok(None);
+                    }
+
+                    self.body
+                        .extend_from_slice(&buffer[self.position..self.position + available]);
+                    self.position += available;
+                    self.remaining = Some(remaining - available);
+                }
+
+                None => {
+                    let line_end = match find_line(buffer, self.position, MAX_CHUNK_LINE_SIZE)? {
+                        Some(line_end) => line_end,
+                        None => return Ok(None),
+                    };
+
+                    let line = &buffer[self.position..line_end];
+                    let size = line.split(|b| *b == b';').next().unwrap_or(line);
+                    let size = str::from_utf8(trim(size)).map_err(invalid)?;
+                    let size = usize::from_str_radix(size, 16).map_err(invalid)?;
+
+                    self.position = line_end + 2;
+
+                    if size == 0 {
+                        self.trailers = Some(self.position);
+                    } else if size > MAX_BODY_SIZE - self.body.len() {
+                        // the size comes from the client, so it's checked
+                        // before it's added to avoid overflowing
+
+                        return Err(invalid("request body is too large"));
+                    } else {
+                        self.remaining = Some(size);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Find the end of the line that starts at `position`, failing if the line
+/// and its CRLF are longer than `max`.
+fn find_line(buffer: &[u8], position: usize, max: usize) -> io::Result<Option<usize>> {
+    let end = buffer.len().min(position + max);
+
+    match find_crlf(&buffer[position..end]) {
+        Some(index) => Ok(Some(position + index)),
+        None if buffer.len() - position >= max => Err(invalid("line is too long")),
+        None => Ok(None),
+    }
+}
+
+fn find_crlf(buffer: &[u8]) -> Option<usize> {
+    buffer.windows(2).position(|w| w == b"\r\n")
+}
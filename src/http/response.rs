@@ -0,0 +1,154 @@
+use super::{find_header, Response};
+use crate::stream::{Action, Logic, LogicEvent, StreamContext};
+
+/// A flow that serializes responses, emitting the data for each response
+/// in its entirety.
+///
+/// Once a response with a `Connection: close` header has been emitted,
+/// the flow completes and cancels upstream.
+pub struct ResponseSerializer {
+    stopped: bool,
+}
+
+impl ResponseSerializer {
+    pub fn new() -> Self {
+        Self { stopped: false }
+    }
+}
+
+impl Default for ResponseSerializer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Logic<Response, Vec<u8>> for ResponseSerializer {
+    type Ctl = ();
+
+    fn name(&self) -> &'static str {
+        "pantomime::http::ResponseSerializer"
+    }
+
+    fn receive(
+        &mut self,
+        msg: LogicEvent<Response, Self::Ctl>,
+        ctx: &mut StreamContext<Response, Vec<u8>, Self::Ctl>,
+    ) -> Action<Vec<u8>, Self::Ctl> {
+        match msg {
+            LogicEvent::Pulled => Action::Pull,
+
+            LogicEvent::Pushed(response) => {
+                let close = find_header(&response.headers, "connection")
+                    .map(|h| h.has_token("close"))
+                    .unwrap_or(false);
+
+                let mut data = Vec::new();
+
+                encode(&response, &mut data);
+
+                if close {
+                    self.stopped = true;
+
+                    ctx.tell(Action::PushAndStop(data, None));
+
+                    Action::Cancel
+                } else {
+                    Action::Push(data)
+                }
+            }
+
+            LogicEvent::Stopped if self.stopped => Action::None,
+
+            LogicEvent::Stopped => {
+                self.stopped = true;
+
+                Action::Stop(None)
+            }
+
+            LogicEvent::Cancelled => Action::Cancel,
+
+            LogicEvent::Started | LogicEvent::Forwarded(()) => Action::None,
+        }
+    }
+}
+
+fn encode(response: &Response, bytes: &mut Vec<u8>) {
+    let chunked = find_header(&response.headers, "transfer-encoding")
+        .map(|h| h.has_token("chunked"))
+        .unwrap_or(false);
+
+    bytes.extend_from_slice(
+        format!(
+            "HTTP/1.1 {} {}\r\n",
+            response.status,
+            reason_phrase(response.status)
+        )
+        .as_bytes(),
+    );
+
+    for header in response.headers.iter() {
+        bytes.extend_from_slice(header.name.as_bytes());
+        bytes.extend_from_slice(b": ");
+        bytes.extend_from_slice(&header.value);
+        bytes.extend_from_slice(b"\r\n");
+    }
+
+    if chunked {
+        bytes.extend_from_slice(b"\r\n");
+
+        if !response.body.is_empty() {
+            bytes.extend_from_slice(format!("{:x}\r\n", response.body.len()).as_bytes());
+            bytes.extend_from_slice(&response.body);
+            bytes.extend_from_slice(b"\r\n");
+        }
+
+        bytes.extend_from_slice(b"0\r\n\r\n");
+    } else {
+        if find_header(&response.headers, "content-length").is_none() {
+            bytes.extend_from_slice(
+                format!("Content-Length: {}\r\n", response.body.len()).as_bytes(),
+            );
+        }
+
+        bytes.extend_from_slice(b"\r\n");
+        bytes.extend_from_slice(&response.body);
+    }
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        100 => "Continue",
+        101 => "Switching Protocols",
+        200 => "OK",
+        201 => "Created",
+        202 => "Accepted",
+        204 => "No Content",
+        206 => "Partial Content",
+        301 => "Moved Permanently",
+        302 => "Found",
+        303 => "See Other",
+        304 => "Not Modified",
+        307 => "Temporary Redirect",
+        308 => "Permanent Redirect",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        408 => "Request Timeout",
+        409 => "Conflict",
+        410 => "Gone",
+        411 => "Length Required",
+        413 => "Payload Too Large",
+        414 => "URI Too Long",
+        415 => "Unsupported Media Type",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        501 => "Not Implemented",
+        502 => "Bad Gateway",
+        503 => "Service Unavailable",
+        504 => "Gateway Timeout",
+        505 => "HTTP Version Not Supported",
+        _ => "",
+    }
+}
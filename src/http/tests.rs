@@ -0,0 +1,379 @@
+use crate::actor::*;
+use crate::http::request::{parse_head, ChunkedBody};
+use crate::http::{serve, HttpServer, Request, Response, Version, MAX_BODY_SIZE, MAX_HEAD_SIZE};
+use crate::stream::source::tcp::TcpConnection;
+use crate::stream::{Flow, Sink, Source};
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+fn parse_chunked(body: &[u8]) -> io::Result<Option<(Vec<u8>, usize)>> {
+    ChunkedBody::new(0).parse(body)
+}
+
+fn any_address() -> SocketAddr {
+    "127.0.0.1:0".parse().unwrap()
+}
+
+/// Connect to the supplied address, write the request data, and read the
+/// responses until the server closes the connection.
+fn exchange(address: SocketAddr, request: &'static [u8]) -> Vec<u8> {
+    let mut stream = loop {
+        if let Ok(stream) = TcpStream::connect(address) {
+            break stream;
+        }
+
+        thread::yield_now();
+    };
+
+    stream.write_all(request).unwrap();
+
+    let mut response = Vec::new();
+
+    stream.read_to_end(&mut response).unwrap();
+
+    response
+}
+
+#[test]
+fn test_serve() {
+    enum TestReaperMsg {
+        Connection(TcpConnection),
+        Received(Vec<u8>),
+    }
+
+    struct TestReaper;
+
+    impl Actor for TestReaper {
+        type Msg = TestReaperMsg;
+
+        fn receive(&mut self, msg: TestReaperMsg, ctx: &mut ActorContext<TestReaperMsg>) {
+            match msg {
+                TestReaperMsg::Connection(connection) => {
+                    let handler = Flow::new().map(|request: Request| {
+                        assert_eq!(request.version(), Version::Http11);
+
+                        let body = format!(
+                            "{} {} {}",
+                            request.method(),
+                            request.path(),
+                            String::from_utf8_lossy(request.body())
+                        );
+
+                        let response = Response::ok().with_body(body);
+
+                        if request.path() == "/chunked" {
+                            response.with_header("Transfer-Encoding", "chunked")
+                        } else {
+                            response
+                        }
+                    });
+
+                    ctx.spawn(serve(connection, handler));
+                }
+
+                TestReaperMsg::Received(response) => {
+                    assert_eq!(
+                        String::from_utf8(response).unwrap(),
+                        "HTTP/1.1 200 OK\r\nContent-Length: 6\r\n\r\nGET / \
+                         HTTP/1.1 200 OK\r\nContent-Length: 16\r\n\r\nPOST /echo hello\
+                         HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n\
+                         14\r\nPUT /chunked abcdefg\r\n0\r\n\r\n"
+                    );
+
+                    ctx.stop();
+                }
+            }
+        }
+
+        fn receive_signal(&mut self, signal: Signal, ctx: &mut ActorContext<TestReaperMsg>) {
+            if let Signal::Started = signal {
+                let (source, address) = Source::tcp_bind(&any_address()).unwrap();
+
+                let actor_ref = ctx.actor_ref().clone();
+
+                ctx.spawn(source.to(Sink::for_each(move |c| {
+                    actor_ref.tell(TestReaperMsg::Connection(c))
+                })));
+
+                let actor_ref = ctx.actor_ref().clone();
+
+                // pipelined requests, the last of which closes the connection
+
+                thread::spawn(move || {
+                    let response = exchange(
+                        address,
+                        b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n\
+                          POST /echo HTTP/1.1\r\nContent-Length: 5\r\n\r\nhello\
+                          PUT /chunked HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\
+                          Connection: close\r\n\r\n\
+                          3\r\nabc\r\n4;ext=1\r\ndefg\r\n0\r\nTrailer: 1\r\n\r\n",
+                    );
+
+                    actor_ref.tell(TestReaperMsg::Received(response));
+                });
+            }
+        }
+    }
+
+    assert!(ActorSystem::new().spawn(TestReaper).is_ok());
+}
+
+/// Serve the first connection that is accepted, calling the supplied
+/// client function with the address once it's bound, and assert whether
+/// the stream that served it succeeded.
+fn assert_serve_completes(client: fn(SocketAddr), succeeded: bool) {
+    enum TestReaperMsg {
+        Connection(TcpConnection),
+        Completed(bool),
+    }
+
+    struct TestReaper {
+        client: fn(SocketAddr),
+        succeeded: bool,
+    }
+
+    impl Actor for TestReaper {
+        type Msg = TestReaperMsg;
+
+        fn receive(&mut self, msg: TestReaperMsg, ctx: &mut ActorContext<TestReaperMsg>) {
+            match msg {
+                TestReaperMsg::Connection(connection) => {
+                    let handler = Flow::new().map(|_: Request| Response::ok());
+
+                    let (_, result) = ctx.spawn(serve(connection, handler));
+
+                    ctx.watch_result(result, |result| TestReaperMsg::Completed(result.is_ok()));
+                }
+
+                TestReaperMsg::Completed(succeeded) => {
+                    assert_eq!(succeeded, self.succeeded);
+
+                    ctx.stop();
+                }
+            }
+        }
+
+        fn receive_signal(&mut self, signal: Signal, ctx: &mut ActorContext<TestReaperMsg>) {
+            if let Signal::Started = signal {
+                let (source, address) = Source::tcp_bind(&any_address()).unwrap();
+
+                let actor_ref = ctx.actor_ref().clone();
+
+                ctx.spawn(source.to(Sink::for_each(move |c| {
+                    actor_ref.tell(TestReaperMsg::Connection(c))
+                })));
+
+                let client = self.client;
+
+                thread::spawn(move || client(address));
+            }
+        }
+    }
+
+    assert!(ActorSystem::new()
+        .spawn(TestReaper { client, succeeded })
+        .is_ok());
+}
+
+#[test]
+fn test_serve_client_close() {
+    // the client closes an idle connection that was kept alive
+
+    assert_serve_completes(
+        |address| {
+            let mut stream = TcpStream::connect(address).unwrap();
+
+            stream.write_all(b"GET / HTTP/1.1\r\n\r\n").unwrap();
+
+            let expected = b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n";
+            let mut response = vec![0; expected.len()];
+
+            stream.read_exact(&mut response).unwrap();
+
+            assert_eq!(&response[..], &expected[..]);
+        },
+        true,
+    );
+}
+
+#[test]
+fn test_serve_malformed() {
+    assert_serve_completes(
+        |address| {
+            let mut stream = TcpStream::connect(address).unwrap();
+
+            stream.write_all(b"NOT HTTP\r\n\r\n").unwrap();
+
+            let mut response = Vec::new();
+
+            stream.read_to_end(&mut response).unwrap();
+
+            assert!(response.is_empty());
+        },
+        false,
+    );
+}
+
+#[test]
+fn test_http_server_ask() {
+    struct Handler;
+
+    impl Actor for Handler {
+        type Msg = (Request, ActorRef<Response>);
+
+        fn receive(&mut self, msg: Self::Msg, _: &mut ActorContext<Self::Msg>) {
+            let (request, reply_to) = msg;
+
+            let response = if request.path() == "/hello" {
+                Response::ok()
+                    .with_header("Connection", "close")
+                    .with_body("world")
+            } else {
+                Response::new(404).with_header("Connection", "close")
+            };
+
+            reply_to.tell(response);
+        }
+    }
+
+    struct TestReaper;
+
+    impl Actor for TestReaper {
+        type Msg = Vec<u8>;
+
+        fn receive(&mut self, response: Vec<u8>, ctx: &mut ActorContext<Vec<u8>>) {
+            assert_eq!(
+                String::from_utf8(response).unwrap(),
+                "HTTP/1.1 200 OK\r\nConnection: close\r\nContent-Length: 5\r\n\r\nworld"
+            );
+
+            ctx.stop();
+        }
+
+        fn receive_signal(&mut self, signal: Signal, ctx: &mut ActorContext<Vec<u8>>) {
+            if let Signal::Started = signal {
+                let handler = ctx.spawn(Handler);
+
+                let server = HttpServer::new(&any_address(), move || {
+                    Flow::new().ask(&handler, Duration::from_secs(10), |request, reply_to| {
+                        (request, reply_to)
+                    })
+                })
+                .unwrap();
+
+                let address = server.local_address();
+
+                ctx.spawn(server);
+
+                let actor_ref = ctx.actor_ref().clone();
+
+                // the response closes the connection, even though the
+                // request asked for it to be kept alive
+
+                thread::spawn(move || {
+                    actor_ref.tell(exchange(
+                        address,
+                        b"GET /hello HTTP/1.1\r\nConnection: keep-alive\r\n\r\n",
+                    ));
+                });
+            }
+        }
+    }
+
+    assert!(ActorSystem::new().spawn(TestReaper).is_ok());
+}
+
+#[test]
+fn test_parse_chunked() {
+    let body = b"3\r\nabc\r\n4;ext=1\r\ndefg\r\n0\r\n\r\nnext";
+
+    assert_eq!(
+        parse_chunked(body).unwrap(),
+        Some((b"abcdefg".to_vec(), body.len() - 4))
+    );
+
+    // incomplete bodies need more data
+
+    assert_eq!(parse_chunked(b"3\r\nab").unwrap(), None);
+    assert_eq!(parse_chunked(b"3\r\nabc\r\n").unwrap(), None);
+}
+
+#[test]
+fn test_parse_chunked_incremental() {
+    let body = b"3\r\nabc\r\n4;ext=1\r\ndefg\r\n0\r\nTrailer: 1\r\n\r\nnext";
+    let end = body.len() - 4;
+
+    let mut chunked = ChunkedBody::new(0);
+
+    // the body is decoded as it arrives, one byte at a time
+
+    for length in 0..end {
+        assert_eq!(chunked.parse(&body[..length]).unwrap(), None);
+    }
+
+    assert_eq!(
+        chunked.parse(&body[..end]).unwrap(),
+        Some((b"abcdefg".to_vec(), end))
+    );
+}
+
+#[test]
+fn test_parse_chunked_invalid() {
+    let invalid = |body: &[u8]| {
+        assert_eq!(
+            parse_chunked(body).map_err(|e| e.kind()),
+            Err(ErrorKind::InvalidData)
+        )
+    };
+
+    // malformed sizes, and a chunk that isn't terminated by CRLF
+
+    invalid(b"xyz\r\nabc\r\n0\r\n\r\n");
+    invalid(b"\r\nabc\r\n0\r\n\r\n");
+    invalid(b"-3\r\nabc\r\n0\r\n\r\n");
+    invalid(b"3\r\nabcd\r\n0\r\n\r\n");
+
+    // oversized chunks, including sizes that overflow when added
+
+    invalid(format!("{:x}\r\n", MAX_BODY_SIZE + 1).as_bytes());
+    invalid(b"3\r\nabc\r\nffffffffffffffff\r\n");
+    invalid(b"3\r\nabc\r\nfffffffffffffffff\r\n");
+    invalid(format!("3\r\nabc\r\n{:x}\r\n", MAX_BODY_SIZE - 2).as_bytes());
+
+    // oversized size lines and trailers, whether or not they're complete
+
+    let extension = "a".repeat(1024);
+
+    invalid(format!("3;{}\r\nabc\r\n0\r\n\r\n", extension).as_bytes());
+    invalid(format!("3;{}", extension).as_bytes());
+
+    let trailer = format!("Trailer: {}\r\n", "a".repeat(1024));
+    let trailers = trailer.repeat(MAX_HEAD_SIZE / trailer.len() + 1);
+
+    invalid(format!("0\r\n{}\r\n", trailers).as_bytes());
+    invalid(format!("0\r\n{}", trailers).as_bytes());
+}
+
+#[test]
+fn test_parse_content_length() {
+    let head = |request: &str| parse_head(request.as_bytes()).map_err(|e| e.kind());
+
+    assert!(head("POST / HTTP/1.1\r\nContent-Length: 5\r\n\r\n")
+        .unwrap()
+        .is_some());
+
+    for length in &["abc", "-1", "5 5", "", "99999999999999999999999"] {
+        let request = format!("POST / HTTP/1.1\r\nContent-Length: {}\r\n\r\n", length);
+
+        assert_eq!(head(&request).err(), Some(ErrorKind::InvalidData));
+    }
+
+    let request = format!(
+        "POST / HTTP/1.1\r\nContent-Length: {}\r\n\r\n",
+        MAX_BODY_SIZE + 1
+    );
+
+    assert_eq!(head(&request).err(), Some(ErrorKind::InvalidData));
+}
@@ -10,9 +10,18 @@ extern crate chrono;
 extern crate conqueue;
 extern crate crossbeam;
 extern crate fern;
+extern crate futures_core;
+extern crate httparse;
 extern crate mio;
 extern crate parking_lot;
 extern crate rand;
+extern crate socket2;
+
+#[cfg(target_family = "unix")]
+extern crate libc;
+
+#[cfg(target_family = "unix")]
+extern crate mio_uds;
 
 #[macro_use]
 extern crate downcast_rs;
@@ -20,9 +29,6 @@ extern crate downcast_rs;
 #[macro_use]
 extern crate log;
 
-#[cfg(feature = "futures-support")]
-extern crate futures;
-
 #[cfg(all(feature = "posix-signals-support", target_family = "unix"))]
 extern crate signal_hook_shim as signal_hook;
 
@@ -44,6 +50,7 @@ extern crate tokio_timer;
 pub mod actor;
 pub mod cfg;
 pub mod dispatcher;
+pub mod http;
 pub mod io;
 pub mod mailbox;
 pub mod prelude;
@@ -1,9 +1,126 @@
 use crate::actor::{ActorContext, Watchable};
 
+#[cfg(target_family = "unix")]
+use libc as consts;
+
+/// Signal numbers for platforms without libc, which never deliver
+/// POSIX signals. These follow the Linux numbering.
+#[cfg(not(target_family = "unix"))]
+#[allow(dead_code)]
+mod consts {
+    pub const SIGHUP: i32 = 1;
+    pub const SIGINT: i32 = 2;
+    pub const SIGQUIT: i32 = 3;
+    pub const SIGABRT: i32 = 6;
+    pub const SIGUSR1: i32 = 10;
+    pub const SIGUSR2: i32 = 12;
+    pub const SIGPIPE: i32 = 13;
+    pub const SIGALRM: i32 = 14;
+    pub const SIGTERM: i32 = 15;
+    pub const SIGCHLD: i32 = 17;
+    pub const SIGCONT: i32 = 18;
+    pub const SIGTSTP: i32 = 20;
+    pub const SIGTTIN: i32 = 21;
+    pub const SIGTTOU: i32 = 22;
+    pub const SIGURG: i32 = 23;
+    pub const SIGXCPU: i32 = 24;
+    pub const SIGXFSZ: i32 = 25;
+    pub const SIGVTALRM: i32 = 26;
+    pub const SIGPROF: i32 = 27;
+    pub const SIGWINCH: i32 = 28;
+    pub const SIGIO: i32 = 29;
+}
+
+/// The POSIX signals that can be handled by Pantomime. Signals that
+/// cannot be caught (SIGKILL, SIGSTOP) or that indicate a fault in the
+/// process itself (SIGILL, SIGFPE, SIGSEGV, SIGBUS) are excluded.
+///
+/// The discriminant of each variant is the platform's signal number.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[repr(i32)]
 pub enum PosixSignal {
-    SIGHUP = 1,
-    SIGINT = 2,
-    SIGTERM = 15,
+    SIGHUP = consts::SIGHUP,
+    SIGINT = consts::SIGINT,
+    SIGQUIT = consts::SIGQUIT,
+    SIGABRT = consts::SIGABRT,
+    SIGUSR1 = consts::SIGUSR1,
+    SIGUSR2 = consts::SIGUSR2,
+    SIGPIPE = consts::SIGPIPE,
+    SIGALRM = consts::SIGALRM,
+    SIGTERM = consts::SIGTERM,
+    SIGCHLD = consts::SIGCHLD,
+    SIGCONT = consts::SIGCONT,
+    SIGTSTP = consts::SIGTSTP,
+    SIGTTIN = consts::SIGTTIN,
+    SIGTTOU = consts::SIGTTOU,
+    SIGURG = consts::SIGURG,
+    SIGXCPU = consts::SIGXCPU,
+    SIGXFSZ = consts::SIGXFSZ,
+    SIGVTALRM = consts::SIGVTALRM,
+    SIGPROF = consts::SIGPROF,
+    SIGWINCH = consts::SIGWINCH,
+    SIGIO = consts::SIGIO,
+}
+
+const SIGNALS: &[(PosixSignal, &str)] = &[
+    (PosixSignal::SIGHUP, "SIGHUP"),
+    (PosixSignal::SIGINT, "SIGINT"),
+    (PosixSignal::SIGQUIT, "SIGQUIT"),
+    (PosixSignal::SIGABRT, "SIGABRT"),
+    (PosixSignal::SIGUSR1, "SIGUSR1"),
+    (PosixSignal::SIGUSR2, "SIGUSR2"),
+    (PosixSignal::SIGPIPE, "SIGPIPE"),
+    (PosixSignal::SIGALRM, "SIGALRM"),
+    (PosixSignal::SIGTERM, "SIGTERM"),
+    (PosixSignal::SIGCHLD, "SIGCHLD"),
+    (PosixSignal::SIGCONT, "SIGCONT"),
+    (PosixSignal::SIGTSTP, "SIGTSTP"),
+    (PosixSignal::SIGTTIN, "SIGTTIN"),
+    (PosixSignal::SIGTTOU, "SIGTTOU"),
+    (PosixSignal::SIGURG, "SIGURG"),
+    (PosixSignal::SIGXCPU, "SIGXCPU"),
+    (PosixSignal::SIGXFSZ, "SIGXFSZ"),
+    (PosixSignal::SIGVTALRM, "SIGVTALRM"),
+    (PosixSignal::SIGPROF, "SIGPROF"),
+    (PosixSignal::SIGWINCH, "SIGWINCH"),
+    (PosixSignal::SIGIO, "SIGIO"),
+];
+
+impl PosixSignal {
+    /// Find the signal with the supplied signal number.
+    pub fn from_i32(signal: i32) -> Option<Self> {
+        SIGNALS
+            .iter()
+            .find(|(s, _)| *s as i32 == signal)
+            .map(|(s, _)| *s)
+    }
+
+    /// Parse a signal specification, which is either a name such as
+    /// `SIGHUP` (the `SIG` prefix is optional and case is ignored) or
+    /// a signal number such as `1`.
+    pub fn parse(spec: &str) -> Option<Self> {
+        let spec = spec.trim().to_uppercase();
+
+        if let Ok(signal) = spec.parse::<i32>() {
+            return Self::from_i32(signal);
+        }
+
+        let name = if spec.starts_with("SIG") {
+            spec
+        } else {
+            format!("SIG{}", spec)
+        };
+
+        SIGNALS.iter().find(|(_, n)| *n == name).map(|(s, _)| *s)
+    }
+
+    pub fn name(self) -> &'static str {
+        SIGNALS
+            .iter()
+            .find(|(s, _)| *s == self)
+            .map(|(_, n)| *n)
+            .expect("pantomime bug: PosixSignal missing from SIGNALS")
+    }
 }
 
 /// When watched, register interest in receiving POSIX signals. When
@@ -27,3 +144,24 @@ where
         self.watch_posix_signals_with(convert);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::PosixSignal;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(PosixSignal::parse("SIGHUP"), Some(PosixSignal::SIGHUP));
+        assert_eq!(PosixSignal::parse(" usr1 "), Some(PosixSignal::SIGUSR1));
+        assert_eq!(PosixSignal::parse("sigwinch"), Some(PosixSignal::SIGWINCH));
+        assert_eq!(PosixSignal::parse("15"), Some(PosixSignal::SIGTERM));
+        assert_eq!(
+            PosixSignal::parse(&(PosixSignal::SIGCHLD as i32).to_string()),
+            Some(PosixSignal::SIGCHLD)
+        );
+        assert_eq!(PosixSignal::parse("SIGKILL"), None);
+        assert_eq!(PosixSignal::parse("0"), None);
+        assert_eq!(PosixSignal::parse(""), None);
+        assert_eq!(PosixSignal::SIGQUIT.name(), "SIGQUIT");
+    }
+}
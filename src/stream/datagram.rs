@@ -0,0 +1,236 @@
+use crate::stream::udp::UdpOptions;
+use crate::stream::Datagram;
+use mio::{event::Evented, net::UdpSocket};
+use std::io::{self, ErrorKind as IoErrorKind, Result as IoResult};
+use std::net::SocketAddr;
+
+#[cfg(target_family = "unix")]
+use crate::stream::UdsDatagram;
+
+#[cfg(target_family = "unix")]
+use mio_uds::UnixDatagram;
+
+#[cfg(target_family = "unix")]
+use std::os::unix::net::SocketAddr as UnixSocketAddr;
+
+#[cfg(target_family = "unix")]
+use std::path::Path;
+
+/// The largest payload that can be carried by an IPv4 UDP datagram.
+pub const MAX_DATAGRAM_SIZE: usize = 65_507;
+
+/// Determines how a datagram stage responds to errors that only affect a
+/// single datagram, such as a datagram that was truncated or a send that
+/// was refused. Errors that leave the socket unusable always fail the
+/// stream.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DatagramErrorPolicy {
+    /// Fail the stream.
+    Fail,
+
+    /// Log the error, drop the datagram and continue.
+    LogAndContinue,
+}
+
+/// Configures a datagram source or sink, and the socket that it
+/// operates on.
+pub trait DatagramOptions<S> {
+    /// The largest datagram that can be received.
+    fn max_datagram_size(&self) -> usize;
+
+    fn error_policy(&self) -> DatagramErrorPolicy;
+
+    /// Apply the options to the socket when the stage starts.
+    fn apply(&self, socket: &S) -> IoResult<()>;
+
+    /// Revert the options that were applied by `apply` when the stage stops.
+    fn unapply(&self, socket: &S) -> IoResult<()>;
+}
+
+/// A datagram-oriented socket that can be used by the `Datagrams` source
+/// and sink.
+pub trait DatagramSocket: Evented + Send + Sized {
+    type Datagram: 'static + Send;
+
+    type Address;
+
+    type Options: 'static + Default + Send + DatagramOptions<Self>;
+
+    fn try_clone(&self) -> IoResult<Self>;
+
+    fn recv_from(&self, buffer: &mut [u8]) -> IoResult<(usize, Self::Address)>;
+
+    /// Send the datagram to its destination, returning the number of
+    /// bytes that were sent.
+    fn send_datagram(&self, datagram: &Self::Datagram) -> IoResult<usize>;
+
+    fn datagram(data: Vec<u8>, address: Self::Address) -> Self::Datagram;
+
+    fn data(datagram: &Self::Datagram) -> &[u8];
+}
+
+impl DatagramSocket for UdpSocket {
+    type Datagram = Datagram;
+    type Address = SocketAddr;
+    type Options = UdpOptions;
+
+    fn try_clone(&self) -> IoResult<Self> {
+        UdpSocket::try_clone(self)
+    }
+
+    fn recv_from(&self, buffer: &mut [u8]) -> IoResult<(usize, SocketAddr)> {
+        UdpSocket::recv_from(self, buffer)
+    }
+
+    fn send_datagram(&self, datagram: &Datagram) -> IoResult<usize> {
+        self.send_to(&datagram.data, &datagram.address)
+    }
+
+    fn datagram(data: Vec<u8>, address: SocketAddr) -> Datagram {
+        Datagram { data, address }
+    }
+
+    fn data(datagram: &Datagram) -> &[u8] {
+        &datagram.data
+    }
+}
+
+/// Configures the Unix domain datagram socket that is used by
+/// `Source::uds_with_options` and `Sink::uds_with_options`.
+#[cfg(target_family = "unix")]
+pub struct UdsOptions {
+    max_datagram_size: usize,
+    error_policy: DatagramErrorPolicy,
+}
+
+#[cfg(target_family = "unix")]
+impl UdsOptions {
+    /// Creates options with a maximum datagram size of `MAX_DATAGRAM_SIZE`,
+    /// so each source allocates a buffer of that size, and an error
+    /// policy of `LogAndContinue`.
+    pub fn new() -> Self {
+        Self {
+            max_datagram_size: MAX_DATAGRAM_SIZE,
+            error_policy: DatagramErrorPolicy::LogAndContinue,
+        }
+    }
+
+    /// The largest datagram that can be received. Larger datagrams are
+    /// truncated by the operating system, which is detected and handled
+    /// according to the error policy.
+    pub fn with_max_datagram_size(mut self, size: usize) -> Self {
+        self.max_datagram_size = size;
+        self
+    }
+
+    pub fn with_error_policy(mut self, policy: DatagramErrorPolicy) -> Self {
+        self.error_policy = policy;
+        self
+    }
+}
+
+#[cfg(target_family = "unix")]
+impl Default for UdsOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(target_family = "unix")]
+impl DatagramOptions<UnixDatagram> for UdsOptions {
+    fn max_datagram_size(&self) -> usize {
+        self.max_datagram_size
+    }
+
+    fn error_policy(&self) -> DatagramErrorPolicy {
+        self.error_policy
+    }
+
+    fn apply(&self, _: &UnixDatagram) -> IoResult<()> {
+        Ok(())
+    }
+
+    fn unapply(&self, _: &UnixDatagram) -> IoResult<()> {
+        Ok(())
+    }
+}
+
+#[cfg(target_family = "unix")]
+impl DatagramSocket for UnixDatagram {
+    type Datagram = UdsDatagram;
+    type Address = UnixSocketAddr;
+    type Options = UdsOptions;
+
+    fn try_clone(&self) -> IoResult<Self> {
+        UnixDatagram::try_clone(self)
+    }
+
+    fn recv_from(&self, buffer: &mut [u8]) -> IoResult<(usize, UnixSocketAddr)> {
+        UnixDatagram::recv_from(self, buffer)
+    }
+
+    fn send_datagram(&self, datagram: &UdsDatagram) -> IoResult<usize> {
+        match datagram.path {
+            Some(ref path) => self.send_to(&datagram.data, path),
+            None => self.send(&datagram.data),
+        }
+    }
+
+    fn datagram(data: Vec<u8>, address: UnixSocketAddr) -> UdsDatagram {
+        UdsDatagram {
+            data,
+            path: address.as_pathname().map(Path::to_path_buf),
+        }
+    }
+
+    fn data(datagram: &UdsDatagram) -> &[u8] {
+        &datagram.data
+    }
+}
+
+/// Determine whether an error only affects a single datagram, leaving the
+/// socket usable for others.
+///
+/// These are errors that pertain to the peer or the route to it, which
+/// another datagram may not share, or to the size of the datagram. Errors
+/// that indicate a misconfigured socket, e.g. being denied permission to
+/// send to a broadcast address, apply to every datagram and aren't
+/// transient.
+pub(in crate::stream) fn is_transient(error: &io::Error) -> bool {
+    #[cfg(target_family = "unix")]
+    {
+        if error.raw_os_error() == Some(libc::EMSGSIZE) {
+            return true;
+        }
+    }
+
+    matches!(
+        error.kind(),
+        IoErrorKind::ConnectionRefused
+            | IoErrorKind::ConnectionReset
+            | IoErrorKind::AddrNotAvailable
+            | IoErrorKind::HostUnreachable
+            | IoErrorKind::NetworkUnreachable
+            | IoErrorKind::InvalidData
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_transient() {
+        assert!(is_transient(&io::Error::from(
+            IoErrorKind::ConnectionRefused
+        )));
+        assert!(is_transient(&io::Error::from(IoErrorKind::InvalidData)));
+
+        // these apply to every datagram that is sent via the socket
+
+        assert!(!is_transient(&io::Error::from(
+            IoErrorKind::PermissionDenied
+        )));
+        assert!(!is_transient(&io::Error::from(IoErrorKind::InvalidInput)));
+    }
+}
@@ -0,0 +1,68 @@
+use std::fs::{File, OpenOptions};
+use std::io::Result as IoResult;
+use std::path::Path;
+
+/// Determines what happens to the existing contents of a file that is
+/// written by `Sink::to_file`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FileWriteMode {
+    /// Discard the existing contents.
+    Truncate,
+
+    /// Write after the existing contents.
+    Append,
+}
+
+/// Configures how `Sink::to_file` opens and finishes a file.
+///
+/// By default, the file is created if it doesn't exist, its existing
+/// contents are truncated, and it is not synced.
+#[derive(Clone, Debug)]
+pub struct FileOptions {
+    mode: FileWriteMode,
+    sync: bool,
+}
+
+impl FileOptions {
+    pub fn new() -> Self {
+        Self {
+            mode: FileWriteMode::Truncate,
+            sync: false,
+        }
+    }
+
+    pub fn with_mode(mut self, mode: FileWriteMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Sync the file's data and metadata to disk (i.e. `fsync`) before
+    /// the sink completes.
+    pub fn with_sync(mut self, sync: bool) -> Self {
+        self.sync = sync;
+        self
+    }
+
+    pub(in crate::stream) fn sync(&self) -> bool {
+        self.sync
+    }
+
+    pub(in crate::stream) fn open(&self, path: &Path) -> IoResult<File> {
+        let mut options = OpenOptions::new();
+
+        options.write(true).create(true);
+
+        match self.mode {
+            FileWriteMode::Truncate => options.truncate(true),
+            FileWriteMode::Append => options.append(true),
+        };
+
+        options.open(path)
+    }
+}
+
+impl Default for FileOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,135 @@
+use crate::actor::{ActorRef, FailureError, FailureReason};
+use crate::stream::{Action, Logic, LogicEvent, StreamContext};
+use std::io::{Error, ErrorKind};
+use std::time::Duration;
+
+pub enum AskMsg<B> {
+    Reply(usize, B),
+    TimedOut(usize),
+}
+
+/// Sends each element to an actor along with a reference to reply to, and
+/// emits the replies. One element is outstanding at a time, so replies are
+/// emitted in order and a slow actor backpressures upstream.
+///
+/// The stream fails if a reply isn't received within the timeout.
+pub struct Ask<M, F>
+where
+    M: 'static + Send,
+{
+    actor_ref: ActorRef<M>,
+    convert: F,
+    timeout: Duration,
+    id: usize,
+    waiting: bool,
+    stopped: bool,
+}
+
+impl<M, F> Ask<M, F>
+where
+    M: 'static + Send,
+{
+    pub fn new(actor_ref: &ActorRef<M>, timeout: Duration, convert: F) -> Self {
+        Self {
+            actor_ref: actor_ref.clone(),
+            convert,
+            timeout,
+            id: 0,
+            waiting: false,
+            stopped: false,
+        }
+    }
+}
+
+impl<A: Send, B: Send, M, F: FnMut(A, ActorRef<B>) -> M + Send> Logic<A, B> for Ask<M, F>
+where
+    B: 'static,
+    M: 'static + Send,
+{
+    type Ctl = AskMsg<B>;
+
+    fn name(&self) -> &'static str {
+        "pantomime::stream::flow::Ask"
+    }
+
+    /// Ask is not fusible, as it relies on the asynchronous nature of
+    /// replies and scheduled deliveries.
+    fn fusible(&self) -> bool {
+        false
+    }
+
+    fn receive(
+        &mut self,
+        msg: LogicEvent<A, Self::Ctl>,
+        ctx: &mut StreamContext<A, B, Self::Ctl>,
+    ) -> Action<B, Self::Ctl> {
+        match msg {
+            LogicEvent::Pulled => Action::Pull,
+
+            LogicEvent::Pushed(element) => {
+                self.id = self.id.wrapping_add(1);
+                self.waiting = true;
+
+                let id = self.id;
+
+                let reply_to = ctx
+                    .stage_ref()
+                    .convert(move |reply| AskMsg::Reply(id, reply))
+                    .actor_ref;
+
+                self.actor_ref.tell((self.convert)(element, reply_to));
+
+                ctx.schedule_delivery("timeout", self.timeout, AskMsg::TimedOut(id));
+
+                Action::None
+            }
+
+            LogicEvent::Forwarded(AskMsg::Reply(id, reply)) if self.waiting && id == self.id => {
+                self.waiting = false;
+
+                if self.stopped {
+                    Action::PushAndStop(reply, None)
+                } else {
+                    Action::Push(reply)
+                }
+            }
+
+            LogicEvent::Forwarded(AskMsg::TimedOut(id)) if self.waiting && id == self.id => {
+                self.waiting = false;
+
+                let reason = Some(FailureReason::Errored(FailureError::new(Error::new(
+                    ErrorKind::TimedOut,
+                    "ask timed out",
+                ))));
+
+                if self.stopped {
+                    Action::Stop(reason)
+                } else {
+                    self.stopped = true;
+
+                    ctx.tell(Action::Stop(reason));
+
+                    Action::Cancel
+                }
+            }
+
+            // replies that arrive after the timeout, and timeouts for
+            // replies that have already arrived
+            LogicEvent::Forwarded(_) => Action::None,
+
+            LogicEvent::Stopped if self.waiting => {
+                self.stopped = true;
+
+                Action::None
+            }
+
+            LogicEvent::Stopped if self.stopped => Action::None,
+
+            LogicEvent::Stopped => Action::Stop(None),
+
+            LogicEvent::Cancelled => Action::Cancel,
+
+            LogicEvent::Started => Action::None,
+        }
+    }
+}
@@ -0,0 +1,498 @@
+use crate::actor::{FailureError, FailureReason, SubscriptionEvent};
+use crate::stream::{Action, Logic, LogicEvent, StreamContext};
+use mio::{event::Evented, net::TcpStream, Poll, PollOpt, Ready, Token};
+use std::io::{self, ErrorKind as IoErrorKind, Read, Result as IoResult, Write};
+use std::net::Shutdown;
+use std::sync::Arc;
+
+#[cfg(target_family = "unix")]
+use mio_uds::UnixStream;
+
+/// A connection-oriented socket that can be used by a `Connection` flow.
+pub trait ConnectionSocket: Evented + Read + Write + Send {
+    fn shutdown(&self, how: Shutdown) -> IoResult<()>;
+
+    fn take_error(&self) -> IoResult<Option<io::Error>>;
+
+    /// Determine whether the socket has finished connecting to its peer.
+    fn is_connected(&self) -> IoResult<bool>;
+}
+
+impl ConnectionSocket for TcpStream {
+    fn shutdown(&self, how: Shutdown) -> IoResult<()> {
+        TcpStream::shutdown(self, how)
+    }
+
+    fn take_error(&self) -> IoResult<Option<io::Error>> {
+        TcpStream::take_error(self)
+    }
+
+    fn is_connected(&self) -> IoResult<bool> {
+        match self.peer_addr() {
+            Ok(_) => Ok(true),
+            Err(ref e) if e.kind() == IoErrorKind::NotConnected => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(target_family = "unix")]
+impl ConnectionSocket for UnixStream {
+    fn shutdown(&self, how: Shutdown) -> IoResult<()> {
+        UnixStream::shutdown(self, how)
+    }
+
+    fn take_error(&self) -> IoResult<Option<io::Error>> {
+        UnixStream::take_error(self)
+    }
+
+    fn is_connected(&self) -> IoResult<bool> {
+        match self.peer_addr() {
+            Ok(_) => Ok(true),
+            Err(ref e) if e.kind() == IoErrorKind::NotConnected => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// A flow over a TCP connection.
+pub type Tcp = Connection<TcpStream>;
+
+/// A flow over a Unix domain stream socket.
+#[cfg(target_family = "unix")]
+pub type Uds = Connection<UnixStream>;
+
+struct Chunk {
+    data: Vec<u8>,
+    written: usize,
+}
+
+/// Reads from and writes to a connected socket. Elements that are pushed
+/// from upstream are written to the stream, and data that is read from
+/// the stream is pushed downstream.
+///
+/// Once upstream has stopped and all of its data has been written, the
+/// write side of the stream is shutdown. Downstream is stopped once the
+/// peer has closed its side of the stream and writing has finished.
+///
+/// If downstream cancels, the connection is closed. Half-closing can
+/// also be disabled, so that the connection is closed as soon as either
+/// side has finished.
+///
+/// Outgoing connections are established asynchronously, and the
+/// stream fails if the connection cannot be established.
+pub struct Connection<S> {
+    connect: Option<Box<dyn FnOnce() -> IoResult<S> + Send>>,
+    stream: Option<S>,
+    error: Option<io::Error>,
+    connecting: bool,
+    buffer: Vec<u8>,
+    chunk: Option<Chunk>,
+    poll: Option<Arc<Poll>>,
+    token: usize,
+    readable: bool,
+    writable: bool,
+    pulled: bool,
+    upstream_stopped: bool,
+    read_closed: bool,
+    write_closed: bool,
+    half_close: bool,
+    stopped: bool,
+}
+
+impl<S: ConnectionSocket> Connection<S> {
+    /// Create a flow for a socket that is already connected.
+    pub fn new(stream: S) -> Self {
+        Self::with_stream(Some(stream), false)
+    }
+
+    /// Create a flow that starts connecting a socket via `connect` when the
+    /// stream starts, e.g. by calling `TcpStream::connect`, so that no
+    /// socket is opened until then.
+    pub fn connect<F>(connect: F) -> Self
+    where
+        F: 'static + Send + FnOnce() -> IoResult<S>,
+    {
+        Self {
+            connect: Some(Box::new(connect)),
+            ..Self::with_stream(None, true)
+        }
+    }
+
+    fn with_stream(stream: Option<S>, connecting: bool) -> Self {
+        Self {
+            connect: None,
+            stream,
+            error: None,
+            connecting,
+            buffer: vec![0; 8192], // @TODO config
+            chunk: None,
+            poll: None,
+            token: 0,
+            readable: false,
+            writable: false,
+            pulled: false,
+            upstream_stopped: false,
+            read_closed: false,
+            write_closed: false,
+            half_close: true,
+            stopped: false,
+        }
+    }
+
+    /// Close the connection once the peer has closed its side, rather
+    /// than continuing to write until upstream stops, and once upstream
+    /// has stopped and its data has been written, rather than continuing
+    /// to read until the peer closes its side.
+    pub fn without_half_close(mut self) -> Self {
+        self.half_close = false;
+        self
+    }
+
+    fn try_read(
+        &mut self,
+        ctx: &mut StreamContext<Vec<u8>, Vec<u8>, SubscriptionEvent>,
+    ) -> Action<Vec<u8>, SubscriptionEvent> {
+        if self.read_closed || !self.readable || !self.pulled {
+            return Action::None;
+        }
+
+        let read = match self.stream {
+            Some(ref mut stream) => stream.read(&mut self.buffer),
+            None => return Action::None,
+        };
+
+        match read {
+            Ok(0) => {
+                self.read_closed = true;
+
+                if self.write_closed || !self.half_close {
+                    self.stop(None, ctx)
+                } else {
+                    Action::None
+                }
+            }
+
+            Ok(bytes_read) => {
+                self.pulled = false;
+
+                Action::Push(self.buffer[0..bytes_read].to_vec())
+            }
+
+            Err(ref e) if e.kind() == IoErrorKind::WouldBlock => {
+                self.readable = false;
+
+                Action::None
+            }
+
+            Err(ref e) if e.kind() == IoErrorKind::Interrupted => self.try_read(ctx),
+
+            Err(e) => self.fail(e, ctx),
+        }
+    }
+
+    fn try_write(
+        &mut self,
+        ctx: &mut StreamContext<Vec<u8>, Vec<u8>, SubscriptionEvent>,
+    ) -> Action<Vec<u8>, SubscriptionEvent> {
+        if !self.writable {
+            return Action::None;
+        }
+
+        // the stream is edge-triggered, so we must write until it would
+        // block, otherwise we won't be notified again
+
+        while let (Some(ref mut stream), Some(ref mut chunk)) = (&mut self.stream, &mut self.chunk)
+        {
+            match stream.write(&chunk.data[chunk.written..]) {
+                // the stream can't accept any more data, so retrying would
+                // spin forever
+                Ok(0) if chunk.written < chunk.data.len() => {
+                    return self.fail(io::Error::from(IoErrorKind::WriteZero), ctx);
+                }
+
+                Ok(bytes_written) => {
+                    chunk.written += bytes_written;
+
+                    if chunk.written == chunk.data.len() {
+                        self.chunk = None;
+                    }
+                }
+
+                Err(ref e) if e.kind() == IoErrorKind::WouldBlock => {
+                    self.writable = false;
+
+                    return Action::None;
+                }
+
+                Err(ref e) if e.kind() == IoErrorKind::Interrupted => {}
+
+                Err(e) => {
+                    return self.fail(e, ctx);
+                }
+            }
+        }
+
+        if self.upstream_stopped {
+            self.close_write(ctx)
+        } else {
+            Action::Pull
+        }
+    }
+
+    /// Determine whether an outgoing connection has been established,
+    /// returning the error if it failed.
+    fn check_connected(&mut self) -> IoResult<bool> {
+        match self.stream {
+            Some(ref stream) => {
+                if let Some(e) = stream.take_error()? {
+                    return Err(e);
+                }
+
+                stream.is_connected()
+            }
+
+            None => Ok(false),
+        }
+    }
+
+    fn close_write(
+        &mut self,
+        ctx: &mut StreamContext<Vec<u8>, Vec<u8>, SubscriptionEvent>,
+    ) -> Action<Vec<u8>, SubscriptionEvent> {
+        // the write side can't be shutdown until the connection has been
+        // established, so this is retried once it has
+
+        if self.write_closed || self.connecting {
+            return Action::None;
+        }
+
+        self.write_closed = true;
+
+        let shutdown = match self.stream {
+            Some(ref stream) => stream.shutdown(Shutdown::Write),
+            None => Ok(()),
+        };
+
+        match shutdown {
+            Ok(()) if self.read_closed || !self.half_close => self.stop(None, ctx),
+
+            Ok(()) => Action::None,
+
+            // the peer may have already closed the connection
+            Err(ref e) if e.kind() == IoErrorKind::NotConnected => self.stop(None, ctx),
+
+            Err(e) => self.fail(e, ctx),
+        }
+    }
+
+    fn fail(
+        &mut self,
+        error: io::Error,
+        ctx: &mut StreamContext<Vec<u8>, Vec<u8>, SubscriptionEvent>,
+    ) -> Action<Vec<u8>, SubscriptionEvent> {
+        self.stop(Some(FailureReason::Errored(FailureError::new(error))), ctx)
+    }
+
+    fn stop(
+        &mut self,
+        reason: Option<FailureReason>,
+        ctx: &mut StreamContext<Vec<u8>, Vec<u8>, SubscriptionEvent>,
+    ) -> Action<Vec<u8>, SubscriptionEvent> {
+        if self.stopped {
+            return Action::None;
+        }
+
+        self.stopped = true;
+
+        if let Some(stream) = self.stream.take() {
+            if let Some(poll) = self.poll.take() {
+                // @TODO expect doesn't seem appropriate
+
+                poll.deregister(&stream)
+                    .expect("pantomime bug: failed to deregister socket");
+            }
+
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+
+        if self.token > 0 {
+            ctx.unsubscribe(self.token);
+
+            self.token = 0;
+        }
+
+        if self.upstream_stopped {
+            Action::Stop(reason)
+        } else {
+            ctx.tell(Action::Stop(reason));
+
+            Action::Cancel
+        }
+    }
+}
+
+impl<S: ConnectionSocket> Logic<Vec<u8>, Vec<u8>> for Connection<S>
+where
+    S: 'static,
+{
+    type Ctl = SubscriptionEvent;
+
+    fn name(&self) -> &'static str {
+        "pantomime::stream::flow::Connection"
+    }
+
+    fn fusible(&self) -> bool {
+        false
+    }
+
+    fn receive(
+        &mut self,
+        msg: LogicEvent<Vec<u8>, Self::Ctl>,
+        ctx: &mut StreamContext<Vec<u8>, Vec<u8>, Self::Ctl>,
+    ) -> Action<Vec<u8>, Self::Ctl> {
+        match msg {
+            LogicEvent::Started => {
+                if let Some(connect) = self.connect.take() {
+                    match connect() {
+                        Ok(stream) => self.stream = Some(stream),
+                        Err(e) => self.error = Some(e),
+                    }
+                }
+
+                if let Some(e) = self.error.take() {
+                    return self.fail(e, ctx);
+                }
+
+                let actor_ref = ctx.stage_ref().actor_ref;
+
+                ctx.subscribe(actor_ref);
+
+                Action::Pull
+            }
+
+            LogicEvent::Forwarded(SubscriptionEvent::Ready(poll, token)) => {
+                if self.stopped {
+                    ctx.unsubscribe(token);
+
+                    return Action::None;
+                }
+
+                let registered = match self.stream {
+                    Some(ref stream) => poll.register(
+                        stream,
+                        Token(token),
+                        Ready::readable() | Ready::writable(),
+                        PollOpt::edge(),
+                    ),
+
+                    None => {
+                        ctx.unsubscribe(token);
+
+                        return Action::None;
+                    }
+                };
+
+                match registered {
+                    Ok(()) => {
+                        self.poll = Some(poll);
+                        self.token = token;
+
+                        Action::None
+                    }
+
+                    Err(e) => {
+                        ctx.unsubscribe(token);
+
+                        self.fail(e, ctx)
+                    }
+                }
+            }
+
+            LogicEvent::Forwarded(SubscriptionEvent::MioEvent(event)) => {
+                if self.stopped {
+                    return Action::None;
+                }
+
+                if self.connecting {
+                    match self.check_connected() {
+                        Ok(true) => {
+                            self.connecting = false;
+                        }
+
+                        Ok(false) => {
+                            return Action::None;
+                        }
+
+                        Err(e) => {
+                            return self.fail(e, ctx);
+                        }
+                    }
+                }
+
+                let readiness = event.readiness();
+
+                if readiness.is_writable() {
+                    self.writable = true;
+                }
+
+                if readiness.is_readable() {
+                    self.readable = true;
+                }
+
+                let write = if self.chunk.is_some() {
+                    self.try_write(ctx)
+                } else if self.upstream_stopped {
+                    self.close_write(ctx)
+                } else {
+                    Action::None
+                };
+
+                match write {
+                    Action::None => self.try_read(ctx),
+
+                    write => {
+                        if !self.stopped {
+                            let read = self.try_read(ctx);
+
+                            if let Action::None = read {
+                            } else {
+                                ctx.tell(read);
+                            }
+                        }
+
+                        write
+                    }
+                }
+            }
+
+            LogicEvent::Pulled => {
+                self.pulled = true;
+
+                self.try_read(ctx)
+            }
+
+            LogicEvent::Pushed(data) => {
+                self.chunk = Some(Chunk { data, written: 0 });
+
+                self.try_write(ctx)
+            }
+
+            LogicEvent::Stopped => {
+                self.upstream_stopped = true;
+
+                if self.chunk.is_none() {
+                    self.close_write(ctx)
+                } else {
+                    Action::None
+                }
+            }
+
+            LogicEvent::Cancelled => {
+                self.read_closed = true;
+
+                self.stop(None, ctx)
+            }
+        }
+    }
+}
@@ -0,0 +1,490 @@
+use crate::actor::{FailureError, FailureReason};
+use crate::stream::{Action, Logic, LogicEvent, StreamContext};
+use std::io::{self, ErrorKind as IoErrorKind};
+
+/// The byte order of a length field.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ByteOrder {
+    BigEndian,
+    LittleEndian,
+}
+
+/// Splits buffered bytes into frames. Implementations are used by
+/// `Framing`, which manages the buffer and the stream protocol.
+pub trait Framer {
+    /// Decode the next complete frame from the front of the buffer, if
+    /// there is one, returning it along with the number of bytes that it
+    /// occupied in the buffer.
+    fn frame(&mut self, buffer: &[u8]) -> io::Result<Option<(Vec<u8>, usize)>>;
+
+    /// Called once upstream has completed and no complete frames remain.
+    /// The buffer is non-empty, and contains a partial frame.
+    fn finish(&mut self, buffer: &[u8]) -> io::Result<Vec<u8>>;
+}
+
+/// Decodes frames that are terminated by a delimiter, e.g. a newline. The
+/// delimiter is not included in the emitted frames.
+pub struct Delimiter {
+    delimiter: Vec<u8>,
+    max_frame_length: usize,
+    allow_truncation: bool,
+    scanned: usize,
+}
+
+impl Delimiter {
+    pub fn new(delimiter: &[u8], max_frame_length: usize, allow_truncation: bool) -> Self {
+        assert!(!delimiter.is_empty(), "delimiter must not be empty");
+
+        Self {
+            delimiter: delimiter.to_vec(),
+            max_frame_length,
+            allow_truncation,
+            scanned: 0,
+        }
+    }
+}
+
+impl Framer for Delimiter {
+    fn frame(&mut self, buffer: &[u8]) -> io::Result<Option<(Vec<u8>, usize)>> {
+        // resume where the last search left off, allowing for a delimiter
+        // that spans two chunks
+
+        let start = self.scanned.saturating_sub(self.delimiter.len() - 1);
+
+        let found = buffer[start..]
+            .windows(self.delimiter.len())
+            .position(|window| window == &self.delimiter[..])
+            .map(|index| start + index);
+
+        match found {
+            Some(index) if index <= self.max_frame_length => {
+                self.scanned = 0;
+
+                Ok(Some((
+                    buffer[..index].to_vec(),
+                    index + self.delimiter.len(),
+                )))
+            }
+
+            // a frame of the maximum length may be followed by part of
+            // the delimiter
+            None if buffer.len() < self.max_frame_length + self.delimiter.len() => {
+                self.scanned = buffer.len();
+
+                Ok(None)
+            }
+
+            _ => Err(io::Error::new(
+                IoErrorKind::InvalidData,
+                format!(
+                    "frame exceeds the maximum length of {} bytes",
+                    self.max_frame_length
+                ),
+            )),
+        }
+    }
+
+    fn finish(&mut self, buffer: &[u8]) -> io::Result<Vec<u8>> {
+        if self.allow_truncation {
+            Ok(buffer.to_vec())
+        } else {
+            Err(io::Error::new(
+                IoErrorKind::UnexpectedEof,
+                "stream completed with a partial frame",
+            ))
+        }
+    }
+}
+
+/// Describes a length field that precedes the payload of each frame. The
+/// length field itself is preceded by `offset` bytes of header, and its
+/// value is the length of the payload that follows it.
+#[derive(Clone, Copy, Debug)]
+pub struct LengthField {
+    width: usize,
+    byte_order: ByteOrder,
+    offset: usize,
+    max_frame_length: usize,
+}
+
+impl LengthField {
+    /// Create a big endian length field that is `width` bytes wide, and
+    /// is at the start of the frame. `width` must be between 1 and 8.
+    pub fn new(width: usize) -> Self {
+        assert!(
+            (1..=8).contains(&width),
+            "length field width must be between 1 and 8"
+        );
+
+        Self {
+            width,
+            byte_order: ByteOrder::BigEndian,
+            offset: 0,
+            max_frame_length: 8 * 1024 * 1024,
+        }
+    }
+
+    pub fn with_byte_order(mut self, byte_order: ByteOrder) -> Self {
+        self.byte_order = byte_order;
+        self
+    }
+
+    pub fn with_offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// The largest payload that may be encoded or decoded.
+    pub fn with_max_frame_length(mut self, max_frame_length: usize) -> Self {
+        self.max_frame_length = max_frame_length;
+        self
+    }
+
+    fn header_length(&self) -> usize {
+        self.offset + self.width
+    }
+
+    fn read(&self, bytes: &[u8]) -> u64 {
+        let fold = |length, byte: &u8| (length << 8) | u64::from(*byte);
+
+        match self.byte_order {
+            ByteOrder::BigEndian => bytes.iter().fold(0, fold),
+            ByteOrder::LittleEndian => bytes.iter().rev().fold(0, fold),
+        }
+    }
+
+    fn write(&self, length: u64, bytes: &mut Vec<u8>) {
+        let shifts = (0..self.width).map(|i| i * 8);
+
+        match self.byte_order {
+            ByteOrder::BigEndian => bytes.extend(shifts.rev().map(|s| (length >> s) as u8)),
+            ByteOrder::LittleEndian => bytes.extend(shifts.map(|s| (length >> s) as u8)),
+        }
+    }
+
+    fn too_long(&self, length: u64) -> io::Error {
+        io::Error::new(
+            IoErrorKind::InvalidData,
+            format!(
+                "frame length of {} bytes exceeds the maximum of {} bytes",
+                length, self.max_frame_length
+            ),
+        )
+    }
+}
+
+/// Decodes frames that are prefixed by a length field. The length field is
+/// removed from the emitted frames, but any bytes that precede it are kept.
+pub struct LengthFieldDecoder {
+    field: LengthField,
+}
+
+impl LengthFieldDecoder {
+    pub fn new(field: LengthField) -> Self {
+        Self { field }
+    }
+}
+
+impl Framer for LengthFieldDecoder {
+    fn frame(&mut self, buffer: &[u8]) -> io::Result<Option<(Vec<u8>, usize)>> {
+        let header_length = self.field.header_length();
+
+        if buffer.len() < header_length {
+            return Ok(None);
+        }
+
+        let length = self.field.read(&buffer[self.field.offset..header_length]);
+
+        if length > self.field.max_frame_length as u64 {
+            return Err(self.field.too_long(length));
+        }
+
+        let end = header_length + length as usize;
+
+        if buffer.len() < end {
+            return Ok(None);
+        }
+
+        let mut frame = Vec::with_capacity(self.field.offset + length as usize);
+
+        frame.extend_from_slice(&buffer[..self.field.offset]);
+        frame.extend_from_slice(&buffer[header_length..end]);
+
+        Ok(Some((frame, end)))
+    }
+
+    fn finish(&mut self, _: &[u8]) -> io::Result<Vec<u8>> {
+        Err(io::Error::new(
+            IoErrorKind::UnexpectedEof,
+            "stream completed with a partial frame",
+        ))
+    }
+}
+
+/// Splits bytes into frames of a fixed size.
+pub struct FixedSize {
+    size: usize,
+    allow_truncation: bool,
+}
+
+impl FixedSize {
+    pub fn new(size: usize, allow_truncation: bool) -> Self {
+        assert!(size > 0, "frame size must be greater than 0");
+
+        Self {
+            size,
+            allow_truncation,
+        }
+    }
+}
+
+impl Framer for FixedSize {
+    fn frame(&mut self, buffer: &[u8]) -> io::Result<Option<(Vec<u8>, usize)>> {
+        if buffer.len() < self.size {
+            Ok(None)
+        } else {
+            Ok(Some((buffer[..self.size].to_vec(), self.size)))
+        }
+    }
+
+    fn finish(&mut self, buffer: &[u8]) -> io::Result<Vec<u8>> {
+        if self.allow_truncation {
+            Ok(buffer.to_vec())
+        } else {
+            Err(io::Error::new(
+                IoErrorKind::UnexpectedEof,
+                "stream completed with a partial frame",
+            ))
+        }
+    }
+}
+
+/// Buffers incoming bytes and emits the frames that are decoded from them
+/// by a `Framer`. Malformed input fails the stream, as does a partial frame
+/// when upstream completes unless the framer allows it.
+pub struct Framing<F> {
+    framer: F,
+    buffer: Vec<u8>,
+    position: usize,
+    pulled: bool,
+    stopped: bool,
+}
+
+impl<F: Framer> Framing<F> {
+    pub fn new(framer: F) -> Self {
+        Self {
+            framer,
+            buffer: Vec::new(),
+            position: 0,
+            pulled: false,
+            stopped: false,
+        }
+    }
+
+    fn try_push(&mut self, ctx: &mut StreamContext<Vec<u8>, Vec<u8>, ()>) -> Action<Vec<u8>, ()> {
+        match self.framer.frame(&self.buffer[self.position..]) {
+            Ok(Some((frame, consumed))) => {
+                self.position += consumed;
+                self.pulled = false;
+
+                Action::Push(frame)
+            }
+
+            Ok(None) if !self.stopped => Action::Pull,
+
+            Ok(None) if self.position == self.buffer.len() => Action::Stop(None),
+
+            Ok(None) => {
+                let finished = self.framer.finish(&self.buffer[self.position..]);
+
+                self.buffer.clear();
+                self.position = 0;
+
+                match finished {
+                    Ok(frame) => Action::PushAndStop(frame, None),
+                    Err(e) => self.fail(e, ctx),
+                }
+            }
+
+            Err(e) => self.fail(e, ctx),
+        }
+    }
+
+    fn fail(
+        &mut self,
+        error: io::Error,
+        ctx: &mut StreamContext<Vec<u8>, Vec<u8>, ()>,
+    ) -> Action<Vec<u8>, ()> {
+        let reason = Some(FailureReason::Errored(FailureError::new(error)));
+
+        self.buffer.clear();
+        self.position = 0;
+
+        if self.stopped {
+            Action::Stop(reason)
+        } else {
+            self.stopped = true;
+
+            ctx.tell(Action::Stop(reason));
+
+            Action::Cancel
+        }
+    }
+}
+
+impl<F: Framer> Logic<Vec<u8>, Vec<u8>> for Framing<F>
+where
+    F: 'static + Send,
+{
+    type Ctl = ();
+
+    fn name(&self) -> &'static str {
+        "pantomime::stream::flow::Framing"
+    }
+
+    fn receive(
+        &mut self,
+        msg: LogicEvent<Vec<u8>, Self::Ctl>,
+        ctx: &mut StreamContext<Vec<u8>, Vec<u8>, Self::Ctl>,
+    ) -> Action<Vec<u8>, Self::Ctl> {
+        match msg {
+            LogicEvent::Pulled => {
+                self.pulled = true;
+
+                self.try_push(ctx)
+            }
+
+            LogicEvent::Pushed(bytes) => {
+                // frames are consumed from the front of the buffer, so
+                // it's compacted as new bytes arrive
+
+                if self.position == self.buffer.len() {
+                    self.buffer = bytes;
+                } else {
+                    self.buffer.drain(..self.position);
+                    self.buffer.extend_from_slice(&bytes);
+                }
+
+                self.position = 0;
+
+                // we only pull once we've been pulled, so there is demand
+
+                self.try_push(ctx)
+            }
+
+            LogicEvent::Stopped if self.stopped => Action::None,
+
+            LogicEvent::Stopped => {
+                self.stopped = true;
+
+                if self.pulled {
+                    self.try_push(ctx)
+                } else {
+                    Action::None
+                }
+            }
+
+            LogicEvent::Cancelled => Action::Cancel,
+
+            LogicEvent::Started | LogicEvent::Forwarded(()) => Action::None,
+        }
+    }
+}
+
+/// Prefixes each element with a length field, treating the first `offset`
+/// bytes of the element as the header that precedes it. This is the
+/// inverse of `LengthFieldDecoder`.
+pub struct LengthFieldEncoder {
+    field: LengthField,
+    stopped: bool,
+}
+
+impl LengthFieldEncoder {
+    pub fn new(field: LengthField) -> Self {
+        Self {
+            field,
+            stopped: false,
+        }
+    }
+
+    fn encode(&self, element: Vec<u8>) -> io::Result<Vec<u8>> {
+        if element.len() < self.field.offset {
+            return Err(io::Error::new(
+                IoErrorKind::InvalidInput,
+                format!(
+                    "element of {} bytes is shorter than the length field offset of {} bytes",
+                    element.len(),
+                    self.field.offset
+                ),
+            ));
+        }
+
+        let length = (element.len() - self.field.offset) as u64;
+
+        let max_encodable = if self.field.width == 8 {
+            u64::MAX
+        } else {
+            (1 << (self.field.width * 8)) - 1
+        };
+
+        if length > self.field.max_frame_length as u64 || length > max_encodable {
+            return Err(self.field.too_long(length));
+        }
+
+        let mut frame = Vec::with_capacity(element.len() + self.field.width);
+
+        frame.extend_from_slice(&element[..self.field.offset]);
+        self.field.write(length, &mut frame);
+        frame.extend_from_slice(&element[self.field.offset..]);
+
+        Ok(frame)
+    }
+}
+
+impl Logic<Vec<u8>, Vec<u8>> for LengthFieldEncoder {
+    type Ctl = ();
+
+    fn name(&self) -> &'static str {
+        "pantomime::stream::flow::LengthFieldEncoder"
+    }
+
+    fn receive(
+        &mut self,
+        msg: LogicEvent<Vec<u8>, Self::Ctl>,
+        ctx: &mut StreamContext<Vec<u8>, Vec<u8>, Self::Ctl>,
+    ) -> Action<Vec<u8>, Self::Ctl> {
+        match msg {
+            LogicEvent::Pulled => Action::Pull,
+
+            LogicEvent::Pushed(element) => match self.encode(element) {
+                Ok(frame) => Action::Push(frame),
+
+                Err(e) => {
+                    let reason = Some(FailureReason::Errored(FailureError::new(e)));
+
+                    if self.stopped {
+                        Action::Stop(reason)
+                    } else {
+                        self.stopped = true;
+
+                        ctx.tell(Action::Stop(reason));
+
+                        Action::Cancel
+                    }
+                }
+            },
+
+            LogicEvent::Stopped if self.stopped => Action::None,
+
+            LogicEvent::Stopped => {
+                self.stopped = true;
+
+                Action::Stop(None)
+            }
+
+            LogicEvent::Cancelled => Action::Cancel,
+
+            LogicEvent::Started | LogicEvent::Forwarded(()) => Action::None,
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use crate::actor::FailureReason;
 use crate::stream::internal::ContainedLogic;
 use crate::stream::{
     Action, Logic, LogicEvent, StageRef, StreamContext, StreamContextAction, StreamContextType,
@@ -31,6 +32,7 @@ where
     down: Box<dyn ContainedLogic<B, C> + Send>,
     down_actions: VecDeque<StreamContextAction<C, Box<dyn Any + Send>>>,
     down_ref: StageRef<Box<dyn Any + Send>>,
+    up_failure: Option<FailureReason>,
     phantom: PhantomData<(A, B, C)>,
 }
 
@@ -52,6 +54,7 @@ where
             down: downstream,
             down_actions: VecDeque::new(),
             down_ref: StageRef::empty(),
+            up_failure: None,
             phantom: PhantomData,
         }
     }
@@ -77,6 +80,7 @@ where
 
         let mut down_ctx = StreamContext {
             ctx: StreamContextType::Fused(&mut self.down_actions, &self.down_ref),
+            upstream_failure: self.up_failure.as_ref().or(ctx.upstream_failure),
             calls: ctx.calls,
         };
 
@@ -91,9 +95,11 @@ where
 
             Action::Cancel => self.up_receive(LogicEvent::Cancelled, ctx),
 
-            Action::PushAndStop(el, reason) => Action::PushAndStop(el, reason),
+            Action::PushAndStop(el, reason) => {
+                Action::PushAndStop(el, reason.or_else(|| self.up_failure.take()))
+            }
 
-            Action::Stop(reason) => Action::Stop(reason),
+            Action::Stop(reason) => Action::Stop(reason.or_else(|| self.up_failure.take())),
         };
 
         while let Some(a) = self.down_actions.pop_front() {
@@ -115,10 +121,12 @@ where
                 }
 
                 StreamContextAction::Action(Action::PushAndStop(el, reason)) => {
-                    Action::PushAndStop(el, reason)
+                    Action::PushAndStop(el, reason.or_else(|| self.up_failure.take()))
                 }
 
-                StreamContextAction::Action(Action::Stop(reason)) => Action::Stop(reason),
+                StreamContextAction::Action(Action::Stop(reason)) => {
+                    Action::Stop(reason.or_else(|| self.up_failure.take()))
+                }
 
                 StreamContextAction::ScheduleDelivery(name, duration, msg) => {
                     ctx.schedule_delivery(name, duration, FusedMsg::ForwardDown(msg)); // @TODO namespace name
@@ -165,6 +173,7 @@ where
 
         let mut up_ctx = StreamContext {
             ctx: StreamContextType::Fused(&mut self.up_actions, &self.up_ref),
+            upstream_failure: ctx.upstream_failure,
             calls: ctx.calls,
         };
 
@@ -179,9 +188,14 @@ where
 
             Action::Cancel => Action::Cancel,
 
-            Action::PushAndStop(el, _reason) => {
-                // @TODO reason
+            Action::PushAndStop(el, reason) => {
+                // if upstream failed, so does downstream, unless its
+                // logic supplies a failure of its own
+
                 let result = self.down_receive(LogicEvent::Pushed(el), ctx);
+
+                self.up_failure = reason;
+
                 let follow_up = self.down_receive(LogicEvent::Stopped, ctx);
 
                 ctx.tell(follow_up);
@@ -189,8 +203,11 @@ where
                 result
             }
 
-            // @TODO reason
-            Action::Stop(_reason) => self.down_receive(LogicEvent::Stopped, ctx),
+            Action::Stop(reason) => {
+                self.up_failure = reason;
+
+                self.down_receive(LogicEvent::Stopped, ctx)
+            }
         };
 
         while let Some(a) = self.up_actions.pop_front() {
@@ -209,9 +226,11 @@ where
 
                 StreamContextAction::Action(Action::Cancel) => Action::Cancel,
 
-                StreamContextAction::Action(Action::PushAndStop(el, _reason)) => {
-                    // @TODO reason
+                StreamContextAction::Action(Action::PushAndStop(el, reason)) => {
                     let result = self.down_receive(LogicEvent::Pushed(el), ctx);
+
+                    self.up_failure = reason;
+
                     let follow_up = self.down_receive(LogicEvent::Stopped, ctx);
 
                     ctx.tell(follow_up);
@@ -219,8 +238,9 @@ where
                     result
                 }
 
-                // @TODO reason
-                StreamContextAction::Action(Action::Stop(_reason)) => {
+                StreamContextAction::Action(Action::Stop(reason)) => {
+                    self.up_failure = reason;
+
                     self.down_receive(LogicEvent::Stopped, ctx)
                 }
 
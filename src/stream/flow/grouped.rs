@@ -0,0 +1,256 @@
+use crate::stream::{Action, Logic, LogicEvent, StreamContext};
+use std::collections::VecDeque;
+use std::mem;
+use std::time::Duration;
+
+/// Emits the elements of its upstream in groups of up to `size` elements.
+/// Only the last group, which is emitted once upstream completes, may
+/// have fewer elements.
+pub struct Grouped<A> {
+    size: usize,
+    group: Vec<A>,
+    pulled: bool,
+    stopped: bool,
+}
+
+impl<A> Grouped<A> {
+    pub fn new(size: usize) -> Self {
+        let size = size.max(1);
+
+        Self {
+            size,
+            group: Vec::with_capacity(size),
+            pulled: false,
+            stopped: false,
+        }
+    }
+
+    fn take_group(&mut self) -> Vec<A> {
+        mem::replace(&mut self.group, Vec::with_capacity(self.size))
+    }
+}
+
+impl<A: Send> Logic<A, Vec<A>> for Grouped<A> {
+    type Ctl = ();
+
+    fn name(&self) -> &'static str {
+        "pantomime::stream::flow::Grouped"
+    }
+
+    fn receive(
+        &mut self,
+        msg: LogicEvent<A, Self::Ctl>,
+        _: &mut StreamContext<A, Vec<A>, Self::Ctl>,
+    ) -> Action<Vec<A>, Self::Ctl> {
+        match msg {
+            LogicEvent::Pulled if self.stopped => Action::PushAndStop(self.take_group(), None),
+
+            LogicEvent::Pulled => {
+                self.pulled = true;
+
+                Action::Pull
+            }
+
+            LogicEvent::Pushed(element) => {
+                self.group.push(element);
+
+                if self.group.len() >= self.size {
+                    self.pulled = false;
+
+                    Action::Push(self.take_group())
+                } else {
+                    Action::Pull
+                }
+            }
+
+            LogicEvent::Stopped if self.group.is_empty() => Action::Stop(None),
+
+            LogicEvent::Stopped if self.pulled => Action::PushAndStop(self.take_group(), None),
+
+            LogicEvent::Stopped => {
+                self.stopped = true;
+
+                Action::None
+            }
+
+            LogicEvent::Cancelled => Action::Cancel,
+
+            LogicEvent::Started => Action::None,
+
+            LogicEvent::Forwarded(()) => Action::None,
+        }
+    }
+}
+
+pub enum GroupedWithinMsg {
+    TimedOut(usize),
+}
+
+/// Emits the elements of its upstream in groups whose total cost, as
+/// returned by the cost function, is at most `max_cost`. A group is also
+/// emitted once `duration` has elapsed since its first element arrived,
+/// and upstream's completion emits the group that remains.
+///
+/// An element that would cause its group to exceed the cost is put in
+/// the next group instead, so only an element that costs more than
+/// `max_cost` by itself is emitted in a group that exceeds it.
+///
+/// Upstream is pulled while no groups are waiting to be emitted, so
+/// groups are filled regardless of downstream's demand.
+pub struct GroupedWithin<A, F> {
+    max_cost: usize,
+    duration: Duration,
+    cost_fn: F,
+    group: Vec<A>,
+    cost: usize,
+    ready: VecDeque<Vec<A>>,
+    timer: usize,
+    pulled: bool,
+    upstream_pulled: bool,
+    stopped: bool,
+}
+
+impl<A, F: FnMut(&A) -> usize> GroupedWithin<A, F> {
+    pub fn new(max_cost: usize, duration: Duration, cost_fn: F) -> Self {
+        Self {
+            max_cost: max_cost.max(1),
+            duration,
+            cost_fn,
+            group: Vec::new(),
+            cost: 0,
+            ready: VecDeque::new(),
+            timer: 0,
+            pulled: false,
+            upstream_pulled: false,
+            stopped: false,
+        }
+    }
+
+    fn close_group(&mut self) {
+        if !self.group.is_empty() {
+            // invalidates the timer for the closed group
+
+            self.timer = self.timer.wrapping_add(1);
+            self.cost = 0;
+
+            self.ready.push_back(mem::take(&mut self.group));
+        }
+    }
+
+    fn try_push(
+        &mut self,
+        ctx: &mut StreamContext<A, Vec<A>, GroupedWithinMsg>,
+    ) -> Action<Vec<A>, GroupedWithinMsg>
+    where
+        A: 'static + Send,
+    {
+        if self.pulled {
+            if let Some(group) = self.ready.pop_front() {
+                self.pulled = false;
+
+                return if self.stopped && self.ready.is_empty() {
+                    Action::PushAndStop(group, None)
+                } else {
+                    if self.ready.is_empty() && !self.stopped && !self.upstream_pulled {
+                        self.upstream_pulled = true;
+
+                        ctx.tell(Action::Pull);
+                    }
+
+                    Action::Push(group)
+                };
+            }
+        }
+
+        if !self.ready.is_empty() {
+            Action::None
+        } else if self.stopped {
+            Action::Stop(None)
+        } else if self.upstream_pulled {
+            Action::None
+        } else {
+            self.upstream_pulled = true;
+
+            Action::Pull
+        }
+    }
+}
+
+impl<A: Send, F: FnMut(&A) -> usize + Send> Logic<A, Vec<A>> for GroupedWithin<A, F>
+where
+    A: 'static,
+{
+    type Ctl = GroupedWithinMsg;
+
+    fn name(&self) -> &'static str {
+        "pantomime::stream::flow::GroupedWithin"
+    }
+
+    /// GroupedWithin is not fusible, as it relies on the asynchronous
+    /// nature of scheduled deliveries.
+    fn fusible(&self) -> bool {
+        false
+    }
+
+    fn receive(
+        &mut self,
+        msg: LogicEvent<A, Self::Ctl>,
+        ctx: &mut StreamContext<A, Vec<A>, Self::Ctl>,
+    ) -> Action<Vec<A>, Self::Ctl> {
+        match msg {
+            LogicEvent::Pulled => {
+                self.pulled = true;
+
+                self.try_push(ctx)
+            }
+
+            LogicEvent::Pushed(element) => {
+                self.upstream_pulled = false;
+
+                let cost = (self.cost_fn)(&element);
+
+                if self.cost + cost > self.max_cost {
+                    self.close_group();
+                }
+
+                if self.group.is_empty() {
+                    ctx.schedule_delivery(
+                        "timeout",
+                        self.duration,
+                        GroupedWithinMsg::TimedOut(self.timer),
+                    );
+                }
+
+                self.group.push(element);
+                self.cost += cost;
+
+                if self.cost >= self.max_cost {
+                    self.close_group();
+                }
+
+                self.try_push(ctx)
+            }
+
+            LogicEvent::Forwarded(GroupedWithinMsg::TimedOut(timer)) if timer == self.timer => {
+                self.close_group();
+
+                self.try_push(ctx)
+            }
+
+            // timers for groups that have already been closed
+            LogicEvent::Forwarded(_) => Action::None,
+
+            LogicEvent::Stopped => {
+                self.stopped = true;
+
+                self.close_group();
+
+                self.try_push(ctx)
+            }
+
+            LogicEvent::Cancelled => Action::Cancel,
+
+            LogicEvent::Started => Action::None,
+        }
+    }
+}
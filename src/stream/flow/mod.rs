@@ -1,28 +1,58 @@
-use crate::stream::internal::{ContainedLogicImpl, IndividualLogic, LogicType, UnionLogic};
-use crate::stream::{Logic, Sink};
+use crate::actor::ActorRef;
+use crate::stream::internal::{ContainedLogicImpl, FanIn, IndividualLogic, LogicType, UnionLogic};
+use crate::stream::source::concat::Concat;
+use crate::stream::source::merge::Merge;
+use crate::stream::source::or_else::OrElse;
+use crate::stream::source::zip::{ZipInput, ZipLatest, ZipWith};
+use crate::stream::{FanInCompletion, Logic, PortLogic, Sink, Source, ThrottleMode};
 use std::any::Any;
 use std::cell::RefCell;
+use std::net::SocketAddr;
+use std::process::Command;
+use std::time::Duration;
 
+#[cfg(target_family = "unix")]
+use std::path::Path;
+
+mod ask;
+mod connection;
 mod delay;
 mod filter;
 mod filter_map;
 mod fold;
+mod framing;
 mod fused;
+mod grouped;
 mod identity;
 mod map;
 mod map_concat;
+mod process;
 mod scan;
 mod take_while;
+mod throttle;
 
+pub use self::ask::{Ask, AskMsg};
+pub use self::connection::{Connection, ConnectionSocket, Tcp};
 pub use self::delay::Delay;
 pub use self::filter::Filter;
 pub use self::filter_map::FilterMap;
 pub use self::fold::Fold;
+pub use self::framing::{
+    ByteOrder, Delimiter, FixedSize, Framer, Framing, LengthField, LengthFieldDecoder,
+    LengthFieldEncoder,
+};
+pub use self::grouped::{Grouped, GroupedWithin, GroupedWithinMsg};
 pub use self::identity::Identity;
 pub use self::map::Map;
 pub use self::map_concat::MapConcat;
+pub use self::process::Process;
 pub use self::scan::Scan;
 pub use self::take_while::TakeWhile;
+pub use self::throttle::{Throttle, ThrottleMsg};
+pub use crate::stream::process::ProcessCtl;
+
+#[cfg(target_family = "unix")]
+pub use self::connection::Uds;
 
 pub(in crate::stream) use fused::Fused;
 
@@ -42,7 +72,7 @@ where
         L::Ctl: 'static + Send,
     {
         Self {
-            logic: if logic.fusible() {
+            logic: if logic.fusible() && logic.dispatcher().is_none() {
                 LogicType::Fusible(Box::new(ContainedLogicImpl::new(logic)))
             } else {
                 LogicType::Spawnable(Box::new(IndividualLogic { logic }))
@@ -140,6 +170,23 @@ where
         self.via(Flow::from_logic(Scan::new(zero, scan_fn)))
     }
 
+    /// Send each element to the supplied actor, along with a reference that
+    /// it should reply to, and emit the replies in order. The stream fails
+    /// if a reply isn't received within `timeout`.
+    pub fn ask<C, M, F: FnMut(B, ActorRef<C>) -> M>(
+        self,
+        actor_ref: &ActorRef<M>,
+        timeout: Duration,
+        convert: F,
+    ) -> Flow<A, C>
+    where
+        C: 'static + Send,
+        M: 'static + Send,
+        F: 'static + Send,
+    {
+        self.via(Flow::from_logic(Ask::new(actor_ref, timeout, convert)))
+    }
+
     pub fn filter<F: FnMut(&B) -> bool>(self, filter: F) -> Self
     where
         F: 'static + Send,
@@ -163,6 +210,36 @@ where
         self.via(Flow::from_logic(Fold::new(zero, fold_fn)))
     }
 
+    /// Emit the elements in groups of up to `size` elements. Only the last
+    /// group, which is emitted once upstream completes, may be smaller.
+    pub fn grouped(self, size: usize) -> Flow<A, Vec<B>> {
+        self.via(Flow::from_logic(Grouped::new(size)))
+    }
+
+    /// Emit the elements in groups of up to `size` elements, emitting a
+    /// smaller group once `duration` has elapsed since its first element
+    /// arrived. Any remaining elements are emitted once upstream completes.
+    pub fn grouped_within(self, size: usize, duration: Duration) -> Flow<A, Vec<B>> {
+        self.grouped_weighted_within(size, duration, |_: &B| 1)
+    }
+
+    /// Emit the elements in groups like `grouped_within`, but with each
+    /// group limited to a total cost of `max_cost`, as returned by the
+    /// supplied function for each element, rather than a number of them.
+    pub fn grouped_weighted_within<F: FnMut(&B) -> usize>(
+        self,
+        max_cost: usize,
+        duration: Duration,
+        cost_fn: F,
+    ) -> Flow<A, Vec<B>>
+    where
+        F: 'static + Send,
+    {
+        self.via(Flow::from_logic(GroupedWithin::new(
+            max_cost, duration, cost_fn,
+        )))
+    }
+
     pub fn map<C, F: FnMut(B) -> C>(self, map_fn: F) -> Flow<A, C>
     where
         C: 'static + Send,
@@ -182,6 +259,241 @@ where
     {
         self.via(Flow::from_logic(MapConcat::new(map_concat)))
     }
+
+    /// Limit the rate of elements to `elements` every `per`, allowing bursts
+    /// of up to `burst` elements. Depending on `mode`, elements that exceed
+    /// the rate are delayed or fail the stream.
+    pub fn throttle(
+        self,
+        elements: usize,
+        per: Duration,
+        burst: usize,
+        mode: ThrottleMode,
+    ) -> Self {
+        self.throttle_with_cost(elements, per, burst, |_: &B| 1, mode)
+    }
+
+    /// Limit the rate of elements like `throttle`, but with each element
+    /// counting as the number of elements returned by the supplied function
+    /// rather than as one element.
+    pub fn throttle_with_cost<F: FnMut(&B) -> usize>(
+        self,
+        elements: usize,
+        per: Duration,
+        burst: usize,
+        cost_fn: F,
+        mode: ThrottleMode,
+    ) -> Self
+    where
+        F: 'static + Send,
+    {
+        self.via(Flow::from_logic(Throttle::new(
+            elements, per, burst, cost_fn, mode,
+        )))
+    }
+
+    /// Emit the elements of this flow and then, once it has completed, the
+    /// elements of the supplied source. The supplied source is only
+    /// materialized once this flow has completed.
+    pub fn concat(self, source: Source<B>) -> Self {
+        self.sequence(source, Concat::new(vec![0, 1]))
+    }
+
+    /// Emit the elements of the supplied source and then, once it has
+    /// completed, the elements of this flow. Upstream's elements are
+    /// buffered but not emitted until the supplied source has completed.
+    pub fn prepend(self, source: Source<B>) -> Self {
+        self.sequence(source, Concat::new(vec![1, 0]))
+    }
+
+    /// Emit the elements of this flow, or if it completes without having
+    /// emitted any, the elements of the supplied fallback. The fallback is
+    /// only materialized if it's needed.
+    pub fn or_else(self, fallback: Source<B>) -> Self {
+        self.sequence(fallback, OrElse::new())
+    }
+
+    fn sequence<L: PortLogic<B, B>>(self, source: Source<B>, logic: L) -> Self
+    where
+        L: 'static + Send,
+    {
+        Flow {
+            logic: LogicType::Spawnable(Box::new(FanIn {
+                logic,
+                primary: Some(self.logic.into_facade()),
+                sources: vec![source.producer().into_facade()],
+                lazy: true,
+            })),
+            empty: false,
+        }
+    }
+
+    /// Emit the elements of both this flow and the supplied source, as
+    /// they become available. Elements are taken from each fairly, and
+    /// the flow completes once both have completed.
+    pub fn merge(self, source: Source<B>) -> Self {
+        let sources = source
+            .producers
+            .into_iter()
+            .map(LogicType::into_facade)
+            .collect::<Vec<_>>();
+
+        Flow {
+            logic: LogicType::Spawnable(Box::new(FanIn {
+                logic: Merge::new(1 + sources.len(), FanInCompletion::Lazy),
+                primary: Some(self.logic.into_facade()),
+                sources,
+                lazy: false,
+            })),
+            empty: false,
+        }
+    }
+
+    /// Emit pairs of the elements of this flow and the supplied source.
+    /// The flow completes once either of them completes.
+    pub fn zip<C>(self, source: Source<C>) -> Flow<A, (B, C)>
+    where
+        C: 'static + Send,
+    {
+        self.zip_with(source, |b, c| (b, c))
+    }
+
+    /// Emit the pairs of the latest elements of this flow and the supplied
+    /// source whenever either emits, once both have emitted. Depending on
+    /// `completion`, the flow completes once either or both of them have
+    /// completed.
+    pub fn zip_latest<C>(self, source: Source<C>, completion: FanInCompletion) -> Flow<A, (B, C)>
+    where
+        B: Clone,
+        C: 'static + Clone + Send,
+    {
+        self.zip_ports(source, ZipLatest::new(completion))
+    }
+
+    /// Combine the elements of this flow and the supplied source with the
+    /// supplied function, emitting the results. The flow completes once
+    /// either of them completes.
+    pub fn zip_with<C, D, F: FnMut(B, C) -> D>(self, source: Source<C>, zip_fn: F) -> Flow<A, D>
+    where
+        C: 'static + Send,
+        D: 'static + Send,
+        F: 'static + Send,
+    {
+        self.zip_ports(source, ZipWith::new(zip_fn))
+    }
+
+    fn zip_ports<C, D, L: PortLogic<ZipInput<B, C>, D>>(
+        self,
+        source: Source<C>,
+        logic: L,
+    ) -> Flow<A, D>
+    where
+        C: 'static + Send,
+        D: 'static + Send,
+        L: 'static + Send,
+    {
+        Flow {
+            logic: LogicType::Spawnable(Box::new(FanIn {
+                logic,
+                primary: Some(self.map(ZipInput::Left).logic.into_facade()),
+                sources: vec![source.map(ZipInput::Right).producer().into_facade()],
+                lazy: false,
+            })),
+            empty: false,
+        }
+    }
+}
+
+impl Flow<Vec<u8>, Vec<u8>> {
+    /// Split bytes into frames that are terminated by `delimiter`, which is
+    /// removed from the frames.
+    ///
+    /// The stream fails if a frame exceeds `max_frame_length` bytes, or if
+    /// upstream completes with a partial frame and `allow_truncation` is
+    /// false. Otherwise, the partial frame is emitted.
+    pub fn delimiter(delimiter: &[u8], max_frame_length: usize, allow_truncation: bool) -> Self {
+        Flow::from_logic(Framing::new(Delimiter::new(
+            delimiter,
+            max_frame_length,
+            allow_truncation,
+        )))
+    }
+
+    /// Split bytes into frames that are prefixed by the supplied length
+    /// field, which is removed from the frames.
+    ///
+    /// The stream fails if a frame exceeds the field's maximum length, or
+    /// if upstream completes with a partial frame.
+    pub fn length_field_decoder(field: LengthField) -> Self {
+        Flow::from_logic(Framing::new(LengthFieldDecoder::new(field)))
+    }
+
+    /// Prefix each element with the supplied length field, such that it
+    /// can be decoded by `length_field_decoder`.
+    pub fn length_field_encoder(field: LengthField) -> Self {
+        Flow::from_logic(LengthFieldEncoder::new(field))
+    }
+
+    /// Split bytes into frames of `size` bytes.
+    ///
+    /// If upstream completes with a partial frame, it is emitted if
+    /// `allow_truncation` is true, and otherwise the stream fails.
+    pub fn fixed_size(size: usize, allow_truncation: bool) -> Self {
+        Flow::from_logic(Framing::new(FixedSize::new(size, allow_truncation)))
+    }
+
+    /// Connect to the supplied address via TCP. Elements pushed into the
+    /// flow are written to the connection, and data read from the
+    /// connection is emitted from it.
+    ///
+    /// No socket is opened until the stream starts, after which the
+    /// connection is established asynchronously, and the stream fails
+    /// if it cannot be established. Once upstream completes, the write
+    /// side of the connection is shutdown, but data continues to be read
+    /// until the peer closes the connection.
+    pub fn tcp_connect(address: &SocketAddr) -> Self {
+        let address = *address;
+
+        Flow::from_logic(Tcp::connect(move || mio::net::TcpStream::connect(&address)))
+    }
+
+    /// Connect to the Unix domain stream socket at the supplied path. This
+    /// otherwise behaves like `tcp_connect`, so the socket isn't opened
+    /// until the stream starts.
+    #[cfg(target_family = "unix")]
+    pub fn uds_connect<P: AsRef<Path>>(path: P) -> Self {
+        let path = path.as_ref().to_path_buf();
+
+        Flow::from_logic(Uds::connect(move || mio_uds::UnixStream::connect(&path)))
+    }
+
+    /// Spawn the supplied command as a child process when the stream
+    /// starts. Elements pushed into the flow are written to its stdin, and
+    /// its stdout is emitted from it. If `log_stderr` is true, each line
+    /// that it writes to stderr is logged, and otherwise it is discarded.
+    ///
+    /// The stream fails if the process cannot be spawned or doesn't exit
+    /// successfully, and the process is killed if downstream cancels.
+    ///
+    /// This differs from `Sink::process`, which outputs the exit status
+    /// whether or not it was successful. A flow emits the process's stdout
+    /// rather than an output of its own, so an unsuccessful exit status is
+    /// surfaced by failing the stream with an error that describes it. To
+    /// inspect the exit status instead, use `Sink::process`.
+    ///
+    /// Its pipes are read from and written to on the `BLOCKING_IO_DISPATCHER`,
+    /// and each running process can occupy up to two of its threads. If it
+    /// is configured with a fixed number of threads, it must have at least
+    /// two for every process that runs concurrently.
+    ///
+    /// In addition, each running process has a dedicated thread that waits
+    /// for it to exit, and if `log_stderr` is true, another that reads its
+    /// stderr. These are blocked for as long as the process runs, so they
+    /// aren't taken from a dispatcher, where they could starve its other
+    /// work.
+    pub fn process(command: Command, log_stderr: bool) -> Self {
+        Flow::from_logic(Process::new(command, log_stderr))
+    }
 }
 
 fn cast<In: 'static, Out: 'static>(value: In) -> Option<Out> {
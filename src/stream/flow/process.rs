@@ -0,0 +1,148 @@
+use crate::actor::{FailureError, FailureReason};
+use crate::stream::process::{ChildProcess, ProcessCtl};
+use crate::stream::{Action, Logic, LogicEvent, StreamContext};
+use std::io::{self, ErrorKind as IoErrorKind};
+use std::process::Command;
+
+/// Spawns a child process when the stream starts. Elements that are pushed
+/// from upstream are written to its stdin, and its stdout is emitted in
+/// chunks. Its stdin is closed once upstream has stopped, and if the child
+/// closes its stdin early, upstream is cancelled. Its stderr is logged if
+/// `log_stderr` is set, and discarded otherwise.
+///
+/// Downstream is stopped once the child has closed its stdout and exited,
+/// and the stream fails if it doesn't exit successfully.
+///
+/// If downstream cancels, the child is killed.
+///
+/// Stdout is only read when downstream has signaled demand. As a read
+/// blocks until the child writes to stdout, each running child can occupy
+/// up to two threads of the `BLOCKING_IO_DISPATCHER`.
+pub struct Process {
+    child: ChildProcess,
+    upstream_stopped: bool,
+    stopped: bool,
+}
+
+impl Process {
+    pub fn new(command: Command, log_stderr: bool) -> Self {
+        Self {
+            child: ChildProcess::new(command, log_stderr),
+            upstream_stopped: false,
+            stopped: false,
+        }
+    }
+
+    fn stop(
+        &mut self,
+        reason: Option<FailureReason>,
+        ctx: &mut StreamContext<Vec<u8>, Vec<u8>, ProcessCtl>,
+    ) -> Action<Vec<u8>, ProcessCtl> {
+        self.stopped = true;
+        self.child.close_stdin();
+        self.child.close_stdout();
+
+        if self.upstream_stopped {
+            Action::Stop(reason)
+        } else {
+            self.upstream_stopped = true;
+
+            ctx.tell(Action::Stop(reason));
+
+            Action::Cancel
+        }
+    }
+}
+
+impl Logic<Vec<u8>, Vec<u8>> for Process {
+    type Ctl = ProcessCtl;
+
+    fn name(&self) -> &'static str {
+        "pantomime::stream::flow::Process"
+    }
+
+    fn fusible(&self) -> bool {
+        false
+    }
+
+    fn receive(
+        &mut self,
+        msg: LogicEvent<Vec<u8>, Self::Ctl>,
+        ctx: &mut StreamContext<Vec<u8>, Vec<u8>, Self::Ctl>,
+    ) -> Action<Vec<u8>, Self::Ctl> {
+        match msg {
+            LogicEvent::Started => match self.child.spawn(true, ctx) {
+                Ok(()) => Action::Pull,
+
+                Err(e) => self.stop(Some(FailureReason::Errored(FailureError::new(e))), ctx),
+            },
+
+            LogicEvent::Pulled => {
+                self.child.read(ctx);
+
+                Action::None
+            }
+
+            LogicEvent::Pushed(chunk) => {
+                self.child.write(chunk, ctx);
+
+                Action::None
+            }
+
+            LogicEvent::Forwarded(ProcessCtl::Written) if !self.upstream_stopped => Action::Pull,
+
+            LogicEvent::Forwarded(ProcessCtl::StdinClosed) if !self.upstream_stopped => {
+                self.child.close_stdin();
+                self.upstream_stopped = true;
+
+                Action::Cancel
+            }
+
+            LogicEvent::Forwarded(ProcessCtl::Stdout(chunk)) if !self.stopped => {
+                Action::Push(chunk)
+            }
+
+            LogicEvent::Forwarded(ProcessCtl::StdoutClosed) if !self.stopped => {
+                self.child.close_stdout();
+
+                Action::None
+            }
+
+            LogicEvent::Forwarded(ProcessCtl::Exited(result)) if !self.stopped => {
+                self.child.exited();
+
+                let reason = match result {
+                    Ok(status) if status.success() => None,
+
+                    Ok(status) => Some(FailureReason::Errored(FailureError::new(io::Error::new(
+                        IoErrorKind::Other,
+                        format!("process {}", status),
+                    )))),
+
+                    Err(e) => Some(FailureReason::Errored(FailureError::new(e))),
+                };
+
+                self.stop(reason, ctx)
+            }
+
+            LogicEvent::Forwarded(_) => Action::None,
+
+            LogicEvent::Stopped => {
+                // closes stdin once everything has been written
+
+                self.child.close_stdin();
+                self.upstream_stopped = true;
+
+                Action::None
+            }
+
+            LogicEvent::Cancelled if self.stopped => Action::None,
+
+            LogicEvent::Cancelled => {
+                self.child.kill();
+
+                self.stop(None, ctx)
+            }
+        }
+    }
+}
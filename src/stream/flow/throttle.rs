@@ -0,0 +1,253 @@
+use crate::actor::{FailureError, FailureReason};
+use crate::stream::{Action, Logic, LogicEvent, StreamContext, ThrottleMode};
+use std::io::{Error, ErrorKind};
+use std::time::{Duration, Instant};
+
+pub enum ThrottleMsg {
+    Ready,
+}
+
+/// Limits the rate of elements with a token bucket that holds up to
+/// `burst` tokens and is refilled with `elements` tokens every `per`.
+/// Each element costs the number of tokens returned by the cost function.
+///
+/// When shaping, an element that costs more tokens than are available
+/// borrows them and is emitted once they would have been refilled, so
+/// upstream isn't pulled until then. When enforcing, such an element
+/// fails the stream instead.
+///
+/// The bucket starts full. Scheduled deliveries are only as precise as the
+/// system's ticker, so waits are rounded up to a multiple of its interval
+/// (10ms by default), and the tokens that are refilled while waiting may
+/// exceed the burst by up to an interval's worth of tokens. This allows
+/// rates above one element per interval.
+pub struct Throttle<A, F> {
+    cost_fn: F,
+    mode: ThrottleMode,
+    interval: Duration,
+    resolution: Duration,
+    burst: u64,
+    tick: u64,
+    tokens: u64,
+    refilled: Instant,
+    pending: Option<A>,
+    stopped: bool,
+}
+
+impl<A, F: FnMut(&A) -> usize> Throttle<A, F> {
+    pub fn new(
+        elements: usize,
+        per: Duration,
+        burst: usize,
+        cost_fn: F,
+        mode: ThrottleMode,
+    ) -> Self {
+        let interval = per.as_nanos() / elements.max(1) as u128;
+        let interval = Duration::from_nanos(interval.max(1) as u64);
+
+        Self {
+            cost_fn,
+            mode,
+            interval,
+            // the resolution and tick are set from the system's config
+            // once the stage starts
+            resolution: Duration::from_nanos(1),
+            burst: burst as u64,
+            tick: 1,
+            tokens: burst as u64,
+            refilled: Instant::now(),
+            pending: None,
+            stopped: false,
+        }
+    }
+
+    /// Use the resolution of the timer that waits are scheduled with,
+    /// updating the tokens that are refilled during a single tick.
+    fn set_resolution(&mut self, resolution: Duration) {
+        self.resolution = resolution.max(Duration::from_nanos(1));
+        self.tick = div_ceil(self.resolution.as_nanos(), self.interval.as_nanos()) as u64;
+    }
+
+    /// Round the wait up to a multiple of the timer's resolution, so that
+    /// deliveries don't fire early.
+    fn round_up(&self, wait: Duration) -> Duration {
+        let ticks = div_ceil(wait.as_nanos(), self.resolution.as_nanos());
+
+        self.resolution * ticks as u32
+    }
+
+    fn refill(&mut self, now: Instant) {
+        // the bucket may have been refilled into the future by
+        // borrowed tokens, in which case there's nothing to do yet
+
+        if now > self.refilled {
+            let refilled = (now - self.refilled).as_nanos() / self.interval.as_nanos();
+
+            if self.tokens >= self.burst {
+                // tokens refilled while waiting may exceed the burst, in
+                // which case the bucket is already full
+
+                self.refilled = now;
+            } else if refilled >= (self.burst - self.tokens) as u128 {
+                self.tokens = self.burst;
+                self.refilled = now;
+            } else {
+                self.tokens += refilled as u64;
+                self.refilled += Duration::from_nanos((self.interval.as_nanos() * refilled) as u64);
+            }
+        }
+    }
+
+    /// Refill the tokens that accrued since a wait ended. As the wait was
+    /// rounded up, these may exceed the burst, but by no more than the
+    /// tokens that are refilled during a single tick of the timer.
+    fn catch_up(&mut self, now: Instant) {
+        if now > self.refilled {
+            let refilled = (now - self.refilled).as_nanos() / self.interval.as_nanos();
+            let capacity = self.burst.max(self.tick);
+
+            self.tokens = capacity.min(self.tokens + refilled as u64);
+            self.refilled += Duration::from_nanos((self.interval.as_nanos() * refilled) as u64);
+        }
+    }
+
+    fn exceeds(&mut self, cost: u64) -> bool {
+        self.refill(Instant::now());
+
+        self.tokens < cost
+    }
+
+    /// Take the supplied number of tokens from the bucket, returning
+    /// how long to wait for those that had to be borrowed.
+    ///
+    /// The wait lasts until the bucket has been refilled, rather than for
+    /// the borrowed tokens alone, as a delivery may fire up to a tick
+    /// early, which would otherwise accumulate over successive waits.
+    fn take(&mut self, cost: u64) -> Duration {
+        let now = Instant::now();
+
+        self.refill(now);
+
+        if self.tokens >= cost {
+            self.tokens -= cost;
+
+            Duration::from_secs(0)
+        } else {
+            let borrowed = (cost - self.tokens) as u128 * self.interval.as_nanos();
+
+            self.tokens = 0;
+            self.refilled = self.refilled.max(now) + Duration::from_nanos(borrowed as u64);
+
+            self.refilled - now
+        }
+    }
+}
+
+impl<A: Send, F: FnMut(&A) -> usize + Send> Logic<A, A> for Throttle<A, F> {
+    type Ctl = ThrottleMsg;
+
+    fn name(&self) -> &'static str {
+        "pantomime::stream::flow::Throttle"
+    }
+
+    /// Throttle is not fusible, as it relies on the asynchronous nature
+    /// of scheduled deliveries.
+    fn fusible(&self) -> bool {
+        false
+    }
+
+    fn receive(
+        &mut self,
+        msg: LogicEvent<A, Self::Ctl>,
+        ctx: &mut StreamContext<A, A, Self::Ctl>,
+    ) -> Action<A, Self::Ctl> {
+        match msg {
+            LogicEvent::Pulled => Action::Pull,
+
+            LogicEvent::Pushed(element) => {
+                let cost = (self.cost_fn)(&element) as u64;
+
+                if self.mode == ThrottleMode::Enforcing && self.exceeds(cost) {
+                    let reason = Some(FailureReason::Errored(FailureError::new(Error::new(
+                        ErrorKind::Other,
+                        "maximum throttle rate exceeded",
+                    ))));
+
+                    self.stopped = true;
+
+                    ctx.tell(Action::Stop(reason));
+
+                    Action::Cancel
+                } else {
+                    let wait = self.take(cost);
+
+                    if wait == Duration::from_secs(0) {
+                        Action::Push(element)
+                    } else {
+                        self.pending = Some(element);
+
+                        let wait = self.round_up(wait);
+
+                        ctx.schedule_delivery("ready", wait, ThrottleMsg::Ready);
+
+                        Action::None
+                    }
+                }
+            }
+
+            LogicEvent::Forwarded(ThrottleMsg::Ready)
+                if self.pending.is_some() && Instant::now() < self.refilled =>
+            {
+                // the delivery fired early, so wait for the rest
+
+                let wait = self.round_up(self.refilled.saturating_duration_since(Instant::now()));
+
+                ctx.schedule_delivery("ready", wait, ThrottleMsg::Ready);
+
+                Action::None
+            }
+
+            LogicEvent::Forwarded(ThrottleMsg::Ready) => {
+                self.catch_up(Instant::now());
+
+                match self.pending.take() {
+                    Some(element) if self.stopped => Action::PushAndStop(element, None),
+
+                    Some(element) => Action::Push(element),
+
+                    None => Action::None,
+                }
+            }
+
+            LogicEvent::Started => {
+                self.set_resolution(ctx.ticker_interval());
+                self.refilled = Instant::now();
+
+                Action::None
+            }
+
+            LogicEvent::Stopped if self.pending.is_some() => {
+                self.stopped = true;
+
+                Action::None
+            }
+
+            LogicEvent::Stopped if self.stopped => Action::None,
+
+            LogicEvent::Stopped => Action::Stop(None),
+
+            LogicEvent::Cancelled => Action::Cancel,
+        }
+    }
+}
+
+/// Divide, rounding up. The divisor must be non-zero.
+fn div_ceil(dividend: u128, divisor: u128) -> u128 {
+    let quotient = dividend / divisor;
+
+    if quotient * divisor < dividend {
+        quotient + 1
+    } else {
+        quotient
+    }
+}
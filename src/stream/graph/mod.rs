@@ -0,0 +1,494 @@
+//! Graphs describe streams whose topology can't be expressed by linear
+//! chains of sources, flows and sinks, such as diamonds and feedback loops.
+//!
+//! Stages are added to a `Graph`, which returns typed handles for their
+//! ports, and the ports are then connected explicitly. Once all but the
+//! ports of its shape are connected, a graph is turned into a runnable
+//! `Stream` or into a `Source`, `Flow` or `Sink` that can be used like
+//! any other.
+//!
+//! Junctions, i.e. stages with several inlets or outlets, are driven by
+//! a `PortLogic` such as `source::merge::Merge` or `sink::fan_out::Broadcast`.
+
+use crate::stream::internal::LogicType;
+use crate::stream::{Flow, PortLogic, Sink, Source, Stream};
+use std::collections::HashMap;
+use std::io;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+mod node;
+
+use self::node::{Edge, GraphLogic, Node, NodeKind};
+
+/// Each graph is assigned an id, which its ports are tagged with so
+/// that ports of other graphs can be rejected.
+static NEXT_GRAPH_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// An inlet of a stage that has been added to a graph, which receives
+/// elements of type `A`.
+pub struct Inlet<A> {
+    graph: usize,
+    node: usize,
+    port: usize,
+    phantom: PhantomData<fn(A)>,
+}
+
+impl<A> Clone for Inlet<A> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<A> Copy for Inlet<A> {}
+
+/// An outlet of a stage that has been added to a graph, which emits
+/// elements of type `A`.
+pub struct Outlet<A> {
+    graph: usize,
+    node: usize,
+    port: usize,
+    phantom: PhantomData<fn() -> A>,
+}
+
+impl<A> Clone for Outlet<A> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<A> Copy for Outlet<A> {}
+
+/// The ports of a junction that has been added to a graph.
+pub struct Ports<A, B> {
+    graph: usize,
+    node: usize,
+    phantom: PhantomData<fn(A) -> B>,
+}
+
+impl<A, B> Clone for Ports<A, B> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<A, B> Copy for Ports<A, B> {}
+
+impl<A, B> Ports<A, B> {
+    pub fn inlet(&self, port: usize) -> Inlet<A> {
+        Inlet {
+            graph: self.graph,
+            node: self.node,
+            port,
+            phantom: PhantomData,
+        }
+    }
+
+    pub fn outlet(&self, port: usize) -> Outlet<B> {
+        Outlet {
+            graph: self.graph,
+            node: self.node,
+            port,
+            phantom: PhantomData,
+        }
+    }
+}
+
+/// A builder for streams of arbitrary topology. The outputs of the sinks
+/// that are added to a graph are gathered, in the order that the sinks
+/// were added, once they have all completed.
+///
+/// Fusible sources, flows and sinks that are connected to each other are
+/// fused, as they would be in a linear stream.
+pub struct Graph<Out = ()>
+where
+    Out: 'static + Send,
+{
+    id: usize,
+    nodes: Vec<Node<Out>>,
+    edges: Vec<Edge>,
+    sinks: Vec<usize>,
+    foreign: bool,
+}
+
+impl<Out> Graph<Out>
+where
+    Out: 'static + Send,
+{
+    pub fn new() -> Self {
+        Self {
+            id: NEXT_GRAPH_ID.fetch_add(1, Ordering::Relaxed),
+            nodes: Vec::new(),
+            edges: Vec::new(),
+            sinks: Vec::new(),
+            foreign: false,
+        }
+    }
+
+    pub fn add_source<A>(&mut self, source: Source<A>) -> Outlet<A>
+    where
+        A: 'static + Send,
+    {
+        let node = self.add(Node::source(source));
+
+        Outlet {
+            graph: self.id,
+            node,
+            port: 0,
+            phantom: PhantomData,
+        }
+    }
+
+    pub fn add_flow<A, B>(&mut self, flow: Flow<A, B>) -> (Inlet<A>, Outlet<B>)
+    where
+        A: 'static + Send,
+        B: 'static + Send,
+    {
+        let node = self.add(Node::flow(flow));
+
+        (
+            Inlet {
+                graph: self.id,
+                node,
+                port: 0,
+                phantom: PhantomData,
+            },
+            Outlet {
+                graph: self.id,
+                node,
+                port: 0,
+                phantom: PhantomData,
+            },
+        )
+    }
+
+    pub fn add_sink<A>(&mut self, sink: Sink<A, Out>) -> Inlet<A>
+    where
+        A: 'static + Send,
+    {
+        let node = self.add(Node::sink(sink));
+
+        self.sinks.push(node);
+
+        Inlet {
+            graph: self.id,
+            node,
+            port: 0,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Add a junction that is driven by the supplied logic, which
+    /// defines its number of inlets and outlets.
+    pub fn add_junction<A, B, L: PortLogic<A, B>>(&mut self, logic: L) -> Ports<A, B>
+    where
+        A: 'static + Send,
+        B: 'static + Send,
+        L: 'static + Send,
+    {
+        let node = self.add(Node::junction(logic));
+
+        Ports {
+            graph: self.id,
+            node,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Connect the supplied outlet to the supplied inlet. Each port must
+    /// belong to this graph and be connected exactly once, which is
+    /// validated when the graph is turned into a stream or one of the
+    /// other shapes.
+    pub fn connect<A>(&mut self, outlet: Outlet<A>, inlet: Inlet<A>)
+    where
+        A: 'static + Send,
+    {
+        if outlet.graph != self.id || inlet.graph != self.id {
+            self.foreign = true;
+
+            return;
+        }
+
+        self.edges.push(Edge {
+            from: (outlet.node, outlet.port),
+            to: (inlet.node, inlet.port),
+            relay: node::spawn_relay::<A>,
+        });
+    }
+
+    /// Create a runnable stream from this graph, all of whose ports must
+    /// be connected. The stream outputs the outputs of the graph's sinks.
+    pub fn stream(self) -> io::Result<Stream<Vec<Out>>> {
+        let logic = self.build::<(), Vec<Out>>(None, None)?;
+
+        Ok(Stream {
+            runnable_stream: Box::new(logic),
+        })
+    }
+
+    /// Create a sink from this graph, whose elements are received by the
+    /// supplied inlet. All of the other ports must be connected, and the
+    /// sink outputs the outputs of the graph's sinks.
+    pub fn sink<A>(self, inlet: Inlet<A>) -> io::Result<Sink<A, Vec<Out>>>
+    where
+        A: 'static + Send,
+    {
+        self.own(inlet.graph)?;
+
+        let logic = self.build::<A, Vec<Out>>(Some((inlet.node, inlet.port)), None)?;
+
+        Ok(Sink {
+            logic: LogicType::Spawnable(Box::new(logic)),
+        })
+    }
+
+    fn own(&self, graph: usize) -> io::Result<()> {
+        if graph == self.id {
+            Ok(())
+        } else {
+            Err(invalid("port belongs to another graph".to_string()))
+        }
+    }
+
+    fn add(&mut self, node: Node<Out>) -> usize {
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+
+    fn build<Up, Down>(
+        self,
+        inlet: Option<(usize, usize)>,
+        outlet: Option<(usize, usize)>,
+    ) -> io::Result<GraphLogic<Up, Down, Out>> {
+        self.validate(inlet, outlet)?;
+
+        let mut logic = GraphLogic {
+            nodes: self.nodes.into_iter().map(Some).collect(),
+            edges: self.edges,
+            sinks: self.sinks,
+            inlet,
+            outlet,
+            phantom: PhantomData,
+        };
+
+        fuse(&mut logic);
+
+        Ok(logic)
+    }
+
+    fn validate(
+        &self,
+        inlet: Option<(usize, usize)>,
+        outlet: Option<(usize, usize)>,
+    ) -> io::Result<()> {
+        if self.foreign {
+            return Err(invalid(
+                "graph connects a port that belongs to another graph".to_string(),
+            ));
+        }
+
+        let mut inlets = HashMap::new();
+        let mut outlets = HashMap::new();
+
+        for (node, port) in self.edges.iter().map(|edge| edge.to).chain(inlet) {
+            self.port(node, port, true)?;
+
+            *inlets.entry((node, port)).or_insert(0) += 1;
+        }
+
+        for (node, port) in self.edges.iter().map(|edge| edge.from).chain(outlet) {
+            self.port(node, port, false)?;
+
+            *outlets.entry((node, port)).or_insert(0) += 1;
+        }
+
+        for (n, node) in self.nodes.iter().enumerate() {
+            let ports = (0..node.inlets)
+                .map(|port| ("inlet", port, inlets.get(&(n, port))))
+                .chain((0..node.outlets).map(|port| ("outlet", port, outlets.get(&(n, port)))));
+
+            for (direction, port, connections) in ports {
+                match connections {
+                    None => {
+                        return Err(invalid(format!(
+                            "{} {} of {} {} is not connected",
+                            direction,
+                            port,
+                            node.kind.name(),
+                            n
+                        )));
+                    }
+
+                    Some(connections) if *connections > 1 => {
+                        return Err(invalid(format!(
+                            "{} {} of {} {} is connected more than once",
+                            direction,
+                            port,
+                            node.kind.name(),
+                            n
+                        )));
+                    }
+
+                    Some(_) => {}
+                }
+            }
+        }
+
+        match (outlet, self.sinks.is_empty()) {
+            (None, true) => Err(invalid("graph does not contain any sinks".to_string())),
+            (Some(_), false) => Err(invalid(
+                "graph with an open outlet cannot contain sinks".to_string(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn port(&self, node: usize, port: usize, inlet: bool) -> io::Result<()> {
+        let (direction, ports) = match self.nodes.get(node) {
+            Some(n) if inlet => ("inlet", n.inlets),
+            Some(n) => ("outlet", n.outlets),
+            None => return Err(invalid(format!("node {} is not part of the graph", node))),
+        };
+
+        if port < ports {
+            Ok(())
+        } else {
+            Err(invalid(format!(
+                "{} {} of {} {} does not exist",
+                direction,
+                port,
+                self.nodes[node].kind.name(),
+                node
+            )))
+        }
+    }
+}
+
+impl Graph<()> {
+    /// Create a source from this graph, which emits the elements of the
+    /// supplied outlet. All of the other ports must be connected, and the
+    /// graph cannot contain sinks.
+    pub fn source<A>(self, outlet: Outlet<A>) -> io::Result<Source<A>>
+    where
+        A: 'static + Send,
+    {
+        self.own(outlet.graph)?;
+
+        let logic = self.build::<(), A>(None, Some((outlet.node, outlet.port)))?;
+
+        Ok(Source {
+            producers: vec![LogicType::Spawnable(Box::new(logic))],
+        })
+    }
+
+    /// Create a flow from this graph, whose elements are received by the
+    /// supplied inlet and emitted by the supplied outlet. All of the other
+    /// ports must be connected, and the graph cannot contain sinks.
+    pub fn flow<A, B>(self, inlet: Inlet<A>, outlet: Outlet<B>) -> io::Result<Flow<A, B>>
+    where
+        A: 'static + Send,
+        B: 'static + Send,
+    {
+        self.own(inlet.graph)?;
+        self.own(outlet.graph)?;
+
+        let logic = self.build::<A, B>(
+            Some((inlet.node, inlet.port)),
+            Some((outlet.node, outlet.port)),
+        )?;
+
+        Ok(Flow {
+            logic: LogicType::Spawnable(Box::new(logic)),
+            empty: false,
+        })
+    }
+}
+
+impl<Out> Default for Graph<Out>
+where
+    Out: 'static + Send,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+/// Fuse each source that emits directly to a flow or sink, and each flow
+/// that emits directly to a sink, until no more can be fused. The fused
+/// node takes the place of the source, or in the case of a flow, the sink.
+fn fuse<Up, Down, Out>(logic: &mut GraphLogic<Up, Down, Out>)
+where
+    Out: 'static + Send,
+{
+    let kind = |nodes: &[Option<Node<Out>>], n: usize| nodes[n].as_ref().map(|node| node.kind);
+
+    loop {
+        let next = logic.edges.iter().position(|edge| {
+            matches!(
+                (
+                    kind(&logic.nodes, edge.from.0),
+                    kind(&logic.nodes, edge.to.0),
+                ),
+                (Some(NodeKind::Source), Some(NodeKind::Flow))
+                    | (Some(NodeKind::Source), Some(NodeKind::Sink))
+                    | (Some(NodeKind::Flow), Some(NodeKind::Sink))
+            )
+        });
+
+        let edge = match next {
+            Some(next) => logic.edges.remove(next),
+            None => return,
+        };
+
+        let (up, down) = (edge.from.0, edge.to.0);
+
+        let (up_node, down_node) = match (logic.nodes[up].take(), logic.nodes[down].take()) {
+            (Some(up_node), Some(down_node)) => (up_node, down_node),
+            _ => panic!("pantomime bug: graph edge connects a removed node"),
+        };
+
+        match up_node.kind {
+            NodeKind::Source => {
+                let fused = down_node.logic.fuse_source(up_node.logic.into_any());
+
+                for edge in logic.edges.iter_mut() {
+                    if edge.from.0 == down {
+                        edge.from.0 = up;
+                    }
+                }
+
+                if logic.outlet.map(|o| o.0) == Some(down) {
+                    logic.outlet = Some((up, 0));
+                }
+
+                for sink in logic.sinks.iter_mut() {
+                    if *sink == down {
+                        *sink = up;
+                    }
+                }
+
+                logic.nodes[up] = Some(fused);
+            }
+
+            _ => {
+                let fused = up_node.logic.fuse_sink(down_node.logic.into_any());
+
+                for edge in logic.edges.iter_mut() {
+                    if edge.to.0 == up {
+                        edge.to.0 = down;
+                    }
+                }
+
+                if logic.inlet.map(|i| i.0) == Some(up) {
+                    logic.inlet = Some((down, 0));
+                }
+
+                logic.nodes[down] = Some(fused);
+            }
+        }
+    }
+}
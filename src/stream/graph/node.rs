@@ -0,0 +1,575 @@
+use crate::actor::{Actor, ActorContext, ActorRef, ActorSpawnContext};
+use crate::stream::internal::{
+    spawn_gather, spawn_junction, spawn_source, DownstreamStageMsg, InternalStreamCtl,
+    LogicContainerFacade, RunnableStream,
+};
+use crate::stream::{Flow, PortLogic, Sink, Source};
+use std::any::Any;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+/// A type-erased `ActorRef<DownstreamStageMsg<A>>`, which is what the
+/// nodes of a graph exchange while it's being materialized. The ports
+/// that a graph connects are typed and belong to it, so downcasting
+/// can't fail.
+pub(super) type AnyRef = Box<dyn Any + Send>;
+
+type SetRelay = Box<dyn FnOnce(AnyRef) + Send>;
+
+fn downcast<T: 'static>(value: AnyRef) -> T {
+    match value.downcast::<T>() {
+        Ok(value) => *value,
+        Err(_) => panic!("pantomime bug: graph port type mismatch"),
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) enum NodeKind {
+    Source,
+    Flow,
+    Sink,
+    Junction,
+
+    /// A source that has been fused with a sink.
+    Runnable,
+}
+
+impl NodeKind {
+    pub(super) fn name(self) -> &'static str {
+        match self {
+            NodeKind::Source => "source",
+            NodeKind::Flow => "flow",
+            NodeKind::Sink | NodeKind::Runnable => "sink",
+            NodeKind::Junction => "junction",
+        }
+    }
+}
+
+pub(super) struct Node<Out> {
+    pub(super) kind: NodeKind,
+    pub(super) inlets: usize,
+    pub(super) outlets: usize,
+    pub(super) logic: Box<dyn NodeLogic<Out> + Send>,
+}
+
+impl<Out> Node<Out>
+where
+    Out: 'static + Send,
+{
+    pub(super) fn source<A>(source: Source<A>) -> Self
+    where
+        A: 'static + Send,
+    {
+        Self {
+            kind: NodeKind::Source,
+            inlets: 0,
+            outlets: 1,
+            logic: Box::new(SourceNode(source)),
+        }
+    }
+
+    pub(super) fn flow<A, B>(flow: Flow<A, B>) -> Self
+    where
+        A: 'static + Send,
+        B: 'static + Send,
+    {
+        Self {
+            kind: NodeKind::Flow,
+            inlets: 1,
+            outlets: 1,
+            logic: Box::new(FlowNode(flow)),
+        }
+    }
+
+    pub(super) fn sink<A>(sink: Sink<A, Out>) -> Self
+    where
+        A: 'static + Send,
+    {
+        Self {
+            kind: NodeKind::Sink,
+            inlets: 1,
+            outlets: 0,
+            logic: Box::new(SinkNode(sink)),
+        }
+    }
+
+    pub(super) fn junction<A, B, L: PortLogic<A, B>>(logic: L) -> Self
+    where
+        A: 'static + Send,
+        B: 'static + Send,
+        L: 'static + Send,
+    {
+        Self {
+            kind: NodeKind::Junction,
+            inlets: logic.inlets(),
+            outlets: logic.outlets(),
+            logic: Box::new(JunctionNode {
+                logic,
+                phantom: PhantomData,
+            }),
+        }
+    }
+}
+
+pub(super) trait NodeLogic<Out> {
+    /// Spawn the node, whose outlets emit to the supplied references (or for
+    /// a sink, whose output is emitted to the single supplied reference),
+    /// returning the references that its inlets receive elements on.
+    fn spawn(
+        self: Box<Self>,
+        downstreams: Vec<AnyRef>,
+        context: &mut ActorSpawnContext,
+    ) -> Vec<AnyRef>;
+
+    /// Unwrap the source or sink that this node holds.
+    fn into_any(self: Box<Self>) -> Box<dyn Any + Send>;
+
+    /// Fuse the supplied source, which this node's inlet is connected to,
+    /// into this node.
+    fn fuse_source(self: Box<Self>, _source: Box<dyn Any + Send>) -> Node<Out> {
+        panic!("pantomime bug: graph node cannot be fused with a source");
+    }
+
+    /// Fuse this node into the supplied sink, which its outlet is
+    /// connected to.
+    fn fuse_sink(self: Box<Self>, _sink: Box<dyn Any + Send>) -> Node<Out> {
+        panic!("pantomime bug: graph node cannot be fused with a sink");
+    }
+}
+
+struct SourceNode<A>(Source<A>);
+
+impl<A, Out> NodeLogic<Out> for SourceNode<A>
+where
+    A: 'static + Send,
+{
+    fn spawn(
+        self: Box<Self>,
+        mut downstreams: Vec<AnyRef>,
+        context: &mut ActorSpawnContext,
+    ) -> Vec<AnyRef> {
+        let downstream = downstreams
+            .pop()
+            .expect("pantomime bug: graph source has no downstream");
+
+        spawn_source(
+            self.0.producer().into_facade(),
+            downcast::<ActorRef<DownstreamStageMsg<A>>>(downstream),
+            context,
+        );
+
+        Vec::new()
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any + Send> {
+        Box::new(self.0)
+    }
+}
+
+struct FlowNode<A, B>(Flow<A, B>);
+
+impl<A, B, Out> NodeLogic<Out> for FlowNode<A, B>
+where
+    A: 'static + Send,
+    B: 'static + Send,
+    Out: 'static + Send,
+{
+    fn spawn(
+        self: Box<Self>,
+        mut downstreams: Vec<AnyRef>,
+        context: &mut ActorSpawnContext,
+    ) -> Vec<AnyRef> {
+        let downstream = downstreams
+            .pop()
+            .expect("pantomime bug: graph flow has no downstream");
+
+        let upstream = self.0.logic.into_facade().spawn(
+            downcast::<ActorRef<DownstreamStageMsg<B>>>(downstream),
+            context,
+        );
+
+        vec![Box::new(upstream)]
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any + Send> {
+        Box::new(self.0)
+    }
+
+    fn fuse_source(self: Box<Self>, source: Box<dyn Any + Send>) -> Node<Out> {
+        Node::source(downcast::<Source<A>>(source).via(self.0))
+    }
+
+    fn fuse_sink(self: Box<Self>, sink: Box<dyn Any + Send>) -> Node<Out> {
+        Node::sink(self.0.to(downcast::<Sink<B, Out>>(sink)))
+    }
+}
+
+struct SinkNode<A, Out>(Sink<A, Out>)
+where
+    Out: 'static + Send;
+
+impl<A, Out> NodeLogic<Out> for SinkNode<A, Out>
+where
+    A: 'static + Send,
+    Out: 'static + Send,
+{
+    fn spawn(
+        self: Box<Self>,
+        mut downstreams: Vec<AnyRef>,
+        context: &mut ActorSpawnContext,
+    ) -> Vec<AnyRef> {
+        let downstream = downstreams
+            .pop()
+            .expect("pantomime bug: graph sink has no downstream");
+
+        let upstream = self.0.logic.into_facade().spawn(
+            downcast::<ActorRef<DownstreamStageMsg<Out>>>(downstream),
+            context,
+        );
+
+        vec![Box::new(upstream)]
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any + Send> {
+        Box::new(self.0)
+    }
+
+    fn fuse_source(self: Box<Self>, source: Box<dyn Any + Send>) -> Node<Out> {
+        // a source is a flow without an input, which lets it be fused
+        // with the sink in the same way that a flow would be
+
+        let source = Flow {
+            logic: downcast::<Source<A>>(source).producer(),
+            empty: false,
+        };
+
+        Node {
+            kind: NodeKind::Runnable,
+            inlets: 0,
+            outlets: 0,
+            logic: Box::new(RunnableNode(source.to(self.0))),
+        }
+    }
+}
+
+struct RunnableNode<Out>(Sink<(), Out>)
+where
+    Out: 'static + Send;
+
+impl<Out> NodeLogic<Out> for RunnableNode<Out>
+where
+    Out: 'static + Send,
+{
+    fn spawn(
+        self: Box<Self>,
+        mut downstreams: Vec<AnyRef>,
+        context: &mut ActorSpawnContext,
+    ) -> Vec<AnyRef> {
+        let downstream = downstreams
+            .pop()
+            .expect("pantomime bug: graph sink has no downstream");
+
+        spawn_source(
+            self.0.logic.into_facade(),
+            downcast::<ActorRef<DownstreamStageMsg<Out>>>(downstream),
+            context,
+        );
+
+        Vec::new()
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any + Send> {
+        Box::new(self.0)
+    }
+}
+
+struct JunctionNode<A, B, L> {
+    logic: L,
+    phantom: PhantomData<(A, B)>,
+}
+
+impl<A, B, L, Out> NodeLogic<Out> for JunctionNode<A, B, L>
+where
+    L: 'static + PortLogic<A, B> + Send,
+    A: 'static + Send,
+    B: 'static + Send,
+{
+    fn spawn(
+        self: Box<Self>,
+        downstreams: Vec<AnyRef>,
+        context: &mut ActorSpawnContext,
+    ) -> Vec<AnyRef> {
+        let downstreams = downstreams
+            .into_iter()
+            .map(downcast::<ActorRef<DownstreamStageMsg<B>>>)
+            .collect();
+
+        spawn_junction(self.logic, downstreams, context)
+            .into_iter()
+            .map(|upstream| Box::new(upstream) as AnyRef)
+            .collect()
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any + Send> {
+        Box::new(self.logic)
+    }
+}
+
+/// A connection from an outlet to an inlet, identified by their node
+/// and port.
+pub(super) struct Edge {
+    pub(super) from: (usize, usize),
+    pub(super) to: (usize, usize),
+
+    /// Spawns a relay for the connection, used when the inlet's node
+    /// hasn't been spawned yet (i.e. for the edges that form a cycle).
+    pub(super) relay: fn(&mut ActorSpawnContext) -> (AnyRef, SetRelay),
+}
+
+pub(super) fn spawn_relay<A>(context: &mut ActorSpawnContext) -> (AnyRef, SetRelay)
+where
+    A: 'static + Send,
+{
+    let relay = context.spawn(Relay {
+        downstream: None,
+        stash: Vec::new(),
+    });
+
+    let upstream = relay.convert(RelayMsg::Relay);
+
+    (
+        Box::new(upstream),
+        Box::new(move |downstream| {
+            relay.tell(RelayMsg::SetDownstream(downcast::<
+                ActorRef<DownstreamStageMsg<A>>,
+            >(downstream)));
+        }),
+    )
+}
+
+enum RelayMsg<A>
+where
+    A: 'static + Send,
+{
+    SetDownstream(ActorRef<DownstreamStageMsg<A>>),
+    Relay(DownstreamStageMsg<A>),
+}
+
+/// Forwards the messages of an upstream to a downstream that is only
+/// known once it has been spawned, stashing them until then.
+struct Relay<A>
+where
+    A: 'static + Send,
+{
+    downstream: Option<ActorRef<DownstreamStageMsg<A>>>,
+    stash: Vec<DownstreamStageMsg<A>>,
+}
+
+impl<A> Relay<A>
+where
+    A: 'static + Send,
+{
+    fn relay(&mut self, msg: DownstreamStageMsg<A>, ctx: &mut ActorContext<RelayMsg<A>>) {
+        match self.downstream {
+            Some(ref downstream) => {
+                let completed = matches!(msg, DownstreamStageMsg::Complete(_));
+
+                downstream.tell(msg);
+
+                if completed {
+                    ctx.stop();
+                }
+            }
+
+            None => {
+                self.stash.push(msg);
+            }
+        }
+    }
+}
+
+impl<A> Actor for Relay<A>
+where
+    A: 'static + Send,
+{
+    type Msg = RelayMsg<A>;
+
+    fn receive(&mut self, msg: RelayMsg<A>, ctx: &mut ActorContext<RelayMsg<A>>) {
+        match msg {
+            RelayMsg::SetDownstream(downstream) => {
+                self.downstream = Some(downstream);
+
+                for msg in std::mem::take(&mut self.stash) {
+                    self.relay(msg, ctx);
+                }
+            }
+
+            RelayMsg::Relay(msg) => {
+                self.relay(msg, ctx);
+            }
+        }
+    }
+}
+
+/// The materialization of a graph, which is a stage whose upstream feeds
+/// the graph's open inlet (if any), and whose downstream is fed by either
+/// its open outlet or, if it has sinks, the gathered outputs of its sinks.
+pub(super) struct GraphLogic<Up, Down, Out> {
+    pub(super) nodes: Vec<Option<Node<Out>>>,
+    pub(super) edges: Vec<Edge>,
+    pub(super) sinks: Vec<usize>,
+    pub(super) inlet: Option<(usize, usize)>,
+    pub(super) outlet: Option<(usize, usize)>,
+    pub(super) phantom: PhantomData<(Up, Down)>,
+}
+
+impl<Up, Down, Out> LogicContainerFacade<Up, Down> for GraphLogic<Up, Down, Out>
+where
+    Up: 'static + Send,
+    Down: 'static + Send,
+    Out: 'static + Send,
+{
+    fn spawn(
+        self: Box<Self>,
+        downstream: ActorRef<DownstreamStageMsg<Down>>,
+        context: &mut ActorSpawnContext,
+    ) -> ActorRef<DownstreamStageMsg<Up>> {
+        let GraphLogic {
+            mut nodes,
+            edges,
+            sinks,
+            inlet,
+            outlet,
+            ..
+        } = *self;
+
+        let mut downstream = Some(Box::new(downstream) as AnyRef);
+
+        let mut outputs = HashMap::new();
+
+        if !sinks.is_empty() {
+            let downstream = downstream
+                .take()
+                .expect("pantomime bug: graph downstream already taken");
+
+            let gather = spawn_gather(
+                sinks.len(),
+                downcast::<ActorRef<DownstreamStageMsg<Vec<Out>>>>(downstream),
+                context,
+            );
+
+            for (node, output) in sinks.into_iter().zip(gather) {
+                outputs.insert(node, Box::new(output) as AnyRef);
+            }
+        }
+
+        let mut edges = edges
+            .into_iter()
+            .map(|edge| (edge.from, edge))
+            .collect::<HashMap<_, _>>();
+
+        let mut upstreams = HashMap::<(usize, usize), AnyRef>::new();
+        let mut relays = Vec::new();
+
+        let mut remaining = (0..nodes.len())
+            .filter(|n| nodes[*n].is_some())
+            .collect::<Vec<_>>();
+
+        // nodes are spawned once all of the nodes that they emit to have
+        // been, and so in reverse topological order. if there's a cycle,
+        // the edges that form it are connected via relays
+
+        while !remaining.is_empty() {
+            let next = remaining
+                .iter()
+                .position(|n| {
+                    edges
+                        .values()
+                        .filter(|edge| edge.from.0 == *n)
+                        .all(|edge| upstreams.contains_key(&edge.to))
+                })
+                .unwrap_or(0);
+
+            let n = remaining.remove(next);
+
+            let node = nodes[n]
+                .take()
+                .expect("pantomime bug: graph node already spawned");
+
+            let downstreams = match node.kind {
+                NodeKind::Sink | NodeKind::Runnable => vec![outputs
+                    .remove(&n)
+                    .expect("pantomime bug: graph sink has no output")],
+
+                _ => (0..node.outlets)
+                    .map(|port| {
+                        if outlet == Some((n, port)) {
+                            return downstream
+                                .take()
+                                .expect("pantomime bug: graph downstream already taken");
+                        }
+
+                        let edge = edges
+                            .remove(&(n, port))
+                            .expect("pantomime bug: graph outlet is not connected");
+
+                        match upstreams.remove(&edge.to) {
+                            Some(upstream) => upstream,
+
+                            None => {
+                                let (upstream, set_downstream) = (edge.relay)(context);
+
+                                relays.push((edge.to, set_downstream));
+
+                                upstream
+                            }
+                        }
+                    })
+                    .collect(),
+            };
+
+            for (port, upstream) in node
+                .logic
+                .spawn(downstreams, context)
+                .into_iter()
+                .enumerate()
+            {
+                upstreams.insert((n, port), upstream);
+            }
+        }
+
+        for (to, set_downstream) in relays {
+            set_downstream(
+                upstreams
+                    .remove(&to)
+                    .expect("pantomime bug: graph relay has no downstream"),
+            );
+        }
+
+        match inlet {
+            Some(inlet) => downcast::<ActorRef<DownstreamStageMsg<Up>>>(
+                upstreams
+                    .remove(&inlet)
+                    .expect("pantomime bug: graph inlet was not spawned"),
+            ),
+
+            None => ActorRef::empty(),
+        }
+    }
+}
+
+impl<Out> RunnableStream<Vec<Out>> for GraphLogic<(), Vec<Out>, Out>
+where
+    Out: 'static + Send,
+{
+    fn run(self: Box<Self>, context: &mut ActorContext<InternalStreamCtl<Vec<Out>>>) {
+        let actor_ref_for_sink = context.actor_ref().convert(InternalStreamCtl::FromSink);
+
+        let mut context_for_graph = context.spawn_context();
+
+        // the graph's sources are started as they're spawned, so unlike
+        // other streams, there's no origin to start
+
+        self.spawn(actor_ref_for_sink, &mut context_for_graph);
+    }
+}
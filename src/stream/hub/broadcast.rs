@@ -0,0 +1,258 @@
+use crate::stream::hub::{HubRef, HubSinkCtl, HubSource, HubSourceCtl};
+use crate::stream::{Action, Logic, LogicEvent, Source, StageRef, StreamContext};
+use std::collections::{HashMap, VecDeque};
+
+/// A handle to a sink created with `Sink::broadcast_hub`, from which
+/// any number of consumer sources can be obtained.
+pub struct BroadcastHub<A>
+where
+    A: Send,
+{
+    hub: HubRef<HubSinkCtl<A>>,
+}
+
+impl<A> BroadcastHub<A>
+where
+    A: 'static + Send,
+{
+    /// Obtain a source that emits the elements of the hub's sink, starting
+    /// with the oldest element that the hub has buffered. The source
+    /// completes once the hub's sink has completed and it has emitted all
+    /// of the buffered elements.
+    pub fn source(&self) -> Source<A> {
+        Source::new(HubSource::new(self.hub.clone()))
+    }
+}
+
+impl<A> Clone for BroadcastHub<A>
+where
+    A: Send,
+{
+    fn clone(&self) -> Self {
+        Self {
+            hub: self.hub.clone(),
+        }
+    }
+}
+
+struct Consumer<A>
+where
+    A: Send,
+{
+    stage_ref: StageRef<HubSourceCtl<A>>,
+    offset: usize,
+    pulled: bool,
+}
+
+/// The sink of a `BroadcastHub`, which emits each of its elements to
+/// all of its consumers.
+///
+/// At most `capacity` elements are buffered, and elements are only
+/// discarded once every consumer has received them, so the hub is
+/// backpressured by its slowest consumer, or when no consumers are
+/// attached.
+pub(in crate::stream) struct BroadcastHubSink<A>
+where
+    A: 'static + Send,
+{
+    hub: HubRef<HubSinkCtl<A>>,
+    consumers: HashMap<usize, Consumer<A>>,
+    buffer: VecDeque<A>,
+    capacity: usize,
+    head: usize,
+    pulled: bool,
+    upstream_pulled: bool,
+    stopped: bool,
+}
+
+impl<A> BroadcastHubSink<A>
+where
+    A: 'static + Clone + Send,
+{
+    pub(in crate::stream) fn new(capacity: usize) -> (Self, BroadcastHub<A>) {
+        let hub = HubRef::new();
+
+        let sink = Self {
+            hub: hub.clone(),
+            consumers: HashMap::new(),
+            buffer: VecDeque::new(),
+            capacity: capacity.max(1),
+            head: 0,
+            pulled: false,
+            upstream_pulled: false,
+            stopped: false,
+        };
+
+        (sink, BroadcastHub { hub })
+    }
+
+    fn tail(&self) -> usize {
+        self.head + self.buffer.len()
+    }
+
+    fn try_emit(&mut self, id: usize) {
+        let head = self.head;
+        let tail = self.tail();
+        let stopped = self.stopped;
+
+        if let Some(consumer) = self.consumers.get_mut(&id) {
+            if consumer.pulled && consumer.offset < tail {
+                consumer.pulled = false;
+
+                consumer.stage_ref.tell(HubSourceCtl::Element(
+                    self.buffer[consumer.offset - head].clone(),
+                ));
+
+                consumer.offset += 1;
+            }
+
+            if stopped && consumer.offset == tail {
+                consumer.stage_ref.tell(HubSourceCtl::Complete);
+
+                self.consumers.remove(&id);
+            }
+        }
+    }
+
+    fn try_pull(&mut self) -> Action<(), HubSinkCtl<A>> {
+        // elements that every consumer has received are discarded, but
+        // they're retained while there aren't any consumers
+
+        if !self.consumers.is_empty() {
+            let offset = self
+                .consumers
+                .values()
+                .map(|c| c.offset)
+                .min()
+                .unwrap_or(self.head);
+
+            while self.head < offset {
+                self.buffer.pop_front();
+                self.head += 1;
+            }
+        }
+
+        if self.stopped {
+            if self.pulled && self.consumers.is_empty() {
+                Action::PushAndStop((), None)
+            } else {
+                Action::None
+            }
+        } else if self.pulled && !self.upstream_pulled && self.buffer.len() < self.capacity {
+            self.upstream_pulled = true;
+
+            Action::Pull
+        } else {
+            Action::None
+        }
+    }
+}
+
+impl<A> Logic<A, ()> for BroadcastHubSink<A>
+where
+    A: 'static + Clone + Send,
+{
+    type Ctl = HubSinkCtl<A>;
+
+    fn name(&self) -> &'static str {
+        "pantomime::stream::hub::BroadcastHubSink"
+    }
+
+    fn receive(
+        &mut self,
+        msg: LogicEvent<A, Self::Ctl>,
+        ctx: &mut StreamContext<A, (), Self::Ctl>,
+    ) -> Action<(), Self::Ctl> {
+        match msg {
+            LogicEvent::Started => {
+                self.hub.start(ctx.stage_ref());
+
+                Action::None
+            }
+
+            LogicEvent::Pulled => {
+                self.pulled = true;
+
+                self.try_pull()
+            }
+
+            LogicEvent::Pushed(element) => {
+                self.upstream_pulled = false;
+                self.buffer.push_back(element);
+
+                let ids = self.consumers.keys().copied().collect::<Vec<_>>();
+
+                for id in ids {
+                    self.try_emit(id);
+                }
+
+                self.try_pull()
+            }
+
+            LogicEvent::Stopped => {
+                self.stopped = true;
+
+                let ids = self.consumers.keys().copied().collect::<Vec<_>>();
+
+                for id in ids {
+                    self.try_emit(id);
+                }
+
+                self.try_pull()
+            }
+
+            LogicEvent::Cancelled => {
+                if self.stopped && self.pulled {
+                    Action::PushAndStop((), None)
+                } else if self.stopped {
+                    Action::Stop(None)
+                } else {
+                    Action::Cancel
+                }
+            }
+
+            LogicEvent::Forwarded(HubSinkCtl::Register(id, stage_ref)) => {
+                let consumer = Consumer {
+                    stage_ref,
+                    offset: self.head,
+                    pulled: false,
+                };
+
+                self.consumers.insert(id, consumer);
+
+                self.try_emit(id);
+
+                Action::None
+            }
+
+            LogicEvent::Forwarded(HubSinkCtl::Pull(id)) => {
+                if let Some(consumer) = self.consumers.get_mut(&id) {
+                    consumer.pulled = true;
+                }
+
+                self.try_emit(id);
+
+                self.try_pull()
+            }
+
+            LogicEvent::Forwarded(HubSinkCtl::Cancel(id)) => {
+                self.consumers.remove(&id);
+
+                self.try_pull()
+            }
+        }
+    }
+}
+
+impl<A> Drop for BroadcastHubSink<A>
+where
+    A: 'static + Send,
+{
+    fn drop(&mut self) {
+        self.hub.stop();
+
+        for consumer in self.consumers.values() {
+            consumer.stage_ref.tell(HubSourceCtl::Complete);
+        }
+    }
+}
@@ -0,0 +1,319 @@
+use crate::stream::hub::HubRef;
+use crate::stream::{Action, Logic, LogicEvent, Sink, StageRef, StreamContext};
+use std::collections::{HashMap, VecDeque};
+
+pub(in crate::stream) enum MergeHubCtl<A>
+where
+    A: Send,
+{
+    Register(usize, StageRef<ProducerCtl>),
+    Push(usize, A),
+    Complete(usize),
+}
+
+pub(in crate::stream) enum ProducerCtl {
+    Demand(usize),
+    Cancel,
+}
+
+/// A handle to a source created with `Source::merge_hub`, from which
+/// any number of producer sinks can be obtained.
+///
+/// Each producer may have at most `buffer_size` elements in flight, after
+/// which it is backpressured until the hub's source has emitted them.
+pub struct MergeHub<A>
+where
+    A: Send,
+{
+    hub: HubRef<MergeHubCtl<A>>,
+    buffer_size: usize,
+}
+
+impl<A> MergeHub<A>
+where
+    A: 'static + Send,
+{
+    /// Obtain a sink whose elements are emitted by the hub's source. The
+    /// sink completes once its upstream has completed, or is cancelled if
+    /// the hub's source is cancelled.
+    ///
+    /// Any number of sinks can be obtained and run, including before the
+    /// hub's source has been run.
+    pub fn sink(&self) -> Sink<A, ()> {
+        Sink::new(MergeHubSink::new(self.hub.clone(), self.buffer_size))
+    }
+}
+
+impl<A> Clone for MergeHub<A>
+where
+    A: Send,
+{
+    fn clone(&self) -> Self {
+        Self {
+            hub: self.hub.clone(),
+            buffer_size: self.buffer_size,
+        }
+    }
+}
+
+/// The source of a `MergeHub`, which emits the elements of its producers
+/// in the order that they arrive.
+///
+/// It never completes on its own, and cancels its producers once it has
+/// been cancelled.
+pub(in crate::stream) struct MergeHubSource<A>
+where
+    A: Send,
+{
+    hub: HubRef<MergeHubCtl<A>>,
+    producers: HashMap<usize, StageRef<ProducerCtl>>,
+    buffer: VecDeque<(usize, A)>,
+    pulled: bool,
+}
+
+impl<A> MergeHubSource<A>
+where
+    A: 'static + Send,
+{
+    pub(in crate::stream) fn new(buffer_size: usize) -> (Self, MergeHub<A>) {
+        let hub = HubRef::new();
+
+        let source = Self {
+            hub: hub.clone(),
+            producers: HashMap::new(),
+            buffer: VecDeque::new(),
+            pulled: false,
+        };
+
+        (source, MergeHub { hub, buffer_size })
+    }
+
+    fn emit(&mut self, producer: usize, element: A) -> Action<A, MergeHubCtl<A>> {
+        self.pulled = false;
+
+        if let Some(producer) = self.producers.get(&producer) {
+            producer.tell(ProducerCtl::Demand(1));
+        }
+
+        Action::Push(element)
+    }
+}
+
+impl<A> Logic<(), A> for MergeHubSource<A>
+where
+    A: 'static + Send,
+{
+    type Ctl = MergeHubCtl<A>;
+
+    fn name(&self) -> &'static str {
+        "pantomime::stream::hub::MergeHubSource"
+    }
+
+    fn receive(
+        &mut self,
+        msg: LogicEvent<(), Self::Ctl>,
+        ctx: &mut StreamContext<(), A, Self::Ctl>,
+    ) -> Action<A, Self::Ctl> {
+        match msg {
+            LogicEvent::Started => {
+                self.hub.start(ctx.stage_ref());
+
+                Action::None
+            }
+
+            LogicEvent::Pulled => match self.buffer.pop_front() {
+                Some((producer, element)) => self.emit(producer, element),
+
+                None => {
+                    self.pulled = true;
+
+                    Action::None
+                }
+            },
+
+            LogicEvent::Forwarded(MergeHubCtl::Register(producer, stage_ref)) => {
+                self.producers.insert(producer, stage_ref);
+
+                Action::None
+            }
+
+            LogicEvent::Forwarded(MergeHubCtl::Push(producer, element)) => {
+                if self.pulled {
+                    self.emit(producer, element)
+                } else {
+                    self.buffer.push_back((producer, element));
+
+                    Action::None
+                }
+            }
+
+            LogicEvent::Forwarded(MergeHubCtl::Complete(producer)) => {
+                self.producers.remove(&producer);
+
+                Action::None
+            }
+
+            LogicEvent::Cancelled => Action::Stop(None),
+
+            LogicEvent::Pushed(()) | LogicEvent::Stopped => Action::None,
+        }
+    }
+}
+
+impl<A> Drop for MergeHubSource<A>
+where
+    A: Send,
+{
+    fn drop(&mut self) {
+        self.hub.stop();
+
+        for producer in self.producers.values() {
+            producer.tell(ProducerCtl::Cancel);
+        }
+    }
+}
+
+/// A producer of a `MergeHub`, which pulls its upstream while it has
+/// credit with the hub.
+///
+/// Producers that are run after the hub has stopped are cancelled
+/// immediately.
+struct MergeHubSink<A>
+where
+    A: Send,
+{
+    hub: HubRef<MergeHubCtl<A>>,
+    id: usize,
+    credit: usize,
+    pulled: bool,
+    upstream_pulled: bool,
+    stopped: bool,
+    cancelled: bool,
+}
+
+impl<A> MergeHubSink<A>
+where
+    A: 'static + Send,
+{
+    fn new(hub: HubRef<MergeHubCtl<A>>, buffer_size: usize) -> Self {
+        let id = hub.next_id();
+
+        Self {
+            hub,
+            id,
+            credit: buffer_size.max(1),
+            pulled: false,
+            upstream_pulled: false,
+            stopped: false,
+            cancelled: false,
+        }
+    }
+
+    fn tell(&mut self, msg: MergeHubCtl<A>) -> Action<(), ProducerCtl> {
+        if self.hub.tell(msg).is_ok() {
+            self.try_pull()
+        } else {
+            self.cancel()
+        }
+    }
+
+    fn try_pull(&mut self) -> Action<(), ProducerCtl> {
+        if self.pulled && !self.upstream_pulled && !self.stopped && self.credit > 0 {
+            self.upstream_pulled = true;
+            self.credit -= 1;
+
+            Action::Pull
+        } else {
+            Action::None
+        }
+    }
+
+    fn cancel(&mut self) -> Action<(), ProducerCtl> {
+        if self.cancelled || self.stopped {
+            Action::None
+        } else {
+            self.cancelled = true;
+
+            Action::Cancel
+        }
+    }
+}
+
+impl<A> Logic<A, ()> for MergeHubSink<A>
+where
+    A: 'static + Send,
+{
+    type Ctl = ProducerCtl;
+
+    fn name(&self) -> &'static str {
+        "pantomime::stream::hub::MergeHubSink"
+    }
+
+    fn receive(
+        &mut self,
+        msg: LogicEvent<A, Self::Ctl>,
+        ctx: &mut StreamContext<A, (), Self::Ctl>,
+    ) -> Action<(), Self::Ctl> {
+        match msg {
+            LogicEvent::Started => {
+                let msg = MergeHubCtl::Register(self.id, ctx.stage_ref());
+
+                self.tell(msg)
+            }
+
+            LogicEvent::Pulled => {
+                self.pulled = true;
+
+                if self.stopped {
+                    Action::PushAndStop((), None)
+                } else {
+                    self.try_pull()
+                }
+            }
+
+            LogicEvent::Pushed(element) => {
+                self.upstream_pulled = false;
+
+                if self.cancelled {
+                    Action::None
+                } else {
+                    let msg = MergeHubCtl::Push(self.id, element);
+
+                    self.tell(msg)
+                }
+            }
+
+            LogicEvent::Forwarded(ProducerCtl::Demand(demand)) => {
+                self.credit += demand;
+
+                self.try_pull()
+            }
+
+            LogicEvent::Forwarded(ProducerCtl::Cancel) => self.cancel(),
+
+            LogicEvent::Stopped => {
+                if !self.cancelled {
+                    let _ = self.hub.tell(MergeHubCtl::Complete(self.id));
+                }
+
+                self.stopped = true;
+
+                if self.pulled {
+                    Action::PushAndStop((), None)
+                } else {
+                    Action::None
+                }
+            }
+
+            LogicEvent::Cancelled => {
+                if self.stopped && self.pulled {
+                    Action::PushAndStop((), None)
+                } else if self.stopped {
+                    Action::Stop(None)
+                } else {
+                    Action::Cancel
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,235 @@
+//! Hubs are stages that are materialized once and then attached to
+//! dynamically, while the stream that they're a part of is running.
+//!
+//! - A `MergeHub` is a source that emits the elements of any number of
+//!   producers, each of which is a sink obtained from `MergeHub::sink`.
+//! - A `BroadcastHub` is a sink that emits its elements to any number of
+//!   consumers, each of which is a source obtained from
+//!   `BroadcastHub::source`.
+//! - A `PartitionHub` is a sink that routes each of its elements to one
+//!   of its consumers, obtained from `PartitionHub::source`.
+
+use crate::stream::{Action, Logic, LogicEvent, StageRef, StreamContext};
+use parking_lot::Mutex;
+use std::sync::Arc;
+
+mod broadcast;
+mod merge;
+mod partition;
+
+pub use self::broadcast::BroadcastHub;
+pub use self::merge::MergeHub;
+pub use self::partition::PartitionHub;
+
+pub(in crate::stream) use self::broadcast::BroadcastHubSink;
+pub(in crate::stream) use self::merge::MergeHubSource;
+pub(in crate::stream) use self::partition::PartitionHubSink;
+
+struct HubState<Ctl>
+where
+    Ctl: Send,
+{
+    stage_ref: Option<StageRef<Ctl>>,
+    pending: Vec<Ctl>,
+    closed: bool,
+    ids: usize,
+}
+
+/// A handle to the stage of a hub, shared by the stage itself and all of
+/// the stages that attach to it.
+///
+/// Messages are buffered until the hub has started, and are rejected once
+/// it has stopped.
+pub(in crate::stream) struct HubRef<Ctl>
+where
+    Ctl: Send,
+{
+    state: Arc<Mutex<HubState<Ctl>>>,
+}
+
+impl<Ctl> HubRef<Ctl>
+where
+    Ctl: 'static + Send,
+{
+    fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(HubState {
+                stage_ref: None,
+                pending: Vec::new(),
+                closed: false,
+                ids: 0,
+            })),
+        }
+    }
+
+    fn next_id(&self) -> usize {
+        let mut state = self.state.lock();
+
+        state.ids += 1;
+
+        state.ids
+    }
+
+    /// Send a message to the hub, returning it if the hub has stopped.
+    fn tell(&self, msg: Ctl) -> Result<(), Ctl> {
+        let mut state = self.state.lock();
+
+        if state.closed {
+            Err(msg)
+        } else if let Some(ref stage_ref) = state.stage_ref {
+            stage_ref.tell(msg);
+
+            Ok(())
+        } else {
+            state.pending.push(msg);
+
+            Ok(())
+        }
+    }
+
+    fn start(&self, stage_ref: StageRef<Ctl>) {
+        let mut state = self.state.lock();
+
+        for msg in state.pending.drain(..) {
+            stage_ref.tell(msg);
+        }
+
+        state.stage_ref = Some(stage_ref);
+    }
+}
+
+impl<Ctl> HubRef<Ctl>
+where
+    Ctl: Send,
+{
+    fn stop(&self) {
+        let mut state = self.state.lock();
+
+        state.closed = true;
+        state.stage_ref = None;
+        state.pending.clear();
+    }
+}
+
+impl<Ctl> Clone for HubRef<Ctl>
+where
+    Ctl: Send,
+{
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+        }
+    }
+}
+
+/// The messages that consumers of a `BroadcastHub` or `PartitionHub` send
+/// to the hub.
+pub(in crate::stream) enum HubSinkCtl<A>
+where
+    A: Send,
+{
+    Register(usize, StageRef<HubSourceCtl<A>>),
+    Pull(usize),
+    Cancel(usize),
+}
+
+/// The messages that a `BroadcastHub` or `PartitionHub` sends to its
+/// consumers.
+pub(in crate::stream) enum HubSourceCtl<A>
+where
+    A: Send,
+{
+    Element(A),
+    Complete,
+}
+
+/// A consumer of a `BroadcastHub` or `PartitionHub`, which requests
+/// an element from the hub each time that it is pulled.
+///
+/// Consumers that attach after the hub has stopped complete immediately.
+pub(in crate::stream) struct HubSource<A>
+where
+    A: Send,
+{
+    hub: HubRef<HubSinkCtl<A>>,
+    id: usize,
+    stopped: bool,
+}
+
+impl<A> HubSource<A>
+where
+    A: 'static + Send,
+{
+    fn new(hub: HubRef<HubSinkCtl<A>>) -> Self {
+        let id = hub.next_id();
+
+        Self {
+            hub,
+            id,
+            stopped: false,
+        }
+    }
+
+    fn tell(&mut self, msg: HubSinkCtl<A>) -> Action<A, HubSourceCtl<A>> {
+        if self.hub.tell(msg).is_ok() {
+            Action::None
+        } else {
+            self.stopped = true;
+
+            Action::Stop(None)
+        }
+    }
+}
+
+impl<A> Logic<(), A> for HubSource<A>
+where
+    A: 'static + Send,
+{
+    type Ctl = HubSourceCtl<A>;
+
+    fn name(&self) -> &'static str {
+        "pantomime::stream::hub::HubSource"
+    }
+
+    fn receive(
+        &mut self,
+        msg: LogicEvent<(), Self::Ctl>,
+        ctx: &mut StreamContext<(), A, Self::Ctl>,
+    ) -> Action<A, Self::Ctl> {
+        if self.stopped {
+            return Action::None;
+        }
+
+        match msg {
+            LogicEvent::Started => {
+                let msg = HubSinkCtl::Register(self.id, ctx.stage_ref());
+
+                self.tell(msg)
+            }
+
+            LogicEvent::Pulled => {
+                let msg = HubSinkCtl::Pull(self.id);
+
+                self.tell(msg)
+            }
+
+            LogicEvent::Forwarded(HubSourceCtl::Element(element)) => Action::Push(element),
+
+            LogicEvent::Forwarded(HubSourceCtl::Complete) => {
+                self.stopped = true;
+
+                Action::Stop(None)
+            }
+
+            LogicEvent::Cancelled => {
+                let _ = self.hub.tell(HubSinkCtl::Cancel(self.id));
+
+                self.stopped = true;
+
+                Action::Stop(None)
+            }
+
+            LogicEvent::Pushed(()) | LogicEvent::Stopped => Action::None,
+        }
+    }
+}
@@ -0,0 +1,245 @@
+use crate::stream::hub::{HubRef, HubSinkCtl, HubSource, HubSourceCtl};
+use crate::stream::{Action, Logic, LogicEvent, Source, StageRef, StreamContext};
+use std::collections::VecDeque;
+
+/// A handle to a sink created with `Sink::partition_hub`, from which
+/// any number of consumer sources can be obtained.
+pub struct PartitionHub<A>
+where
+    A: Send,
+{
+    hub: HubRef<HubSinkCtl<A>>,
+}
+
+impl<A> PartitionHub<A>
+where
+    A: 'static + Send,
+{
+    /// Obtain a source that emits the elements of the hub's sink that are
+    /// routed to it. The source completes once the hub's sink has completed
+    /// and it has emitted all of the elements routed to it.
+    pub fn source(&self) -> Source<A> {
+        Source::new(HubSource::new(self.hub.clone()))
+    }
+}
+
+impl<A> Clone for PartitionHub<A>
+where
+    A: Send,
+{
+    fn clone(&self) -> Self {
+        Self {
+            hub: self.hub.clone(),
+        }
+    }
+}
+
+struct Consumer<A>
+where
+    A: Send,
+{
+    id: usize,
+    stage_ref: StageRef<HubSourceCtl<A>>,
+    buffer: VecDeque<A>,
+    pulled: bool,
+}
+
+impl<A> Consumer<A>
+where
+    A: 'static + Send,
+{
+    fn try_emit(&mut self, stopped: bool) -> bool {
+        if self.pulled {
+            if let Some(element) = self.buffer.pop_front() {
+                self.pulled = false;
+
+                self.stage_ref.tell(HubSourceCtl::Element(element));
+            }
+        }
+
+        if stopped && self.buffer.is_empty() {
+            self.stage_ref.tell(HubSourceCtl::Complete);
+
+            false
+        } else {
+            true
+        }
+    }
+}
+
+/// The sink of a `PartitionHub`, which emits each of its elements to the
+/// consumer whose index is returned by the supplied function, given the
+/// number of consumers and the element. Consumers are indexed in the order
+/// that they attached, and elements for which the index is out of range
+/// are discarded.
+///
+/// At most `capacity` elements are buffered in total, and the hub only
+/// pulls while at least one consumer is attached.
+pub(in crate::stream) struct PartitionHubSink<A, F>
+where
+    A: 'static + Send,
+{
+    hub: HubRef<HubSinkCtl<A>>,
+    partition_fn: F,
+    consumers: Vec<Consumer<A>>,
+    capacity: usize,
+    pulled: bool,
+    upstream_pulled: bool,
+    stopped: bool,
+}
+
+impl<A, F: FnMut(usize, &A) -> usize> PartitionHubSink<A, F>
+where
+    A: 'static + Send,
+{
+    pub(in crate::stream) fn new(capacity: usize, partition_fn: F) -> (Self, PartitionHub<A>) {
+        let hub = HubRef::new();
+
+        let sink = Self {
+            hub: hub.clone(),
+            partition_fn,
+            consumers: Vec::new(),
+            capacity: capacity.max(1),
+            pulled: false,
+            upstream_pulled: false,
+            stopped: false,
+        };
+
+        (sink, PartitionHub { hub })
+    }
+
+    fn try_emit(&mut self) {
+        let stopped = self.stopped;
+
+        self.consumers
+            .retain_mut(|consumer| consumer.try_emit(stopped));
+    }
+
+    fn try_pull(&mut self) -> Action<(), HubSinkCtl<A>> {
+        let buffered = self.consumers.iter().map(|c| c.buffer.len()).sum::<usize>();
+
+        if self.stopped {
+            if self.pulled && self.consumers.is_empty() {
+                Action::PushAndStop((), None)
+            } else {
+                Action::None
+            }
+        } else if self.pulled
+            && !self.upstream_pulled
+            && !self.consumers.is_empty()
+            && buffered < self.capacity
+        {
+            self.upstream_pulled = true;
+
+            Action::Pull
+        } else {
+            Action::None
+        }
+    }
+}
+
+impl<A, F: FnMut(usize, &A) -> usize> Logic<A, ()> for PartitionHubSink<A, F>
+where
+    A: 'static + Send,
+    F: 'static + Send,
+{
+    type Ctl = HubSinkCtl<A>;
+
+    fn name(&self) -> &'static str {
+        "pantomime::stream::hub::PartitionHubSink"
+    }
+
+    fn receive(
+        &mut self,
+        msg: LogicEvent<A, Self::Ctl>,
+        ctx: &mut StreamContext<A, (), Self::Ctl>,
+    ) -> Action<(), Self::Ctl> {
+        match msg {
+            LogicEvent::Started => {
+                self.hub.start(ctx.stage_ref());
+
+                Action::None
+            }
+
+            LogicEvent::Pulled => {
+                self.pulled = true;
+
+                self.try_pull()
+            }
+
+            LogicEvent::Pushed(element) => {
+                self.upstream_pulled = false;
+
+                let index = (self.partition_fn)(self.consumers.len(), &element);
+
+                if let Some(consumer) = self.consumers.get_mut(index) {
+                    consumer.buffer.push_back(element);
+                }
+
+                self.try_emit();
+
+                self.try_pull()
+            }
+
+            LogicEvent::Stopped => {
+                self.stopped = true;
+
+                self.try_emit();
+
+                self.try_pull()
+            }
+
+            LogicEvent::Cancelled => {
+                if self.stopped && self.pulled {
+                    Action::PushAndStop((), None)
+                } else if self.stopped {
+                    Action::Stop(None)
+                } else {
+                    Action::Cancel
+                }
+            }
+
+            LogicEvent::Forwarded(HubSinkCtl::Register(id, stage_ref)) => {
+                self.consumers.push(Consumer {
+                    id,
+                    stage_ref,
+                    buffer: VecDeque::new(),
+                    pulled: false,
+                });
+
+                self.try_emit();
+
+                self.try_pull()
+            }
+
+            LogicEvent::Forwarded(HubSinkCtl::Pull(id)) => {
+                if let Some(consumer) = self.consumers.iter_mut().find(|c| c.id == id) {
+                    consumer.pulled = true;
+                }
+
+                self.try_emit();
+
+                self.try_pull()
+            }
+
+            LogicEvent::Forwarded(HubSinkCtl::Cancel(id)) => {
+                self.consumers.retain(|c| c.id != id);
+
+                self.try_pull()
+            }
+        }
+    }
+}
+
+impl<A, F> Drop for PartitionHubSink<A, F>
+where
+    A: 'static + Send,
+{
+    fn drop(&mut self) {
+        self.hub.stop();
+
+        for consumer in &self.consumers {
+            consumer.stage_ref.tell(HubSourceCtl::Complete);
+        }
+    }
+}
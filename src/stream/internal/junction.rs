@@ -0,0 +1,675 @@
+use crate::actor::{
+    Actor, ActorContext, ActorRef, ActorSpawnContext, FailureError, FailureReason, Signal,
+};
+use crate::stream::internal::{DownstreamStageMsg, LogicContainerFacade, UpstreamStageMsg};
+use crate::stream::{LogicPortEvent, PortAction, PortContext, PortLogic};
+use std::collections::VecDeque;
+use std::io;
+use std::marker::PhantomData;
+
+pub(in crate::stream) enum JunctionMsg<A>
+where
+    A: 'static + Send,
+{
+    Inlet(usize, DownstreamStageMsg<A>),
+    Outlet(usize, UpstreamStageMsg),
+}
+
+struct Inlet<A>
+where
+    A: 'static + Send,
+{
+    /// A source that is materialized when the inlet is first pulled.
+    source: Option<Box<dyn LogicContainerFacade<(), A> + Send>>,
+    upstream: Option<ActorRef<UpstreamStageMsg>>,
+    buffer: VecDeque<A>,
+    capacity: u64,
+    demand: u64,
+    pulled: bool,
+
+    /// The upstream has completed.
+    stopped: bool,
+
+    /// The logic has been told that the inlet stopped or failed, or it
+    /// has cancelled the inlet. No further events are delivered for it.
+    closed: bool,
+}
+
+impl<A> Inlet<A>
+where
+    A: 'static + Send,
+{
+    fn check_demand(&mut self) {
+        if self.stopped || self.closed {
+            return;
+        }
+
+        let available = self.capacity - self.demand;
+
+        if available >= (self.capacity / 2).max(1) {
+            if let Some(ref upstream) = self.upstream {
+                upstream.tell(UpstreamStageMsg::Pull(available));
+                self.demand += available;
+            }
+        }
+    }
+}
+
+struct Outlet<B>
+where
+    B: 'static + Send,
+{
+    downstream: ActorRef<DownstreamStageMsg<B>>,
+    demand: u64,
+    pulled: bool,
+    completed: bool,
+}
+
+/// A stage with multiple inlets and outlets, which is driven by a
+/// `PortLogic`. Like `Stage`, each inlet buffers elements to amortize
+/// the cost of passing them over asynchronous boundaries.
+pub(in crate::stream) struct Junction<A, B, L: PortLogic<A, B>>
+where
+    A: 'static + Send,
+    B: 'static + Send,
+{
+    logic: L,
+    inlets: Vec<Inlet<A>>,
+    outlets: Vec<Outlet<B>>,
+    events: VecDeque<LogicPortEvent<A>>,
+    actions: VecDeque<(usize, PortAction<B>)>,
+    stopped: bool,
+}
+
+impl<A, B, L: PortLogic<A, B>> Junction<A, B, L>
+where
+    A: 'static + Send,
+    B: 'static + Send,
+    L: 'static + Send,
+{
+    pub(in crate::stream) fn new(
+        logic: L,
+        downstreams: Vec<ActorRef<DownstreamStageMsg<B>>>,
+        buffer_size: usize,
+    ) -> Self {
+        assert_eq!(
+            downstreams.len(),
+            logic.outlets(),
+            "pantomime bug: junction has the wrong number of outlets"
+        );
+
+        let capacity = logic.buffer_size().unwrap_or(buffer_size).max(1);
+        let inlets = logic.inlets();
+
+        Self {
+            logic,
+            inlets: (0..inlets)
+                .map(|_| Inlet {
+                    source: None,
+                    upstream: None,
+                    buffer: VecDeque::with_capacity(capacity),
+                    capacity: capacity as u64,
+                    demand: 0,
+                    pulled: false,
+                    stopped: false,
+                    closed: false,
+                })
+                .collect(),
+            outlets: downstreams
+                .into_iter()
+                .map(|downstream| Outlet {
+                    downstream,
+                    demand: 0,
+                    pulled: false,
+                    completed: false,
+                })
+                .collect(),
+            events: VecDeque::new(),
+            actions: VecDeque::new(),
+            stopped: false,
+        }
+    }
+
+    /// Materialize the supplied source when the inlet is first pulled,
+    /// rather than when the stage starts.
+    pub(in crate::stream) fn with_lazy_source(
+        mut self,
+        port: usize,
+        source: Box<dyn LogicContainerFacade<(), A> + Send>,
+    ) -> Self {
+        self.inlets[port].source = Some(source);
+        self
+    }
+
+    fn run(&mut self, ctx: &mut ActorContext<JunctionMsg<A>>) {
+        while let Some(event) = self.events.pop_front() {
+            let mut port_ctx = PortContext {
+                actions: &mut self.actions,
+                inlets: self.inlets.len(),
+                outlets: self.outlets.len(),
+            };
+
+            self.logic.receive(event, &mut port_ctx);
+
+            while let Some((port, action)) = self.actions.pop_front() {
+                self.receive_action(port, action, ctx);
+            }
+        }
+
+        if self.outlets.iter().all(|outlet| outlet.completed) {
+            for inlet in self.inlets.iter_mut() {
+                if !inlet.stopped && !inlet.closed {
+                    if let Some(ref upstream) = inlet.upstream {
+                        upstream.tell(UpstreamStageMsg::Cancel);
+                    }
+                }
+            }
+
+            self.stopped = true;
+
+            ctx.stop();
+        }
+    }
+
+    fn receive_action(
+        &mut self,
+        port: usize,
+        action: PortAction<B>,
+        ctx: &mut ActorContext<JunctionMsg<A>>,
+    ) {
+        match action {
+            PortAction::Pull => {
+                let inlet = &mut self.inlets[port];
+
+                if inlet.closed || inlet.pulled {
+                    // @TODO must fail - logic has violated the rules
+                    return;
+                }
+
+                if let Some(source) = inlet.source.take() {
+                    inlet.pulled = true;
+
+                    let downstream = ctx
+                        .actor_ref()
+                        .convert(move |msg| JunctionMsg::Inlet(port, msg));
+
+                    spawn_source(source, downstream, &mut ctx.spawn_context());
+
+                    return;
+                }
+
+                match inlet.buffer.pop_front() {
+                    Some(element) => {
+                        inlet.demand -= 1;
+                        inlet.check_demand();
+
+                        self.events.push_back(LogicPortEvent::Pushed(port, element));
+
+                        if inlet.stopped && inlet.buffer.is_empty() {
+                            inlet.closed = true;
+
+                            self.events.push_back(LogicPortEvent::Stopped(port));
+                        }
+                    }
+
+                    None => {
+                        inlet.pulled = true;
+                    }
+                }
+            }
+
+            PortAction::Cancel => {
+                let inlet = &mut self.inlets[port];
+
+                if !inlet.closed {
+                    inlet.closed = true;
+                    inlet.pulled = false;
+                    inlet.source = None;
+                    inlet.buffer.clear();
+
+                    if !inlet.stopped {
+                        if let Some(ref upstream) = inlet.upstream {
+                            upstream.tell(UpstreamStageMsg::Cancel);
+                        }
+                    }
+                }
+            }
+
+            PortAction::Push(element) => {
+                let outlet = &mut self.outlets[port];
+
+                if outlet.completed || outlet.demand == 0 {
+                    // @TODO must fail - logic has violated the rules
+                    return;
+                }
+
+                outlet.downstream.tell(DownstreamStageMsg::Produce(element));
+                outlet.demand -= 1;
+                outlet.pulled = outlet.demand > 0;
+
+                if outlet.pulled {
+                    self.events.push_back(LogicPortEvent::Pulled(port));
+                }
+            }
+
+            PortAction::Complete(reason) => {
+                let outlet = &mut self.outlets[port];
+
+                if !outlet.completed {
+                    outlet.completed = true;
+
+                    outlet.downstream.tell(DownstreamStageMsg::Complete(reason));
+                }
+            }
+        }
+    }
+
+    fn receive_inlet(&mut self, port: usize, msg: DownstreamStageMsg<A>) {
+        let inlet = &mut self.inlets[port];
+
+        match msg {
+            DownstreamStageMsg::SetUpstream(upstream) => {
+                if inlet.closed {
+                    // the logic cancelled the inlet before it started
+
+                    upstream.tell(UpstreamStageMsg::Cancel);
+                } else {
+                    inlet.upstream = Some(upstream);
+                    inlet.check_demand();
+
+                    self.events.push_back(LogicPortEvent::Started(port));
+                }
+            }
+
+            DownstreamStageMsg::Produce(element) if !inlet.closed => {
+                if inlet.pulled {
+                    inlet.pulled = false;
+                    inlet.demand -= 1;
+                    inlet.check_demand();
+
+                    self.events.push_back(LogicPortEvent::Pushed(port, element));
+                } else {
+                    inlet.buffer.push_back(element);
+                }
+            }
+
+            DownstreamStageMsg::Complete(reason) if !inlet.closed => {
+                inlet.stopped = true;
+
+                match reason {
+                    Some(reason) => {
+                        inlet.closed = true;
+                        inlet.buffer.clear();
+
+                        self.events.push_back(LogicPortEvent::Failed(port, reason));
+                    }
+
+                    None if inlet.buffer.is_empty() => {
+                        inlet.closed = true;
+
+                        self.events.push_back(LogicPortEvent::Stopped(port));
+                    }
+
+                    None => {
+                        // stopped is delivered once the buffer is drained
+                    }
+                }
+            }
+
+            DownstreamStageMsg::Produce(_) | DownstreamStageMsg::Complete(_) => {}
+        }
+    }
+
+    fn receive_outlet(&mut self, port: usize, msg: UpstreamStageMsg) {
+        let outlet = &mut self.outlets[port];
+
+        if outlet.completed {
+            return;
+        }
+
+        match msg {
+            UpstreamStageMsg::Pull(demand) => {
+                outlet.demand += demand;
+
+                if outlet.demand > 0 && !outlet.pulled {
+                    outlet.pulled = true;
+
+                    self.events.push_back(LogicPortEvent::Pulled(port));
+                }
+            }
+
+            UpstreamStageMsg::Cancel => {
+                self.events.push_back(LogicPortEvent::Cancelled(port));
+            }
+        }
+    }
+}
+
+impl<A, B, L: PortLogic<A, B>> Actor for Junction<A, B, L>
+where
+    A: 'static + Send,
+    B: 'static + Send,
+    L: 'static + Send,
+{
+    type Msg = JunctionMsg<A>;
+
+    fn receive_signal(&mut self, signal: Signal, ctx: &mut ActorContext<JunctionMsg<A>>) {
+        if let Signal::Started = signal {
+            for (port, outlet) in self.outlets.iter().enumerate() {
+                outlet.downstream.tell(DownstreamStageMsg::SetUpstream(
+                    ctx.actor_ref()
+                        .convert(move |msg| JunctionMsg::Outlet(port, msg)),
+                ));
+            }
+        }
+    }
+
+    fn receive(&mut self, msg: JunctionMsg<A>, ctx: &mut ActorContext<JunctionMsg<A>>) {
+        if self.stopped {
+            // an upstream that starts after the junction has stopped would
+            // otherwise never be cancelled
+
+            if let JunctionMsg::Inlet(_, DownstreamStageMsg::SetUpstream(upstream)) = msg {
+                upstream.tell(UpstreamStageMsg::Cancel);
+            }
+
+            return;
+        }
+
+        match msg {
+            JunctionMsg::Inlet(port, msg) => self.receive_inlet(port, msg),
+            JunctionMsg::Outlet(port, msg) => self.receive_outlet(port, msg),
+        }
+
+        self.run(ctx);
+    }
+}
+
+/// Spawn a junction that runs the supplied logic, returning the references
+/// that the upstreams of each of its inlets are to send their elements to.
+pub(in crate::stream) fn spawn_junction<A, B, L>(
+    logic: L,
+    downstreams: Vec<ActorRef<DownstreamStageMsg<B>>>,
+    context: &mut ActorSpawnContext,
+) -> Vec<ActorRef<DownstreamStageMsg<A>>>
+where
+    L: 'static + PortLogic<A, B> + Send,
+    A: 'static + Send,
+    B: 'static + Send,
+{
+    let buffer_size = context
+        .system_context()
+        .config()
+        .default_streams_buffer_size;
+
+    let inlets = logic.inlets();
+
+    let junction = context.spawn(Junction::new(logic, downstreams, buffer_size));
+
+    (0..inlets)
+        .map(|port| junction.convert(move |msg| JunctionMsg::Inlet(port, msg)))
+        .collect()
+}
+
+/// Spawn a junction that gathers the outputs of the supplied number of
+/// sinks, returning the references that each of the sinks is to output to.
+pub(in crate::stream) fn spawn_gather<Out>(
+    sinks: usize,
+    downstream: ActorRef<DownstreamStageMsg<Vec<Out>>>,
+    context: &mut ActorSpawnContext,
+) -> Vec<ActorRef<DownstreamStageMsg<Out>>>
+where
+    Out: 'static + Send,
+{
+    spawn_junction(Gather::new(sinks), vec![downstream], context)
+}
+
+/// Spawn the supplied source and start it, as it has no upstream.
+pub(in crate::stream) fn spawn_source<A>(
+    source: Box<dyn LogicContainerFacade<(), A> + Send>,
+    downstream: ActorRef<DownstreamStageMsg<A>>,
+    context: &mut ActorSpawnContext,
+) where
+    A: 'static + Send,
+{
+    let source = source.spawn(downstream, context);
+
+    // like the stream's origin, a source's upstream is immediately
+    // completed as it has none
+
+    source.tell(DownstreamStageMsg::SetUpstream(ActorRef::empty()));
+    source.tell(DownstreamStageMsg::Complete(None));
+}
+
+/// Runs a `PortLogic` with a single outlet, whose inlets are fed by the
+/// supplied sources and, optionally, by the stage's own upstream.
+///
+/// When there is a primary inlet, it is inlet 0 and receives the elements
+/// of the stage's upstream. The sources are otherwise started immediately,
+/// as they have no upstream of their own, unless they are lazy, in which
+/// case each is started when its inlet is first pulled.
+pub(in crate::stream) struct FanIn<Up, A, L>
+where
+    Up: 'static + Send,
+    A: 'static + Send,
+{
+    pub(in crate::stream) logic: L,
+    pub(in crate::stream) primary: Option<Box<dyn LogicContainerFacade<Up, A> + Send>>,
+    pub(in crate::stream) sources: Vec<Box<dyn LogicContainerFacade<(), A> + Send>>,
+    pub(in crate::stream) lazy: bool,
+}
+
+impl<Up, A, B, L> LogicContainerFacade<Up, B> for FanIn<Up, A, L>
+where
+    L: 'static + PortLogic<A, B> + Send,
+    Up: 'static + Send,
+    A: 'static + Send,
+    B: 'static + Send,
+{
+    fn spawn(
+        self: Box<Self>,
+        downstream: ActorRef<DownstreamStageMsg<B>>,
+        context: &mut ActorSpawnContext,
+    ) -> ActorRef<DownstreamStageMsg<Up>> {
+        let buffer_size = context
+            .system_context()
+            .config()
+            .default_streams_buffer_size;
+
+        let offset = if self.primary.is_some() { 1 } else { 0 };
+
+        assert_eq!(
+            offset + self.sources.len(),
+            self.logic.inlets(),
+            "pantomime bug: junction has the wrong number of inlets"
+        );
+
+        let mut junction = Junction::new(self.logic, vec![downstream], buffer_size);
+
+        let mut sources = Vec::new();
+
+        for (port, source) in self.sources.into_iter().enumerate() {
+            if self.lazy {
+                junction = junction.with_lazy_source(offset + port, source);
+            } else {
+                sources.push((offset + port, source));
+            }
+        }
+
+        let junction = context.spawn(junction);
+
+        for (port, source) in sources {
+            spawn_source(
+                source,
+                junction.convert(move |msg| JunctionMsg::Inlet(port, msg)),
+                context,
+            );
+        }
+
+        match self.primary {
+            Some(primary) => {
+                primary.spawn(junction.convert(|msg| JunctionMsg::Inlet(0, msg)), context)
+            }
+
+            None => ActorRef::empty(),
+        }
+    }
+}
+
+/// Collects the single value that each of a number of sinks outputs,
+/// emitting them in the order of the sinks once all have completed.
+struct Gather<Out> {
+    outputs: Vec<Option<Out>>,
+    stopped: Vec<bool>,
+    pulled: bool,
+    completed: bool,
+}
+
+impl<Out> Gather<Out> {
+    fn new(inlets: usize) -> Self {
+        Self {
+            outputs: (0..inlets).map(|_| None).collect(),
+            stopped: vec![false; inlets],
+            pulled: false,
+            completed: false,
+        }
+    }
+
+    fn try_push(&mut self, ctx: &mut PortContext<Vec<Out>>) {
+        if self.pulled && self.stopped.iter().all(|s| *s) {
+            let outputs = self
+                .outputs
+                .drain(..)
+                .map(|output| output.expect("pantomime bug: output is missing"))
+                .collect();
+
+            self.completed = true;
+
+            ctx.tell(0, PortAction::Push(outputs));
+            ctx.tell(0, PortAction::Complete(None));
+        }
+    }
+
+    fn complete(&mut self, reason: Option<FailureReason>, ctx: &mut PortContext<Vec<Out>>) {
+        for (inlet, stopped) in self.stopped.iter_mut().enumerate() {
+            if !*stopped {
+                *stopped = true;
+
+                ctx.tell(inlet, PortAction::Cancel);
+            }
+        }
+
+        self.completed = true;
+
+        ctx.tell(0, PortAction::Complete(reason));
+    }
+}
+
+impl<Out> PortLogic<Out, Vec<Out>> for Gather<Out>
+where
+    Out: 'static + Send,
+{
+    fn name(&self) -> &'static str {
+        "pantomime::stream::internal::Gather"
+    }
+
+    fn inlets(&self) -> usize {
+        self.outputs.len()
+    }
+
+    fn outlets(&self) -> usize {
+        1
+    }
+
+    fn receive(&mut self, event: LogicPortEvent<Out>, ctx: &mut PortContext<Vec<Out>>) {
+        if self.completed {
+            return;
+        }
+
+        match event {
+            LogicPortEvent::Started(inlet) => {
+                ctx.tell(inlet, PortAction::Pull);
+            }
+
+            LogicPortEvent::Pulled(_) => {
+                self.pulled = true;
+
+                self.try_push(ctx);
+            }
+
+            LogicPortEvent::Pushed(inlet, output) => {
+                self.outputs[inlet] = Some(output);
+            }
+
+            LogicPortEvent::Stopped(inlet) => {
+                self.stopped[inlet] = true;
+
+                // a sink that stops without producing its output leaves
+                // nothing to emit in its place, so the stage fails
+
+                if self.outputs[inlet].is_some() {
+                    self.try_push(ctx);
+                } else {
+                    let error = io::Error::new(
+                        io::ErrorKind::Other,
+                        "sink stopped without producing an output",
+                    );
+
+                    self.complete(Some(FailureReason::Errored(FailureError::new(error))), ctx);
+                }
+            }
+
+            LogicPortEvent::Failed(inlet, reason) => {
+                self.stopped[inlet] = true;
+
+                self.complete(Some(reason), ctx);
+            }
+
+            LogicPortEvent::Cancelled(_) => {
+                self.complete(None, ctx);
+            }
+        }
+    }
+}
+
+/// Runs a `PortLogic` with a single inlet, which receives the elements of
+/// the stage's upstream, and whose outlets feed the supplied sinks.
+///
+/// The outputs of the sinks are gathered by a second junction, which emits
+/// them in the order of the sinks once they have all completed.
+pub(in crate::stream) struct FanOut<A, B, Out, L>
+where
+    B: 'static + Send,
+    Out: 'static + Send,
+{
+    pub(in crate::stream) logic: L,
+    pub(in crate::stream) sinks: Vec<Box<dyn LogicContainerFacade<B, Out> + Send>>,
+    pub(in crate::stream) phantom: PhantomData<A>,
+}
+
+impl<A, B, Out, L> LogicContainerFacade<A, Vec<Out>> for FanOut<A, B, Out, L>
+where
+    L: 'static + PortLogic<A, B> + Send,
+    A: 'static + Send,
+    B: 'static + Send,
+    Out: 'static + Send,
+{
+    fn spawn(
+        self: Box<Self>,
+        downstream: ActorRef<DownstreamStageMsg<Vec<Out>>>,
+        context: &mut ActorSpawnContext,
+    ) -> ActorRef<DownstreamStageMsg<A>> {
+        let gather = spawn_gather(self.sinks.len(), downstream, context);
+
+        let outlets = self
+            .sinks
+            .into_iter()
+            .zip(gather)
+            .map(|(sink, downstream)| sink.spawn(downstream, context))
+            .collect();
+
+        spawn_junction(self.logic, outlets, context)
+            .pop()
+            .expect("pantomime bug: junction has no inlets")
+    }
+}
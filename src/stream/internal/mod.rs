@@ -1,16 +1,26 @@
 use crate::actor::{
-    Actor, ActorContext, ActorRef, ActorSpawnContext, FailureReason, Signal, StopReason, Watchable,
+    Actor, ActorContext, ActorRef, ActorSpawnContext, ActorSystemContext, FailureReason, Signal,
+    StopReason, Watchable,
 };
+use crate::dispatcher::Dispatcher;
 use crate::stream::flow::Fused;
 use crate::stream::{
-    Action, Logic, LogicEvent, StageRef, Stream, StreamComplete, StreamContext,
-    StreamContextAction, StreamContextType, StreamCtl,
+    stopped_without_value, Action, Logic, LogicEvent, StageRef, Stream, StreamComplete,
+    StreamContext, StreamContextAction, StreamContextType, StreamCtl,
 };
 use crossbeam::atomic::AtomicCell;
+use parking_lot::Mutex;
 use std::any::Any;
 use std::collections::VecDeque;
 use std::marker::PhantomData;
 use std::sync::Arc;
+use std::task::Waker;
+
+mod junction;
+
+pub(in crate::stream) use self::junction::{
+    spawn_gather, spawn_junction, spawn_source, FanIn, FanOut,
+};
 
 // @TODO config
 const MAX_CALLS: usize = 10;
@@ -20,6 +30,19 @@ pub(in crate::stream) enum LogicType<Up, Down> {
     Fusible(Box<dyn ContainedLogic<Up, Down> + Send>),
 }
 
+impl<Up, Down> LogicType<Up, Down>
+where
+    Up: Send,
+    Down: Send,
+{
+    pub(in crate::stream) fn into_facade(self) -> Box<dyn LogicContainerFacade<Up, Down> + Send> {
+        match self {
+            LogicType::Spawnable(facade) => facade,
+            LogicType::Fusible(logic) => logic.into_facade(),
+        }
+    }
+}
+
 pub(in crate::stream) trait ContainedLogic<Up, Down>
 where
     Up: Send,
@@ -74,6 +97,7 @@ where
 
         let mut stream_ctx = StreamContext {
             ctx: StreamContextType::Fused(&mut self.actions, &self.stage_ref),
+            upstream_failure: ctx.upstream_failure,
             calls: ctx.calls,
         };
 
@@ -84,6 +108,7 @@ where
         while let Some(a) = self.actions.pop_front() {
             let mut stream_ctx = StreamContext {
                 ctx: StreamContextType::Fused(&mut self.actions, &self.stage_ref),
+                upstream_failure: ctx.upstream_failure,
                 calls: ctx.calls,
             };
 
@@ -295,6 +320,7 @@ where
     state: StageState<A, B, Msg>,
     pulled: bool,
     upstream_stopped: bool,
+    upstream_failure: Option<FailureReason>,
     midstream_stopped: bool,
     downstream_demand: u64,
     upstream_demand: u64,
@@ -354,6 +380,7 @@ where
         let action = {
             let mut ctx = StreamContext {
                 ctx: StreamContextType::Spawned(ctx),
+                upstream_failure: self.upstream_failure.as_ref(),
                 calls: self.calls,
             };
 
@@ -449,6 +476,12 @@ where
 
             Action::Stop(reason) => {
                 //println!("{} Action::Stop", self.logic.name());
+
+                // if upstream failed, so does this stage, unless the
+                // logic supplied a failure of its own
+
+                let reason = reason.or_else(|| self.upstream_failure.take());
+
                 self.downstream.tell(DownstreamStageMsg::Complete(reason));
 
                 self.midstream_stopped = true;
@@ -458,8 +491,18 @@ where
 
             Action::PushAndStop(el, reason) => {
                 //println!("{} Action::PushAndStop", self.logic.name());
-                self.receive_action(Action::Push(el), ctx);
-                //println!("did push");
+
+                // unlike Action::Push, the logic must not be pulled again
+                // as it is stopping
+
+                if self.downstream_demand == 0 {
+                    // @TODO must fail - logic has violated the rules
+                } else {
+                    self.downstream.tell(DownstreamStageMsg::Produce(el));
+                    self.downstream_demand -= 1;
+                    self.logic_pulled = false;
+                }
+
                 self.receive_action(Action::Stop(reason), ctx);
             }
 
@@ -477,6 +520,22 @@ where
 {
     type Msg = StageMsg<A, B, Msg>;
 
+    fn config_dispatcher(&self, ctx: &ActorSystemContext) -> Option<Dispatcher> {
+        self.logic.dispatcher().and_then(|name| {
+            let dispatcher = ctx.named_dispatcher(name);
+
+            if dispatcher.is_none() {
+                warn!(
+                    "{} requested unknown dispatcher {}, using default",
+                    self.logic.name(),
+                    name
+                );
+            }
+
+            dispatcher
+        })
+    }
+
     fn receive_signal(&mut self, signal: Signal, ctx: &mut ActorContext<StageMsg<A, B, Msg>>) {
         self.calls = 0;
 
@@ -550,13 +609,13 @@ where
                         self.receive_logic_event(LogicEvent::Cancelled, ctx);
                     }
 
-                    StageMsg::Stopped(_reason) => {
+                    StageMsg::Stopped(reason) => {
                         //println!("{} StageMsg::Stopped (cancelled={})", self.logic.name(), self.cancelled);
-                        // @TODO reason
                         // upstream has stopped, need to drain buffers and be done
                         // this solution has a rare race though and freezes
 
                         self.upstream_stopped = true;
+                        self.upstream_failure = reason;
 
                         // TODO here's the problem, see #66
                         if self.buffer.is_empty() || self.cancelled {
@@ -653,6 +712,7 @@ where
             state: StageState::Waiting(None),
             pulled: false,
             upstream_stopped: false,
+            upstream_failure: None,
             midstream_stopped: false,
             downstream_demand: 0,
             upstream_demand: 0,
@@ -773,6 +833,7 @@ where
             state: StageState::Waiting(None),
             pulled: false,
             upstream_stopped: false,
+            upstream_failure: None,
             midstream_stopped: false,
             downstream_demand: 0,
             upstream_demand: 0,
@@ -794,10 +855,14 @@ where
 {
     fn perform_spawn(&mut self, stream: Stream<Out>) -> (ActorRef<StreamCtl>, StreamComplete<Out>) {
         let state = Arc::new(AtomicCell::new(None));
+        let stopped = Arc::new(Mutex::new(None));
+        let waker = Arc::new(Mutex::new(None));
 
         let controller_ref = self.spawn(StreamController {
             stream: Some(stream),
             state: state.clone(),
+            stopped: stopped.clone(),
+            waker: waker.clone(),
             produced: false,
             stream_stopped: false,
         });
@@ -807,6 +872,8 @@ where
             StreamComplete {
                 controller_ref,
                 state,
+                stopped,
+                waker,
             },
         )
     }
@@ -819,30 +886,63 @@ where
     F: 'static + Send,
 {
     fn perform_watch(&mut self, subject: StreamComplete<Out>, convert: F) {
-        self.watch(
+        self.watch_with_option(
             &subject.controller_ref.clone(),
             move |_reason: StopReason| {
-                // @TODO inspect reason
-
-                match subject.state.swap(None) {
-                    Some(value) => convert(value),
+                // a stream that failed or stopped without a value has
+                // nothing to deliver, see `watch_result`
 
-                    None => {
-                        panic!("TODO");
-                        // @TODO
-                    }
+                match subject.result() {
+                    Some(Ok(value)) => Some(convert(value)),
+                    _ => None,
                 }
             },
         );
     }
 }
 
+impl<Msg> ActorContext<Msg>
+where
+    Msg: 'static + Send,
+{
+    /// Watch the supplied stream, receiving a domain message with its
+    /// result when it completes.
+    ///
+    /// Unlike `watch`, which only delivers the value that the stream
+    /// produced, this also delivers its failure, or an error if it was
+    /// stopped before producing a value.
+    pub fn watch_result<Out, F: Fn(Result<Out, FailureReason>) -> Msg>(
+        &mut self,
+        subject: StreamComplete<Out>,
+        convert: F,
+    ) where
+        Out: 'static + Send,
+        F: 'static + Send,
+    {
+        self.watch(
+            &subject.controller_ref.clone(),
+            move |_reason: StopReason| {
+                // the controller records how the stream stopped before its
+                // watchers are notified, so the result is always available
+
+                convert(
+                    subject
+                        .result()
+                        .unwrap_or_else(|| Err(stopped_without_value())),
+                )
+            },
+        );
+    }
+}
+
 struct StreamController<Out>
 where
     Out: Send,
 {
     stream: Option<Stream<Out>>,
     state: Arc<AtomicCell<Option<Out>>>,
+    stopped: Arc<Mutex<Option<Option<FailureReason>>>>,
+    waker: Arc<Mutex<Option<Waker>>>,
     produced: bool,
     stream_stopped: bool,
     // @TODO
@@ -858,8 +958,20 @@ where
     type Msg = InternalStreamCtl<Out>;
 
     fn receive_signal(&mut self, signal: Signal, ctx: &mut ActorContext<InternalStreamCtl<Out>>) {
-        if let Signal::Started = signal {
-            self.stream.take().unwrap().run(ctx);
+        match signal {
+            Signal::Started => {
+                self.stream.take().unwrap().run(ctx);
+            }
+
+            Signal::Stopped(reason) => {
+                *self.stopped.lock() = Some(reason);
+
+                if let Some(waker) = self.waker.lock().take() {
+                    waker.wake();
+                }
+            }
+
+            Signal::Resumed => {}
         }
     }
 
@@ -887,8 +999,14 @@ where
                 self.produced = true;
             }
 
-            InternalStreamCtl::FromSink(DownstreamStageMsg::Complete(_reason)) => {
-                // @TODO reason
+            InternalStreamCtl::FromSink(DownstreamStageMsg::Complete(Some(reason))) => {
+                // the sink may have produced a value before failing, which
+                // watchers still receive, but the stream itself fails
+
+                ctx.fail_with_reason(reason);
+            }
+
+            InternalStreamCtl::FromSink(DownstreamStageMsg::Complete(None)) => {
                 assert!(self.produced, "pantomime bug: sink did not produce a value");
 
                 ctx.stop();
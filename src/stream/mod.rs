@@ -1,21 +1,40 @@
-use crate::actor::{ActorContext, ActorRef, FailureReason, SubscriptionEvent};
+use crate::actor::{ActorContext, ActorRef, FailureError, FailureReason, SubscriptionEvent};
+use crate::dispatcher::Dispatcher;
 use crate::stream::internal::{InternalStreamCtl, RunnableStream, StageMsg};
 use crossbeam::atomic::AtomicCell;
+use parking_lot::Mutex;
 use std::collections::VecDeque;
+use std::future::Future;
+use std::io;
 use std::net::SocketAddr;
+use std::pin::Pin;
 use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
 use std::time::Duration;
 
+#[cfg(target_family = "unix")]
+use std::path::PathBuf;
+
+#[cfg(feature = "posix-signals-support")]
+use crate::posix_signals::{PosixSignal, PosixSignals};
+
+pub mod datagram;
+pub mod file;
 pub mod flow;
+pub mod graph;
+pub mod hub;
 pub mod sink;
 pub mod source;
+pub mod udp;
 
 pub use crate::stream::flow::Flow;
+pub use crate::stream::graph::Graph;
 pub use crate::stream::sink::Sink;
 pub use crate::stream::source::queue::QueueRef;
 pub use crate::stream::source::Source;
 
 mod internal;
+mod process;
 
 #[cfg(test)]
 mod tests;
@@ -41,6 +60,22 @@ impl Datagram {
     }
 }
 
+/// A datagram sent or received on a Unix domain socket. A datagram
+/// without a path is sent to the socket's connected peer, and is
+/// received from peers that are unnamed.
+#[cfg(target_family = "unix")]
+pub struct UdsDatagram {
+    pub data: Vec<u8>,
+    pub path: Option<PathBuf>,
+}
+
+#[cfg(target_family = "unix")]
+impl UdsDatagram {
+    pub fn new(data: Vec<u8>, path: Option<PathBuf>) -> Self {
+        Self { data, path }
+    }
+}
+
 pub enum LogicEvent<A, Msg> {
     Pulled,
     Pushed(A),
@@ -50,20 +85,75 @@ pub enum LogicEvent<A, Msg> {
     Forwarded(Msg),
 }
 
+/// The events that are received by a `PortLogic`. Elements are pushed
+/// to, and upstreams stop on, inlets, while outlets are pulled and
+/// cancelled by their downstreams. Each carries the index of its port.
 pub enum LogicPortEvent<A> {
+    /// The outlet has been pulled by its downstream.
     Pulled(usize),
+
+    /// An element has been pushed to the inlet after it was pulled.
     Pushed(usize, A),
+
+    /// The inlet's upstream has been connected, so it can be pulled.
     Started(usize),
+
+    /// The inlet's upstream has stopped, and all of its elements
+    /// have been pushed.
     Stopped(usize),
+
+    /// The inlet's upstream has failed. Any elements that it buffered
+    /// are discarded.
+    Failed(usize, FailureReason),
+
+    /// The outlet has been cancelled by its downstream.
     Cancelled(usize),
 }
 
+/// Determines when a stage with multiple inputs, such as a merge,
+/// completes.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FanInCompletion {
+    /// Complete once all of the inputs have completed.
+    Lazy,
+
+    /// Complete as soon as any of the inputs has completed, cancelling
+    /// the others.
+    Eager,
+}
+
+/// Determines when a stage with multiple outputs, such as a broadcast,
+/// cancels its input.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FanOutCancellation {
+    /// Cancel once all of the outputs have been cancelled. Elements
+    /// that would be emitted to cancelled outputs are discarded.
+    Lazy,
+
+    /// Cancel as soon as any of the outputs has been cancelled,
+    /// completing the others.
+    Eager,
+}
+
+/// Determines how a throttle reacts to elements that exceed its rate.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ThrottleMode {
+    /// Delay the elements until they conform to the rate, backpressuring
+    /// upstream in the meantime.
+    Shaping,
+
+    /// Fail the stream.
+    Enforcing,
+}
+
 // @TODO add backpressure, fail
 pub enum OverflowStrategy {
     DropNewest,
     DropOldest,
 }
 
+/// The actions that a `PortLogic` can take. Inlets are pulled and
+/// cancelled, while elements are pushed to and completed on outlets.
 pub enum PortAction<A> {
     Cancel,
     Complete(Option<FailureReason>),
@@ -113,6 +203,17 @@ where
         true
     }
 
+    /// Defines the name of the dispatcher that the stage running this
+    /// logic should execute on. The dispatcher must be declared in the
+    /// configuration (via `PANTOMIME_DISPATCHERS`), and if it cannot be
+    /// found, the actor's default dispatcher is used instead.
+    ///
+    /// Logic that specifies a dispatcher is never fused, as it must run
+    /// in its own stage.
+    fn dispatcher(&self) -> Option<&str> {
+        None
+    }
+
     /// Handle the following event, returning an action to take. Implementations
     /// must respect the following rules, which are largely based on the work
     /// of Reactive Streams. These rules ensure that the dynamic push-pull
@@ -137,6 +238,65 @@ where
     ) -> Action<Out, Self::Ctl>;
 }
 
+/// Logic for stages with multiple inputs (inlets) and outputs (outlets),
+/// such as fan-in and fan-out stages. Ports are identified by their index,
+/// and inlets and outlets are numbered independently.
+///
+/// Each inlet and outlet is buffered by the stage, so implementations
+/// must only follow the same rules as `Logic`, per port:
+///
+/// * PortAction::Push may only be told to an outlet after receiving
+///   LogicPortEvent::Pulled for it
+/// * LogicPortEvent::Pushed is only received for an inlet after telling
+///   it PortAction::Pull
+///
+/// Once every outlet has been completed, the stage stops and any inlets
+/// that are still running are cancelled.
+pub trait PortLogic<In: Send, Out: Send>
+where
+    Self: Send + Sized,
+{
+    fn name(&self) -> &'static str;
+
+    /// The number of inlets of the stage that runs this logic.
+    fn inlets(&self) -> usize;
+
+    /// The number of outlets of the stage that runs this logic.
+    fn outlets(&self) -> usize;
+
+    /// Defines the buffer size for each inlet of the stage that runs
+    /// this logic.
+    fn buffer_size(&self) -> Option<usize> {
+        None
+    }
+
+    fn receive(&mut self, event: LogicPortEvent<In>, ctx: &mut PortContext<Out>);
+}
+
+pub struct PortContext<'a, Out> {
+    actions: &'a mut VecDeque<(usize, PortAction<Out>)>,
+    inlets: usize,
+    outlets: usize,
+}
+
+impl<'a, Out> PortContext<'a, Out> {
+    /// The number of inlets of the stage.
+    pub fn inlets(&self) -> usize {
+        self.inlets
+    }
+
+    /// The number of outlets of the stage.
+    pub fn outlets(&self) -> usize {
+        self.outlets
+    }
+
+    /// Take the supplied action on a port, i.e. an inlet when pulling or
+    /// cancelling, and an outlet when pushing or completing.
+    pub fn tell(&mut self, port: usize, action: PortAction<Out>) {
+        self.actions.push_back((port, action));
+    }
+}
+
 pub(in crate::stream) enum StreamContextAction<Out, Ctl> {
     Action(Action<Out, Ctl>),
     ScheduleDelivery(String, Duration, Ctl),
@@ -159,7 +319,7 @@ pub struct StageRef<Ctl>
 where
     Ctl: Send,
 {
-    actor_ref: ActorRef<Ctl>,
+    pub(crate) actor_ref: ActorRef<Ctl>,
 }
 
 impl<Ctl> StageRef<Ctl>
@@ -194,6 +354,7 @@ where
     Ctl: 'static + Send,
 {
     ctx: StreamContextType<'a, In, Out, Ctl>,
+    upstream_failure: Option<&'a FailureReason>,
     calls: usize,
 }
 
@@ -203,6 +364,11 @@ where
     Out: 'static + Send,
     Ctl: 'static + Send,
 {
+    /// The reason that upstream failed, if it has stopped due to a failure.
+    pub fn upstream_failure(&self) -> Option<&FailureReason> {
+        self.upstream_failure
+    }
+
     pub fn schedule_delivery<S: AsRef<str>>(&mut self, name: S, timeout: Duration, msg: Ctl) {
         match self.ctx {
             StreamContextType::Fused(ref mut actions, _) => {
@@ -245,6 +411,32 @@ where
         }
     }
 
+    /// Obtain a dispatcher that was declared in the configuration by its name,
+    /// e.g. to execute blocking work on.
+    pub(crate) fn named_dispatcher(&mut self, name: &str) -> Option<Dispatcher> {
+        match self.ctx {
+            StreamContextType::Spawned(ref mut ctx) => ctx.system_context().named_dispatcher(name),
+
+            StreamContextType::Fused(_, _) => {
+                panic!("StreamContext::named_dispatcher isn't supported by fused stages");
+            }
+        }
+    }
+
+    /// The interval at which the system's ticker fires, which determines
+    /// the resolution of scheduled deliveries.
+    pub(crate) fn ticker_interval(&mut self) -> Duration {
+        match self.ctx {
+            StreamContextType::Spawned(ref mut ctx) => {
+                Duration::from_millis(ctx.system_context().config().ticker_interval_ms)
+            }
+
+            StreamContextType::Fused(_, _) => {
+                panic!("StreamContext::ticker_interval isn't supported by fused stages");
+            }
+        }
+    }
+
     pub(crate) fn subscribe(&mut self, actor_ref: ActorRef<SubscriptionEvent>) {
         match self.ctx {
             StreamContextType::Spawned(ref mut ctx) => {
@@ -257,6 +449,24 @@ where
         }
     }
 
+    #[cfg(feature = "posix-signals-support")]
+    pub(crate) fn watch_posix_signals<F: Fn(PosixSignal) -> Ctl>(&mut self, convert: F)
+    where
+        F: 'static + Send,
+    {
+        match self.ctx {
+            StreamContextType::Spawned(ref mut ctx) => {
+                ctx.watch(PosixSignals, move |signal| {
+                    StageMsg::Action(Action::Forward(convert(signal)))
+                });
+            }
+
+            StreamContextType::Fused(_, _) => {
+                panic!("StreamContext::watch_posix_signals isn't supported by fused stages");
+            }
+        }
+    }
+
     pub(crate) fn unsubscribe(&mut self, token: usize) {
         match self.ctx {
             StreamContextType::Spawned(ref mut ctx) => {
@@ -275,12 +485,70 @@ pub enum StreamCtl {
     Fail,
 }
 
+/// A handle to the value that a stream's sink produces once the
+/// stream has completed.
+///
+/// It can be watched by an actor via `ActorContext::watch`, which
+/// delivers the value if the stream succeeds, or via
+/// `ActorContext::watch_result`, which also delivers the reason that
+/// the stream failed. It can also be awaited from asynchronous code, in
+/// which case it resolves with the stream's result.
 pub struct StreamComplete<Out>
 where
     Out: 'static + Send,
 {
     controller_ref: ActorRef<InternalStreamCtl<Out>>,
     state: Arc<AtomicCell<Option<Out>>>,
+    stopped: Arc<Mutex<Option<Option<FailureReason>>>>,
+    waker: Arc<Mutex<Option<Waker>>>,
+}
+
+impl<Out> Future for StreamComplete<Out>
+where
+    Out: 'static + Send,
+{
+    type Output = Result<Out, FailureReason>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        // the waker is registered before checking whether the stream
+        // has stopped, so that a stop in between is not missed
+
+        *self.waker.lock() = Some(cx.waker().clone());
+
+        match self.result() {
+            Some(result) => Poll::Ready(result),
+            None => Poll::Pending,
+        }
+    }
+}
+
+impl<Out> StreamComplete<Out>
+where
+    Out: 'static + Send,
+{
+    /// Take the result of the stream, if it has stopped.
+    ///
+    /// A stream that failed resolves with its failure, even if its sink
+    /// produced a value beforehand.
+    pub(in crate::stream) fn result(&self) -> Option<Result<Out, FailureReason>> {
+        match self.stopped.lock().take() {
+            Some(Some(reason)) => Some(Err(reason)),
+
+            // the value is stored before the stream stops, so a stream
+            // that stopped without one, e.g. because it was stopped
+            // externally, will never produce it
+            Some(None) => Some(self.state.swap(None).ok_or_else(stopped_without_value)),
+
+            None => None,
+        }
+    }
+}
+
+pub(in crate::stream) fn stopped_without_value() -> FailureReason {
+    FailureReason::Errored(FailureError::new(io::Error::new(
+        io::ErrorKind::Other,
+        "stream stopped without producing a value",
+    )))
 }
 
 pub struct Stream<Out> {
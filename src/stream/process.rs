@@ -0,0 +1,271 @@
+use crate::cfg::BLOCKING_IO_DISPATCHER;
+use crate::dispatcher::Dispatcher;
+use crate::stream::{StageRef, StreamContext};
+use crossbeam::channel::{bounded, Sender};
+use parking_lot::Mutex;
+use std::io::{self, BufRead, BufReader, ErrorKind as IoErrorKind, Read, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, ExitStatus, Stdio};
+use std::sync::Arc;
+use std::thread;
+
+const CHUNK_SIZE: usize = 8192;
+
+pub enum ProcessCtl {
+    Stdout(Vec<u8>),
+    StdoutClosed,
+    Written,
+    StdinClosed,
+    Exited(io::Result<ExitStatus>),
+}
+
+/// A child process that is spawned by a stage, shared by the `Process`
+/// flow and sink. The outcome of each operation is forwarded to the stage
+/// as a `ProcessCtl`.
+///
+/// Stdout is read from and stdin is written to by work that is executed on
+/// the `BLOCKING_IO_DISPATCHER`. Waiting for the child to exit and logging
+/// its stderr last as long as the child does, so they are performed by
+/// dedicated threads instead.
+///
+/// The child is killed when this is dropped.
+pub(in crate::stream) struct ChildProcess {
+    command: Option<Command>,
+    log_stderr: bool,
+    dispatcher: Option<Dispatcher>,
+    child: Option<Arc<Mutex<Child>>>,
+    stdin: Option<Arc<ChildStdin>>,
+    stdout: Option<Arc<Mutex<ChildStdout>>>,
+    wait: Option<Sender<()>>,
+}
+
+impl ChildProcess {
+    pub(in crate::stream) fn new(command: Command, log_stderr: bool) -> Self {
+        Self {
+            command: Some(command),
+            log_stderr,
+            dispatcher: None,
+            child: None,
+            stdin: None,
+            stdout: None,
+            wait: None,
+        }
+    }
+
+    /// Spawn the child. If `stdout` is set, its stdout is piped and the
+    /// child is only waited on once it has been closed, and otherwise it
+    /// is discarded and the child is waited on immediately.
+    pub(in crate::stream) fn spawn<Out: 'static + Send>(
+        &mut self,
+        stdout: bool,
+        ctx: &mut StreamContext<Vec<u8>, Out, ProcessCtl>,
+    ) -> io::Result<()> {
+        let mut command = self
+            .command
+            .take()
+            .expect("pantomime bug: ChildProcess::command is None");
+
+        command
+            .stdin(Stdio::piped())
+            .stdout(if stdout {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stderr(if self.log_stderr {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            });
+
+        let mut child = command.spawn()?;
+
+        let dispatcher = ctx
+            .named_dispatcher(BLOCKING_IO_DISPATCHER)
+            .expect("pantomime bug: blocking-io dispatcher is missing");
+
+        self.stdin = child.stdin.take().map(Arc::new);
+        self.stdout = child
+            .stdout
+            .take()
+            .map(|stdout| Arc::new(Mutex::new(stdout)));
+
+        if let Some(stderr) = child.stderr.take() {
+            let program = command.get_program().to_string_lossy().into_owned();
+
+            thread::spawn(move || {
+                for line in BufReader::new(stderr).lines() {
+                    match line {
+                        Ok(line) => warn!("{}: {}", program, line),
+                        Err(_) => break,
+                    }
+                }
+            });
+        }
+
+        let child = Arc::new(Mutex::new(child));
+
+        {
+            // nothing is sent on this channel, the child is waited on once
+            // it is disconnected, i.e. once the child has closed its stdout
+            // or has been killed
+
+            let (sender, receiver) = bounded::<()>(0);
+            let child = child.clone();
+            let stage_ref = ctx.stage_ref();
+
+            if stdout {
+                self.wait = Some(sender);
+            }
+
+            thread::spawn(move || {
+                let _ = receiver.recv();
+
+                stage_ref.tell(ProcessCtl::Exited(wait(&child)));
+            });
+        }
+
+        self.child = Some(child);
+        self.dispatcher = Some(dispatcher);
+
+        Ok(())
+    }
+
+    pub(in crate::stream) fn read<Out: 'static + Send>(
+        &mut self,
+        ctx: &mut StreamContext<Vec<u8>, Out, ProcessCtl>,
+    ) {
+        if let (Some(stdout), Some(dispatcher)) = (&self.stdout, &self.dispatcher) {
+            let stdout = stdout.clone();
+            let stage_ref = ctx.stage_ref();
+
+            dispatcher.execute(move || read_stdout(&stdout, &stage_ref));
+        }
+    }
+
+    pub(in crate::stream) fn write<Out: 'static + Send>(
+        &mut self,
+        chunk: Vec<u8>,
+        ctx: &mut StreamContext<Vec<u8>, Out, ProcessCtl>,
+    ) {
+        if let (Some(stdin), Some(dispatcher)) = (&self.stdin, &self.dispatcher) {
+            // the write holds its own reference to stdin, so it is only
+            // closed once the write has completed
+
+            let stdin = stdin.clone();
+            let stage_ref = ctx.stage_ref();
+
+            dispatcher.execute(move || write_stdin(&stdin, &chunk, &stage_ref));
+        }
+    }
+
+    pub(in crate::stream) fn close_stdin(&mut self) {
+        self.stdin = None;
+    }
+
+    /// Stop reading stdout, and start waiting for the child to exit.
+    pub(in crate::stream) fn close_stdout(&mut self) {
+        self.stdout = None;
+        self.wait = None;
+    }
+
+    /// Record that the child has exited, so it's no longer killed.
+    pub(in crate::stream) fn exited(&mut self) {
+        self.child = None;
+    }
+
+    pub(in crate::stream) fn kill(&mut self) {
+        if let Some(ref child) = self.child {
+            // the lock is only held while the child is being waited on,
+            // and an error means that it has already exited
+
+            if let Some(mut child) = child.try_lock() {
+                let _ = child.kill();
+            }
+        }
+
+        // the exit status is still forwarded to the stage
+
+        self.wait = None;
+        self.child = None;
+    }
+}
+
+impl Drop for ChildProcess {
+    fn drop(&mut self) {
+        self.kill();
+    }
+}
+
+fn write_stdin(mut stdin: &ChildStdin, chunk: &[u8], stage_ref: &StageRef<ProcessCtl>) {
+    if stdin.write_all(chunk).is_ok() {
+        stage_ref.tell(ProcessCtl::Written);
+    } else {
+        stage_ref.tell(ProcessCtl::StdinClosed);
+    }
+}
+
+fn read_stdout(stdout: &Mutex<ChildStdout>, stage_ref: &StageRef<ProcessCtl>) {
+    let mut stdout = stdout.lock();
+    let mut chunk = vec![0; CHUNK_SIZE];
+
+    let read = loop {
+        match stdout.read(&mut chunk) {
+            Err(ref e) if e.kind() == IoErrorKind::Interrupted => {}
+            result => break result,
+        }
+    };
+
+    match read {
+        Ok(0) => {
+            stage_ref.tell(ProcessCtl::StdoutClosed);
+        }
+
+        Ok(n) => {
+            chunk.truncate(n);
+
+            stage_ref.tell(ProcessCtl::Stdout(chunk));
+        }
+
+        Err(e) => {
+            warn!("failed to read from child process: {}", e);
+
+            stage_ref.tell(ProcessCtl::StdoutClosed);
+        }
+    }
+}
+
+/// Block until the child has exited, returning its exit status.
+///
+/// `Child::wait` needs exclusive access to the child, so calling it right
+/// away would hold the lock until the child has exited, and `kill` would no
+/// longer be able to kill it. On Unix, this first waits for the child to
+/// exit without reaping it, so that the lock is only held once it has
+/// exited. Elsewhere, the lock is held while waiting, so the child can no
+/// longer be killed once it has closed its stdout.
+fn wait(child: &Mutex<Child>) -> io::Result<ExitStatus> {
+    #[cfg(target_family = "unix")]
+    {
+        let pid = child.lock().id() as libc::id_t;
+
+        loop {
+            // SAFETY: siginfo_t is a plain C struct, for which all zeroes
+            // is a valid value
+            let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+
+            // SAFETY: info is a valid, exclusively borrowed siginfo_t that
+            // outlives the call. WNOWAIT leaves the child waitable, so its
+            // pid isn't released and reused before `Child::wait` reaps it
+            // below, and std doesn't reap it elsewhere
+            let result =
+                unsafe { libc::waitid(libc::P_PID, pid, &mut info, libc::WEXITED | libc::WNOWAIT) };
+
+            // any other error is surfaced by reaping the child below
+
+            if result == 0 || io::Error::last_os_error().kind() != IoErrorKind::Interrupted {
+                break;
+            }
+        }
+    }
+
+    child.lock().wait()
+}
@@ -0,0 +1,240 @@
+use crate::stream::{Action, Logic, LogicEvent, StageRef, StreamContext};
+use futures_core::Stream;
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
+
+pub enum AsyncSinkCtl {
+    Demand,
+    Cancel,
+}
+
+struct Shared<A> {
+    buffer: VecDeque<A>,
+    capacity: usize,
+    stage_ref: Option<StageRef<AsyncSinkCtl>>,
+    waiting: bool,
+    completed: bool,
+    failure: Option<String>,
+    cancelled: bool,
+    waker: Option<Waker>,
+}
+
+/// The asynchronous stream of elements that have been received by
+/// a sink created with `Sink::async_stream`.
+///
+/// Elements are pulled from upstream as the buffer drains, so a slow
+/// consumer backpressures the stream. Dropping it cancels the stream.
+///
+/// The stream ends when upstream stops, including when it fails, in
+/// which case `failure` describes why.
+pub struct SinkStream<A> {
+    shared: Arc<Mutex<Shared<A>>>,
+}
+
+impl<A> SinkStream<A> {
+    /// A description of why upstream failed, if it has stopped due
+    /// to a failure. The stream itself still fails with the reason.
+    pub fn failure(&self) -> Option<String> {
+        self.shared.lock().failure.clone()
+    }
+}
+
+impl<A> Stream for SinkStream<A> {
+    type Item = A;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<A>> {
+        let mut shared = self.shared.lock();
+
+        match shared.buffer.pop_front() {
+            Some(element) => {
+                if shared.waiting {
+                    shared.waiting = false;
+
+                    if let Some(ref stage_ref) = shared.stage_ref {
+                        stage_ref.tell(AsyncSinkCtl::Demand);
+                    }
+                }
+
+                Poll::Ready(Some(element))
+            }
+
+            None if shared.completed => Poll::Ready(None),
+
+            None => {
+                shared.waker = Some(cx.waker().clone());
+
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<A> Drop for SinkStream<A> {
+    fn drop(&mut self) {
+        let mut shared = self.shared.lock();
+
+        shared.cancelled = true;
+
+        if let Some(ref stage_ref) = shared.stage_ref {
+            stage_ref.tell(AsyncSinkCtl::Cancel);
+        }
+    }
+}
+
+pub struct AsyncSink<A> {
+    shared: Arc<Mutex<Shared<A>>>,
+    pulled: bool,
+    stopped: bool,
+    cancelled: bool,
+}
+
+impl<A> AsyncSink<A>
+where
+    A: 'static + Send,
+{
+    pub fn new(capacity: usize) -> (Self, SinkStream<A>) {
+        let capacity = capacity.max(1);
+
+        let shared = Arc::new(Mutex::new(Shared {
+            buffer: VecDeque::with_capacity(capacity),
+            capacity,
+            stage_ref: None,
+            waiting: false,
+            completed: false,
+            failure: None,
+            cancelled: false,
+            waker: None,
+        }));
+
+        (
+            Self {
+                shared: shared.clone(),
+                pulled: false,
+                stopped: false,
+                cancelled: false,
+            },
+            SinkStream { shared },
+        )
+    }
+
+    fn demand(&mut self) -> Action<(), AsyncSinkCtl> {
+        if self.cancelled || self.stopped {
+            return Action::None;
+        }
+
+        let mut shared = self.shared.lock();
+
+        if shared.cancelled {
+            self.cancelled = true;
+
+            Action::Cancel
+        } else if shared.buffer.len() < shared.capacity {
+            Action::Pull
+        } else {
+            shared.waiting = true;
+
+            Action::None
+        }
+    }
+}
+
+impl<A> Logic<A, ()> for AsyncSink<A>
+where
+    A: 'static + Send,
+{
+    type Ctl = AsyncSinkCtl;
+
+    fn name(&self) -> &'static str {
+        "pantomime::stream::sink::AsyncSink"
+    }
+
+    fn fusible(&self) -> bool {
+        false
+    }
+
+    fn receive(
+        &mut self,
+        msg: LogicEvent<A, Self::Ctl>,
+        ctx: &mut StreamContext<A, (), Self::Ctl>,
+    ) -> Action<(), Self::Ctl> {
+        match msg {
+            LogicEvent::Started => {
+                self.shared.lock().stage_ref = Some(ctx.stage_ref());
+
+                Action::None
+            }
+
+            LogicEvent::Pulled => {
+                self.pulled = true;
+
+                if self.stopped {
+                    Action::PushAndStop((), None)
+                } else {
+                    self.demand()
+                }
+            }
+
+            LogicEvent::Pushed(element) => {
+                if !self.cancelled {
+                    let mut shared = self.shared.lock();
+
+                    shared.buffer.push_back(element);
+
+                    if let Some(waker) = shared.waker.take() {
+                        waker.wake();
+                    }
+                }
+
+                self.demand()
+            }
+
+            LogicEvent::Forwarded(AsyncSinkCtl::Demand) => self.demand(),
+
+            LogicEvent::Forwarded(AsyncSinkCtl::Cancel) => {
+                if self.cancelled || self.stopped {
+                    Action::None
+                } else {
+                    self.cancelled = true;
+
+                    Action::Cancel
+                }
+            }
+
+            LogicEvent::Stopped => {
+                self.stopped = true;
+
+                {
+                    let mut shared = self.shared.lock();
+
+                    shared.completed = true;
+                    shared.failure = ctx.upstream_failure().map(|reason| reason.to_string());
+
+                    if let Some(waker) = shared.waker.take() {
+                        waker.wake();
+                    }
+                }
+
+                if self.pulled {
+                    Action::PushAndStop((), None)
+                } else {
+                    Action::None
+                }
+            }
+
+            LogicEvent::Cancelled => {
+                if self.stopped && self.pulled {
+                    Action::PushAndStop((), None)
+                } else if self.stopped {
+                    Action::Stop(None)
+                } else {
+                    self.cancelled = true;
+
+                    Action::Cancel
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,303 @@
+use crate::actor::{FailureError, FailureReason, SubscriptionEvent};
+use crate::stream::datagram::{self, DatagramErrorPolicy, DatagramOptions, DatagramSocket};
+use crate::stream::{Action, Logic, LogicEvent, StreamContext};
+use mio::{net::UdpSocket, Poll, PollOpt, Ready, Token};
+use std::io::{self, ErrorKind as IoErrorKind, Result as IoResult};
+use std::mem;
+use std::sync::Arc;
+
+#[cfg(target_family = "unix")]
+use mio_uds::UnixDatagram;
+
+/// A sink that sends datagrams via a UDP socket.
+pub type Udp = Datagrams<UdpSocket>;
+
+/// A sink that sends datagrams via a Unix domain datagram socket.
+#[cfg(target_family = "unix")]
+pub type Uds = Datagrams<UnixDatagram>;
+
+/// Sends each datagram that is pushed from upstream via a socket. Errors
+/// that only affect a single datagram, including a datagram that was only
+/// partially sent, are handled according to the error policy.
+pub struct Datagrams<S: DatagramSocket> {
+    socket: IoResult<S>,
+    options: S::Options,
+    datagram: Option<S::Datagram>,
+    applied: bool,
+    ready: bool,
+    poll: Option<Arc<Poll>>,
+    token: usize,
+    pulled: bool,
+    stopped: bool,
+}
+
+impl<S: DatagramSocket> Datagrams<S>
+where
+    S: 'static,
+{
+    /// Use the supplied socket with the default options.
+    pub fn new(socket: &S) -> Self {
+        Self::with_options(socket, S::Options::default())
+    }
+
+    pub fn with_options(socket: &S, options: S::Options) -> Self {
+        Self {
+            socket: socket.try_clone(),
+            options,
+            datagram: None,
+            applied: false,
+            ready: false,
+            poll: None,
+            token: 0,
+            pulled: false,
+            stopped: false,
+        }
+    }
+
+    fn complete(&self, reason: Option<FailureReason>) -> Action<(), SubscriptionEvent> {
+        if self.pulled {
+            Action::PushAndStop((), reason)
+        } else {
+            Action::Stop(reason)
+        }
+    }
+
+    fn try_write(
+        &mut self,
+        ctx: &mut StreamContext<S::Datagram, (), SubscriptionEvent>,
+    ) -> Action<(), SubscriptionEvent> {
+        loop {
+            let sent = match (&self.socket, &self.datagram) {
+                (Ok(ref s), Some(ref datagram)) if self.ready => s
+                    .send_datagram(datagram)
+                    .map(|bytes_written| (bytes_written, S::data(datagram).len())),
+
+                _ => return Action::None,
+            };
+
+            match sent {
+                Ok((bytes_written, len)) if bytes_written < len => {
+                    let error = io::Error::new(
+                        IoErrorKind::InvalidData,
+                        format!(
+                            "datagram truncated, sent {} of {} bytes",
+                            bytes_written, len
+                        ),
+                    );
+
+                    return self
+                        .handle_error(error, ctx)
+                        .unwrap_or_else(|| self.written(ctx));
+                }
+
+                Ok(_) => {
+                    return self.written(ctx);
+                }
+
+                Err(ref e) if e.kind() == IoErrorKind::WouldBlock => {
+                    self.ready = false;
+
+                    return Action::None;
+                }
+
+                Err(ref e) if e.kind() == IoErrorKind::Interrupted => {}
+
+                Err(e) => {
+                    return self
+                        .handle_error(e, ctx)
+                        .unwrap_or_else(|| self.written(ctx));
+                }
+            }
+        }
+    }
+
+    /// Called once the current datagram has been handled, either by
+    /// sending or discarding it.
+    fn written(
+        &mut self,
+        ctx: &mut StreamContext<S::Datagram, (), SubscriptionEvent>,
+    ) -> Action<(), SubscriptionEvent> {
+        self.datagram = None;
+
+        if self.stopped {
+            self.unregister(ctx);
+            self.complete(None)
+        } else {
+            Action::Pull
+        }
+    }
+
+    /// Log and discard transient errors if configured to do so, otherwise
+    /// fail the stream.
+    fn handle_error(
+        &mut self,
+        error: io::Error,
+        ctx: &mut StreamContext<S::Datagram, (), SubscriptionEvent>,
+    ) -> Option<Action<(), SubscriptionEvent>> {
+        if self.options.error_policy() == DatagramErrorPolicy::LogAndContinue
+            && datagram::is_transient(&error)
+        {
+            warn!("{}: discarding datagram: {}", self.name(), error);
+
+            None
+        } else {
+            Some(self.fail(error, ctx))
+        }
+    }
+
+    fn fail(
+        &mut self,
+        error: io::Error,
+        ctx: &mut StreamContext<S::Datagram, (), SubscriptionEvent>,
+    ) -> Action<(), SubscriptionEvent> {
+        self.datagram = None;
+        self.unregister(ctx);
+
+        let action = self.complete(Some(FailureReason::Errored(FailureError::new(error))));
+
+        if self.stopped {
+            action
+        } else {
+            self.stopped = true;
+
+            ctx.tell(action);
+
+            Action::Cancel
+        }
+    }
+
+    fn unregister(&mut self, ctx: &mut StreamContext<S::Datagram, (), SubscriptionEvent>) {
+        if let Some(poll) = self.poll.take() {
+            if let Ok(ref socket) = self.socket {
+                // @TODO expect doesn't seem appropriate
+
+                poll.deregister(socket)
+                    .expect("pantomime bug: failed to deregister socket");
+            }
+        }
+
+        if self.applied {
+            self.applied = false;
+
+            if let Ok(ref socket) = self.socket {
+                if let Err(e) = self.options.unapply(socket) {
+                    warn!("{}: failed to leave multicast groups: {}", self.name(), e);
+                }
+            }
+        }
+
+        if self.token > 0 {
+            ctx.unsubscribe(self.token);
+
+            self.token = 0;
+        }
+    }
+}
+
+impl<S: DatagramSocket> Logic<S::Datagram, ()> for Datagrams<S>
+where
+    S: 'static,
+{
+    type Ctl = SubscriptionEvent;
+
+    fn name(&self) -> &'static str {
+        "pantomime::stream::sink::Datagrams"
+    }
+
+    fn fusible(&self) -> bool {
+        false
+    }
+
+    fn receive(
+        &mut self,
+        msg: LogicEvent<S::Datagram, Self::Ctl>,
+        ctx: &mut StreamContext<S::Datagram, (), Self::Ctl>,
+    ) -> Action<(), Self::Ctl> {
+        match msg {
+            LogicEvent::Pushed(datagram) => {
+                self.datagram = Some(datagram);
+
+                self.try_write(ctx)
+            }
+
+            LogicEvent::Forwarded(SubscriptionEvent::MioEvent(event)) => {
+                if event.readiness().is_writable() {
+                    self.ready = true;
+
+                    self.try_write(ctx)
+                } else {
+                    Action::None
+                }
+            }
+
+            LogicEvent::Forwarded(SubscriptionEvent::Ready(poll, token)) => {
+                let registered = match self.socket {
+                    Ok(ref socket) => {
+                        poll.register(socket, Token(token), Ready::writable(), PollOpt::edge())
+                    }
+
+                    Err(_) => {
+                        ctx.unsubscribe(token);
+
+                        return Action::None;
+                    }
+                };
+
+                self.poll = Some(poll);
+                self.token = token;
+
+                match registered {
+                    Ok(()) => Action::None,
+                    Err(e) => self.fail(e, ctx),
+                }
+            }
+
+            LogicEvent::Started => {
+                let applied = match self.socket {
+                    Ok(ref socket) => self.options.apply(socket),
+
+                    Err(_) => mem::replace(
+                        &mut self.socket,
+                        Err(io::Error::from(IoErrorKind::NotConnected)),
+                    )
+                    .map(|_| ()),
+                };
+
+                if let Err(e) = applied {
+                    return self.fail(e, ctx);
+                }
+
+                self.applied = true;
+
+                let actor_ref = ctx.stage_ref().actor_ref;
+
+                ctx.subscribe(actor_ref);
+
+                Action::None
+            }
+
+            LogicEvent::Pulled => {
+                self.pulled = true;
+
+                Action::Pull
+            }
+
+            LogicEvent::Stopped => {
+                self.stopped = true;
+
+                if self.datagram.is_none() {
+                    self.unregister(ctx);
+                    self.complete(None)
+                } else {
+                    Action::None
+                }
+            }
+
+            LogicEvent::Cancelled => {
+                self.datagram = None;
+                self.unregister(ctx);
+                self.complete(None)
+            }
+        }
+    }
+}
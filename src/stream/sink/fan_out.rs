@@ -0,0 +1,427 @@
+use crate::actor::{FailureError, FailureReason};
+use crate::stream::{FanOutCancellation, LogicPortEvent, PortAction, PortContext, PortLogic};
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::sync::Arc;
+
+const INLET: usize = 0;
+
+/// The failure of the inlet, which is shared by all of the outlets as
+/// failure reasons cannot be copied.
+#[derive(Clone)]
+struct SharedFailure(Arc<Mutex<FailureReason>>);
+
+impl fmt::Debug for SharedFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SharedFailure({})", self)
+    }
+}
+
+impl fmt::Display for SharedFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "upstream failed: {}", *self.0.lock())
+    }
+}
+
+impl Error for SharedFailure {}
+
+fn complete_outlets<B>(
+    closed: &mut [bool],
+    reason: Option<FailureReason>,
+    ctx: &mut PortContext<B>,
+) {
+    let failure = reason.map(|reason| SharedFailure(Arc::new(Mutex::new(reason))));
+
+    for (outlet, closed) in closed.iter_mut().enumerate() {
+        if !*closed {
+            *closed = true;
+
+            let reason = failure
+                .as_ref()
+                .map(|failure| FailureReason::Errored(FailureError::new(failure.clone())));
+
+            ctx.tell(outlet, PortAction::Complete(reason));
+        }
+    }
+}
+
+fn cancel_outlet<B>(
+    closed: &mut [bool],
+    outlet: usize,
+    cancellation: FanOutCancellation,
+    ctx: &mut PortContext<B>,
+) -> bool {
+    // like any other stage, a cancelled outlet is still completed so
+    // that its downstream can finish
+
+    closed[outlet] = true;
+
+    ctx.tell(outlet, PortAction::Complete(None));
+
+    cancellation == FanOutCancellation::Eager || closed.iter().all(|c| *c)
+}
+
+/// Emits each element of its inlet to all of its outlets, and is
+/// thus backpressured by the slowest of them.
+///
+/// Depending on its cancellation, the inlet is cancelled once any or
+/// all of the outlets have been cancelled.
+pub struct Broadcast {
+    cancellation: FanOutCancellation,
+    pulled: Vec<bool>,
+    closed: Vec<bool>,
+    inlet_pulled: bool,
+    completed: bool,
+}
+
+impl Broadcast {
+    pub fn new(outlets: usize, cancellation: FanOutCancellation) -> Self {
+        Self {
+            cancellation,
+            pulled: vec![false; outlets],
+            closed: vec![false; outlets],
+            inlet_pulled: false,
+            completed: false,
+        }
+    }
+
+    fn try_pull<A>(&mut self, ctx: &mut PortContext<A>) {
+        let ready = self
+            .pulled
+            .iter()
+            .zip(self.closed.iter())
+            .all(|(pulled, closed)| *pulled || *closed);
+
+        if ready && !self.inlet_pulled {
+            self.inlet_pulled = true;
+
+            ctx.tell(INLET, PortAction::Pull);
+        }
+    }
+
+    fn complete<A>(&mut self, reason: Option<FailureReason>, ctx: &mut PortContext<A>) {
+        self.completed = true;
+
+        ctx.tell(INLET, PortAction::Cancel);
+
+        complete_outlets(&mut self.closed, reason, ctx);
+    }
+}
+
+impl<A> PortLogic<A, A> for Broadcast
+where
+    A: 'static + Clone + Send,
+{
+    fn name(&self) -> &'static str {
+        "pantomime::stream::sink::Broadcast"
+    }
+
+    fn inlets(&self) -> usize {
+        1
+    }
+
+    fn outlets(&self) -> usize {
+        self.closed.len()
+    }
+
+    fn receive(&mut self, event: LogicPortEvent<A>, ctx: &mut PortContext<A>) {
+        if self.completed {
+            return;
+        }
+
+        match event {
+            LogicPortEvent::Started(_) => {}
+
+            LogicPortEvent::Pulled(outlet) => {
+                self.pulled[outlet] = true;
+
+                self.try_pull(ctx);
+            }
+
+            LogicPortEvent::Pushed(_, element) => {
+                self.inlet_pulled = false;
+
+                for outlet in 0..self.closed.len() {
+                    if !self.closed[outlet] {
+                        self.pulled[outlet] = false;
+
+                        ctx.tell(outlet, PortAction::Push(element.clone()));
+                    }
+                }
+            }
+
+            LogicPortEvent::Stopped(_) => {
+                self.complete(None, ctx);
+            }
+
+            LogicPortEvent::Failed(_, reason) => {
+                self.complete(Some(reason), ctx);
+            }
+
+            LogicPortEvent::Cancelled(outlet) => {
+                if cancel_outlet(&mut self.closed, outlet, self.cancellation, ctx) {
+                    self.complete(None, ctx);
+                } else {
+                    self.try_pull(ctx);
+                }
+            }
+        }
+    }
+}
+
+/// Emits each element of its inlet to one of its outlets, namely the
+/// first one to have signaled demand.
+///
+/// Depending on its cancellation, the inlet is cancelled once any or
+/// all of the outlets have been cancelled.
+pub struct Balance<A> {
+    cancellation: FanOutCancellation,
+    available: VecDeque<usize>,
+    closed: Vec<bool>,
+    pending: Option<A>,
+    inlet_pulled: bool,
+    stopped: bool,
+    completed: bool,
+}
+
+impl<A> Balance<A> {
+    pub fn new(outlets: usize, cancellation: FanOutCancellation) -> Self {
+        Self {
+            cancellation,
+            available: VecDeque::with_capacity(outlets),
+            closed: vec![false; outlets],
+            pending: None,
+            inlet_pulled: false,
+            stopped: false,
+            completed: false,
+        }
+    }
+
+    fn try_push(&mut self, ctx: &mut PortContext<A>) {
+        if self.pending.is_some() {
+            if let Some(outlet) = self.available.pop_front() {
+                if let Some(element) = self.pending.take() {
+                    ctx.tell(outlet, PortAction::Push(element));
+                }
+            }
+        }
+
+        if self.pending.is_none() {
+            if self.stopped {
+                self.complete(None, ctx);
+            } else if !self.available.is_empty() && !self.inlet_pulled {
+                self.inlet_pulled = true;
+
+                ctx.tell(INLET, PortAction::Pull);
+            }
+        }
+    }
+
+    fn complete(&mut self, reason: Option<FailureReason>, ctx: &mut PortContext<A>) {
+        self.completed = true;
+
+        ctx.tell(INLET, PortAction::Cancel);
+
+        complete_outlets(&mut self.closed, reason, ctx);
+    }
+}
+
+impl<A> PortLogic<A, A> for Balance<A>
+where
+    A: 'static + Send,
+{
+    fn name(&self) -> &'static str {
+        "pantomime::stream::sink::Balance"
+    }
+
+    fn inlets(&self) -> usize {
+        1
+    }
+
+    fn outlets(&self) -> usize {
+        self.closed.len()
+    }
+
+    fn receive(&mut self, event: LogicPortEvent<A>, ctx: &mut PortContext<A>) {
+        if self.completed {
+            return;
+        }
+
+        match event {
+            LogicPortEvent::Started(_) => {}
+
+            LogicPortEvent::Pulled(outlet) => {
+                self.available.push_back(outlet);
+
+                self.try_push(ctx);
+            }
+
+            LogicPortEvent::Pushed(_, element) => {
+                self.inlet_pulled = false;
+                self.pending = Some(element);
+
+                self.try_push(ctx);
+            }
+
+            LogicPortEvent::Stopped(_) => {
+                self.stopped = true;
+
+                self.try_push(ctx);
+            }
+
+            LogicPortEvent::Failed(_, reason) => {
+                self.complete(Some(reason), ctx);
+            }
+
+            LogicPortEvent::Cancelled(outlet) => {
+                self.available.retain(|o| *o != outlet);
+
+                if cancel_outlet(&mut self.closed, outlet, self.cancellation, ctx) {
+                    self.complete(None, ctx);
+                }
+            }
+        }
+    }
+}
+
+/// Emits each element of its inlet to the outlet whose index is returned
+/// by the supplied function. Elements destined for cancelled outlets are
+/// discarded, and the stage fails if an index is out of range.
+///
+/// Depending on its cancellation, the inlet is cancelled once any or
+/// all of the outlets have been cancelled.
+pub struct Partition<A, F: FnMut(&A) -> usize> {
+    cancellation: FanOutCancellation,
+    partition_fn: F,
+    pulled: Vec<bool>,
+    closed: Vec<bool>,
+    pending: Option<(usize, A)>,
+    stopped: bool,
+    completed: bool,
+}
+
+impl<A, F: FnMut(&A) -> usize> Partition<A, F> {
+    pub fn new(outlets: usize, partition_fn: F, cancellation: FanOutCancellation) -> Self {
+        Self {
+            cancellation,
+            partition_fn,
+            pulled: vec![false; outlets],
+            closed: vec![false; outlets],
+            pending: None,
+            stopped: false,
+            completed: false,
+        }
+    }
+
+    fn try_push(&mut self, ctx: &mut PortContext<A>) {
+        if let Some((outlet, element)) = self.pending.take() {
+            if self.closed[outlet] {
+                // discarded, as its outlet has been cancelled
+            } else if self.pulled[outlet] {
+                self.pulled[outlet] = false;
+
+                ctx.tell(outlet, PortAction::Push(element));
+            } else {
+                self.pending = Some((outlet, element));
+
+                return;
+            }
+        }
+
+        if self.stopped {
+            self.complete(None, ctx);
+        } else {
+            ctx.tell(INLET, PortAction::Pull);
+        }
+    }
+
+    fn complete(&mut self, reason: Option<FailureReason>, ctx: &mut PortContext<A>) {
+        self.completed = true;
+
+        ctx.tell(INLET, PortAction::Cancel);
+
+        complete_outlets(&mut self.closed, reason, ctx);
+    }
+}
+
+impl<A, F: FnMut(&A) -> usize> PortLogic<A, A> for Partition<A, F>
+where
+    A: 'static + Send,
+    F: 'static + Send,
+{
+    fn name(&self) -> &'static str {
+        "pantomime::stream::sink::Partition"
+    }
+
+    fn inlets(&self) -> usize {
+        1
+    }
+
+    fn outlets(&self) -> usize {
+        self.closed.len()
+    }
+
+    fn receive(&mut self, event: LogicPortEvent<A>, ctx: &mut PortContext<A>) {
+        if self.completed {
+            return;
+        }
+
+        match event {
+            LogicPortEvent::Started(_) => {
+                ctx.tell(INLET, PortAction::Pull);
+            }
+
+            LogicPortEvent::Pulled(outlet) => {
+                self.pulled[outlet] = true;
+
+                if let Some((pending, _)) = self.pending {
+                    if pending == outlet {
+                        self.try_push(ctx);
+                    }
+                }
+            }
+
+            LogicPortEvent::Pushed(_, element) => {
+                let outlet = (self.partition_fn)(&element);
+
+                if outlet < self.closed.len() {
+                    self.pending = Some((outlet, element));
+
+                    self.try_push(ctx);
+                } else {
+                    let error = io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("partition {} is out of range", outlet),
+                    );
+
+                    self.complete(Some(FailureReason::Errored(FailureError::new(error))), ctx);
+                }
+            }
+
+            LogicPortEvent::Stopped(_) => {
+                self.stopped = true;
+
+                if self.pending.is_none() {
+                    self.complete(None, ctx);
+                }
+            }
+
+            LogicPortEvent::Failed(_, reason) => {
+                self.complete(Some(reason), ctx);
+            }
+
+            LogicPortEvent::Cancelled(outlet) => {
+                if cancel_outlet(&mut self.closed, outlet, self.cancellation, ctx) {
+                    self.complete(None, ctx);
+                } else if let Some((pending, _)) = self.pending {
+                    if pending == outlet {
+                        self.try_push(ctx);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use crate::stream::file::FileOptions;
+use crate::stream::sink::writer::Writer;
+use crate::stream::{Action, Logic, LogicEvent, StreamContext};
+use std::fs::File as StdFile;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+/// A file that is opened when it's first written or flushed, so that
+/// failing to open it fails the sink. Flushing also syncs the file if
+/// its options specify to.
+struct LazyFile {
+    path: PathBuf,
+    options: FileOptions,
+    file: Option<StdFile>,
+}
+
+impl LazyFile {
+    fn file(&mut self) -> io::Result<&mut StdFile> {
+        if self.file.is_none() {
+            self.file = Some(self.options.open(&self.path)?);
+        }
+
+        Ok(self
+            .file
+            .as_mut()
+            .expect("pantomime bug: LazyFile::file is None"))
+    }
+}
+
+impl Write for LazyFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.file()?.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let sync = self.options.sync();
+        let file = self.file()?;
+
+        file.flush()?;
+
+        if sync {
+            file.sync_all()?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A sink that writes each element to a file, outputting the number of
+/// bytes that were written, or the first error that was encountered.
+///
+/// This is a `Writer` of the file, which is opened when the first element
+/// is written, or when upstream completes, so the file is created even if
+/// nothing was written to it.
+pub struct File(Writer<LazyFile>);
+
+impl File {
+    pub fn new(path: PathBuf, options: FileOptions) -> Self {
+        File(Writer::new(LazyFile {
+            path,
+            options,
+            file: None,
+        }))
+    }
+}
+
+impl Logic<Vec<u8>, io::Result<u64>> for File {
+    type Ctl = ();
+
+    fn name(&self) -> &'static str {
+        "pantomime::stream::sink::File"
+    }
+
+    fn fusible(&self) -> bool {
+        self.0.fusible()
+    }
+
+    fn dispatcher(&self) -> Option<&str> {
+        self.0.dispatcher()
+    }
+
+    fn receive(
+        &mut self,
+        msg: LogicEvent<Vec<u8>, Self::Ctl>,
+        ctx: &mut StreamContext<Vec<u8>, io::Result<u64>, Self::Ctl>,
+    ) -> Action<io::Result<u64>, Self::Ctl> {
+        self.0.receive(msg, ctx)
+    }
+}
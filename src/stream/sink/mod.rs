@@ -1,12 +1,28 @@
-use crate::stream::internal::{ContainedLogicImpl, IndividualLogic, LogicType};
-use crate::stream::{Datagram, Logic};
+use crate::stream::file::FileOptions;
+use crate::stream::internal::{ContainedLogicImpl, FanOut, IndividualLogic, LogicType};
+use crate::stream::udp::UdpOptions;
+use crate::stream::{hub, Datagram, FanOutCancellation, Logic, PortLogic};
+use std::io::{self, Write};
+use std::marker::PhantomData;
+use std::path::Path;
+use std::process::{Command, ExitStatus};
 
+#[cfg(target_family = "unix")]
+use crate::stream::datagram::UdsOptions;
+
+pub mod async_stream;
 pub mod collect;
+pub mod datagram;
+pub mod fan_out;
+pub mod file;
 pub mod first;
 pub mod for_each;
 pub mod ignore;
 pub mod last;
+pub mod process;
+pub mod readable;
 pub mod udp;
+pub mod writer;
 
 /// A `Sink` is a stage that accepts a single output, and outputs a
 /// terminal value.
@@ -45,7 +61,7 @@ where
         L::Ctl: 'static + Send,
     {
         Self {
-            logic: if logic.fusible() {
+            logic: if logic.fusible() && logic.dispatcher().is_none() {
                 LogicType::Fusible(Box::new(ContainedLogicImpl::new(logic)))
             } else {
                 LogicType::Spawnable(Box::new(IndividualLogic { logic }))
@@ -78,9 +94,131 @@ where
         Sink::new(for_each::ForEach::new(for_each_fn))
     }
 
+    /// Create a sink whose elements are exposed as an asynchronous stream,
+    /// for consumption by code outside of the stream.
+    ///
+    /// At most `capacity` elements are buffered, after which the sink
+    /// stops pulling until the returned stream has been polled.
+    pub fn async_stream(capacity: usize) -> (Self, async_stream::SinkStream<A>) {
+        let (logic, stream) = async_stream::AsyncSink::new(capacity);
+
+        (Sink::new(logic), stream)
+    }
+
     pub fn ignore() -> Self {
         Sink::new(ignore::Ignore::new())
     }
+
+    /// Create a sink that emits each of its elements to all of the sources
+    /// obtained from the returned hub, which can be attached at any time.
+    ///
+    /// At most `capacity` elements are buffered, and the sink stops pulling
+    /// while the buffer is full, so the slowest source determines the rate
+    /// at which elements are consumed.
+    pub fn broadcast_hub(capacity: usize) -> (Self, hub::BroadcastHub<A>)
+    where
+        A: Clone,
+    {
+        let (logic, hub) = hub::BroadcastHubSink::new(capacity);
+
+        (Sink::new(logic), hub)
+    }
+
+    /// Create a sink that emits each of its elements to one of the sources
+    /// obtained from the returned hub, namely the one whose index is
+    /// returned by the supplied function given the number of attached
+    /// sources and the element.
+    ///
+    /// At most `capacity` elements are buffered across all of the sources,
+    /// and the sink stops pulling while no sources are attached.
+    pub fn partition_hub<F: FnMut(usize, &A) -> usize>(
+        capacity: usize,
+        partition_fn: F,
+    ) -> (Self, hub::PartitionHub<A>)
+    where
+        F: 'static + Send,
+    {
+        let (logic, hub) = hub::PartitionHubSink::new(capacity, partition_fn);
+
+        (Sink::new(logic), hub)
+    }
+}
+
+impl<A, Out> Sink<A, Vec<Out>>
+where
+    A: 'static + Send,
+    Out: 'static + Send,
+{
+    /// Emit each element to all of the supplied sinks, outputting the
+    /// values that they output in the same order. The slowest sink
+    /// determines the rate at which elements are consumed.
+    ///
+    /// Depending on `cancellation`, upstream is cancelled once any or all
+    /// of the sinks have cancelled.
+    pub fn broadcast<I: IntoIterator<Item = Sink<A, Out>>>(
+        sinks: I,
+        cancellation: FanOutCancellation,
+    ) -> Self
+    where
+        A: Clone,
+    {
+        let sinks = sinks.into_iter().collect::<Vec<_>>();
+
+        Self::fan_out(fan_out::Broadcast::new(sinks.len(), cancellation), sinks)
+    }
+
+    /// Emit each element to one of the supplied sinks, namely the first
+    /// one that is available, outputting the values that they output in
+    /// the same order.
+    ///
+    /// Depending on `cancellation`, upstream is cancelled once any or all
+    /// of the sinks have cancelled.
+    pub fn balance<I: IntoIterator<Item = Sink<A, Out>>>(
+        sinks: I,
+        cancellation: FanOutCancellation,
+    ) -> Self {
+        let sinks = sinks.into_iter().collect::<Vec<_>>();
+
+        Self::fan_out(fan_out::Balance::new(sinks.len(), cancellation), sinks)
+    }
+
+    /// Emit each element to the sink whose index is returned by the
+    /// supplied function, outputting the values that they output in
+    /// the same order. The stream fails if an index is out of range.
+    ///
+    /// Depending on `cancellation`, upstream is cancelled once any or all
+    /// of the sinks have cancelled.
+    pub fn partition<I: IntoIterator<Item = Sink<A, Out>>, F: FnMut(&A) -> usize>(
+        sinks: I,
+        partition_fn: F,
+        cancellation: FanOutCancellation,
+    ) -> Self
+    where
+        F: 'static + Send,
+    {
+        let sinks = sinks.into_iter().collect::<Vec<_>>();
+
+        Self::fan_out(
+            fan_out::Partition::new(sinks.len(), partition_fn, cancellation),
+            sinks,
+        )
+    }
+
+    fn fan_out<L: PortLogic<A, A>>(logic: L, sinks: Vec<Sink<A, Out>>) -> Self
+    where
+        L: 'static + Send,
+    {
+        Self {
+            logic: LogicType::Spawnable(Box::new(FanOut {
+                logic,
+                sinks: sinks
+                    .into_iter()
+                    .map(|sink| sink.logic.into_facade())
+                    .collect(),
+                phantom: PhantomData,
+            })),
+        }
+    }
 }
 
 impl<A> Sink<A, Vec<A>>
@@ -92,8 +230,78 @@ where
     }
 }
 
+impl Sink<Vec<u8>, io::Result<u64>> {
+    /// Write each element to the file at the supplied path, outputting the
+    /// number of bytes that were written. The file is written on a
+    /// dedicated dispatcher for blocking I/O.
+    pub fn to_file<P: AsRef<Path>>(path: P, options: FileOptions) -> Self {
+        Self::new(file::File::new(path.as_ref().to_path_buf(), options))
+    }
+
+    /// Write each element to the supplied writer, outputting the number
+    /// of bytes that were written. The writer is written on a dedicated
+    /// dispatcher for blocking I/O.
+    pub fn to_writer<W: Write>(writer: W) -> Self
+    where
+        W: 'static + Send,
+    {
+        Self::new(writer::Writer::new(writer))
+    }
+}
+
+impl Sink<Vec<u8>, io::Result<ExitStatus>> {
+    /// Spawn the supplied command as a child process when the stream
+    /// starts, writing each element to its stdin and outputting its exit
+    /// status, or the error that prevented it from being spawned or waited
+    /// on. Its stdout is discarded, and if `log_stderr` is true, each line
+    /// that it writes to stderr is logged.
+    ///
+    /// Unlike `Flow::process`, an unsuccessful exit doesn't fail the
+    /// stream. The process is killed if the stream is cancelled.
+    ///
+    /// Its stdin is written to on the `BLOCKING_IO_DISPATCHER`, occupying
+    /// one of its threads per write. Each running process also has two
+    /// dedicated threads, as described for `Flow::process`.
+    pub fn process(command: Command, log_stderr: bool) -> Self {
+        Self::new(process::Process::new(command, log_stderr))
+    }
+}
+
+impl Sink<Vec<u8>, ()> {
+    /// Create a sink whose data is exposed via the returned reader, for
+    /// integration with code that consumes data via `std::io::Read`.
+    ///
+    /// At most `capacity` elements are buffered, after which the sink
+    /// stops pulling until the reader has been read.
+    pub fn readable(capacity: usize) -> (Self, readable::SinkReader) {
+        let (sink, stream) = Self::async_stream(capacity);
+
+        (sink, readable::SinkReader::new(stream))
+    }
+}
+
 impl Sink<Datagram, ()> {
     pub fn udp(socket: &mio::net::UdpSocket) -> Self {
-        Self::new(udp::Udp::new(socket))
+        Self::udp_with_options(socket, UdpOptions::new())
+    }
+
+    /// Send each datagram via the supplied socket, configuring it with
+    /// the supplied options.
+    pub fn udp_with_options(socket: &mio::net::UdpSocket, options: UdpOptions) -> Self {
+        Self::new(datagram::Udp::with_options(socket, options))
+    }
+}
+
+#[cfg(target_family = "unix")]
+impl Sink<crate::stream::UdsDatagram, ()> {
+    /// Send each datagram via the supplied Unix domain datagram socket.
+    pub fn uds(socket: &mio_uds::UnixDatagram) -> Self {
+        Self::uds_with_options(socket, UdsOptions::new())
+    }
+
+    /// Send each datagram via the supplied Unix domain datagram socket,
+    /// using the supplied options.
+    pub fn uds_with_options(socket: &mio_uds::UnixDatagram, options: UdsOptions) -> Self {
+        Self::new(datagram::Uds::with_options(socket, options))
     }
 }
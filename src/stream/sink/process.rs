@@ -0,0 +1,147 @@
+use crate::stream::process::{ChildProcess, ProcessCtl};
+use crate::stream::{Action, Logic, LogicEvent, StreamContext};
+use std::io;
+use std::process::{Command, ExitStatus};
+
+/// A sink that spawns a child process when the stream starts, writing each
+/// element to its stdin and outputting its exit status, or the error that
+/// prevented it from being spawned or waited on. Its stdout is discarded,
+/// and its stderr is logged if `log_stderr` is set.
+///
+/// Its stdin is closed once upstream has stopped, and if the child closes
+/// its stdin early, upstream is cancelled. The exit status is output once
+/// the child has exited, whether or not it was successful. If the sink is
+/// cancelled, the child is killed.
+pub struct Process {
+    child: ChildProcess,
+    result: Option<io::Result<ExitStatus>>,
+    pulled: bool,
+    upstream_stopped: bool,
+    stopped: bool,
+}
+
+impl Process {
+    pub fn new(command: Command, log_stderr: bool) -> Self {
+        Self {
+            child: ChildProcess::new(command, log_stderr),
+            result: None,
+            pulled: false,
+            upstream_stopped: false,
+            stopped: false,
+        }
+    }
+
+    /// Keep the result until it has been pulled, at which point it's
+    /// emitted and the sink stops.
+    fn complete(
+        &mut self,
+        result: io::Result<ExitStatus>,
+        ctx: &mut StreamContext<Vec<u8>, io::Result<ExitStatus>, ProcessCtl>,
+    ) -> Action<io::Result<ExitStatus>, ProcessCtl> {
+        self.result = Some(result);
+        self.stopped = true;
+        self.child.close_stdin();
+
+        let action = self.try_emit();
+
+        if self.upstream_stopped {
+            action
+        } else {
+            self.upstream_stopped = true;
+
+            ctx.tell(action);
+
+            Action::Cancel
+        }
+    }
+
+    fn try_emit(&mut self) -> Action<io::Result<ExitStatus>, ProcessCtl> {
+        match self.result.take() {
+            Some(result) if self.pulled => Action::PushAndStop(result, None),
+
+            result => {
+                self.result = result;
+
+                Action::None
+            }
+        }
+    }
+}
+
+impl Logic<Vec<u8>, io::Result<ExitStatus>> for Process {
+    type Ctl = ProcessCtl;
+
+    fn name(&self) -> &'static str {
+        "pantomime::stream::sink::Process"
+    }
+
+    fn fusible(&self) -> bool {
+        false
+    }
+
+    fn receive(
+        &mut self,
+        msg: LogicEvent<Vec<u8>, Self::Ctl>,
+        ctx: &mut StreamContext<Vec<u8>, io::Result<ExitStatus>, Self::Ctl>,
+    ) -> Action<io::Result<ExitStatus>, Self::Ctl> {
+        match msg {
+            LogicEvent::Started => match self.child.spawn(false, ctx) {
+                Ok(()) => Action::Pull,
+                Err(e) => self.complete(Err(e), ctx),
+            },
+
+            LogicEvent::Pulled => {
+                self.pulled = true;
+
+                self.try_emit()
+            }
+
+            LogicEvent::Pushed(chunk) => {
+                self.child.write(chunk, ctx);
+
+                Action::None
+            }
+
+            LogicEvent::Forwarded(ProcessCtl::Written) if !self.upstream_stopped => Action::Pull,
+
+            LogicEvent::Forwarded(ProcessCtl::StdinClosed) if !self.upstream_stopped => {
+                self.child.close_stdin();
+                self.upstream_stopped = true;
+
+                Action::Cancel
+            }
+
+            LogicEvent::Forwarded(ProcessCtl::Exited(result)) if !self.stopped => {
+                self.child.exited();
+
+                self.complete(result, ctx)
+            }
+
+            LogicEvent::Forwarded(_) => Action::None,
+
+            LogicEvent::Stopped => {
+                // closes stdin once everything has been written
+
+                self.child.close_stdin();
+                self.upstream_stopped = true;
+
+                Action::None
+            }
+
+            LogicEvent::Cancelled if self.upstream_stopped => {
+                // the exit status of the killed child is still output
+
+                self.child.kill();
+
+                Action::None
+            }
+
+            LogicEvent::Cancelled => {
+                self.child.kill();
+                self.upstream_stopped = true;
+
+                Action::Cancel
+            }
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use crate::stream::sink::async_stream::SinkStream;
+use futures_core::Stream;
+use std::io::{self, Read};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+
+/// Unparks the reading thread when more data is available.
+struct ThreadWaker {
+    thread: Thread,
+}
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.thread.unpark();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.thread.unpark();
+    }
+}
+
+/// A blocking reader of the data that has been received by a sink
+/// created with `Sink::readable`. Reads return 0 once the stream has
+/// completed and all of its data has been read, or an error of kind
+/// `UnexpectedEof` if upstream failed instead.
+///
+/// Data is pulled from upstream as it is read, so a slow reader
+/// backpressures the stream. Dropping it cancels the stream.
+///
+/// Reads block the calling thread, so this should not be read from
+/// within an actor.
+pub struct SinkReader {
+    stream: SinkStream<Vec<u8>>,
+    chunk: Vec<u8>,
+    position: usize,
+}
+
+impl SinkReader {
+    pub(in crate::stream) fn new(stream: SinkStream<Vec<u8>>) -> Self {
+        Self {
+            stream,
+            chunk: Vec::new(),
+            position: 0,
+        }
+    }
+
+    /// Block until the next chunk is available, returning `false` if the
+    /// stream has completed, or an error if it failed.
+    fn next_chunk(&mut self) -> io::Result<bool> {
+        let waker = Waker::from(Arc::new(ThreadWaker {
+            thread: thread::current(),
+        }));
+
+        let mut cx = Context::from_waker(&waker);
+
+        loop {
+            match Pin::new(&mut self.stream).poll_next(&mut cx) {
+                Poll::Ready(Some(chunk)) => {
+                    self.chunk = chunk;
+                    self.position = 0;
+
+                    return Ok(true);
+                }
+
+                Poll::Ready(None) => {
+                    return match self.stream.failure() {
+                        Some(failure) => Err(io::Error::new(io::ErrorKind::UnexpectedEof, failure)),
+                        None => Ok(false),
+                    };
+                }
+
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+}
+
+impl Read for SinkReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        while self.position == self.chunk.len() {
+            if !self.next_chunk()? {
+                return Ok(0);
+            }
+        }
+
+        let n = buf.len().min(self.chunk.len() - self.position);
+
+        buf[..n].copy_from_slice(&self.chunk[self.position..self.position + n]);
+
+        self.position += n;
+
+        Ok(n)
+    }
+}
@@ -0,0 +1,136 @@
+use crate::cfg::BLOCKING_IO_DISPATCHER;
+use crate::stream::{Action, Logic, LogicEvent, StreamContext};
+use std::io::{self, Write};
+
+/// A sink that writes each element to a writer, outputting the number
+/// of bytes that were written, or the first error that was encountered.
+/// The writer is flushed and dropped once upstream completes.
+///
+/// Writes may block, so this executes on the `BLOCKING_IO_DISPATCHER`.
+/// If an error is encountered, upstream is cancelled.
+pub struct Writer<W> {
+    writer: Option<W>,
+    result: Option<io::Result<u64>>,
+    pulled: bool,
+    stopped: bool,
+}
+
+impl<W: Write> Writer<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: Some(writer),
+            result: Some(Ok(0)),
+            pulled: false,
+            stopped: false,
+        }
+    }
+
+    /// Flush and drop the writer. The result is kept until it has been
+    /// pulled, at which point it's emitted and the sink stops.
+    fn complete(&mut self) -> Action<io::Result<u64>, ()> {
+        if let (Some(Ok(_)), Some(writer)) = (&self.result, self.writer.as_mut()) {
+            if let Err(e) = writer.flush() {
+                self.result = Some(Err(e));
+            }
+        }
+
+        self.writer = None;
+        self.stopped = true;
+
+        self.try_emit()
+    }
+
+    fn try_emit(&mut self) -> Action<io::Result<u64>, ()> {
+        match self.result.take() {
+            Some(result) if self.pulled => Action::PushAndStop(result, None),
+
+            result => {
+                self.result = result;
+
+                Action::None
+            }
+        }
+    }
+}
+
+impl<W: Write> Logic<Vec<u8>, io::Result<u64>> for Writer<W>
+where
+    W: 'static + Send,
+{
+    type Ctl = ();
+
+    fn name(&self) -> &'static str {
+        "pantomime::stream::sink::Writer"
+    }
+
+    fn fusible(&self) -> bool {
+        false
+    }
+
+    fn dispatcher(&self) -> Option<&str> {
+        Some(BLOCKING_IO_DISPATCHER)
+    }
+
+    fn receive(
+        &mut self,
+        msg: LogicEvent<Vec<u8>, Self::Ctl>,
+        ctx: &mut StreamContext<Vec<u8>, io::Result<u64>, Self::Ctl>,
+    ) -> Action<io::Result<u64>, Self::Ctl> {
+        match msg {
+            LogicEvent::Pushed(data) => {
+                let written = match self.writer {
+                    Some(ref mut writer) => writer.write_all(&data),
+                    None => Ok(()),
+                };
+
+                match written {
+                    Ok(()) => {
+                        if let Some(Ok(ref mut written)) = self.result {
+                            *written += data.len() as u64;
+                        }
+
+                        Action::Pull
+                    }
+
+                    Err(e) => {
+                        self.result = Some(Err(e));
+
+                        let action = self.complete();
+
+                        ctx.tell(action);
+
+                        Action::Cancel
+                    }
+                }
+            }
+
+            LogicEvent::Pulled if self.stopped => {
+                self.pulled = true;
+
+                self.try_emit()
+            }
+
+            LogicEvent::Pulled => {
+                self.pulled = true;
+
+                Action::Pull
+            }
+
+            LogicEvent::Stopped if self.stopped => Action::None,
+
+            LogicEvent::Stopped => self.complete(),
+
+            LogicEvent::Cancelled if self.stopped => Action::None,
+
+            LogicEvent::Cancelled => {
+                let action = self.complete();
+
+                ctx.tell(action);
+
+                Action::Cancel
+            }
+
+            LogicEvent::Started | LogicEvent::Forwarded(()) => Action::None,
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use crate::stream::{Action, Logic, LogicEvent, StageRef, StreamContext};
+use futures_core::Stream;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+
+pub enum AsyncStreamCtl {
+    Wake,
+}
+
+/// Wakes the stage when the stream is ready to be polled again.
+struct StageWaker {
+    stage_ref: StageRef<AsyncStreamCtl>,
+}
+
+impl Wake for StageWaker {
+    fn wake(self: Arc<Self>) {
+        self.stage_ref.tell(AsyncStreamCtl::Wake);
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.stage_ref.tell(AsyncStreamCtl::Wake);
+    }
+}
+
+/// A source that emits the elements of an asynchronous stream.
+///
+/// The stream is only polled after this stage has been pulled, so
+/// backpressure from downstream is propagated to it.
+pub struct AsyncStream<S> {
+    stream: Option<Pin<Box<S>>>,
+    waker: Option<Waker>,
+    pulled: bool,
+}
+
+impl<A, S: Stream<Item = A>> AsyncStream<S>
+where
+    A: 'static + Send,
+    S: 'static + Send,
+{
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream: Some(Box::pin(stream)),
+            waker: None,
+            pulled: false,
+        }
+    }
+
+    fn poll(&mut self) -> Action<A, AsyncStreamCtl> {
+        let result = match (&mut self.stream, &self.waker) {
+            (Some(stream), Some(waker)) => {
+                stream.as_mut().poll_next(&mut Context::from_waker(waker))
+            }
+
+            _ => Poll::Pending,
+        };
+
+        match result {
+            Poll::Ready(Some(element)) => {
+                self.pulled = false;
+
+                Action::Push(element)
+            }
+
+            Poll::Ready(None) => {
+                self.stream = None;
+
+                Action::Stop(None)
+            }
+
+            Poll::Pending => Action::None,
+        }
+    }
+}
+
+impl<A, S: Stream<Item = A>> Logic<(), A> for AsyncStream<S>
+where
+    A: 'static + Send,
+    S: 'static + Send,
+{
+    type Ctl = AsyncStreamCtl;
+
+    fn name(&self) -> &'static str {
+        "pantomime::stream::source::AsyncStream"
+    }
+
+    fn fusible(&self) -> bool {
+        false
+    }
+
+    fn receive(
+        &mut self,
+        msg: LogicEvent<(), Self::Ctl>,
+        ctx: &mut StreamContext<(), A, Self::Ctl>,
+    ) -> Action<A, Self::Ctl> {
+        match msg {
+            LogicEvent::Started => {
+                self.waker = Some(Waker::from(Arc::new(StageWaker {
+                    stage_ref: ctx.stage_ref(),
+                })));
+
+                Action::None
+            }
+
+            LogicEvent::Pulled => {
+                self.pulled = true;
+
+                self.poll()
+            }
+
+            LogicEvent::Forwarded(AsyncStreamCtl::Wake) => {
+                if self.pulled {
+                    self.poll()
+                } else {
+                    Action::None
+                }
+            }
+
+            LogicEvent::Cancelled => {
+                self.stream = None;
+
+                Action::Stop(None)
+            }
+
+            LogicEvent::Pushed(()) | LogicEvent::Stopped => Action::None,
+        }
+    }
+}
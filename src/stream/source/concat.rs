@@ -0,0 +1,118 @@
+use crate::actor::FailureReason;
+use crate::stream::{LogicPortEvent, PortAction, PortContext, PortLogic};
+
+/// Emits all of the elements of each of its inlets in turn, in the
+/// supplied order. An inlet is only pulled once the ones before it
+/// have completed.
+///
+/// If any inlet fails, the others are cancelled and the stage fails.
+pub struct Concat {
+    order: Vec<usize>,
+    stopped: Vec<bool>,
+    current: usize,
+    pulled: bool,
+    inlet_pulled: bool,
+    completed: bool,
+}
+
+impl Concat {
+    pub fn new(order: Vec<usize>) -> Self {
+        let inlets = order.len();
+
+        Self {
+            order,
+            stopped: vec![false; inlets],
+            current: 0,
+            pulled: false,
+            inlet_pulled: false,
+            completed: false,
+        }
+    }
+
+    fn try_pull<A>(&mut self, ctx: &mut PortContext<A>) {
+        // inlets that are further along may have already stopped
+
+        while self.current < self.order.len() && self.stopped[self.order[self.current]] {
+            self.current += 1;
+            self.inlet_pulled = false;
+        }
+
+        if self.current == self.order.len() {
+            self.complete(None, ctx);
+        } else if self.pulled && !self.inlet_pulled {
+            self.inlet_pulled = true;
+
+            ctx.tell(self.order[self.current], PortAction::Pull);
+        }
+    }
+
+    fn complete<A>(&mut self, reason: Option<FailureReason>, ctx: &mut PortContext<A>) {
+        for (inlet, stopped) in self.stopped.iter_mut().enumerate() {
+            if !*stopped {
+                *stopped = true;
+
+                ctx.tell(inlet, PortAction::Cancel);
+            }
+        }
+
+        self.completed = true;
+
+        ctx.tell(0, PortAction::Complete(reason));
+    }
+}
+
+impl<A> PortLogic<A, A> for Concat
+where
+    A: 'static + Send,
+{
+    fn name(&self) -> &'static str {
+        "pantomime::stream::source::Concat"
+    }
+
+    fn inlets(&self) -> usize {
+        self.order.len()
+    }
+
+    fn outlets(&self) -> usize {
+        1
+    }
+
+    fn receive(&mut self, event: LogicPortEvent<A>, ctx: &mut PortContext<A>) {
+        if self.completed {
+            return;
+        }
+
+        match event {
+            LogicPortEvent::Started(_) => {}
+
+            LogicPortEvent::Pulled(_) => {
+                self.pulled = true;
+
+                self.try_pull(ctx);
+            }
+
+            LogicPortEvent::Pushed(_, element) => {
+                self.pulled = false;
+                self.inlet_pulled = false;
+
+                ctx.tell(0, PortAction::Push(element));
+            }
+
+            LogicPortEvent::Stopped(inlet) => {
+                self.stopped[inlet] = true;
+
+                self.try_pull(ctx);
+            }
+
+            LogicPortEvent::Failed(inlet, reason) => {
+                self.stopped[inlet] = true;
+
+                self.complete(Some(reason), ctx);
+            }
+
+            LogicPortEvent::Cancelled(_) => {
+                self.complete(None, ctx);
+            }
+        }
+    }
+}
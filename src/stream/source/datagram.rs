@@ -0,0 +1,269 @@
+use crate::actor::{FailureError, FailureReason, SubscriptionEvent};
+use crate::stream::datagram::{self, DatagramErrorPolicy, DatagramOptions, DatagramSocket};
+use crate::stream::{Action, Logic, LogicEvent, StreamContext};
+use mio::{net::UdpSocket, Poll, PollOpt, Ready, Token};
+use std::io::{self, ErrorKind as IoErrorKind, Result as IoResult};
+use std::mem;
+use std::sync::Arc;
+
+#[cfg(target_family = "unix")]
+use mio_uds::UnixDatagram;
+
+/// A source that receives datagrams from a UDP socket.
+pub type Udp = Datagrams<UdpSocket>;
+
+/// A source that receives datagrams from a Unix domain datagram socket.
+#[cfg(target_family = "unix")]
+pub type Uds = Datagrams<UnixDatagram>;
+
+/// Emits the datagrams that are received on a socket, reading one each
+/// time that downstream pulls.
+///
+/// A buffer that can hold the maximum datagram size is allocated when the
+/// source is created. Datagrams that exceed it are truncated by the
+/// operating system, which is detected and handled according to the error
+/// policy, as are other errors that only affect a single datagram.
+pub struct Datagrams<S: DatagramSocket> {
+    socket: IoResult<S>,
+    options: S::Options,
+    buffer: Vec<u8>,
+    applied: bool,
+    ready: bool,
+    waiting: bool,
+    poll: Option<Arc<Poll>>,
+    token: usize,
+}
+
+impl<S: DatagramSocket> Datagrams<S>
+where
+    S: 'static,
+{
+    /// Use the supplied socket with the default options.
+    pub fn new(socket: &S) -> Self {
+        Self::with_options(socket, S::Options::default())
+    }
+
+    pub fn with_options(socket: &S, options: S::Options) -> Self {
+        // an extra byte allows truncated datagrams to be detected
+
+        let buffer = vec![0; options.max_datagram_size() + 1];
+
+        Self {
+            socket: socket.try_clone(),
+            options,
+            buffer,
+            applied: false,
+            ready: false,
+            waiting: false,
+            poll: None,
+            token: 0,
+        }
+    }
+
+    fn try_read(
+        &mut self,
+        ctx: &mut StreamContext<(), S::Datagram, SubscriptionEvent>,
+    ) -> Action<S::Datagram, SubscriptionEvent> {
+        loop {
+            let received = match self.socket {
+                Ok(ref s) if self.ready && self.waiting => s.recv_from(&mut self.buffer),
+                _ => return Action::None,
+            };
+
+            match received {
+                Ok((bytes_read, _)) if bytes_read > self.options.max_datagram_size() => {
+                    let error = io::Error::new(
+                        IoErrorKind::InvalidData,
+                        format!(
+                            "datagram exceeds the maximum size of {} bytes",
+                            self.options.max_datagram_size()
+                        ),
+                    );
+
+                    if let Some(action) = self.handle_error(error, ctx) {
+                        return action;
+                    }
+                }
+
+                Ok((bytes_read, address)) => {
+                    self.waiting = false;
+
+                    return Action::Push(S::datagram(self.buffer[0..bytes_read].to_vec(), address));
+                }
+
+                Err(ref e) if e.kind() == IoErrorKind::WouldBlock => {
+                    self.ready = false;
+
+                    return Action::None;
+                }
+
+                Err(ref e) if e.kind() == IoErrorKind::Interrupted => {}
+
+                Err(e) => {
+                    if let Some(action) = self.handle_error(e, ctx) {
+                        return action;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Log and discard transient errors if configured to do so, otherwise
+    /// fail the stream.
+    fn handle_error(
+        &mut self,
+        error: io::Error,
+        ctx: &mut StreamContext<(), S::Datagram, SubscriptionEvent>,
+    ) -> Option<Action<S::Datagram, SubscriptionEvent>> {
+        if self.options.error_policy() == DatagramErrorPolicy::LogAndContinue
+            && datagram::is_transient(&error)
+        {
+            warn!("{}: discarding datagram: {}", self.name(), error);
+
+            None
+        } else {
+            Some(self.fail(error, ctx))
+        }
+    }
+
+    fn fail(
+        &mut self,
+        error: io::Error,
+        ctx: &mut StreamContext<(), S::Datagram, SubscriptionEvent>,
+    ) -> Action<S::Datagram, SubscriptionEvent> {
+        self.unregister(ctx);
+
+        Action::Stop(Some(FailureReason::Errored(FailureError::new(error))))
+    }
+
+    fn unregister(&mut self, ctx: &mut StreamContext<(), S::Datagram, SubscriptionEvent>) {
+        if let Some(poll) = self.poll.take() {
+            if let Ok(ref socket) = self.socket {
+                // @TODO expect doesn't seem appropriate
+
+                poll.deregister(socket)
+                    .expect("pantomime bug: failed to deregister socket");
+            }
+        }
+
+        if self.applied {
+            self.applied = false;
+
+            if let Ok(ref socket) = self.socket {
+                if let Err(e) = self.options.unapply(socket) {
+                    warn!("{}: failed to leave multicast groups: {}", self.name(), e);
+                }
+            }
+        }
+
+        if self.token > 0 {
+            ctx.unsubscribe(self.token);
+
+            self.token = 0;
+        }
+    }
+}
+
+impl<S: DatagramSocket> Logic<(), S::Datagram> for Datagrams<S>
+where
+    S: 'static,
+{
+    type Ctl = SubscriptionEvent;
+
+    fn name(&self) -> &'static str {
+        "pantomime::stream::source::Datagrams"
+    }
+
+    fn buffer_size(&self) -> Option<usize> {
+        Some(0)
+    }
+
+    fn fusible(&self) -> bool {
+        false
+    }
+
+    fn receive(
+        &mut self,
+        msg: LogicEvent<(), Self::Ctl>,
+        ctx: &mut StreamContext<(), S::Datagram, Self::Ctl>,
+    ) -> Action<S::Datagram, Self::Ctl> {
+        match msg {
+            LogicEvent::Pulled => {
+                self.waiting = true;
+
+                self.try_read(ctx)
+            }
+
+            LogicEvent::Forwarded(SubscriptionEvent::MioEvent(event)) => {
+                if event.readiness().is_readable() {
+                    self.ready = true;
+
+                    self.try_read(ctx)
+                } else {
+                    Action::None
+                }
+            }
+
+            LogicEvent::Forwarded(SubscriptionEvent::Ready(poll, token)) => {
+                let registered = match self.socket {
+                    Ok(ref socket) => {
+                        poll.register(socket, Token(token), Ready::readable(), PollOpt::edge())
+                    }
+
+                    Err(_) => {
+                        ctx.unsubscribe(token);
+
+                        return Action::None;
+                    }
+                };
+
+                self.poll = Some(poll);
+                self.token = token;
+
+                match registered {
+                    Ok(()) => {
+                        // datagrams may have been queued before registering
+
+                        self.ready = true;
+
+                        self.try_read(ctx)
+                    }
+
+                    Err(e) => self.fail(e, ctx),
+                }
+            }
+
+            LogicEvent::Started => {
+                let applied = match self.socket {
+                    Ok(ref socket) => self.options.apply(socket),
+
+                    Err(_) => mem::replace(
+                        &mut self.socket,
+                        Err(io::Error::from(IoErrorKind::NotConnected)),
+                    )
+                    .map(|_| ()),
+                };
+
+                if let Err(e) = applied {
+                    return self.fail(e, ctx);
+                }
+
+                self.applied = true;
+
+                let actor_ref = ctx.stage_ref().actor_ref;
+
+                ctx.subscribe(actor_ref);
+
+                Action::None
+            }
+
+            LogicEvent::Cancelled => {
+                self.unregister(ctx);
+
+                Action::Stop(None)
+            }
+
+            LogicEvent::Pushed(()) | LogicEvent::Stopped => Action::None,
+        }
+    }
+}
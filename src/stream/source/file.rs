@@ -0,0 +1,62 @@
+use crate::stream::source::reader::Reader;
+use crate::stream::{Action, Logic, LogicEvent, StreamContext};
+use std::fs::File as StdFile;
+use std::io::{self, Read};
+use std::path::PathBuf;
+
+/// A file that is opened when it's first read, so that failing to open
+/// it fails the stream.
+struct LazyFile {
+    path: PathBuf,
+    file: Option<StdFile>,
+}
+
+impl Read for LazyFile {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.file.is_none() {
+            self.file = Some(StdFile::open(&self.path)?);
+        }
+
+        self.file
+            .as_mut()
+            .expect("pantomime bug: LazyFile::file is None")
+            .read(buf)
+    }
+}
+
+/// A source that emits the contents of a file in chunks of at most
+/// `chunk_size` bytes.
+///
+/// This is a `Reader` of the file, which is opened when the source is
+/// first pulled. The stream fails if the file cannot be opened or read.
+pub struct File(Reader<LazyFile>);
+
+impl File {
+    pub fn new(path: PathBuf, chunk_size: usize) -> Self {
+        File(Reader::new(LazyFile { path, file: None }, chunk_size))
+    }
+}
+
+impl Logic<(), Vec<u8>> for File {
+    type Ctl = ();
+
+    fn name(&self) -> &'static str {
+        "pantomime::stream::source::File"
+    }
+
+    fn fusible(&self) -> bool {
+        self.0.fusible()
+    }
+
+    fn dispatcher(&self) -> Option<&str> {
+        self.0.dispatcher()
+    }
+
+    fn receive(
+        &mut self,
+        msg: LogicEvent<(), Self::Ctl>,
+        ctx: &mut StreamContext<(), Vec<u8>, Self::Ctl>,
+    ) -> Action<Vec<u8>, Self::Ctl> {
+        self.0.receive(msg, ctx)
+    }
+}
@@ -1,223 +1,148 @@
-/*
-use crate::actor::ActorRef;
-use crate::stream::internal::{Downstream, DownstreamStageMsg, Producer, UpstreamStageMsg};
-use crate::stream::{Action, Logic, LogicEvent, StreamContext};
-use std::collections::VecDeque;
-use std::marker::PhantomData;
-
-pub struct Merge<A>
-where
-    A: 'static + Send,
-{
-    producers: Vec<Box<Producer<(), A> + Send>>,
-    actors: Vec<ActorRef<DownstreamStageMsg<()>>>,
-    buffer: VecDeque<A>,
+use crate::actor::FailureReason;
+use crate::stream::{FanInCompletion, LogicPortEvent, PortAction, PortContext, PortLogic};
+
+/// Emits the elements of each of its inlets as they become available.
+///
+/// Inlets are pulled fairly, i.e. when more than one inlet has an element
+/// available, they are taken from in a round-robin fashion.
+///
+/// If any inlet fails, the others are cancelled and the merge fails. If
+/// downstream cancels, all of the inlets are cancelled.
+pub struct Merge<A> {
+    completion: FanInCompletion,
+    pending: Vec<Option<A>>,
+    stopped: Vec<bool>,
+    next: usize,
     pulled: bool,
-    completed: usize,
-    demand: Vec<u64>,
+    completed: bool,
 }
 
-// @TODO visibility here...
-pub enum MergeMsg<A>
-where
-    A: 'static + Send,
-{
-    FromUpstream(usize, FromUpstreamMsg<A>),
-    ForUpstream(usize, ForUpstreamMsg),
-}
+impl<A> Merge<A> {
+    pub fn new(inlets: usize, completion: FanInCompletion) -> Self {
+        Self {
+            completion,
+            pending: (0..inlets).map(|_| None).collect(),
+            stopped: vec![false; inlets],
+            next: 0,
+            pulled: false,
+            completed: false,
+        }
+    }
 
-pub struct FromUpstreamMsg<A>
-where
-    A: 'static + Send,
-{
-    msg: DownstreamStageMsg<A>,
-}
+    fn try_push(&mut self, ctx: &mut PortContext<A>) {
+        let inlets = self.pending.len();
 
-pub struct ForUpstreamMsg {
-    msg: UpstreamStageMsg,
-}
+        if self.pulled {
+            let ready = (0..inlets)
+                .map(|i| (self.next + i) % inlets)
+                .find(|i| self.pending[*i].is_some());
 
-const BUFFER_SIZE_TEMP: u64 = 16;
+            if let Some(inlet) = ready {
+                if let Some(element) = self.pending[inlet].take() {
+                    self.pulled = false;
+                    self.next = (inlet + 1) % inlets;
 
-impl<A> Merge<A>
-where
-    A: Send,
-{
-    pub(in crate::stream) fn new(producers: Vec<Box<Producer<(), A> + Send>>) -> Self {
-        let len = producers.len();
+                    ctx.tell(0, PortAction::Push(element));
 
-        Self {
-            producers,
-            actors: Vec::with_capacity(len),
-            buffer: VecDeque::with_capacity(len * BUFFER_SIZE_TEMP as usize),
-            pulled: false,
-            completed: 0,
-            demand: Vec::with_capacity(len),
+                    if !self.stopped[inlet] {
+                        ctx.tell(inlet, PortAction::Pull);
+                    }
+                }
+            }
+        }
+
+        if self.stopped.iter().all(|s| *s) && self.pending.iter().all(Option::is_none) {
+            self.complete(None, ctx);
         }
     }
 
-    fn check_demand(&mut self, id: usize) {
-        let available = BUFFER_SIZE_TEMP - self.demand[id];
+    fn complete(&mut self, reason: Option<FailureReason>, ctx: &mut PortContext<A>) {
+        for (inlet, stopped) in self.stopped.iter_mut().enumerate() {
+            if !*stopped {
+                *stopped = true;
 
-        if available >= BUFFER_SIZE_TEMP / 2 {
-            self.actors[id].tell(DownstreamStageMsg::Converted(UpstreamStageMsg::Pull(
-                available,
-            )));
-            self.demand[id] += available;
+                ctx.tell(inlet, PortAction::Cancel);
+            }
         }
+
+        self.completed = true;
+
+        ctx.tell(0, PortAction::Complete(reason));
     }
 }
 
-impl<A> Logic<(), A> for Merge<A>
+impl<A> PortLogic<A, A> for Merge<A>
 where
     A: 'static + Send,
 {
-    type Ctl = MergeMsg<A>;
-
     fn name(&self) -> &'static str {
-        "Merge"
+        "pantomime::stream::source::Merge"
     }
 
-    fn buffer_size(&self) -> Option<usize> {
-        Some(0)
+    fn inlets(&self) -> usize {
+        self.pending.len()
     }
 
-    fn receive(
-        &mut self,
-        msg: LogicEvent<(), Self::Ctl>,
-        ctx: &mut StreamContext<(), A, Self::Ctl>,
-    ) {
-        match msg {
-            LogicEvent::Pulled => match self.buffer.pop_front() {
-                Some(el) => {
-                    ctx.tell(Action::Push(el));
-                }
-
-                None if self.completed == self.actors.len() => {
-                    ctx.tell(Action::Complete(None));
-                }
-
-                None => {
-                    self.pulled = true;
-                }
-            },
-
-            LogicEvent::Forwarded(MergeMsg::FromUpstream(
-                id,
-                FromUpstreamMsg {
-                    msg: DownstreamStageMsg::Produce(a),
-                },
-            )) => {
-                if self.pulled {
-                    self.pulled = false;
+    fn outlets(&self) -> usize {
+        1
+    }
 
-                    ctx.tell(Action::Push(a));
-                } else {
-                    self.buffer.push_back(a);
-                };
+    fn receive(&mut self, event: LogicPortEvent<A>, ctx: &mut PortContext<A>) {
+        if self.completed {
+            return;
+        }
 
-                if self.demand[id] == 0 {
-                    // @TODO fail
-                } else {
-                    self.demand[id] -= 1;
-                    self.check_demand(id);
-                }
+        match event {
+            LogicPortEvent::Started(inlet) => {
+                ctx.tell(inlet, PortAction::Pull);
             }
 
-            LogicEvent::Forwarded(MergeMsg::FromUpstream(
-                id,
-                FromUpstreamMsg {
-                    msg: DownstreamStageMsg::Complete(reason),
-                },
-            )) => {
-                self.completed = self.actors.len().min(self.completed + 1);
+            LogicPortEvent::Pulled(_) => {
+                self.pulled = true;
 
-                if self.completed == self.actors.len() && self.buffer.is_empty() {
-                    ctx.tell(Action::Complete(None));
-                }
+                self.try_push(ctx);
             }
 
-            LogicEvent::Forwarded(MergeMsg::FromUpstream(
-                id,
-                FromUpstreamMsg {
-                    msg: DownstreamStageMsg::SetUpstream(upstream),
-                },
-            )) => {
-                // @TODO not sure what this means
-            }
+            LogicPortEvent::Pushed(inlet, element) => {
+                self.pending[inlet] = Some(element);
 
-            LogicEvent::Forwarded(MergeMsg::FromUpstream(
-                id,
-                FromUpstreamMsg {
-                    msg: DownstreamStageMsg::Converted(UpstreamStageMsg::Pull(demand)),
-                },
-            )) => {
-                // @TODO not sure what this means
+                self.try_push(ctx);
             }
 
-            LogicEvent::Forwarded(MergeMsg::FromUpstream(
-                id,
-                FromUpstreamMsg {
-                    msg: DownstreamStageMsg::Converted(UpstreamStageMsg::Cancel),
-                },
-            )) => {
-                // @TODO not sure what this means
-            }
+            LogicPortEvent::Stopped(inlet) => {
+                self.stopped[inlet] = true;
 
-            LogicEvent::Forwarded(MergeMsg::ForUpstream(
-                id,
-                ForUpstreamMsg {
-                    msg: UpstreamStageMsg::Pull(_),
-                },
-            )) => {
-                // ignore
-            }
+                if self.completion == FanInCompletion::Eager {
+                    // any element that this inlet has already pushed
+                    // is still emitted
 
-            LogicEvent::Forwarded(MergeMsg::ForUpstream(
-                id,
-                ForUpstreamMsg {
-                    msg: UpstreamStageMsg::Cancel,
-                },
-            )) => {
-                // ignore
-            }
+                    for (i, pending) in self.pending.iter_mut().enumerate() {
+                        if i != inlet {
+                            *pending = None;
+                        }
+                    }
 
-            LogicEvent::Cancelled => {
-                self.completed = self.actors.len();
+                    for (i, stopped) in self.stopped.iter_mut().enumerate() {
+                        if !*stopped {
+                            *stopped = true;
 
-                for producer in self.actors.iter() {
-                    producer.tell(DownstreamStageMsg::Converted(UpstreamStageMsg::Cancel));
+                            ctx.tell(i, PortAction::Cancel);
+                        }
+                    }
                 }
-            }
-
-            LogicEvent::Started => {
-                for (id, p) in self.producers.drain(..).enumerate() {
-                    let downstream = ctx.actor_ref().convert(move |msg| {
-                        Action::Forward(MergeMsg::FromUpstream(id, FromUpstreamMsg { msg }))
-                    });
-                    let mut spawn_ctx = ctx.ctx.spawn_context();
-
-                    let actor_ref = p.spawn(Downstream::Spawned(downstream), &mut spawn_ctx);
 
-                    actor_ref.tell(DownstreamStageMsg::SetUpstream(ctx.actor_ref().convert(
-                        move |msg| {
-                            Action::Forward(MergeMsg::ForUpstream(id, ForUpstreamMsg { msg }))
-                        },
-                    )));
-                    actor_ref.tell(DownstreamStageMsg::Complete(None));
-
-                    self.actors.push(actor_ref);
-                    self.demand.push(0);
-                }
+                self.try_push(ctx);
+            }
 
-                for id in 0..self.actors.len() {
-                    self.check_demand(id);
-                }
+            LogicPortEvent::Failed(inlet, reason) => {
+                self.stopped[inlet] = true;
 
-                self.producers = Vec::with_capacity(0);
+                self.complete(Some(reason), ctx);
             }
 
-            LogicEvent::Pushed(()) | LogicEvent::Stopped => {}
+            LogicPortEvent::Cancelled(_) => {
+                self.complete(None, ctx);
+            }
         }
     }
-}*/
+}
@@ -1,16 +1,42 @@
-use crate::stream::internal::{ContainedLogicImpl, LogicType, SourceLike, UnionLogic};
+use crate::actor::ActorRef;
+use crate::stream::internal::{ContainedLogicImpl, FanIn, LogicType, SourceLike, UnionLogic};
 use crate::stream::sink::Sink;
+use crate::stream::udp::UdpOptions;
 use crate::stream::{flow, flow::Flow, flow::Fused};
-use crate::stream::{Datagram, Logic, Stream};
+use crate::stream::{hub, Datagram, FanInCompletion, Logic, PortLogic, Stream, ThrottleMode};
+use futures_core::Stream as AsyncStream;
+use std::io::{self, Read};
 use std::iter::Iterator as Iter;
 use std::marker::PhantomData;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
 
+#[cfg(target_family = "unix")]
+use crate::stream::datagram::UdsOptions;
+
+pub mod async_stream;
+pub mod concat;
+pub mod datagram;
+pub mod file;
 pub mod iterator;
 pub mod merge;
+pub mod or_else;
 pub mod queue;
+pub mod reader;
 pub mod repeat;
 pub mod single;
+pub mod tcp;
 pub mod udp;
+pub mod writable;
+pub mod zip;
+
+#[cfg(feature = "posix-signals-support")]
+pub mod posix_signals;
+
+#[cfg(target_family = "unix")]
+pub mod uds;
 
 pub struct Source<A> {
     pub(in crate::stream) producers: Vec<LogicType<(), A>>,
@@ -26,7 +52,7 @@ where
         L::Ctl: Send,
     {
         Self {
-            producers: vec![if logic.fusible() {
+            producers: vec![if logic.fusible() && logic.dispatcher().is_none() {
                 LogicType::Fusible(Box::new(ContainedLogicImpl::new(logic)))
             } else {
                 LogicType::Spawnable(Box::new(SourceLike {
@@ -37,6 +63,17 @@ where
         }
     }
 
+    /// Create a source that emits the elements of an asynchronous stream,
+    /// completing once the stream has ended.
+    ///
+    /// The stream is only polled when downstream has signaled demand.
+    pub fn from_async_stream<S>(stream: S) -> Self
+    where
+        S: 'static + AsyncStream<Item = A> + Send,
+    {
+        Self::new(async_stream::AsyncStream::new(stream))
+    }
+
     pub fn iterator<I: Iter<Item = A>>(iterator: I) -> Self
     where
         I: 'static + Send,
@@ -44,6 +81,38 @@ where
         Self::new(iterator::Iterator::new(iterator))
     }
 
+    /// Emit the elements of all of the supplied sources as they become
+    /// available, taking from each fairly. Depending on `completion`, the
+    /// merged source completes once all of them or any of them has completed.
+    ///
+    /// If any of the sources fails, the others are cancelled and the merged
+    /// source fails.
+    pub fn merge_all<I: IntoIterator<Item = Source<A>>>(
+        sources: I,
+        completion: FanInCompletion,
+    ) -> Self {
+        let producers = sources
+            .into_iter()
+            .map(|source| source.producer())
+            .collect();
+
+        Self {
+            producers: vec![Self::merge_producers(producers, completion)],
+        }
+    }
+
+    /// Create a source that emits the elements of all of the sinks obtained
+    /// from the returned hub, which can be run at any time. The source never
+    /// completes on its own, and cancelling it cancels the sinks.
+    ///
+    /// Each sink has at most `buffer_size` elements in flight, after which
+    /// it stops pulling until the source has emitted them.
+    pub fn merge_hub(buffer_size: usize) -> (Self, hub::MergeHub<A>) {
+        let (logic, hub) = hub::MergeHubSource::new(buffer_size);
+
+        (Self::new(logic), hub)
+    }
+
     pub fn queue() -> queue::SourceQueue<A> {
         queue::SourceQueue::new()
     }
@@ -96,6 +165,25 @@ where
     // ALL METHODS BELOW SHOULD ALSO EXIST
     // ON Flow
 
+    /// Send each element to the supplied actor, along with a reference that
+    /// it should reply to, and emit the replies in order. The stream fails
+    /// if a reply isn't received within `timeout`.
+    pub fn ask<B, M, F: FnMut(A, ActorRef<B>) -> M>(
+        self,
+        actor_ref: &ActorRef<M>,
+        timeout: Duration,
+        convert: F,
+    ) -> Source<B>
+    where
+        B: 'static + Send,
+        M: 'static + Send,
+        F: 'static + Send,
+    {
+        self.via(Flow::from_logic(flow::Ask::new(
+            actor_ref, timeout, convert,
+        )))
+    }
+
     pub fn filter<F: FnMut(&A) -> bool>(self, filter: F) -> Source<A>
     where
         F: 'static + Send,
@@ -119,6 +207,36 @@ where
         self.via(Flow::from_logic(flow::Fold::new(zero, fold_fn)))
     }
 
+    /// Emit the elements in groups of up to `size` elements. Only the last
+    /// group, which is emitted once upstream completes, may be smaller.
+    pub fn grouped(self, size: usize) -> Source<Vec<A>> {
+        self.via(Flow::from_logic(flow::Grouped::new(size)))
+    }
+
+    /// Emit the elements in groups of up to `size` elements, emitting a
+    /// smaller group once `duration` has elapsed since its first element
+    /// arrived. Any remaining elements are emitted once upstream completes.
+    pub fn grouped_within(self, size: usize, duration: Duration) -> Source<Vec<A>> {
+        self.grouped_weighted_within(size, duration, |_: &A| 1)
+    }
+
+    /// Emit the elements in groups like `grouped_within`, but with each
+    /// group limited to a total cost of `max_cost`, as returned by the
+    /// supplied function for each element, rather than a number of them.
+    pub fn grouped_weighted_within<F: FnMut(&A) -> usize>(
+        self,
+        max_cost: usize,
+        duration: Duration,
+        cost_fn: F,
+    ) -> Source<Vec<A>>
+    where
+        F: 'static + Send,
+    {
+        self.via(Flow::from_logic(flow::GroupedWithin::new(
+            max_cost, duration, cost_fn,
+        )))
+    }
+
     pub fn map<B, F: FnMut(A) -> B>(self, map_fn: F) -> Source<B>
     where
         B: 'static + Send,
@@ -139,6 +257,81 @@ where
         self.via(Flow::from_logic(flow::MapConcat::new(map_concat)))
     }
 
+    /// Limit the rate of elements to `elements` every `per`, allowing bursts
+    /// of up to `burst` elements. Depending on `mode`, elements that exceed
+    /// the rate are delayed or fail the stream.
+    pub fn throttle(
+        self,
+        elements: usize,
+        per: Duration,
+        burst: usize,
+        mode: ThrottleMode,
+    ) -> Self {
+        self.throttle_with_cost(elements, per, burst, |_: &A| 1, mode)
+    }
+
+    /// Limit the rate of elements like `throttle`, but with each element
+    /// counting as the number of elements returned by the supplied function
+    /// rather than as one element.
+    pub fn throttle_with_cost<F: FnMut(&A) -> usize>(
+        self,
+        elements: usize,
+        per: Duration,
+        burst: usize,
+        cost_fn: F,
+        mode: ThrottleMode,
+    ) -> Self
+    where
+        F: 'static + Send,
+    {
+        self.via(Flow::from_logic(flow::Throttle::new(
+            elements, per, burst, cost_fn, mode,
+        )))
+    }
+
+    /// Emit the elements of this source and then, once it has completed,
+    /// the elements of the supplied one. The supplied source is only
+    /// materialized once this one has completed.
+    pub fn concat(self, source: Source<A>) -> Self {
+        Self::sequence(vec![self, source], concat::Concat::new(vec![0, 1]))
+    }
+
+    /// Emit the elements of the supplied source and then, once it has
+    /// completed, the elements of this one. This source is only
+    /// materialized once the supplied one has completed.
+    pub fn prepend(self, source: Source<A>) -> Self {
+        Self::sequence(vec![source, self], concat::Concat::new(vec![0, 1]))
+    }
+
+    /// Emit the elements of this source, or if it completes without having
+    /// emitted any, the elements of the supplied fallback. The fallback is
+    /// only materialized if it's needed.
+    pub fn or_else(self, fallback: Source<A>) -> Self {
+        Self::sequence(vec![self, fallback], or_else::OrElse::new())
+    }
+
+    fn sequence<L: PortLogic<A, A>>(sources: Vec<Source<A>>, logic: L) -> Self
+    where
+        L: 'static + Send,
+    {
+        Self {
+            producers: vec![LogicType::Spawnable(Box::new(FanIn {
+                logic,
+                primary: None,
+                sources: sources
+                    .into_iter()
+                    .map(|source| source.producer().into_facade())
+                    .collect(),
+                lazy: true,
+            }))],
+        }
+    }
+
+    /// Emit the elements of both this source and the supplied one, as they
+    /// become available. Elements are taken from each fairly, and the merged
+    /// source completes once both have completed.
+    ///
+    /// Merging several sources in a chain merges them in a single stage.
     pub fn merge(self, source: Source<A>) -> Self {
         let mut producers = self.producers;
 
@@ -149,6 +342,62 @@ where
         Source { producers }
     }
 
+    /// Emit pairs of the elements of this source and the supplied one.
+    /// The zipped source completes once either of them completes.
+    pub fn zip<B>(self, source: Source<B>) -> Source<(A, B)>
+    where
+        B: 'static + Send,
+    {
+        self.zip_with(source, |a, b| (a, b))
+    }
+
+    /// Emit the pairs of the latest elements of this source and the supplied
+    /// one whenever either emits, once both have emitted. Depending on
+    /// `completion`, the zipped source completes once either or both of
+    /// them have completed.
+    pub fn zip_latest<B>(self, source: Source<B>, completion: FanInCompletion) -> Source<(A, B)>
+    where
+        A: Clone,
+        B: 'static + Clone + Send,
+    {
+        self.zip_ports(source, zip::ZipLatest::new(completion))
+    }
+
+    /// Combine the elements of this source and the supplied one with the
+    /// supplied function, emitting the results. The zipped source completes
+    /// once either of them completes.
+    pub fn zip_with<B, C, F: FnMut(A, B) -> C>(self, source: Source<B>, zip_fn: F) -> Source<C>
+    where
+        B: 'static + Send,
+        C: 'static + Send,
+        F: 'static + Send,
+    {
+        self.zip_ports(source, zip::ZipWith::new(zip_fn))
+    }
+
+    fn zip_ports<B, C, L: PortLogic<zip::ZipInput<A, B>, C>>(
+        self,
+        source: Source<B>,
+        logic: L,
+    ) -> Source<C>
+    where
+        B: 'static + Send,
+        C: 'static + Send,
+        L: 'static + Send,
+    {
+        Source {
+            producers: vec![LogicType::Spawnable(Box::new(FanIn {
+                logic,
+                primary: None,
+                sources: vec![
+                    self.map(zip::ZipInput::Left).producer().into_facade(),
+                    source.map(zip::ZipInput::Right).producer().into_facade(),
+                ],
+                lazy: false,
+            }))],
+        }
+    }
+
     pub fn via<B>(self, flow: Flow<A, B>) -> Source<B>
     where
         B: 'static + Send,
@@ -185,17 +434,147 @@ where
 
     pub(in crate::stream) fn producer(mut self) -> LogicType<(), A> {
         if self.producers.len() > 1 {
-            panic!("merge not supported until ports are implemented")
+            Self::merge_producers(self.producers, FanInCompletion::Lazy)
         } else {
             self.producers
                 .pop()
                 .expect("pantomime bug: Source::producers is empty")
         }
     }
+
+    fn merge_producers(
+        producers: Vec<LogicType<(), A>>,
+        completion: FanInCompletion,
+    ) -> LogicType<(), A> {
+        LogicType::Spawnable(Box::new(FanIn {
+            logic: merge::Merge::new(producers.len(), completion),
+            primary: None,
+            sources: producers.into_iter().map(LogicType::into_facade).collect(),
+            lazy: false,
+        }))
+    }
+}
+
+impl Source<Vec<u8>> {
+    /// Emit the contents of the file at the supplied path, in chunks of
+    /// at most `chunk_size` bytes. The file is read on a dedicated
+    /// dispatcher for blocking I/O.
+    ///
+    /// Panics if `chunk_size` is 0.
+    pub fn from_file<P: AsRef<Path>>(path: P, chunk_size: usize) -> Self {
+        assert!(chunk_size > 0, "chunk_size must be greater than 0");
+
+        Self::new(file::File::new(path.as_ref().to_path_buf(), chunk_size))
+    }
+
+    /// Emit the data read from the supplied reader, in chunks of at most
+    /// `chunk_size` bytes. The reader is read on a dedicated dispatcher
+    /// for blocking I/O.
+    ///
+    /// Panics if `chunk_size` is 0.
+    pub fn from_reader<R: Read>(reader: R, chunk_size: usize) -> Self
+    where
+        R: 'static + Send,
+    {
+        assert!(chunk_size > 0, "chunk_size must be greater than 0");
+
+        Self::new(reader::Reader::new(reader, chunk_size))
+    }
+
+    /// Spawn the supplied command as a child process when the stream
+    /// starts, emitting its stdout. Its stdin is closed immediately.
+    ///
+    /// This otherwise behaves like `Flow::process`.
+    pub fn process(command: Command, log_stderr: bool) -> Self {
+        Source::iterator(std::iter::empty()).via(Flow::process(command, log_stderr))
+    }
+
+    /// Create a source that emits the data written to the returned writer,
+    /// for integration with code that produces data via `std::io::Write`.
+    ///
+    /// At most `capacity` writes are buffered, after which writes block
+    /// until the source has been pulled.
+    pub fn writable(capacity: usize) -> (Self, writable::SourceWriter) {
+        let (logic, writer) = writable::Writable::new(capacity);
+
+        (Self::new(logic), writer)
+    }
+}
+
+#[cfg(feature = "posix-signals-support")]
+impl Source<crate::posix_signals::PosixSignal> {
+    /// Create a source that emits the POSIX signals that the process
+    /// receives. It never completes on its own.
+    ///
+    /// Only the signals that are listed in `PANTOMIME_POSIX_SIGNALS`
+    /// are handled. Signals that are received while there is no demand
+    /// are buffered.
+    pub fn posix_signals() -> Self {
+        Self::new(posix_signals::PosixSignals::new())
+    }
+}
+
+impl Source<tcp::TcpConnection> {
+    /// Bind a TCP listener to the supplied address, emitting a
+    /// connection for each peer that connects to it.
+    ///
+    /// The listener is bound immediately, and the address that it is
+    /// bound to is returned along with the source, e.g. to find out the
+    /// port that was assigned when binding to port 0.
+    pub fn tcp_bind(address: &SocketAddr) -> io::Result<(Self, SocketAddr)> {
+        let bind = tcp::TcpBind::new(address)?;
+        let local_address = bind.local_address()?;
+
+        Ok((Self::new(bind), local_address))
+    }
 }
 
 impl Source<Datagram> {
+    /// Emit the datagrams received on the supplied socket.
+    ///
+    /// Datagrams of up to `MAX_DATAGRAM_SIZE` bytes can be received, so
+    /// a buffer of that size is allocated for the source. A smaller
+    /// maximum can be configured via `udp_with_options`.
     pub fn udp(socket: &mio::net::UdpSocket) -> Self {
-        Self::new(udp::Udp::new(socket))
+        Self::udp_with_options(socket, UdpOptions::new())
+    }
+
+    /// Emit the datagrams received on the supplied socket, configuring
+    /// it with the supplied options.
+    pub fn udp_with_options(socket: &mio::net::UdpSocket, options: UdpOptions) -> Self {
+        Self::new(datagram::Udp::with_options(socket, options))
+    }
+}
+
+#[cfg(target_family = "unix")]
+impl Source<uds::UdsConnection> {
+    /// Bind a Unix domain stream socket listener to the supplied path,
+    /// emitting a connection for each peer that connects to it.
+    ///
+    /// This otherwise behaves like `Source::tcp_bind`.
+    pub fn uds_bind<P: AsRef<std::path::Path>>(
+        path: P,
+    ) -> io::Result<(Self, std::os::unix::net::SocketAddr)> {
+        let bind = uds::UdsBind::new(path)?;
+        let local_address = bind.local_address()?;
+
+        Ok((Self::new(bind), local_address))
+    }
+}
+
+#[cfg(target_family = "unix")]
+impl Source<crate::stream::UdsDatagram> {
+    /// Emit the datagrams received on the supplied Unix domain
+    /// datagram socket.
+    ///
+    /// This otherwise behaves like `Source::udp`.
+    pub fn uds(socket: &mio_uds::UnixDatagram) -> Self {
+        Self::uds_with_options(socket, UdsOptions::new())
+    }
+
+    /// Emit the datagrams received on the supplied Unix domain
+    /// datagram socket, using the supplied options.
+    pub fn uds_with_options(socket: &mio_uds::UnixDatagram, options: UdsOptions) -> Self {
+        Self::new(datagram::Uds::with_options(socket, options))
     }
 }
@@ -0,0 +1,126 @@
+use crate::actor::FailureReason;
+use crate::stream::{LogicPortEvent, PortAction, PortContext, PortLogic};
+
+const PRIMARY: usize = 0;
+const FALLBACK: usize = 1;
+
+/// Emits the elements of its primary inlet (0), but if it completes
+/// without having emitted any, emits the elements of its fallback
+/// inlet (1) instead. The fallback is only pulled if the primary
+/// completes empty, and is otherwise cancelled.
+///
+/// If either inlet fails, the other is cancelled and the stage fails.
+pub struct OrElse {
+    current: usize,
+    emitted: bool,
+    stopped: [bool; 2],
+    pulled: bool,
+    inlet_pulled: bool,
+    completed: bool,
+}
+
+impl OrElse {
+    pub fn new() -> Self {
+        Self {
+            current: PRIMARY,
+            emitted: false,
+            stopped: [false; 2],
+            pulled: false,
+            inlet_pulled: false,
+            completed: false,
+        }
+    }
+
+    fn try_pull<A>(&mut self, ctx: &mut PortContext<A>) {
+        if self.pulled && !self.inlet_pulled {
+            self.inlet_pulled = true;
+
+            ctx.tell(self.current, PortAction::Pull);
+        }
+    }
+
+    fn complete<A>(&mut self, reason: Option<FailureReason>, ctx: &mut PortContext<A>) {
+        for (inlet, stopped) in self.stopped.iter_mut().enumerate() {
+            if !*stopped {
+                *stopped = true;
+
+                ctx.tell(inlet, PortAction::Cancel);
+            }
+        }
+
+        self.completed = true;
+
+        ctx.tell(0, PortAction::Complete(reason));
+    }
+}
+
+impl Default for OrElse {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A> PortLogic<A, A> for OrElse
+where
+    A: 'static + Send,
+{
+    fn name(&self) -> &'static str {
+        "pantomime::stream::source::OrElse"
+    }
+
+    fn inlets(&self) -> usize {
+        2
+    }
+
+    fn outlets(&self) -> usize {
+        1
+    }
+
+    fn receive(&mut self, event: LogicPortEvent<A>, ctx: &mut PortContext<A>) {
+        if self.completed {
+            return;
+        }
+
+        match event {
+            LogicPortEvent::Started(_) => {}
+
+            LogicPortEvent::Pulled(_) => {
+                self.pulled = true;
+
+                self.try_pull(ctx);
+            }
+
+            LogicPortEvent::Pushed(_, element) => {
+                self.emitted = true;
+                self.pulled = false;
+                self.inlet_pulled = false;
+
+                ctx.tell(0, PortAction::Push(element));
+            }
+
+            LogicPortEvent::Stopped(PRIMARY) if !self.emitted => {
+                self.stopped[PRIMARY] = true;
+                self.current = FALLBACK;
+                self.inlet_pulled = false;
+
+                self.try_pull(ctx);
+            }
+
+            LogicPortEvent::Stopped(inlet) => {
+                self.stopped[inlet] = true;
+
+                self.complete(None, ctx);
+            }
+
+            LogicPortEvent::Failed(inlet, reason) => {
+                self.stopped[inlet] = true;
+
+                self.complete(Some(reason), ctx);
+            }
+
+            LogicPortEvent::Cancelled(_) => {
+                self.complete(None, ctx);
+            }
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use crate::posix_signals::PosixSignal;
+use crate::stream::{Action, Logic, LogicEvent, StreamContext};
+use std::collections::VecDeque;
+
+pub struct PosixSignals {
+    buffer: VecDeque<PosixSignal>,
+    pulled: bool,
+}
+
+impl PosixSignals {
+    pub fn new() -> Self {
+        Self {
+            buffer: VecDeque::new(),
+            pulled: false,
+        }
+    }
+}
+
+impl Default for PosixSignals {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Logic<(), PosixSignal> for PosixSignals {
+    type Ctl = PosixSignal;
+
+    fn name(&self) -> &'static str {
+        "pantomime::stream::source::PosixSignals"
+    }
+
+    fn fusible(&self) -> bool {
+        false
+    }
+
+    fn receive(
+        &mut self,
+        msg: LogicEvent<(), Self::Ctl>,
+        ctx: &mut StreamContext<(), PosixSignal, Self::Ctl>,
+    ) -> Action<PosixSignal, Self::Ctl> {
+        match msg {
+            LogicEvent::Started => {
+                ctx.watch_posix_signals(|signal| signal);
+
+                Action::None
+            }
+
+            LogicEvent::Pulled => match self.buffer.pop_front() {
+                Some(signal) => Action::Push(signal),
+
+                None => {
+                    self.pulled = true;
+
+                    Action::None
+                }
+            },
+
+            LogicEvent::Forwarded(signal) if self.pulled => {
+                self.pulled = false;
+
+                Action::Push(signal)
+            }
+
+            LogicEvent::Forwarded(signal) => {
+                self.buffer.push_back(signal);
+
+                Action::None
+            }
+
+            LogicEvent::Cancelled => Action::Stop(None),
+
+            LogicEvent::Pushed(()) | LogicEvent::Stopped => Action::None,
+        }
+    }
+}
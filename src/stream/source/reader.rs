@@ -0,0 +1,100 @@
+use crate::actor::{FailureError, FailureReason};
+use crate::cfg::BLOCKING_IO_DISPATCHER;
+use crate::stream::{Action, Logic, LogicEvent, StreamContext};
+use std::io::{self, ErrorKind as IoErrorKind, Read};
+
+/// A source that emits the data read from a reader in chunks of at
+/// most `chunk_size` bytes, completing once it reaches EOF.
+///
+/// Reads may block, so this executes on the `BLOCKING_IO_DISPATCHER`.
+/// The stream fails if the reader returns an error.
+pub struct Reader<R> {
+    reader: Option<R>,
+    chunk_size: usize,
+}
+
+impl<R: Read> Reader<R> {
+    pub fn new(reader: R, chunk_size: usize) -> Self {
+        Self {
+            reader: Some(reader),
+            chunk_size,
+        }
+    }
+
+    fn read(&mut self) -> io::Result<Vec<u8>> {
+        let mut chunk = vec![0; self.chunk_size];
+
+        let reader = match self.reader {
+            Some(ref mut reader) => reader,
+            None => return Ok(Vec::new()),
+        };
+
+        loop {
+            match reader.read(&mut chunk) {
+                Ok(n) => {
+                    chunk.truncate(n);
+
+                    return Ok(chunk);
+                }
+
+                Err(ref e) if e.kind() == IoErrorKind::Interrupted => {}
+
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+impl<R: Read> Logic<(), Vec<u8>> for Reader<R>
+where
+    R: 'static + Send,
+{
+    type Ctl = ();
+
+    fn name(&self) -> &'static str {
+        "pantomime::stream::source::Reader"
+    }
+
+    fn fusible(&self) -> bool {
+        false
+    }
+
+    fn dispatcher(&self) -> Option<&str> {
+        Some(BLOCKING_IO_DISPATCHER)
+    }
+
+    fn receive(
+        &mut self,
+        msg: LogicEvent<(), Self::Ctl>,
+        _: &mut StreamContext<(), Vec<u8>, Self::Ctl>,
+    ) -> Action<Vec<u8>, Self::Ctl> {
+        match msg {
+            LogicEvent::Pulled => match self.read() {
+                Ok(ref chunk) if chunk.is_empty() => {
+                    self.reader = None;
+
+                    Action::Stop(None)
+                }
+
+                Ok(chunk) => Action::Push(chunk),
+
+                Err(e) => {
+                    self.reader = None;
+
+                    Action::Stop(Some(FailureReason::Errored(FailureError::new(e))))
+                }
+            },
+
+            LogicEvent::Cancelled => {
+                self.reader = None;
+
+                Action::Stop(None)
+            }
+
+            LogicEvent::Pushed(())
+            | LogicEvent::Stopped
+            | LogicEvent::Started
+            | LogicEvent::Forwarded(()) => Action::None,
+        }
+    }
+}
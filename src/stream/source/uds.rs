@@ -0,0 +1,209 @@
+use crate::actor::{FailureError, FailureReason, SubscriptionEvent};
+use crate::stream::flow::{self, Flow};
+use crate::stream::{Action, Logic, LogicEvent, StreamContext};
+use mio::{Poll, PollOpt, Ready, Token};
+use mio_uds::{UnixListener, UnixStream};
+use std::io::{self, ErrorKind as IoErrorKind, Result as IoResult};
+use std::os::unix::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+
+/// A connection that has been accepted by a Unix domain socket listener.
+pub struct UdsConnection {
+    stream: UnixStream,
+    local_address: SocketAddr,
+    peer_address: SocketAddr,
+}
+
+impl UdsConnection {
+    pub fn local_address(&self) -> &SocketAddr {
+        &self.local_address
+    }
+
+    pub fn peer_address(&self) -> &SocketAddr {
+        &self.peer_address
+    }
+
+    /// Convert this connection into a flow. Elements pushed into the
+    /// flow are written to the connection, and data read from the
+    /// connection is emitted from it.
+    pub fn flow(self) -> Flow<Vec<u8>, Vec<u8>> {
+        Flow::from_logic(flow::Uds::new(self.stream))
+    }
+}
+
+/// A source that binds a Unix domain stream socket listener to a path,
+/// emitting each connection that it accepts.
+///
+/// The listener is bound when the source is created, so that failing to
+/// bind it can be handled by the caller.
+pub struct UdsBind {
+    listener: UnixListener,
+    ready: bool,
+    pulled: bool,
+    poll: Option<Arc<Poll>>,
+    token: usize,
+}
+
+impl UdsBind {
+    pub fn new<P: AsRef<Path>>(path: P) -> IoResult<Self> {
+        Ok(Self {
+            listener: UnixListener::bind(path)?,
+            ready: false,
+            pulled: false,
+            poll: None,
+            token: 0,
+        })
+    }
+
+    /// The address that the listener is bound to.
+    pub fn local_address(&self) -> IoResult<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    fn try_accept(
+        &mut self,
+        ctx: &mut StreamContext<(), UdsConnection, SubscriptionEvent>,
+    ) -> Action<UdsConnection, SubscriptionEvent> {
+        if !(self.ready && self.pulled) {
+            return Action::None;
+        }
+
+        let accepted = self.listener.accept();
+
+        match accepted {
+            Ok(Some((stream, peer_address))) => match stream.local_addr() {
+                Ok(local_address) => {
+                    self.pulled = false;
+
+                    Action::Push(UdsConnection {
+                        stream,
+                        local_address,
+                        peer_address,
+                    })
+                }
+
+                Err(e) => self.fail(e, ctx),
+            },
+
+            Ok(None) => {
+                self.ready = false;
+
+                Action::None
+            }
+
+            Err(ref e) if e.kind() == IoErrorKind::WouldBlock => {
+                self.ready = false;
+
+                Action::None
+            }
+
+            Err(e) => self.fail(e, ctx),
+        }
+    }
+
+    fn fail(
+        &mut self,
+        error: io::Error,
+        ctx: &mut StreamContext<(), UdsConnection, SubscriptionEvent>,
+    ) -> Action<UdsConnection, SubscriptionEvent> {
+        self.unregister(ctx);
+
+        Action::Stop(Some(FailureReason::Errored(FailureError::new(error))))
+    }
+
+    fn unregister(&mut self, ctx: &mut StreamContext<(), UdsConnection, SubscriptionEvent>) {
+        if let Some(poll) = self.poll.take() {
+            // @TODO expect doesn't seem appropriate
+
+            poll.deregister(&self.listener)
+                .expect("pantomime bug: failed to deregister listener");
+        }
+
+        if self.token > 0 {
+            ctx.unsubscribe(self.token);
+
+            self.token = 0;
+        }
+    }
+}
+
+impl Logic<(), UdsConnection> for UdsBind {
+    type Ctl = SubscriptionEvent;
+
+    fn name(&self) -> &'static str {
+        "pantomime::stream::source::UdsBind"
+    }
+
+    fn buffer_size(&self) -> Option<usize> {
+        Some(0)
+    }
+
+    fn fusible(&self) -> bool {
+        false
+    }
+
+    fn receive(
+        &mut self,
+        msg: LogicEvent<(), Self::Ctl>,
+        ctx: &mut StreamContext<(), UdsConnection, Self::Ctl>,
+    ) -> Action<UdsConnection, Self::Ctl> {
+        match msg {
+            LogicEvent::Started => {
+                let actor_ref = ctx.stage_ref().actor_ref;
+
+                ctx.subscribe(actor_ref);
+
+                Action::None
+            }
+
+            LogicEvent::Forwarded(SubscriptionEvent::Ready(poll, token)) => {
+                let registered = poll.register(
+                    &self.listener,
+                    Token(token),
+                    Ready::readable(),
+                    PollOpt::edge(),
+                );
+
+                self.poll = Some(poll);
+                self.token = token;
+
+                match registered {
+                    Ok(()) => {
+                        // connections may have been queued before registering
+
+                        self.ready = true;
+
+                        self.try_accept(ctx)
+                    }
+
+                    Err(e) => self.fail(e, ctx),
+                }
+            }
+
+            LogicEvent::Forwarded(SubscriptionEvent::MioEvent(event)) => {
+                if event.readiness().is_readable() {
+                    self.ready = true;
+
+                    self.try_accept(ctx)
+                } else {
+                    Action::None
+                }
+            }
+
+            LogicEvent::Pulled => {
+                self.pulled = true;
+
+                self.try_accept(ctx)
+            }
+
+            LogicEvent::Cancelled => {
+                self.unregister(ctx);
+
+                Action::Stop(None)
+            }
+
+            LogicEvent::Pushed(()) | LogicEvent::Stopped => Action::None,
+        }
+    }
+}
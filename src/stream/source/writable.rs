@@ -0,0 +1,218 @@
+use crate::stream::{Action, Logic, LogicEvent, StageRef, StreamContext};
+use parking_lot::{Condvar, Mutex};
+use std::collections::VecDeque;
+use std::io::{self, ErrorKind as IoErrorKind, Write};
+use std::sync::Arc;
+
+pub enum WritableCtl {
+    Available,
+}
+
+struct Shared {
+    buffer: VecDeque<Vec<u8>>,
+    capacity: usize,
+    stage_ref: Option<StageRef<WritableCtl>>,
+    waiting: bool,
+    closed: bool,
+    cancelled: bool,
+}
+
+struct State {
+    shared: Mutex<Shared>,
+    condvar: Condvar,
+}
+
+/// A blocking writer that pushes data into a source created with
+/// `Source::writable`. Dropping it completes the source once the data
+/// that has been written is emitted.
+///
+/// Each write is buffered as a chunk, and writes block while the buffer
+/// is full, so a slow stream backpressures the writer. Writes fail with
+/// `BrokenPipe` once the stream has been cancelled.
+///
+/// Writes block the calling thread, so this should not be written to
+/// from within an actor.
+pub struct SourceWriter {
+    state: Arc<State>,
+}
+
+impl SourceWriter {
+    fn tell_available(shared: &mut Shared) {
+        if shared.waiting {
+            shared.waiting = false;
+
+            if let Some(ref stage_ref) = shared.stage_ref {
+                stage_ref.tell(WritableCtl::Available);
+            }
+        }
+    }
+}
+
+impl Write for SourceWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut shared = self.state.shared.lock();
+
+        while !shared.cancelled && shared.buffer.len() >= shared.capacity {
+            self.state.condvar.wait(&mut shared);
+        }
+
+        if shared.cancelled {
+            return Err(io::Error::from(IoErrorKind::BrokenPipe));
+        }
+
+        if !buf.is_empty() {
+            shared.buffer.push_back(buf.to_vec());
+
+            Self::tell_available(&mut shared);
+        }
+
+        Ok(buf.len())
+    }
+
+    /// Block until all of the data that has been written has been
+    /// emitted by the source.
+    fn flush(&mut self) -> io::Result<()> {
+        let mut shared = self.state.shared.lock();
+
+        while !shared.cancelled && !shared.buffer.is_empty() {
+            self.state.condvar.wait(&mut shared);
+        }
+
+        if shared.cancelled {
+            Err(io::Error::from(IoErrorKind::BrokenPipe))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Drop for SourceWriter {
+    fn drop(&mut self) {
+        let mut shared = self.state.shared.lock();
+
+        shared.closed = true;
+
+        Self::tell_available(&mut shared);
+    }
+}
+
+pub struct Writable {
+    state: Arc<State>,
+    pulled: bool,
+}
+
+impl Writable {
+    pub fn new(capacity: usize) -> (Self, SourceWriter) {
+        let capacity = capacity.max(1);
+
+        let state = Arc::new(State {
+            shared: Mutex::new(Shared {
+                buffer: VecDeque::with_capacity(capacity),
+                capacity,
+                stage_ref: None,
+                waiting: false,
+                closed: false,
+                cancelled: false,
+            }),
+            condvar: Condvar::new(),
+        });
+
+        (
+            Self {
+                state: state.clone(),
+                pulled: false,
+            },
+            SourceWriter { state },
+        )
+    }
+
+    fn try_push(&mut self) -> Action<Vec<u8>, WritableCtl> {
+        if !self.pulled {
+            return Action::None;
+        }
+
+        let mut shared = self.state.shared.lock();
+
+        match shared.buffer.pop_front() {
+            Some(chunk) => {
+                self.pulled = false;
+
+                self.state.condvar.notify_all();
+
+                Action::Push(chunk)
+            }
+
+            None if shared.closed => Action::Stop(None),
+
+            None => {
+                shared.waiting = true;
+
+                Action::None
+            }
+        }
+    }
+}
+
+impl Logic<(), Vec<u8>> for Writable {
+    type Ctl = WritableCtl;
+
+    fn name(&self) -> &'static str {
+        "pantomime::stream::source::Writable"
+    }
+
+    fn buffer_size(&self) -> Option<usize> {
+        Some(0)
+    }
+
+    fn fusible(&self) -> bool {
+        false
+    }
+
+    fn receive(
+        &mut self,
+        msg: LogicEvent<(), Self::Ctl>,
+        ctx: &mut StreamContext<(), Vec<u8>, Self::Ctl>,
+    ) -> Action<Vec<u8>, Self::Ctl> {
+        match msg {
+            LogicEvent::Started => {
+                self.state.shared.lock().stage_ref = Some(ctx.stage_ref());
+
+                Action::None
+            }
+
+            LogicEvent::Pulled => {
+                self.pulled = true;
+
+                self.try_push()
+            }
+
+            LogicEvent::Forwarded(WritableCtl::Available) => self.try_push(),
+
+            LogicEvent::Cancelled => {
+                let mut shared = self.state.shared.lock();
+
+                shared.cancelled = true;
+                shared.buffer.clear();
+
+                self.state.condvar.notify_all();
+
+                Action::Stop(None)
+            }
+
+            LogicEvent::Pushed(()) | LogicEvent::Stopped => Action::None,
+        }
+    }
+}
+
+impl Drop for Writable {
+    fn drop(&mut self) {
+        // unblock any writers if the stream has stopped
+
+        let mut shared = self.state.shared.lock();
+
+        shared.cancelled = true;
+        shared.stage_ref = None;
+
+        self.state.condvar.notify_all();
+    }
+}
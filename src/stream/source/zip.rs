@@ -0,0 +1,288 @@
+use crate::actor::FailureReason;
+use crate::stream::{FanInCompletion, LogicPortEvent, PortAction, PortContext, PortLogic};
+
+/// The elements that are pushed into a zip stage, tagged with the input
+/// that they came from. The left input is inlet 0 and the right is inlet 1.
+pub enum ZipInput<A, B> {
+    Left(A),
+    Right(B),
+}
+
+const LEFT: usize = 0;
+const RIGHT: usize = 1;
+
+fn cancel_inlets<C>(stopped: &mut [bool; 2], ctx: &mut PortContext<C>) {
+    for (inlet, stopped) in stopped.iter_mut().enumerate() {
+        if !*stopped {
+            *stopped = true;
+
+            ctx.tell(inlet, PortAction::Cancel);
+        }
+    }
+}
+
+/// Combines an element from each of its inputs with the supplied function,
+/// emitting the result. Once either input completes (and its last element
+/// has been combined), the other is cancelled and the stage completes.
+///
+/// If either input fails, the other is cancelled and the stage fails.
+pub struct ZipWith<A, B, F> {
+    zip_fn: F,
+    left: Option<A>,
+    right: Option<B>,
+    stopped: [bool; 2],
+    pulled: bool,
+    completed: bool,
+}
+
+impl<A, B, F> ZipWith<A, B, F> {
+    pub fn new<C>(zip_fn: F) -> Self
+    where
+        F: FnMut(A, B) -> C,
+    {
+        Self {
+            zip_fn,
+            left: None,
+            right: None,
+            stopped: [false; 2],
+            pulled: false,
+            completed: false,
+        }
+    }
+
+    fn complete<C>(&mut self, reason: Option<FailureReason>, ctx: &mut PortContext<C>) {
+        cancel_inlets(&mut self.stopped, ctx);
+
+        self.completed = true;
+
+        ctx.tell(0, PortAction::Complete(reason));
+    }
+
+    fn try_push<C>(&mut self, ctx: &mut PortContext<C>)
+    where
+        F: FnMut(A, B) -> C,
+    {
+        if self.pulled && self.left.is_some() && self.right.is_some() {
+            if let (Some(left), Some(right)) = (self.left.take(), self.right.take()) {
+                self.pulled = false;
+
+                ctx.tell(0, PortAction::Push((self.zip_fn)(left, right)));
+
+                for inlet in &[LEFT, RIGHT] {
+                    if !self.stopped[*inlet] {
+                        ctx.tell(*inlet, PortAction::Pull);
+                    }
+                }
+            }
+        }
+
+        // once an input has stopped and there's nothing left to pair its
+        // last element with, no more elements can be emitted
+
+        if (self.stopped[LEFT] && self.left.is_none())
+            || (self.stopped[RIGHT] && self.right.is_none())
+        {
+            self.complete(None, ctx);
+        }
+    }
+}
+
+impl<A, B, C, F: FnMut(A, B) -> C> PortLogic<ZipInput<A, B>, C> for ZipWith<A, B, F>
+where
+    A: 'static + Send,
+    B: 'static + Send,
+    C: 'static + Send,
+    F: 'static + Send,
+{
+    fn name(&self) -> &'static str {
+        "pantomime::stream::source::ZipWith"
+    }
+
+    fn inlets(&self) -> usize {
+        2
+    }
+
+    fn outlets(&self) -> usize {
+        1
+    }
+
+    fn receive(&mut self, event: LogicPortEvent<ZipInput<A, B>>, ctx: &mut PortContext<C>) {
+        if self.completed {
+            return;
+        }
+
+        match event {
+            LogicPortEvent::Started(inlet) => {
+                ctx.tell(inlet, PortAction::Pull);
+            }
+
+            LogicPortEvent::Pulled(_) => {
+                self.pulled = true;
+
+                self.try_push(ctx);
+            }
+
+            LogicPortEvent::Pushed(_, ZipInput::Left(left)) => {
+                self.left = Some(left);
+
+                self.try_push(ctx);
+            }
+
+            LogicPortEvent::Pushed(_, ZipInput::Right(right)) => {
+                self.right = Some(right);
+
+                self.try_push(ctx);
+            }
+
+            LogicPortEvent::Stopped(inlet) => {
+                self.stopped[inlet] = true;
+
+                self.try_push(ctx);
+            }
+
+            LogicPortEvent::Failed(inlet, reason) => {
+                self.stopped[inlet] = true;
+
+                self.complete(Some(reason), ctx);
+            }
+
+            LogicPortEvent::Cancelled(_) => {
+                self.complete(None, ctx);
+            }
+        }
+    }
+}
+
+/// Emits the latest element of each of its inputs whenever either of them
+/// emits, once both have emitted at least once. Inputs are always pulled,
+/// so elements that arrive while downstream isn't pulling replace the ones
+/// that haven't been emitted yet.
+///
+/// Depending on its completion, the stage completes once either or both of
+/// its inputs have completed. It always completes if an input completes
+/// without having emitted anything.
+///
+/// If either input fails, the other is cancelled and the stage fails.
+pub struct ZipLatest<A, B> {
+    completion: FanInCompletion,
+    left: Option<A>,
+    right: Option<B>,
+    stopped: [bool; 2],
+    changed: bool,
+    pulled: bool,
+    completed: bool,
+}
+
+impl<A, B> ZipLatest<A, B>
+where
+    A: Clone,
+    B: Clone,
+{
+    pub fn new(completion: FanInCompletion) -> Self {
+        Self {
+            completion,
+            left: None,
+            right: None,
+            stopped: [false; 2],
+            changed: false,
+            pulled: false,
+            completed: false,
+        }
+    }
+
+    fn complete(&mut self, reason: Option<FailureReason>, ctx: &mut PortContext<(A, B)>) {
+        cancel_inlets(&mut self.stopped, ctx);
+
+        self.completed = true;
+
+        ctx.tell(0, PortAction::Complete(reason));
+    }
+
+    fn try_push(&mut self, ctx: &mut PortContext<(A, B)>) {
+        if self.pulled && self.changed {
+            if let (Some(left), Some(right)) = (&self.left, &self.right) {
+                self.pulled = false;
+                self.changed = false;
+
+                ctx.tell(0, PortAction::Push((left.clone(), right.clone())));
+            }
+        }
+
+        let finished = match self.completion {
+            FanInCompletion::Eager => self.stopped[LEFT] || self.stopped[RIGHT],
+            FanInCompletion::Lazy => self.stopped[LEFT] && self.stopped[RIGHT],
+        };
+
+        let starved = (self.stopped[LEFT] && self.left.is_none())
+            || (self.stopped[RIGHT] && self.right.is_none());
+
+        if starved || (finished && !(self.changed && self.left.is_some() && self.right.is_some())) {
+            self.complete(None, ctx);
+        }
+    }
+}
+
+impl<A, B> PortLogic<ZipInput<A, B>, (A, B)> for ZipLatest<A, B>
+where
+    A: 'static + Clone + Send,
+    B: 'static + Clone + Send,
+{
+    fn name(&self) -> &'static str {
+        "pantomime::stream::source::ZipLatest"
+    }
+
+    fn inlets(&self) -> usize {
+        2
+    }
+
+    fn outlets(&self) -> usize {
+        1
+    }
+
+    fn receive(&mut self, event: LogicPortEvent<ZipInput<A, B>>, ctx: &mut PortContext<(A, B)>) {
+        if self.completed {
+            return;
+        }
+
+        match event {
+            LogicPortEvent::Started(inlet) => {
+                ctx.tell(inlet, PortAction::Pull);
+            }
+
+            LogicPortEvent::Pulled(_) => {
+                self.pulled = true;
+
+                self.try_push(ctx);
+            }
+
+            LogicPortEvent::Pushed(inlet, element) => {
+                match element {
+                    ZipInput::Left(left) => self.left = Some(left),
+                    ZipInput::Right(right) => self.right = Some(right),
+                }
+
+                self.changed = true;
+
+                ctx.tell(inlet, PortAction::Pull);
+
+                self.try_push(ctx);
+            }
+
+            LogicPortEvent::Stopped(inlet) => {
+                self.stopped[inlet] = true;
+
+                self.try_push(ctx);
+            }
+
+            LogicPortEvent::Failed(inlet, reason) => {
+                self.stopped[inlet] = true;
+
+                self.complete(Some(reason), ctx);
+            }
+
+            LogicPortEvent::Cancelled(_) => {
+                self.complete(None, ctx);
+            }
+        }
+    }
+}
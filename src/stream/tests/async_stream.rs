@@ -0,0 +1,342 @@
+use super::{assert_stream_fails, expect_value, Failing};
+use crate::actor::*;
+use crate::stream::{Flow, Sink, Source};
+use futures_core::Stream;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::thread;
+use std::time::Duration;
+
+/// Emits the numbers up to `max`, becoming ready for each one after
+/// being woken from another thread. Tracks the number of polls.
+struct Ticks {
+    next: usize,
+    max: usize,
+    ready: bool,
+    polls: Arc<AtomicUsize>,
+}
+
+impl Stream for Ticks {
+    type Item = usize;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<usize>> {
+        self.polls.fetch_add(1, Ordering::SeqCst);
+
+        if self.next > self.max {
+            Poll::Ready(None)
+        } else if self.ready {
+            self.ready = false;
+            self.next += 1;
+
+            Poll::Ready(Some(self.next - 1))
+        } else {
+            self.ready = true;
+
+            let waker = cx.waker().clone();
+
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(1));
+
+                waker.wake();
+            });
+
+            Poll::Pending
+        }
+    }
+}
+
+/// Collects all of the elements of a stream.
+struct Collect<S: Stream> {
+    stream: S,
+    elements: Vec<S::Item>,
+}
+
+impl<S: Stream + Unpin> Future for Collect<S>
+where
+    S::Item: Unpin,
+{
+    type Output = Vec<S::Item>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Vec<S::Item>> {
+        loop {
+            match Pin::new(&mut self.stream).poll_next(cx) {
+                Poll::Ready(Some(element)) => {
+                    self.elements.push(element);
+                }
+
+                Poll::Ready(None) => {
+                    return Poll::Ready(self.elements.drain(..).collect());
+                }
+
+                Poll::Pending => {
+                    return Poll::Pending;
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_from_async_stream() {
+    struct TestReaper {
+        polls: Arc<AtomicUsize>,
+    }
+
+    impl Actor for TestReaper {
+        type Msg = Vec<usize>;
+
+        fn receive(&mut self, msg: Vec<usize>, ctx: &mut ActorContext<Vec<usize>>) {
+            assert_eq!(msg, vec![1, 2, 3, 4, 5]);
+
+            // each element takes two polls, and one more to observe the end
+
+            assert_eq!(self.polls.load(Ordering::SeqCst), 11);
+
+            ctx.stop();
+        }
+
+        fn receive_signal(&mut self, signal: Signal, ctx: &mut ActorContext<Vec<usize>>) {
+            if let Signal::Started = signal {
+                let (_, result) = ctx.spawn(
+                    Source::from_async_stream(Ticks {
+                        next: 1,
+                        max: 5,
+                        ready: false,
+                        polls: self.polls.clone(),
+                    })
+                    .to(Sink::collect()),
+                );
+
+                ctx.watch_result(result, expect_value);
+            }
+        }
+    }
+
+    assert!(ActorSystem::new()
+        .spawn(TestReaper {
+            polls: Arc::new(AtomicUsize::new(0))
+        })
+        .is_ok());
+}
+
+#[test]
+fn test_sink_async_stream() {
+    enum TestReaperMsg {
+        Collected(Vec<usize>),
+        Completed,
+    }
+
+    struct TestReaper {
+        collected: bool,
+        completed: bool,
+    }
+
+    impl Actor for TestReaper {
+        type Msg = TestReaperMsg;
+
+        fn receive(&mut self, msg: TestReaperMsg, ctx: &mut ActorContext<TestReaperMsg>) {
+            match msg {
+                TestReaperMsg::Collected(elements) => {
+                    assert_eq!(elements, (1..=100).collect::<Vec<_>>());
+
+                    self.collected = true;
+                }
+
+                TestReaperMsg::Completed => {
+                    self.completed = true;
+                }
+            }
+
+            if self.collected && self.completed {
+                ctx.stop();
+            }
+        }
+
+        fn receive_signal(&mut self, signal: Signal, ctx: &mut ActorContext<TestReaperMsg>) {
+            if let Signal::Started = signal {
+                let (sink, stream) = Sink::async_stream(4);
+
+                let (_, result) = ctx.spawn(Source::iterator(1..=100).to(sink));
+
+                ctx.pipe_to_self(
+                    Collect {
+                        stream,
+                        elements: Vec::new(),
+                    },
+                    |elements| TestReaperMsg::Collected(elements.unwrap_or_default()),
+                );
+
+                ctx.pipe_to_self(result, |_| TestReaperMsg::Completed);
+            }
+        }
+    }
+
+    assert!(ActorSystem::new()
+        .spawn(TestReaper {
+            collected: false,
+            completed: false
+        })
+        .is_ok());
+}
+
+#[test]
+fn test_stream_complete() {
+    struct TestReaper;
+
+    impl Actor for TestReaper {
+        type Msg = Vec<usize>;
+
+        fn receive(&mut self, values: Vec<usize>, ctx: &mut ActorContext<Vec<usize>>) {
+            assert_eq!(values, vec![2, 4, 6]);
+
+            ctx.stop();
+        }
+
+        fn receive_signal(&mut self, signal: Signal, ctx: &mut ActorContext<Vec<usize>>) {
+            if let Signal::Started = signal {
+                let (_, result) = ctx.spawn(
+                    Source::iterator(1..=3)
+                        .via(Flow::new().map(|n: usize| n * 2))
+                        .to(Sink::collect()),
+                );
+
+                ctx.pipe_to_self(result, |result| match result {
+                    Ok(Ok(values)) => values,
+                    _ => panic!("stream failed"),
+                });
+            }
+        }
+    }
+
+    assert!(ActorSystem::new().spawn(TestReaper).is_ok());
+}
+
+#[test]
+fn test_stream_complete_failure() {
+    // the failure passes through the fused flow and the sink, neither
+    // of which fail themselves
+
+    assert_stream_fails(
+        Source::new(Failing)
+            .via(Flow::new().map(|n: usize| n * 2))
+            .to(Sink::collect()),
+    );
+
+    assert_stream_fails(
+        Source::new(Failing)
+            .via(Flow::new().map(|n: usize| n * 2).filter(|n: &usize| *n > 0))
+            .to(Sink::last()),
+    );
+}
+
+/// Spawns a stream, asserting that its `StreamComplete` resolves with a
+/// failure, either by watching it or by awaiting it.
+struct FailureReaper {
+    stream: Option<crate::stream::Stream<Option<usize>>>,
+    stop: bool,
+    watch: bool,
+}
+
+impl Actor for FailureReaper {
+    type Msg = bool;
+
+    fn receive(&mut self, failed: bool, ctx: &mut ActorContext<bool>) {
+        assert!(failed, "stream did not fail");
+
+        ctx.stop();
+    }
+
+    fn receive_signal(&mut self, signal: Signal, ctx: &mut ActorContext<bool>) {
+        if let Signal::Started = signal {
+            let (stream_ref, result) = ctx.spawn(self.stream.take().unwrap());
+
+            if self.watch {
+                ctx.watch_result(result, |result| result.is_err());
+            } else {
+                ctx.pipe_to_self(result, |result| matches!(result, Ok(Err(_))));
+            }
+
+            if self.stop {
+                stream_ref.stop();
+            }
+        }
+    }
+}
+
+#[test]
+fn test_stream_complete_without_value() {
+    // a stream that is stopped before its sink produces a value must
+    // resolve rather than pend forever or panic its watcher
+
+    for watch in &[false, true] {
+        assert!(ActorSystem::new()
+            .spawn(FailureReaper {
+                stream: Some(Source::repeat(1).to(Sink::last())),
+                stop: true,
+                watch: *watch,
+            })
+            .is_ok());
+    }
+}
+
+#[test]
+fn test_watch_stream_complete_failure() {
+    // the sink produces the last element it received before the
+    // failure, but the watcher sees the failure
+
+    assert!(ActorSystem::new()
+        .spawn(FailureReaper {
+            stream: Some(Source::new(Failing).to(Sink::last())),
+            stop: false,
+            watch: true,
+        })
+        .is_ok());
+
+    assert!(ActorSystem::new()
+        .spawn(FailureReaper {
+            stream: Some(
+                Source::iterator(1..=3)
+                    .concat(Source::new(Failing))
+                    .to(Sink::last()),
+            ),
+            stop: false,
+            watch: true,
+        })
+        .is_ok());
+}
+
+#[test]
+fn test_watch_stream_complete_value() {
+    // watching a stream's value delivers nothing if it fails, even if
+    // its sink produced a value beforehand
+
+    struct ValueReaper;
+
+    impl Actor for ValueReaper {
+        type Msg = bool;
+
+        fn receive(&mut self, stopped: bool, ctx: &mut ActorContext<bool>) {
+            assert!(stopped, "failed stream delivered a value");
+
+            ctx.stop();
+        }
+
+        fn receive_signal(&mut self, signal: Signal, ctx: &mut ActorContext<bool>) {
+            if let Signal::Started = signal {
+                let (stream_ref, result) = ctx.spawn(
+                    Source::iterator(1..=3)
+                        .concat(Source::new(Failing))
+                        .to(Sink::last()),
+                );
+
+                ctx.watch(result, |_: Option<usize>| false);
+                ctx.watch(stream_ref, |_: StopReason| true);
+            }
+        }
+    }
+
+    assert!(ActorSystem::new().spawn(ValueReaper).is_ok());
+}
@@ -0,0 +1,194 @@
+use super::assert_stream;
+use crate::stream::{Action, Flow, Logic, LogicEvent, Sink, Source, StreamContext};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::vec;
+
+/// A source that emits the supplied elements, recording when it starts
+/// and when it completes.
+///
+/// If it should only start once another source has completed, it is
+/// only considered to have started if that one had completed by then.
+struct Recording {
+    elements: vec::IntoIter<usize>,
+    started: Arc<AtomicBool>,
+    completed: Arc<AtomicBool>,
+    after: Option<Arc<AtomicBool>>,
+}
+
+impl Recording {
+    fn new(elements: Vec<usize>) -> Self {
+        Self {
+            elements: elements.into_iter(),
+            started: Arc::new(AtomicBool::new(false)),
+            completed: Arc::new(AtomicBool::new(false)),
+            after: None,
+        }
+    }
+
+    fn after(mut self, other: &Recording) -> Self {
+        self.after = Some(other.completed.clone());
+        self
+    }
+}
+
+impl Logic<(), usize> for Recording {
+    type Ctl = ();
+
+    fn name(&self) -> &'static str {
+        "Recording"
+    }
+
+    fn receive(
+        &mut self,
+        msg: LogicEvent<(), ()>,
+        _: &mut StreamContext<(), usize, ()>,
+    ) -> Action<usize, ()> {
+        match msg {
+            LogicEvent::Started => {
+                let in_order = self
+                    .after
+                    .as_ref()
+                    .is_none_or(|after| after.load(Ordering::SeqCst));
+
+                self.started.store(in_order, Ordering::SeqCst);
+
+                Action::None
+            }
+
+            LogicEvent::Pulled => match self.elements.next() {
+                Some(element) => Action::Push(element),
+
+                None => {
+                    self.completed.store(true, Ordering::SeqCst);
+
+                    Action::Stop(None)
+                }
+            },
+
+            LogicEvent::Cancelled => Action::Stop(None),
+
+            LogicEvent::Pushed(()) | LogicEvent::Stopped | LogicEvent::Forwarded(()) => {
+                Action::None
+            }
+        }
+    }
+}
+
+#[test]
+fn test_concat() {
+    let source = Source::iterator(1..=3)
+        .concat(Source::iterator(4..=5))
+        .concat(Source::single(6));
+
+    assert_stream(source.to(Sink::collect()), |values: Vec<usize>| {
+        assert_eq!(values, vec![1, 2, 3, 4, 5, 6]);
+    });
+}
+
+#[test]
+fn test_concat_lazy() {
+    // the second source isn't started until the first has completed
+
+    let first = Recording::new(vec![1, 2, 3]);
+    let second = Recording::new(vec![4, 5]).after(&first);
+
+    let started = second.started.clone();
+
+    let source = Source::new(first).concat(Source::new(second));
+
+    assert_stream(source.to(Sink::collect()), |values: Vec<usize>| {
+        assert_eq!(values, vec![1, 2, 3, 4, 5]);
+    });
+
+    assert!(started.load(Ordering::SeqCst));
+}
+
+#[test]
+fn test_prepend() {
+    let source = Source::iterator(4..=5).prepend(Source::iterator(1..=3));
+
+    assert_stream(source.to(Sink::collect()), |values: Vec<usize>| {
+        assert_eq!(values, vec![1, 2, 3, 4, 5]);
+    });
+}
+
+#[test]
+fn test_or_else() {
+    let primary = Recording::new(Vec::new());
+    let fallback = Recording::new(vec![1, 2, 3]).after(&primary);
+
+    let started = fallback.started.clone();
+
+    let source = Source::new(primary).or_else(Source::new(fallback));
+
+    assert_stream(source.to(Sink::collect()), |values: Vec<usize>| {
+        assert_eq!(values, vec![1, 2, 3]);
+    });
+
+    assert!(started.load(Ordering::SeqCst));
+}
+
+#[test]
+fn test_or_else_non_empty() {
+    // the fallback never completes, so this completes because
+    // it was never materialized
+
+    let source = Source::iterator(1..=3).or_else(Source::repeat(0));
+
+    assert_stream(source.to(Sink::collect()), |values: Vec<usize>| {
+        assert_eq!(values, vec![1, 2, 3]);
+    });
+
+    let fallback = Recording::new(vec![0]);
+
+    let started = fallback.started.clone();
+
+    let source = Source::iterator(1..=3).or_else(Source::new(fallback));
+
+    assert_stream(source.to(Sink::collect()), |values: Vec<usize>| {
+        assert_eq!(values, vec![1, 2, 3]);
+    });
+
+    assert!(!started.load(Ordering::SeqCst));
+}
+
+#[test]
+fn test_concat_flow() {
+    let flow = Flow::new()
+        .map(|n: usize| n * 2)
+        .concat(Source::iterator(7..=8));
+
+    assert_stream(
+        Source::iterator(1..=3).via(flow).to(Sink::collect()),
+        |values: Vec<usize>| {
+            assert_eq!(values, vec![2, 4, 6, 7, 8]);
+        },
+    );
+}
+
+#[test]
+fn test_prepend_flow() {
+    let flow = Flow::new().prepend(Source::iterator(1..=2));
+
+    assert_stream(
+        Source::iterator(3..=5).via(flow).to(Sink::collect()),
+        |values: Vec<usize>| {
+            assert_eq!(values, vec![1, 2, 3, 4, 5]);
+        },
+    );
+}
+
+#[test]
+fn test_or_else_flow() {
+    let flow = Flow::new()
+        .filter(|n: &usize| *n > 10)
+        .or_else(Source::single(0));
+
+    assert_stream(
+        Source::iterator(1..=3).via(flow).to(Sink::collect()),
+        |values: Vec<usize>| {
+            assert_eq!(values, vec![0]);
+        },
+    );
+}
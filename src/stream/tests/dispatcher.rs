@@ -0,0 +1,122 @@
+use crate::stream::{Action, Logic, LogicEvent, StreamContext};
+use std::thread::{self, ThreadId};
+
+/// Doubles each element, pairing it with the thread that the stage
+/// executed on.
+struct Double {
+    dispatcher: &'static str,
+}
+
+impl Logic<usize, (usize, ThreadId)> for Double {
+    type Ctl = ();
+
+    fn name(&self) -> &'static str {
+        "Double"
+    }
+
+    fn dispatcher(&self) -> Option<&str> {
+        Some(self.dispatcher)
+    }
+
+    fn receive(
+        &mut self,
+        msg: LogicEvent<usize, Self::Ctl>,
+        _: &mut StreamContext<usize, (usize, ThreadId), Self::Ctl>,
+    ) -> Action<(usize, ThreadId), Self::Ctl> {
+        match msg {
+            LogicEvent::Pulled => Action::Pull,
+            LogicEvent::Pushed(element) => Action::Push((element * 2, thread::current().id())),
+            LogicEvent::Cancelled => Action::Cancel,
+            LogicEvent::Stopped => Action::Stop(None),
+            LogicEvent::Started => Action::None,
+            LogicEvent::Forwarded(()) => Action::None,
+        }
+    }
+}
+
+#[test]
+fn test() {
+    use crate::actor::*;
+    use crate::cfg::Config;
+    use crate::stream::{Flow, Sink, Source};
+
+    enum TestMsg {
+        Probed(ThreadId),
+        Doubled(&'static str, usize, ThreadId),
+    }
+
+    struct TestReaper {
+        probe: Option<ThreadId>,
+        elements: Vec<(&'static str, usize, ThreadId)>,
+    }
+
+    impl Actor for TestReaper {
+        type Msg = TestMsg;
+
+        fn receive(&mut self, msg: Self::Msg, ctx: &mut ActorContext<Self::Msg>) {
+            match msg {
+                TestMsg::Probed(thread_id) => {
+                    self.probe = Some(thread_id);
+                }
+
+                TestMsg::Doubled(dispatcher, n, thread_id) => {
+                    self.elements.push((dispatcher, n, thread_id));
+                }
+            }
+
+            if let Some(probe) = self.probe {
+                if self.elements.len() == 6 {
+                    let sum: usize = self.elements.iter().map(|(_, n, _)| n).sum();
+
+                    assert_eq!(sum, 24);
+
+                    // the blocking-io dispatcher has a single thread, and the
+                    // stage that refers to an unknown dispatcher runs elsewhere
+
+                    for (dispatcher, _, thread_id) in &self.elements {
+                        assert_eq!(*dispatcher == "blocking-io", *thread_id == probe);
+                    }
+
+                    ctx.stop();
+                }
+            }
+        }
+
+        fn receive_signal(&mut self, signal: Signal, ctx: &mut ActorContext<Self::Msg>) {
+            if let Signal::Started = signal {
+                let actor_ref = ctx.actor_ref().clone();
+
+                ctx.system_context()
+                    .named_dispatcher("blocking-io")
+                    .expect("named dispatcher is missing")
+                    .execute(move || actor_ref.tell(TestMsg::Probed(thread::current().id())));
+
+                // the second stage refers to a dispatcher that doesn't exist,
+                // so it should fallback to the default dispatcher
+
+                for dispatcher in &["blocking-io", "unknown"] {
+                    let actor_ref = ctx.actor_ref().clone();
+
+                    ctx.spawn(
+                        Source::iterator(1..=3)
+                            .via(Flow::from_logic(Double { dispatcher }))
+                            .to(Sink::for_each(move |(n, thread_id)| {
+                                actor_ref.tell(TestMsg::Doubled(dispatcher, n, thread_id))
+                            })),
+                    );
+                }
+            }
+        }
+    }
+
+    assert!(ActorSystem::new()
+        .with_config(&Config::new(&[
+            ("PANTOMIME_DISPATCHERS", "blocking-io"),
+            ("PANTOMIME_DISPATCHER_BLOCKING_IO_LOGIC", "single-threaded"),
+        ]))
+        .spawn(TestReaper {
+            probe: None,
+            elements: Vec::new(),
+        })
+        .is_ok());
+}
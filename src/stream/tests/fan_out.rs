@@ -0,0 +1,188 @@
+use super::{assert_stream, assert_stream_fails, Failing};
+use crate::stream::{Action, FanOutCancellation, Logic, LogicEvent, Sink, Source, StreamContext};
+use crate::testkit::eventually;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A sink that counts the times that upstream failed, and outputs
+/// nothing if it is told to stop early.
+struct Recording {
+    failures: Arc<AtomicUsize>,
+    stop_early: bool,
+}
+
+impl Logic<usize, Option<usize>> for Recording {
+    type Ctl = ();
+
+    fn name(&self) -> &'static str {
+        "Recording"
+    }
+
+    fn receive(
+        &mut self,
+        msg: LogicEvent<usize, ()>,
+        ctx: &mut StreamContext<usize, Option<usize>, ()>,
+    ) -> Action<Option<usize>, ()> {
+        match msg {
+            LogicEvent::Started if self.stop_early => Action::Stop(None),
+
+            LogicEvent::Started | LogicEvent::Pushed(_) => Action::Pull,
+
+            LogicEvent::Stopped => {
+                if ctx.upstream_failure().is_some() {
+                    self.failures.fetch_add(1, Ordering::SeqCst);
+                }
+
+                Action::Stop(None)
+            }
+
+            LogicEvent::Cancelled => Action::Cancel,
+
+            LogicEvent::Pulled | LogicEvent::Forwarded(()) => Action::None,
+        }
+    }
+}
+
+#[test]
+fn test_broadcast() {
+    let sink = Sink::broadcast(
+        vec![Sink::collect(), Sink::collect()],
+        FanOutCancellation::Lazy,
+    );
+
+    assert_stream(
+        Source::iterator(1..=5).to(sink),
+        |values: Vec<Vec<usize>>| {
+            assert_eq!(values, vec![vec![1, 2, 3, 4, 5], vec![1, 2, 3, 4, 5]]);
+        },
+    );
+}
+
+#[test]
+fn test_broadcast_lazy() {
+    // the first sink cancels after one element, but the other still
+    // receives all of them
+
+    let sink = Sink::broadcast(vec![Sink::first(), Sink::last()], FanOutCancellation::Lazy);
+
+    assert_stream(
+        Source::iterator(1..=5).to(sink),
+        |values: Vec<Option<usize>>| {
+            assert_eq!(values, vec![Some(1), Some(5)]);
+        },
+    );
+}
+
+#[test]
+fn test_broadcast_eager() {
+    // the source never completes, so this completes because
+    // the first sink cancelled
+
+    let sink = Sink::broadcast(vec![Sink::first(), Sink::last()], FanOutCancellation::Eager);
+
+    assert_stream(
+        Source::iterator(1..).to(sink),
+        |values: Vec<Option<usize>>| {
+            assert_eq!(values[0], Some(1));
+            assert!(values[1].is_some());
+        },
+    );
+}
+
+#[test]
+fn test_balance() {
+    let sink = Sink::balance(
+        vec![Sink::collect(), Sink::collect(), Sink::collect()],
+        FanOutCancellation::Lazy,
+    );
+
+    assert_stream(
+        Source::iterator(1..=100).to(sink),
+        |values: Vec<Vec<usize>>| {
+            assert_eq!(values.len(), 3);
+
+            let mut all = values.into_iter().flatten().collect::<Vec<_>>();
+
+            all.sort();
+
+            assert_eq!(all, (1..=100).collect::<Vec<_>>());
+        },
+    );
+}
+
+#[test]
+fn test_partition() {
+    let sink = Sink::partition(
+        vec![Sink::collect(), Sink::collect()],
+        |n: &usize| n % 2,
+        FanOutCancellation::Lazy,
+    );
+
+    assert_stream(
+        Source::iterator(1..=6).to(sink),
+        |values: Vec<Vec<usize>>| {
+            assert_eq!(values, vec![vec![2, 4, 6], vec![1, 3, 5]]);
+        },
+    );
+}
+
+#[test]
+fn test_partition_cancelled() {
+    // elements destined for the cancelled sink are discarded
+
+    let sink = Sink::partition(
+        vec![Sink::first(), Sink::last()],
+        |n: &usize| if *n < 5 { 0 } else { 1 },
+        FanOutCancellation::Lazy,
+    );
+
+    assert_stream(
+        Source::iterator(1..=6).to(sink),
+        |values: Vec<Option<usize>>| {
+            assert_eq!(values, vec![Some(1), Some(6)]);
+        },
+    );
+}
+
+#[test]
+fn test_broadcast_failure() {
+    // every sink observes the failure, not just the first
+
+    let failures = Arc::new(AtomicUsize::new(0));
+
+    let sinks = (0..3).map(|_| {
+        Sink::new(Recording {
+            failures: failures.clone(),
+            stop_early: false,
+        })
+    });
+
+    assert_stream_fails(Source::new(Failing).to(Sink::broadcast(sinks, FanOutCancellation::Lazy)));
+
+    // the stream fails as soon as one of the sinks has, so the others
+    // may still be stopping
+
+    eventually(Duration::from_millis(3000), || {
+        failures.load(Ordering::SeqCst) == 3
+    });
+}
+
+#[test]
+fn test_broadcast_missing_output() {
+    // the first sink stops without producing its output, so there are
+    // no values to emit in its place
+
+    let sink = Sink::broadcast(
+        vec![
+            Sink::new(Recording {
+                failures: Arc::new(AtomicUsize::new(0)),
+                stop_early: true,
+            }),
+            Sink::last(),
+        ],
+        FanOutCancellation::Lazy,
+    );
+
+    assert_stream_fails(Source::iterator(1..=5).to(sink));
+}
@@ -0,0 +1,146 @@
+use super::expect_value;
+use crate::actor::*;
+use crate::stream::file::{FileOptions, FileWriteMode};
+use crate::stream::{Sink, Source};
+use std::path::PathBuf;
+use std::{fs, io, process};
+
+fn file_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("pantomime-{}-{}", process::id(), name));
+
+    let _ = fs::remove_file(&path);
+
+    path
+}
+
+#[test]
+fn test_file() {
+    enum TestReaperMsg {
+        Written(io::Result<u64>),
+        Appended(io::Result<u64>),
+        Read(Vec<Vec<u8>>),
+    }
+
+    struct TestReaper {
+        path: PathBuf,
+    }
+
+    impl Actor for TestReaper {
+        type Msg = TestReaperMsg;
+
+        fn receive(&mut self, msg: TestReaperMsg, ctx: &mut ActorContext<TestReaperMsg>) {
+            match msg {
+                TestReaperMsg::Written(result) => {
+                    assert_eq!(result.unwrap(), 11);
+
+                    let options = FileOptions::new()
+                        .with_mode(FileWriteMode::Append)
+                        .with_sync(true);
+
+                    let (_, result) = ctx.spawn(
+                        Source::single(b"!".to_vec()).to(Sink::to_file(&self.path, options)),
+                    );
+
+                    ctx.watch_result(result, |result| {
+                        TestReaperMsg::Appended(expect_value(result))
+                    });
+                }
+
+                TestReaperMsg::Appended(result) => {
+                    assert_eq!(result.unwrap(), 1);
+
+                    let (_, result) =
+                        ctx.spawn(Source::from_file(&self.path, 4).to(Sink::collect()));
+
+                    ctx.watch_result(result, |result| TestReaperMsg::Read(expect_value(result)));
+                }
+
+                TestReaperMsg::Read(chunks) => {
+                    assert!(chunks.iter().all(|c| !c.is_empty() && c.len() <= 4));
+                    assert_eq!(chunks.concat(), b"hello world!".to_vec());
+
+                    let _ = fs::remove_file(&self.path);
+
+                    ctx.stop();
+                }
+            }
+        }
+
+        fn receive_signal(&mut self, signal: Signal, ctx: &mut ActorContext<TestReaperMsg>) {
+            if let Signal::Started = signal {
+                fs::write(&self.path, b"truncated").unwrap();
+
+                let (_, result) = ctx.spawn(
+                    Source::iterator(vec![b"hello ".to_vec(), b"world".to_vec()].into_iter())
+                        .to(Sink::to_file(&self.path, FileOptions::new())),
+                );
+
+                ctx.watch_result(result, |result| {
+                    TestReaperMsg::Written(expect_value(result))
+                });
+            }
+        }
+    }
+
+    assert!(ActorSystem::new()
+        .spawn(TestReaper {
+            path: file_path("file"),
+        })
+        .is_ok());
+}
+
+#[test]
+fn test_file_errors() {
+    enum TestReaperMsg {
+        Read(Result<Vec<Vec<u8>>, FailureReason>),
+        Written(io::Result<u64>),
+    }
+
+    struct TestReaper;
+
+    impl Actor for TestReaper {
+        type Msg = TestReaperMsg;
+
+        fn receive(&mut self, msg: TestReaperMsg, ctx: &mut ActorContext<TestReaperMsg>) {
+            match msg {
+                TestReaperMsg::Read(result) => {
+                    assert!(result.is_err());
+
+                    // a directory cannot be opened for writing
+
+                    let (_, result) = ctx.spawn(
+                        Source::single(b"hello".to_vec())
+                            .to(Sink::to_file(std::env::temp_dir(), FileOptions::new())),
+                    );
+
+                    ctx.watch_result(result, |result| {
+                        TestReaperMsg::Written(expect_value(result))
+                    });
+                }
+
+                TestReaperMsg::Written(result) => {
+                    assert!(result.is_err());
+
+                    ctx.stop();
+                }
+            }
+        }
+
+        fn receive_signal(&mut self, signal: Signal, ctx: &mut ActorContext<TestReaperMsg>) {
+            if let Signal::Started = signal {
+                let (_, result) =
+                    ctx.spawn(Source::from_file(file_path("missing"), 1024).to(Sink::collect()));
+
+                ctx.watch_result(result, TestReaperMsg::Read);
+            }
+        }
+    }
+
+    assert!(ActorSystem::new().spawn(TestReaper).is_ok());
+}
+
+#[test]
+#[should_panic(expected = "chunk_size must be greater than 0")]
+fn test_file_empty_chunk_size() {
+    let _ = Source::from_file(file_path("empty-chunk-size"), 0);
+}
@@ -0,0 +1,108 @@
+use crate::actor::{Actor, ActorContext, ActorSystem, Signal};
+use crate::stream::flow::{ByteOrder, LengthField};
+use crate::stream::tests::{assert_stream_fails, expect_value};
+use crate::stream::{Flow, Sink, Source};
+
+struct TestReaper {
+    source: Option<Source<Vec<u8>>>,
+    expected: Vec<&'static [u8]>,
+}
+
+impl Actor for TestReaper {
+    type Msg = Vec<Vec<u8>>;
+
+    fn receive(&mut self, frames: Self::Msg, ctx: &mut ActorContext<Self::Msg>) {
+        assert_eq!(frames, self.expected);
+
+        ctx.stop();
+    }
+
+    fn receive_signal(&mut self, signal: Signal, ctx: &mut ActorContext<Self::Msg>) {
+        if let Signal::Started = signal {
+            let source = self.source.take().unwrap();
+
+            let (_, result) = ctx.spawn(source.to(Sink::collect()));
+
+            ctx.watch_result(result, expect_value);
+        }
+    }
+}
+
+fn assert_frames(source: Source<Vec<u8>>, expected: Vec<&'static [u8]>) {
+    assert!(ActorSystem::new()
+        .spawn(TestReaper {
+            source: Some(source),
+            expected,
+        })
+        .is_ok());
+}
+
+fn assert_frames_fail(source: Source<Vec<u8>>) {
+    assert_stream_fails(source.to(Sink::collect()));
+}
+
+fn chunks(chunks: Vec<&'static [u8]>) -> Source<Vec<u8>> {
+    Source::iterator(chunks.into_iter().map(|c| c.to_vec()))
+}
+
+#[test]
+fn test_delimiter() {
+    let input = vec![&b"hel"[..], b"lo\r", b"\nworld\r\n\r\nfoo"];
+
+    assert_frames(
+        chunks(input.clone()).via(Flow::delimiter(b"\r\n", 16, true)),
+        vec![b"hello", b"world", b"", b"foo"],
+    );
+
+    // the partial frame fails the stream
+
+    assert_frames_fail(chunks(input).via(Flow::delimiter(b"\r\n", 16, false)));
+}
+
+#[test]
+fn test_delimiter_max_frame_length() {
+    assert_frames_fail(chunks(vec![b"1234\n12", b"345\n123"]).via(Flow::delimiter(b"\n", 4, true)));
+}
+
+#[test]
+fn test_length_field() {
+    let field = LengthField::new(2)
+        .with_byte_order(ByteOrder::LittleEndian)
+        .with_offset(1);
+
+    // encode, then split into small chunks before decoding
+
+    assert_frames(
+        chunks(vec![b"ahello", b"b", b"cworld"])
+            .via(Flow::length_field_encoder(field))
+            .via(Flow::fixed_size(2, true))
+            .via(Flow::length_field_decoder(field)),
+        vec![b"ahello", b"b", b"cworld"],
+    );
+
+    assert_frames(
+        chunks(vec![&[0, 3, 1, 2, 3][..]]).via(Flow::length_field_decoder(LengthField::new(2))),
+        vec![&[1, 2, 3][..]],
+    );
+
+    // the partial frame fails the stream
+
+    assert_frames_fail(
+        chunks(vec![&[0, 3, 1, 2, 3, 0, 2, 1][..]])
+            .via(Flow::length_field_decoder(LengthField::new(2))),
+    );
+
+    assert_frames_fail(chunks(vec![b"123456"]).via(Flow::length_field_encoder(
+        LengthField::new(1).with_max_frame_length(5),
+    )));
+}
+
+#[test]
+fn test_fixed_size() {
+    assert_frames(
+        chunks(vec![b"12", b"3456", b"78"]).via(Flow::fixed_size(3, true)),
+        vec![b"123", b"456", b"78"],
+    );
+
+    assert_frames_fail(chunks(vec![b"12", b"3456", b"78"]).via(Flow::fixed_size(3, false)));
+}
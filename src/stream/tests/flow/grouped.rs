@@ -0,0 +1,59 @@
+use crate::stream::tests::assert_stream;
+use crate::stream::{Sink, Source, ThrottleMode};
+use std::time::Duration;
+
+#[test]
+fn test_grouped() {
+    // the partial group is emitted once upstream completes
+
+    assert_stream(
+        Source::iterator(1..=7).grouped(3).to(Sink::collect()),
+        |values: Vec<Vec<usize>>| {
+            assert_eq!(values, vec![vec![1, 2, 3], vec![4, 5, 6], vec![7]]);
+        },
+    );
+}
+
+#[test]
+fn test_grouped_within() {
+    assert_stream(
+        Source::iterator(1..=7)
+            .grouped_within(3, Duration::from_secs(10))
+            .to(Sink::collect()),
+        |values: Vec<Vec<usize>>| {
+            assert_eq!(values, vec![vec![1, 2, 3], vec![4, 5, 6], vec![7]]);
+        },
+    );
+}
+
+#[test]
+fn test_grouped_within_elapsed() {
+    // an element arrives every 100ms, so the groups are emitted
+    // because their duration elapses rather than because they're full
+
+    assert_stream(
+        Source::iterator(1..=4)
+            .throttle(10, Duration::from_secs(1), 1, ThrottleMode::Shaping)
+            .grouped_within(10, Duration::from_millis(150))
+            .to(Sink::collect()),
+        |values: Vec<Vec<usize>>| {
+            assert!(values.len() > 1);
+            assert!(values.iter().all(|group| group.len() < 4));
+            assert_eq!(values.concat(), vec![1, 2, 3, 4]);
+        },
+    );
+}
+
+#[test]
+fn test_grouped_weighted_within() {
+    // an element that would exceed the group's cost starts the next one
+
+    assert_stream(
+        Source::iterator(vec![3, 3, 3, 5, 1, 8].into_iter())
+            .grouped_weighted_within(6, Duration::from_secs(10), |n| *n)
+            .to(Sink::collect()),
+        |values: Vec<Vec<usize>>| {
+            assert_eq!(values, vec![vec![3, 3], vec![3], vec![5, 1], vec![8]]);
+        },
+    );
+}
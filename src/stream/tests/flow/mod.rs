@@ -1,3 +1,9 @@
 mod filter_map;
 mod fold;
+mod framing;
+mod grouped;
 mod map_concat;
+mod throttle;
+
+#[cfg(target_family = "unix")]
+mod process;
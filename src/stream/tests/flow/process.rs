@@ -0,0 +1,195 @@
+use crate::actor::*;
+use crate::cfg::Config;
+use crate::stream::tests::expect_value;
+use crate::stream::{Flow, Sink, Source, Stream};
+use std::io;
+use std::process::Command;
+
+struct TestReaper<Out>
+where
+    Out: 'static + Send,
+{
+    stream: Option<Stream<Out>>,
+    assert: fn(Result<Out, FailureReason>),
+}
+
+impl<Out> Actor for TestReaper<Out>
+where
+    Out: 'static + Send,
+{
+    type Msg = Result<Out, FailureReason>;
+
+    fn receive(&mut self, result: Self::Msg, ctx: &mut ActorContext<Self::Msg>) {
+        (self.assert)(result);
+
+        ctx.stop();
+    }
+
+    fn receive_signal(&mut self, signal: Signal, ctx: &mut ActorContext<Self::Msg>) {
+        if let Signal::Started = signal {
+            let (_, result) = ctx.spawn(self.stream.take().unwrap());
+
+            ctx.watch_result(result, |result| result);
+        }
+    }
+}
+
+fn assert_process<Out: 'static + Send>(
+    stream: Stream<Out>,
+    assert: fn(Result<Out, FailureReason>),
+) {
+    assert_process_with_config(&Config::new(&[]), stream, assert);
+}
+
+fn assert_process_with_config<Out: 'static + Send>(
+    config: &Config,
+    stream: Stream<Out>,
+    assert: fn(Result<Out, FailureReason>),
+) {
+    assert!(ActorSystem::new()
+        .with_config(config)
+        .spawn(TestReaper {
+            stream: Some(stream),
+            assert,
+        })
+        .is_ok());
+}
+
+/// Concatenate the output of a process, emitting it when the process exits.
+fn concat() -> Flow<Vec<u8>, Vec<u8>> {
+    Flow::new().fold(Vec::new(), |mut output, chunk: Vec<u8>| {
+        output.extend(chunk);
+        output
+    })
+}
+
+fn sh(script: &str) -> Command {
+    let mut command = Command::new("sh");
+
+    command.arg("-c").arg(script);
+
+    command
+}
+
+#[test]
+fn test_process() {
+    let stream = Source::iterator(vec![b"hello ".to_vec(), b"world".to_vec()].into_iter())
+        .via(Flow::process(Command::new("cat"), false))
+        .via(concat())
+        .to(Sink::last());
+
+    assert_process(stream, |output| {
+        assert_eq!(expect_value(output), Some(b"hello world".to_vec()));
+    });
+}
+
+#[test]
+fn test_process_exit_status() {
+    let stream = Source::process(sh("echo out; echo err >&2; exit 3"), true)
+        .via(concat())
+        .to(Sink::last());
+
+    assert_process(stream, |output| {
+        // the non-zero exit fails the stream
+
+        assert!(output.is_err());
+    });
+}
+
+#[test]
+fn test_process_killed_on_cancel() {
+    // first cancels upstream after the first chunk, which kills the process
+    // rather than waiting for it to exit
+
+    let stream = Source::process(sh("echo hello; exec sleep 30"), false).to(Sink::first());
+
+    assert_process(stream, |output| {
+        assert_eq!(expect_value(output), Some(b"hello\n".to_vec()));
+    });
+}
+
+#[test]
+fn test_process_spawn_failure() {
+    let stream = Source::process(Command::new("pantomime-does-not-exist"), false)
+        .via(concat())
+        .to(Sink::last());
+
+    assert_process(stream, |output| {
+        assert!(output.is_err());
+    });
+}
+
+#[test]
+fn test_process_exits_after_closing_stdout() {
+    // downstream is only stopped once the process has exited, even if it
+    // has closed its stdout well before then
+
+    let stream = Source::process(sh("echo hello; exec >&-; sleep 0.2; exit 2"), false)
+        .via(concat())
+        .to(Sink::last());
+
+    assert_process(stream, |output| {
+        assert!(output.is_err());
+    });
+}
+
+#[test]
+fn test_process_small_blocking_io_dispatcher() {
+    // a pending read and write occupy both threads, so neither waiting
+    // for the process nor logging its stderr may require another
+
+    let config = Config::new(&[
+        ("PANTOMIME_DISPATCHERS", "blocking-io"),
+        ("PANTOMIME_DISPATCHER_BLOCKING_IO_LOGIC", "thread-pool"),
+        ("PANTOMIME_DISPATCHER_BLOCKING_IO_THREAD_POOL_SIZE", "2"),
+    ]);
+
+    let stream = Source::iterator(vec![b"hello ".to_vec(), b"world".to_vec()].into_iter())
+        .via(Flow::process(sh("echo started >&2; cat"), true))
+        .via(concat())
+        .to(Sink::last());
+
+    assert_process_with_config(&config, stream, |output| {
+        assert_eq!(expect_value(output), Some(b"hello world".to_vec()));
+    });
+}
+
+#[test]
+fn test_process_sink_exit_status() {
+    // the exit status is output whether or not it's successful
+
+    let stream = Source::iterator(vec![b"hello".to_vec()].into_iter()).to(Sink::process(
+        sh("test \"$(cat)\" = hello && exit 3"),
+        false,
+    ));
+
+    assert_process(stream, |output| {
+        assert_eq!(expect_value(output).unwrap().code(), Some(3));
+    });
+}
+
+#[test]
+fn test_process_sink_exits_early() {
+    // the process exits without reading stdin, which cancels upstream
+
+    let stream = Source::repeat(vec![0; 1024]).to(Sink::process(sh("exit 0"), false));
+
+    assert_process(stream, |output| {
+        assert!(expect_value(output).unwrap().success());
+    });
+}
+
+#[test]
+fn test_process_sink_spawn_failure() {
+    let stream = Source::iterator(vec![b"hello".to_vec()].into_iter()).to(Sink::process(
+        Command::new("pantomime-does-not-exist"),
+        false,
+    ));
+
+    assert_process(stream, |output| {
+        assert_eq!(
+            expect_value(output).unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+    });
+}
@@ -0,0 +1,113 @@
+use crate::cfg::Config;
+use crate::stream::tests::{assert_stream, assert_stream_fails, assert_stream_with_config};
+use crate::stream::{Sink, Source, ThrottleMode};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+fn elapsed(values: &[(usize, Instant)]) -> Duration {
+    values[values.len() - 1].1 - values[0].1
+}
+
+#[test]
+fn test_throttle_shaping() {
+    // the first two elements are a burst, after which the
+    // rest are emitted every 50ms
+
+    assert_stream(
+        Source::iterator(1..=6)
+            .throttle(20, Duration::from_secs(1), 2, ThrottleMode::Shaping)
+            .map(|n| (n, Instant::now()))
+            .to(Sink::collect()),
+        |values: Vec<(usize, Instant)>| {
+            let elements = values.iter().map(|(n, _)| *n).collect::<Vec<_>>();
+
+            assert_eq!(elements, vec![1, 2, 3, 4, 5, 6]);
+            assert!(elapsed(&values) >= Duration::from_millis(200));
+            assert!(elapsed(&values[..2]) < Duration::from_millis(50));
+        },
+    );
+}
+
+#[test]
+fn test_throttle_high_rate() {
+    // the timer ticks every 10ms, so each tick emits the ten
+    // elements that were refilled during it rather than one
+
+    assert_stream(
+        Source::iterator(1..=100)
+            .throttle(1000, Duration::from_secs(1), 1, ThrottleMode::Shaping)
+            .map(|n| (n, Instant::now()))
+            .to(Sink::collect()),
+        |values: Vec<(usize, Instant)>| {
+            assert_eq!(values.len(), 100);
+            assert!(elapsed(&values) >= Duration::from_millis(80));
+            assert!(elapsed(&values) < Duration::from_millis(400));
+        },
+    );
+}
+
+#[test]
+fn test_throttle_ticker_interval() {
+    // the ticker fires every 100ms, so the first wait emits the hundred
+    // elements that were refilled during it rather than ten. The wait
+    // may end a tick or two late, but waiting for each element would
+    // take seconds
+
+    let config = Config::new(&[("PANTOMIME_TICKER_INTERVAL_MS", "100")]);
+
+    assert_stream_with_config(
+        &config,
+        Source::iterator(1..=50)
+            .throttle(1000, Duration::from_secs(1), 1, ThrottleMode::Shaping)
+            .map(|n| (n, Instant::now()))
+            .to(Sink::collect()),
+        |values: Vec<(usize, Instant)>| {
+            assert_eq!(values.len(), 50);
+            assert!(elapsed(&values) >= Duration::from_millis(80));
+            assert!(elapsed(&values) < Duration::from_millis(400));
+        },
+    );
+}
+
+#[test]
+fn test_throttle_cost() {
+    // the first two elements use up the bucket, so the third
+    // waits for its five tokens
+
+    assert_stream(
+        Source::iterator(vec![5, 5, 5].into_iter())
+            .throttle_with_cost(
+                20,
+                Duration::from_secs(1),
+                10,
+                |n| *n,
+                ThrottleMode::Shaping,
+            )
+            .map(|n| (n, Instant::now()))
+            .to(Sink::collect()),
+        |values: Vec<(usize, Instant)>| {
+            assert_eq!(values.len(), 3);
+            assert!(elapsed(&values) >= Duration::from_millis(250));
+        },
+    );
+}
+
+#[test]
+fn test_throttle_enforcing() {
+    // the burst is emitted, but the next element fails the stream
+
+    let emitted = Arc::new(Mutex::new(Vec::new()));
+    let recorded = emitted.clone();
+
+    assert_stream_fails(
+        Source::iterator(1..=10)
+            .throttle(1, Duration::from_secs(1), 3, ThrottleMode::Enforcing)
+            .map(move |n| {
+                recorded.lock().unwrap().push(n);
+                n
+            })
+            .to(Sink::collect()),
+    );
+
+    assert_eq!(*emitted.lock().unwrap(), vec![1, 2, 3]);
+}
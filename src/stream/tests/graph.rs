@@ -0,0 +1,229 @@
+use super::assert_stream;
+use crate::stream::sink::fan_out::{Broadcast, Partition};
+use crate::stream::source::merge::Merge;
+use crate::stream::{FanInCompletion, FanOutCancellation, Flow, Graph, Sink, Source};
+use std::io;
+
+#[test]
+fn test_graph_diamond() {
+    let mut graph = Graph::new();
+
+    let source = graph.add_source(Source::iterator(1..=3));
+    let broadcast = graph.add_junction(Broadcast::new(2, FanOutCancellation::Lazy));
+    let (tens_in, tens_out) = graph.add_flow(Flow::new().map(|n: usize| n * 10));
+    let (hundreds_in, hundreds_out) = graph.add_flow(Flow::new().map(|n: usize| n * 100));
+    let merge = graph.add_junction(Merge::new(2, FanInCompletion::Lazy));
+    let sink = graph.add_sink(Sink::collect());
+
+    graph.connect(source, broadcast.inlet(0));
+    graph.connect(broadcast.outlet(0), tens_in);
+    graph.connect(broadcast.outlet(1), hundreds_in);
+    graph.connect(tens_out, merge.inlet(0));
+    graph.connect(hundreds_out, merge.inlet(1));
+    graph.connect(merge.outlet(0), sink);
+
+    assert_stream(graph.stream().unwrap(), |mut values: Vec<Vec<usize>>| {
+        let mut values = values.pop().unwrap();
+
+        values.sort();
+
+        assert_eq!(values, vec![10, 20, 30, 100, 200, 300]);
+    });
+}
+
+#[test]
+fn test_graph_sinks() {
+    let mut graph = Graph::new();
+
+    let source = graph.add_source(Source::iterator(1..=3));
+    let broadcast = graph.add_junction(Broadcast::new(2, FanOutCancellation::Lazy));
+    let (flow_in, flow_out) = graph.add_flow(Flow::new().map(|n: usize| n * 2));
+    let first = graph.add_sink(Sink::collect());
+    let second = graph.add_sink(Sink::collect());
+
+    graph.connect(source, broadcast.inlet(0));
+    graph.connect(broadcast.outlet(0), first);
+    graph.connect(broadcast.outlet(1), flow_in);
+    graph.connect(flow_out, second);
+
+    assert_stream(graph.stream().unwrap(), |values: Vec<Vec<usize>>| {
+        assert_eq!(values, vec![vec![1, 2, 3], vec![2, 4, 6]]);
+    });
+}
+
+#[test]
+fn test_graph_source() {
+    let mut graph = Graph::new();
+
+    let first = graph.add_source(Source::iterator(1..=3));
+    let second = graph.add_source(Source::iterator(4..=6));
+    let merge = graph.add_junction(Merge::new(2, FanInCompletion::Lazy));
+
+    graph.connect(first, merge.inlet(0));
+    graph.connect(second, merge.inlet(1));
+
+    let source = graph.source(merge.outlet(0)).unwrap();
+
+    assert_stream(source.to(Sink::collect()), |mut values: Vec<usize>| {
+        values.sort();
+
+        assert_eq!(values, vec![1, 2, 3, 4, 5, 6]);
+    });
+}
+
+#[test]
+fn test_graph_flow() {
+    let mut graph = Graph::new();
+
+    let broadcast = graph.add_junction(Broadcast::new(2, FanOutCancellation::Lazy));
+    let (flow_in, flow_out) = graph.add_flow(Flow::new().map(|n: usize| n + 10));
+    let merge = graph.add_junction(Merge::new(2, FanInCompletion::Lazy));
+
+    graph.connect(broadcast.outlet(0), merge.inlet(0));
+    graph.connect(broadcast.outlet(1), flow_in);
+    graph.connect(flow_out, merge.inlet(1));
+
+    let flow = graph.flow(broadcast.inlet(0), merge.outlet(0)).unwrap();
+
+    assert_stream(
+        Source::iterator(1..=2).via(flow).to(Sink::collect()),
+        |mut values: Vec<usize>| {
+            values.sort();
+
+            assert_eq!(values, vec![1, 2, 11, 12]);
+        },
+    );
+}
+
+#[test]
+fn test_graph_sink() {
+    let mut graph = Graph::new();
+
+    let (flow_in, flow_out) = graph.add_flow(Flow::new().map(|n: usize| n * 2));
+    let partition = graph.add_junction(Partition::new(
+        2,
+        |n: &usize| n % 4 / 2,
+        FanOutCancellation::Lazy,
+    ));
+    let first = graph.add_sink(Sink::collect());
+    let second = graph.add_sink(Sink::collect());
+
+    graph.connect(flow_out, partition.inlet(0));
+    graph.connect(partition.outlet(0), first);
+    graph.connect(partition.outlet(1), second);
+
+    let sink = graph.sink(flow_in).unwrap();
+
+    assert_stream(
+        Source::iterator(1..=4).to(sink),
+        |values: Vec<Vec<usize>>| {
+            assert_eq!(values, vec![vec![4, 8], vec![2, 6]]);
+        },
+    );
+}
+
+#[test]
+fn test_graph_cycle() {
+    // each element is incremented until it reaches 10, at which point it
+    // leaves the loop
+
+    let mut graph = Graph::new();
+
+    let source = graph.add_source(Source::single(5));
+    let merge = graph.add_junction(Merge::new(2, FanInCompletion::Lazy));
+    let partition = graph.add_junction(Partition::new(
+        2,
+        |n: &usize| if *n < 10 { 0 } else { 1 },
+        FanOutCancellation::Eager,
+    ));
+    let (increment_in, increment_out) = graph.add_flow(Flow::new().map(|n: usize| n + 1));
+    let sink = graph.add_sink(Sink::first());
+
+    graph.connect(source, merge.inlet(0));
+    graph.connect(merge.outlet(0), partition.inlet(0));
+    graph.connect(partition.outlet(0), increment_in);
+    graph.connect(increment_out, merge.inlet(1));
+    graph.connect(partition.outlet(1), sink);
+
+    assert_stream(graph.stream().unwrap(), |values: Vec<Option<usize>>| {
+        assert_eq!(values, vec![Some(10)]);
+    });
+}
+
+#[test]
+fn test_graph_validation() {
+    fn error<A>(result: io::Result<A>) -> String {
+        match result {
+            Ok(_) => panic!("graph is valid"),
+            Err(e) => {
+                assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+                e.to_string()
+            }
+        }
+    }
+
+    let mut graph = Graph::<Vec<usize>>::new();
+    let source = graph.add_source(Source::single(1));
+    let merge = graph.add_junction(Merge::new(2, FanInCompletion::Lazy));
+    let sink = graph.add_sink(Sink::collect());
+    graph.connect(source, merge.inlet(0));
+    graph.connect(merge.outlet(0), sink);
+
+    assert_eq!(
+        error(graph.stream()),
+        "inlet 1 of junction 1 is not connected"
+    );
+
+    let mut graph = Graph::<Vec<usize>>::new();
+    let source = graph.add_source(Source::single(1));
+    let first = graph.add_sink(Sink::collect());
+    let second = graph.add_sink(Sink::collect());
+    graph.connect(source, first);
+    graph.connect(source, second);
+
+    assert_eq!(
+        error(graph.stream()),
+        "outlet 0 of source 0 is connected more than once"
+    );
+
+    let mut graph = Graph::new();
+    let source = graph.add_source(Source::single(1));
+    let broadcast = graph.add_junction(Broadcast::new(1, FanOutCancellation::Lazy));
+    graph.connect(source, broadcast.inlet(0));
+
+    assert_eq!(
+        error(graph.source(broadcast.outlet(1))),
+        "outlet 1 of junction 1 does not exist"
+    );
+
+    let mut graph = Graph::<()>::new();
+    let source = graph.add_source(Source::single(1));
+    let (flow_in, _) = graph.add_flow(Flow::new());
+    graph.connect(source, flow_in);
+
+    assert_eq!(error(graph.stream()), "outlet 0 of flow 1 is not connected");
+
+    let mut other = Graph::<Vec<usize>>::new();
+    let foreign = other.add_sink(Sink::collect());
+
+    let mut graph = Graph::<Vec<usize>>::new();
+    let source = graph.add_source(Source::single(1));
+    let sink = graph.add_sink(Sink::collect());
+    graph.connect(source, foreign);
+    graph.connect(source, sink);
+
+    assert_eq!(
+        error(graph.stream()),
+        "graph connects a port that belongs to another graph"
+    );
+
+    let mut graph = Graph::<()>::new();
+    let source = graph.add_source(Source::single(1));
+    let (flow_in, _) = graph.add_flow(Flow::new());
+    graph.connect(source, flow_in);
+
+    assert_eq!(
+        error(graph.source(other.add_source(Source::single(1)))),
+        "port belongs to another graph"
+    );
+}
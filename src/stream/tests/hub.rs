@@ -0,0 +1,218 @@
+use super::expect_value;
+use crate::actor::{Actor, ActorContext, ActorSystem, FailureError, Signal};
+use crate::stream::flow::TakeWhile;
+use crate::stream::{Flow, Sink, Source, Stream};
+use std::io::{Error, ErrorKind};
+use std::time::Duration;
+
+enum HubMsg<Out> {
+    Spawn,
+    Consumed(usize, Out),
+    Produced,
+}
+
+/// Runs the consumers and then, once they've had a chance to attach,
+/// the producers, asserting on the values that the consumers produce
+/// once every stream has completed.
+struct HubReaper<Out>
+where
+    Out: 'static + Send,
+{
+    consumers: Vec<Stream<Out>>,
+    producers: Vec<Stream<()>>,
+    values: Vec<Option<Out>>,
+    remaining: usize,
+    assert: fn(Vec<Out>),
+}
+
+impl<Out> Actor for HubReaper<Out>
+where
+    Out: 'static + Send,
+{
+    type Msg = HubMsg<Out>;
+
+    fn receive(&mut self, msg: HubMsg<Out>, ctx: &mut ActorContext<HubMsg<Out>>) {
+        match msg {
+            HubMsg::Spawn => {
+                for producer in self.producers.drain(..) {
+                    let (_, result) = ctx.spawn(producer);
+
+                    ctx.watch_result(result, |result| {
+                        expect_value(result);
+
+                        HubMsg::Produced
+                    });
+                }
+            }
+
+            HubMsg::Consumed(i, value) => {
+                self.values[i] = Some(value);
+                self.remaining -= 1;
+            }
+
+            HubMsg::Produced => {
+                self.remaining -= 1;
+            }
+        }
+
+        if self.remaining == 0 {
+            (self.assert)(self.values.drain(..).flatten().collect());
+
+            ctx.stop();
+        }
+    }
+
+    fn receive_signal(&mut self, signal: Signal, ctx: &mut ActorContext<HubMsg<Out>>) {
+        if let Signal::Started = signal {
+            {
+                let actor_ref = ctx.actor_ref().clone();
+
+                ctx.schedule_thunk(Duration::from_secs(10), move || {
+                    actor_ref.fail(FailureError::new(Error::new(ErrorKind::Other, "failed")))
+                });
+            }
+
+            for (i, consumer) in self.consumers.drain(..).enumerate() {
+                let (_, result) = ctx.spawn(consumer);
+
+                ctx.watch_result(result, move |result| {
+                    HubMsg::Consumed(i, expect_value(result))
+                });
+            }
+
+            ctx.schedule_delivery("spawn", Duration::from_millis(100), HubMsg::Spawn);
+        }
+    }
+}
+
+fn assert_hub<Out>(consumers: Vec<Stream<Out>>, producers: Vec<Stream<()>>, assert: fn(Vec<Out>))
+where
+    Out: 'static + Send,
+{
+    let remaining = consumers.len() + producers.len();
+
+    assert!(ActorSystem::new()
+        .spawn(HubReaper {
+            values: consumers.iter().map(|_| None).collect(),
+            consumers,
+            producers,
+            remaining,
+            assert,
+        })
+        .is_ok());
+}
+
+#[test]
+fn test_merge_hub() {
+    // the producers never complete on their own, so they're cancelled
+    // once the hub's stream has completed
+
+    let (source, hub) = Source::merge_hub(2);
+
+    let mut n = 0;
+
+    let source = source.via(Flow::from_logic(TakeWhile::new(move |_: &usize| {
+        n += 1;
+        n <= 6
+    })));
+
+    assert_hub(
+        vec![source.to(Sink::collect())],
+        vec![
+            Source::iterator(1..).to(hub.sink()),
+            Source::iterator(1001..).to(hub.sink()),
+        ],
+        |values: Vec<Vec<usize>>| {
+            let (low, high): (Vec<usize>, Vec<usize>) = values[0].iter().partition(|n| **n < 1000);
+
+            // each producer's elements are emitted in order
+
+            assert_eq!(values[0].len(), 6);
+            assert_eq!(low, (1..=low.len()).collect::<Vec<_>>());
+            assert_eq!(high, (1001..=1000 + high.len()).collect::<Vec<_>>());
+        },
+    );
+}
+
+#[test]
+fn test_merge_hub_cancelled() {
+    // the producers never complete on their own, so this completes
+    // because the hub's source cancelled them
+
+    let (source, hub) = Source::<usize>::merge_hub(4);
+
+    assert_hub(
+        vec![source.to(Sink::first())],
+        vec![
+            Source::iterator(1..).to(hub.sink()),
+            Source::iterator(1..).to(hub.sink()),
+        ],
+        |values: Vec<Option<usize>>| {
+            assert_eq!(values, vec![Some(1)]);
+        },
+    );
+}
+
+#[test]
+fn test_broadcast_hub() {
+    let (sink, hub) = Sink::broadcast_hub(2);
+
+    assert_hub(
+        vec![
+            hub.source().to(Sink::collect()),
+            hub.source().to(Sink::collect()),
+        ],
+        vec![Source::iterator(1..=5).to(sink)],
+        |values: Vec<Vec<usize>>| {
+            assert_eq!(values, vec![vec![1, 2, 3, 4, 5], vec![1, 2, 3, 4, 5]]);
+        },
+    );
+}
+
+#[test]
+fn test_broadcast_hub_cancelled() {
+    // one consumer cancels after the first element, after which
+    // the other is no longer held back by it
+
+    let (sink, hub) = Sink::broadcast_hub(1);
+
+    assert_hub(
+        vec![
+            hub.source().to(Sink::first()),
+            hub.source().to(Sink::last()),
+        ],
+        vec![Source::iterator(1..=5).to(sink)],
+        |values: Vec<Option<usize>>| {
+            assert_eq!(values, vec![Some(1), Some(5)]);
+        },
+    );
+}
+
+#[test]
+fn test_partition_hub() {
+    let (sink, hub) = Sink::partition_hub(4, |consumers, n: &usize| n % consumers);
+
+    assert_hub(
+        vec![
+            hub.source().to(Sink::collect()),
+            hub.source().to(Sink::collect()),
+        ],
+        vec![Source::iterator(1..=6).to(sink)],
+        |values: Vec<Vec<usize>>| {
+            // the sources usually attach before the producer starts, but
+            // if one is late the elements are partitioned between fewer
+            // sources until it attaches, so only the order in which each
+            // source receives its elements is deterministic
+
+            for v in values.iter() {
+                assert!(v.windows(2).all(|w| w[0] < w[1]));
+            }
+
+            let mut all = values.concat();
+
+            all.sort();
+
+            assert_eq!(all, (1..=6).collect::<Vec<_>>());
+        },
+    );
+}
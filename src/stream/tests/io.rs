@@ -0,0 +1,217 @@
+use super::{expect_value, Failing};
+use crate::actor::*;
+use crate::stream::{Action, Flow, Logic, LogicEvent, Sink, Source, StreamContext};
+use std::io::{self, Cursor, Read, Write};
+use std::thread;
+
+#[test]
+fn test_reader_and_writer() {
+    struct TestReaper;
+
+    impl Actor for TestReaper {
+        type Msg = io::Result<u64>;
+
+        fn receive(&mut self, result: io::Result<u64>, ctx: &mut ActorContext<io::Result<u64>>) {
+            assert_eq!(result.unwrap(), 1000);
+
+            ctx.stop();
+        }
+
+        fn receive_signal(&mut self, signal: Signal, ctx: &mut ActorContext<io::Result<u64>>) {
+            if let Signal::Started = signal {
+                let data = (0..1000).map(|i| i as u8).collect::<Vec<_>>();
+
+                let (_, result) = ctx.spawn(
+                    Source::from_reader(Cursor::new(data), 7)
+                        .via(Flow::new().filter(|chunk: &Vec<u8>| chunk.len() <= 7))
+                        .to(Sink::to_writer(Vec::new())),
+                );
+
+                ctx.watch_result(result, expect_value);
+            }
+        }
+    }
+
+    assert!(ActorSystem::new().spawn(TestReaper).is_ok());
+}
+
+#[test]
+fn test_writer_completion() {
+    // the writer's result is emitted even if upstream completes, or the
+    // writer fails, before the result has been pulled
+
+    /// A source that completes as soon as it starts, which is usually
+    /// before the writer has been pulled.
+    struct Empty;
+
+    impl Logic<(), Vec<u8>> for Empty {
+        type Ctl = ();
+
+        fn name(&self) -> &'static str {
+            "Empty"
+        }
+
+        fn receive(
+            &mut self,
+            msg: LogicEvent<(), ()>,
+            _: &mut StreamContext<(), Vec<u8>, ()>,
+        ) -> Action<Vec<u8>, ()> {
+            match msg {
+                LogicEvent::Started => Action::Stop(None),
+                _ => Action::None,
+            }
+        }
+    }
+
+    struct FailingWriter;
+
+    impl Write for FailingWriter {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::Error::new(io::ErrorKind::Other, "failed"))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    struct TestReaper {
+        remaining: usize,
+    }
+
+    impl Actor for TestReaper {
+        type Msg = (bool, io::Result<u64>);
+
+        fn receive(&mut self, msg: (bool, io::Result<u64>), ctx: &mut ActorContext<Self::Msg>) {
+            match msg {
+                (true, result) => assert_eq!(result.unwrap(), 0),
+                (false, result) => assert!(result.is_err()),
+            }
+
+            self.remaining -= 1;
+
+            if self.remaining == 0 {
+                ctx.stop();
+            }
+        }
+
+        fn receive_signal(&mut self, signal: Signal, ctx: &mut ActorContext<Self::Msg>) {
+            if let Signal::Started = signal {
+                for _ in 0..10 {
+                    let (_, result) = ctx.spawn(Source::new(Empty).to(Sink::to_writer(Vec::new())));
+
+                    ctx.watch_result(result, |result| (true, expect_value(result)));
+
+                    let (_, result) =
+                        ctx.spawn(Source::repeat(vec![0]).to(Sink::to_writer(FailingWriter)));
+
+                    ctx.watch_result(result, |result| (false, expect_value(result)));
+                }
+            }
+        }
+    }
+
+    assert!(ActorSystem::new()
+        .spawn(TestReaper { remaining: 20 })
+        .is_ok());
+}
+
+#[test]
+fn test_writable_and_readable() {
+    struct TestReaper;
+
+    impl Actor for TestReaper {
+        type Msg = Vec<u8>;
+
+        fn receive(&mut self, data: Vec<u8>, ctx: &mut ActorContext<Vec<u8>>) {
+            let expected = (0..100)
+                .map(|i| format!("LINE {}\n", i))
+                .collect::<String>();
+
+            assert_eq!(String::from_utf8(data).unwrap(), expected);
+
+            ctx.stop();
+        }
+
+        fn receive_signal(&mut self, signal: Signal, ctx: &mut ActorContext<Vec<u8>>) {
+            if let Signal::Started = signal {
+                // both ends buffer very little, so the writer and reader
+                // are backpressured by each other
+
+                let (source, mut writer) = Source::writable(1);
+                let (sink, mut reader) = Sink::readable(1);
+
+                ctx.spawn(
+                    source
+                        .via(Flow::new().map(|chunk: Vec<u8>| chunk.to_ascii_uppercase()))
+                        .to(sink),
+                );
+
+                thread::spawn(move || {
+                    for i in 0..100 {
+                        writeln!(writer, "line {}", i).unwrap();
+                    }
+
+                    writer.flush().unwrap();
+                });
+
+                let actor_ref = ctx.actor_ref().clone();
+
+                thread::spawn(move || {
+                    let mut data = Vec::new();
+
+                    reader.read_to_end(&mut data).unwrap();
+
+                    actor_ref.tell(data);
+                });
+            }
+        }
+    }
+
+    assert!(ActorSystem::new().spawn(TestReaper).is_ok());
+}
+
+#[test]
+fn test_readable_failure() {
+    struct TestReaper;
+
+    impl Actor for TestReaper {
+        type Msg = io::Result<usize>;
+
+        fn receive(&mut self, result: io::Result<usize>, ctx: &mut ActorContext<Self::Msg>) {
+            // upstream failing isn't a clean end of the data
+
+            assert_eq!(result.unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+
+            ctx.stop();
+        }
+
+        fn receive_signal(&mut self, signal: Signal, ctx: &mut ActorContext<Self::Msg>) {
+            if let Signal::Started = signal {
+                let (sink, mut reader) = Sink::readable(1);
+
+                ctx.spawn(
+                    Source::new(Failing)
+                        .via(Flow::new().map(|n: usize| vec![n as u8]))
+                        .to(sink),
+                );
+
+                let actor_ref = ctx.actor_ref().clone();
+
+                thread::spawn(move || {
+                    let mut data = Vec::new();
+
+                    actor_ref.tell(reader.read_to_end(&mut data));
+                });
+            }
+        }
+    }
+
+    assert!(ActorSystem::new().spawn(TestReaper).is_ok());
+}
+
+#[test]
+#[should_panic(expected = "chunk_size must be greater than 0")]
+fn test_reader_empty_chunk_size() {
+    let _ = Source::from_reader(Cursor::new(vec![1, 2, 3]), 0);
+}
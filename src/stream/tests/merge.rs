@@ -0,0 +1,147 @@
+use super::{assert_stream, assert_stream_fails, Failing};
+use crate::stream::flow::TakeWhile;
+use crate::stream::{
+    Action, FanInCompletion, Flow, Logic, LogicEvent, Sink, Source, StreamContext,
+};
+use crate::testkit::eventually;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Repeats its element, like `Source::repeat`, recording when it has
+/// stopped, i.e. when it was cancelled or its stream was torn down.
+struct Repeating {
+    stopped: Arc<AtomicBool>,
+}
+
+impl Drop for Repeating {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+    }
+}
+
+impl Logic<(), usize> for Repeating {
+    type Ctl = ();
+
+    fn name(&self) -> &'static str {
+        "Repeating"
+    }
+
+    fn receive(
+        &mut self,
+        msg: LogicEvent<(), ()>,
+        _: &mut StreamContext<(), usize, ()>,
+    ) -> Action<usize, ()> {
+        match msg {
+            LogicEvent::Pulled => Action::Push(0),
+
+            LogicEvent::Cancelled => {
+                self.stopped.store(true, Ordering::SeqCst);
+
+                Action::Stop(None)
+            }
+
+            _ => Action::None,
+        }
+    }
+}
+
+#[test]
+fn test_merge() {
+    let source = Source::iterator(1..=10)
+        .merge(Source::iterator(11..=20))
+        .merge(Source::iterator(21..=30));
+
+    assert_stream(source.to(Sink::collect()), |mut values: Vec<usize>| {
+        values.sort();
+
+        assert_eq!(values, (1..=30).collect::<Vec<_>>());
+    });
+}
+
+#[test]
+fn test_merge_flow() {
+    let flow = Flow::new()
+        .map(|n: usize| n * 2)
+        .merge(Source::iterator(1..=3));
+
+    assert_stream(
+        Source::iterator(1..=3).via(flow).to(Sink::collect()),
+        |mut values: Vec<usize>| {
+            values.sort();
+
+            assert_eq!(values, vec![1, 2, 2, 3, 4, 6]);
+        },
+    );
+}
+
+#[test]
+fn test_merge_fair() {
+    let mut n = 0;
+
+    let source = Source::repeat(1)
+        .merge(Source::repeat(2))
+        .via(Flow::from_logic(TakeWhile::new(move |_: &usize| {
+            n += 1;
+            n <= 100
+        })));
+
+    assert_stream(source.to(Sink::collect()), |values: Vec<usize>| {
+        let ones = values.iter().filter(|n| **n == 1).count();
+
+        assert_eq!(values.len(), 100);
+        assert!(ones > 20 && ones < 80, "unfair merge: {} of 100", ones);
+    });
+}
+
+#[test]
+fn test_merge_eager() {
+    // the repeated source never completes, so the merge only completes
+    // because the other one did
+
+    let source = Source::merge_all(
+        vec![Source::iterator(1..=3), Source::repeat(0)],
+        FanInCompletion::Eager,
+    );
+
+    assert_stream(source.to(Sink::collect()), |values: Vec<usize>| {
+        let values = values.into_iter().filter(|n| *n != 0).collect::<Vec<_>>();
+
+        assert_eq!(values, vec![1, 2, 3]);
+    });
+}
+
+#[test]
+fn test_merge_failure() {
+    // the failure stops the merge even though it completes lazily, and
+    // the branch that would otherwise repeat forever is stopped. the
+    // failing stream is torn down, which may happen before the branch
+    // has observed its cancellation
+
+    let stopped = Arc::new(AtomicBool::new(false));
+
+    let source = Source::merge_all(
+        vec![
+            Source::new(Failing),
+            Source::new(Repeating {
+                stopped: stopped.clone(),
+            }),
+        ],
+        FanInCompletion::Lazy,
+    );
+
+    assert_stream_fails(source.to(Sink::last()));
+
+    eventually(Duration::from_millis(3000), || {
+        stopped.load(Ordering::SeqCst)
+    });
+}
+
+#[test]
+fn test_merge_cancel() {
+    let source = Source::repeat(1).merge(Source::repeat(2));
+
+    assert_stream(source.to(Sink::first()), |value: Option<usize>| {
+        assert!(value == Some(1) || value == Some(2));
+    });
+}
@@ -1,5 +1,148 @@
+use crate::actor::{Actor, ActorContext, ActorSystem, FailureError, FailureReason, Signal};
+use crate::cfg::Config;
+use crate::stream::{Action, Logic, LogicEvent, Stream, StreamContext};
+
+mod async_stream;
+mod concat;
 mod context_stage_ref;
+mod dispatcher;
+mod fan_out;
+mod file;
 mod flow;
+mod graph;
+mod hub;
+mod io;
 mod legacy;
+mod merge;
 mod queue;
+mod tcp;
 mod udp;
+mod zip;
+
+#[cfg(all(feature = "posix-signals-support", target_family = "unix"))]
+mod posix_signals;
+
+#[cfg(target_family = "unix")]
+mod uds;
+
+struct TestReaper<Out>
+where
+    Out: 'static + Send,
+{
+    stream: Option<Stream<Out>>,
+    assert: fn(Out),
+}
+
+impl<Out> Actor for TestReaper<Out>
+where
+    Out: 'static + Send,
+{
+    type Msg = Out;
+
+    fn receive(&mut self, value: Out, ctx: &mut ActorContext<Out>) {
+        (self.assert)(value);
+
+        ctx.stop();
+    }
+
+    fn receive_signal(&mut self, signal: Signal, ctx: &mut ActorContext<Out>) {
+        if let Signal::Started = signal {
+            let (_, result) = ctx.spawn(self.stream.take().unwrap());
+
+            ctx.watch_result(result, expect_value);
+        }
+    }
+}
+
+/// Unwrap the result of a watched stream, panicking if it failed.
+fn expect_value<Out>(result: Result<Out, FailureReason>) -> Out {
+    result.unwrap_or_else(|reason| panic!("stream failed: {}", reason))
+}
+
+/// Run the supplied stream, asserting on the value that it produces.
+fn assert_stream<Out>(stream: Stream<Out>, assert: fn(Out))
+where
+    Out: 'static + Send,
+{
+    assert_stream_with_config(&Config::new(&[]), stream, assert);
+}
+
+/// Run the supplied stream in a system with the supplied config, asserting
+/// on the value that it produces.
+fn assert_stream_with_config<Out>(config: &Config, stream: Stream<Out>, assert: fn(Out))
+where
+    Out: 'static + Send,
+{
+    assert!(ActorSystem::new()
+        .with_config(config)
+        .spawn(TestReaper {
+            stream: Some(stream),
+            assert,
+        })
+        .is_ok());
+}
+
+struct FailureReaper<Out>
+where
+    Out: 'static + Send,
+{
+    stream: Option<Stream<Out>>,
+}
+
+impl<Out> Actor for FailureReaper<Out>
+where
+    Out: 'static + Send,
+{
+    type Msg = bool;
+
+    fn receive(&mut self, failed: bool, ctx: &mut ActorContext<bool>) {
+        assert!(failed, "stream did not fail");
+
+        ctx.stop();
+    }
+
+    fn receive_signal(&mut self, signal: Signal, ctx: &mut ActorContext<bool>) {
+        if let Signal::Started = signal {
+            let (_, result) = ctx.spawn(self.stream.take().unwrap());
+
+            ctx.pipe_to_self(result, |result| matches!(result, Ok(Err(_))));
+        }
+    }
+}
+
+/// Run the supplied stream, asserting that it fails.
+fn assert_stream_fails<Out>(stream: Stream<Out>)
+where
+    Out: 'static + Send,
+{
+    assert!(ActorSystem::new()
+        .spawn(FailureReaper {
+            stream: Some(stream),
+        })
+        .is_ok());
+}
+
+/// A source that fails as soon as it starts.
+struct Failing;
+
+impl Logic<(), usize> for Failing {
+    type Ctl = ();
+
+    fn name(&self) -> &'static str {
+        "Failing"
+    }
+
+    fn receive(
+        &mut self,
+        msg: LogicEvent<(), ()>,
+        _: &mut StreamContext<(), usize, ()>,
+    ) -> Action<usize, ()> {
+        match msg {
+            LogicEvent::Started => Action::Stop(Some(FailureReason::Errored(FailureError::new(
+                std::io::Error::new(std::io::ErrorKind::Other, "failed"),
+            )))),
+
+            _ => Action::None,
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use super::expect_value;
+use crate::actor::*;
+use crate::cfg::Config;
+use crate::posix_signals::PosixSignal;
+use crate::stream::{Sink, Source};
+use std::time::Duration;
+
+#[test]
+fn test_posix_signals() {
+    enum TestReaperMsg {
+        Raise,
+        Received(Option<PosixSignal>),
+    }
+
+    struct TestReaper;
+
+    impl Actor for TestReaper {
+        type Msg = TestReaperMsg;
+
+        fn receive(&mut self, msg: TestReaperMsg, ctx: &mut ActorContext<TestReaperMsg>) {
+            match msg {
+                TestReaperMsg::Raise => unsafe {
+                    // SIGWINCH is ignored by default, so the test would
+                    // hang rather than terminate if it isn't handled
+
+                    libc::raise(libc::SIGWINCH);
+                },
+
+                TestReaperMsg::Received(signal) => {
+                    assert_eq!(signal, Some(PosixSignal::SIGWINCH));
+
+                    ctx.stop();
+                }
+            }
+        }
+
+        fn receive_signal(&mut self, signal: Signal, ctx: &mut ActorContext<TestReaperMsg>) {
+            if let Signal::Started = signal {
+                let (_, result) = ctx.spawn(Source::posix_signals().to(Sink::first()));
+
+                ctx.watch_result(result, |result| {
+                    TestReaperMsg::Received(expect_value(result))
+                });
+
+                ctx.schedule_delivery("raise", Duration::from_millis(100), TestReaperMsg::Raise);
+            }
+        }
+    }
+
+    // the signal is specified numerically
+
+    let signals = format!("SIGINT,SIGTERM,{}", libc::SIGWINCH);
+
+    let config = Config::new(&[("PANTOMIME_POSIX_SIGNALS", signals.as_str())]);
+
+    assert!(ActorSystem::new()
+        .with_config(&config)
+        .spawn(TestReaper)
+        .is_ok());
+}
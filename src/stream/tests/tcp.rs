@@ -0,0 +1,228 @@
+use super::{assert_stream_fails, expect_value};
+use crate::actor::*;
+use crate::stream::source::tcp::TcpConnection;
+use crate::stream::{Flow, Sink, Source};
+use std::io::{ErrorKind, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::thread;
+
+#[test]
+fn test_tcp_bind() {
+    enum TestReaperMsg {
+        Connection(TcpConnection),
+        Received(Vec<Vec<u8>>),
+    }
+
+    struct TestReaper;
+
+    impl Actor for TestReaper {
+        type Msg = TestReaperMsg;
+
+        fn receive(&mut self, msg: TestReaperMsg, ctx: &mut ActorContext<TestReaperMsg>) {
+            match msg {
+                TestReaperMsg::Connection(connection) => {
+                    // greet the client and then close our write side, collecting
+                    // everything that the client sends until it closes its side
+
+                    let (_, result) = ctx.spawn(
+                        Source::single(b"hello".to_vec())
+                            .via(connection.flow())
+                            .to(Sink::collect()),
+                    );
+
+                    ctx.watch_result(result, |result| {
+                        TestReaperMsg::Received(expect_value(result))
+                    });
+                }
+
+                TestReaperMsg::Received(chunks) => {
+                    assert_eq!(chunks.concat(), b"world".to_vec());
+
+                    ctx.stop();
+                }
+            }
+        }
+
+        fn receive_signal(&mut self, signal: Signal, ctx: &mut ActorContext<TestReaperMsg>) {
+            if let Signal::Started = signal {
+                let (source, address) = Source::tcp_bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+
+                let actor_ref = ctx.actor_ref().clone();
+
+                ctx.spawn(source.to(Sink::for_each(move |c| {
+                    actor_ref.tell(TestReaperMsg::Connection(c))
+                })));
+
+                thread::spawn(move || {
+                    let mut stream = loop {
+                        if let Ok(stream) = TcpStream::connect(address) {
+                            break stream;
+                        }
+
+                        thread::yield_now();
+                    };
+
+                    let mut greeting = Vec::new();
+
+                    stream.read_to_end(&mut greeting).unwrap();
+
+                    assert_eq!(greeting, b"hello".to_vec());
+
+                    stream.write_all(b"world").unwrap();
+                    stream.shutdown(Shutdown::Write).unwrap();
+                });
+            }
+        }
+    }
+
+    assert!(ActorSystem::new().spawn(TestReaper).is_ok());
+}
+
+#[test]
+fn test_tcp_bind_failure() {
+    // the address is already in use, so the error is returned
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+
+    let error = Source::tcp_bind(&listener.local_addr().unwrap())
+        .err()
+        .expect("bound an address that is in use");
+
+    assert_eq!(error.kind(), ErrorKind::AddrInUse);
+}
+
+#[test]
+fn test_tcp_connect() {
+    struct TestReaper;
+
+    impl Actor for TestReaper {
+        type Msg = Vec<Vec<u8>>;
+
+        fn receive(&mut self, chunks: Vec<Vec<u8>>, ctx: &mut ActorContext<Vec<Vec<u8>>>) {
+            let data = chunks.concat();
+
+            assert_eq!(data.len(), 1024 * 1024);
+            assert!(data.iter().enumerate().all(|(i, b)| *b == (i % 251) as u8));
+
+            ctx.stop();
+        }
+
+        fn receive_signal(&mut self, signal: Signal, ctx: &mut ActorContext<Vec<Vec<u8>>>) {
+            if let Signal::Started = signal {
+                // an echo server that reads until we've closed our write side,
+                // so the data is large enough to require partial writes
+
+                let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+                let address = listener.local_addr().unwrap();
+
+                thread::spawn(move || {
+                    let (mut stream, _) = listener.accept().unwrap();
+
+                    let mut data = Vec::new();
+
+                    stream.read_to_end(&mut data).unwrap();
+                    stream.write_all(&data).unwrap();
+                });
+
+                let data = (0..1024 * 1024)
+                    .map(|i| (i % 251) as u8)
+                    .collect::<Vec<u8>>();
+
+                let chunks = data
+                    .chunks(64 * 1024)
+                    .map(|c| c.to_vec())
+                    .collect::<Vec<_>>();
+
+                let (_, result) = ctx.spawn(
+                    Source::iterator(chunks.into_iter())
+                        .via(Flow::tcp_connect(&address))
+                        .to(Sink::collect()),
+                );
+
+                ctx.watch_result(result, expect_value);
+            }
+        }
+    }
+
+    assert!(ActorSystem::new().spawn(TestReaper).is_ok());
+}
+
+#[test]
+fn test_tcp_connect_upstream_completed() {
+    struct TestReaper;
+
+    impl Actor for TestReaper {
+        type Msg = Vec<Vec<u8>>;
+
+        fn receive(&mut self, chunks: Vec<Vec<u8>>, ctx: &mut ActorContext<Vec<Vec<u8>>>) {
+            assert_eq!(chunks.concat(), b"hello".to_vec());
+
+            ctx.stop();
+        }
+
+        fn receive_signal(&mut self, signal: Signal, ctx: &mut ActorContext<Vec<Vec<u8>>>) {
+            if let Signal::Started = signal {
+                // upstream completes before the connection is established, so
+                // the server only replies once our write side has been shutdown
+
+                let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+                let address = listener.local_addr().unwrap();
+
+                thread::spawn(move || {
+                    let (mut stream, _) = listener.accept().unwrap();
+
+                    let mut data = Vec::new();
+
+                    stream.read_to_end(&mut data).unwrap();
+                    stream.write_all(b"hello").unwrap();
+                });
+
+                let (_, result) = ctx.spawn(
+                    Source::iterator(Vec::<Vec<u8>>::new().into_iter())
+                        .via(Flow::tcp_connect(&address))
+                        .to(Sink::collect()),
+                );
+
+                ctx.watch_result(result, expect_value);
+            }
+        }
+    }
+
+    assert!(ActorSystem::new().spawn(TestReaper).is_ok());
+}
+
+#[test]
+fn test_tcp_connect_failure() {
+    // nothing is listening on this address once the listener is dropped
+
+    let address = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+
+    assert_stream_fails(
+        Source::single(b"hello".to_vec())
+            .via(Flow::tcp_connect(&address))
+            .to(Sink::collect()),
+    );
+}
+
+#[test]
+fn test_tcp_connect_deferred() {
+    use std::io::ErrorKind;
+    use std::time::Duration;
+
+    // no connection is made until the flow is run
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+
+    listener.set_nonblocking(true).unwrap();
+
+    let flow: Flow<Vec<u8>, Vec<u8>> = Flow::tcp_connect(&listener.local_addr().unwrap());
+
+    thread::sleep(Duration::from_millis(50));
+
+    assert_eq!(listener.accept().unwrap_err().kind(), ErrorKind::WouldBlock);
+
+    drop(flow);
+}
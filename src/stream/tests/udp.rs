@@ -1,3 +1,4 @@
+use super::expect_value;
 use crate::stream::*;
 use mio::net::UdpSocket;
 use std::str;
@@ -45,3 +46,163 @@ fn basic_test() {
 
     assert!(ActorSystem::new().spawn(TestReaper::new()).is_ok());
 }
+
+#[test]
+fn test_udp_stages() {
+    // the UDP stages can still be used directly, with the default options
+
+    use crate::actor::*;
+    use crate::stream::{sink, source, Sink, Source};
+
+    struct TestReaper;
+
+    impl Actor for TestReaper {
+        type Msg = Option<Datagram>;
+
+        fn receive(&mut self, msg: Self::Msg, ctx: &mut ActorContext<Self::Msg>) {
+            assert_eq!(msg.unwrap().data, b"12345".to_vec());
+
+            ctx.stop();
+        }
+
+        fn receive_signal(&mut self, signal: Signal, ctx: &mut ActorContext<Self::Msg>) {
+            if let Signal::Started = signal {
+                let socket = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+                let addr = socket.local_addr().unwrap();
+                let source = Source::new(source::udp::Udp::new(&socket));
+
+                let (_, result) = ctx.spawn(source.to(Sink::first()));
+                ctx.watch(result, |value| value);
+
+                let _ = ctx.spawn(
+                    Source::single(Datagram::new(b"12345".to_vec(), addr))
+                        .to(Sink::new(sink::udp::Udp::new(&socket))),
+                );
+            }
+        }
+    }
+
+    assert!(ActorSystem::new().spawn(TestReaper).is_ok());
+}
+
+#[test]
+fn test_udp_truncation() {
+    use crate::actor::*;
+    use crate::stream::datagram::DatagramErrorPolicy;
+    use crate::stream::udp::UdpOptions;
+
+    struct TestReaper {
+        policy: DatagramErrorPolicy,
+    }
+
+    impl Actor for TestReaper {
+        type Msg = Result<Option<Datagram>, FailureReason>;
+
+        fn receive(&mut self, msg: Self::Msg, ctx: &mut ActorContext<Self::Msg>) {
+            match self.policy {
+                DatagramErrorPolicy::LogAndContinue => {
+                    // the oversized datagram is discarded
+
+                    assert_eq!(expect_value(msg).unwrap().data, b"123".to_vec());
+                }
+
+                DatagramErrorPolicy::Fail => {
+                    assert!(msg.is_err());
+                }
+            }
+
+            ctx.stop();
+        }
+
+        fn receive_signal(&mut self, signal: Signal, ctx: &mut ActorContext<Self::Msg>) {
+            if let Signal::Started = signal {
+                let socket = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+                let addr = socket.local_addr().unwrap();
+
+                let options = UdpOptions::new()
+                    .with_max_datagram_size(4)
+                    .with_error_policy(self.policy);
+
+                let (_, result) =
+                    ctx.spawn(Source::udp_with_options(&socket, options).to(Sink::first()));
+
+                ctx.watch_result(result, |result| result);
+
+                let _ = ctx.spawn(
+                    Source::iterator(
+                        vec![
+                            Datagram::new(b"12345".to_vec(), addr),
+                            Datagram::new(b"123".to_vec(), addr),
+                        ]
+                        .into_iter(),
+                    )
+                    .to(Sink::udp(&socket)),
+                );
+            }
+        }
+    }
+
+    for policy in &[
+        DatagramErrorPolicy::LogAndContinue,
+        DatagramErrorPolicy::Fail,
+    ] {
+        assert!(ActorSystem::new()
+            .spawn(TestReaper { policy: *policy })
+            .is_ok());
+    }
+}
+
+#[test]
+fn test_udp_options() {
+    use crate::actor::*;
+    use crate::stream::udp::UdpOptions;
+
+    struct TestReaper {
+        socket: Option<UdpSocket>,
+    }
+
+    impl Actor for TestReaper {
+        type Msg = Option<Datagram>;
+
+        fn receive(&mut self, msg: Self::Msg, ctx: &mut ActorContext<Self::Msg>) {
+            assert_eq!(msg.unwrap().data, b"12345".to_vec());
+
+            // options are applied to the supplied socket as well
+
+            let socket = self.socket.take().unwrap();
+
+            assert!(socket.broadcast().unwrap());
+            assert_eq!(socket.ttl().unwrap(), 32);
+
+            ctx.stop();
+        }
+
+        fn receive_signal(&mut self, signal: Signal, ctx: &mut ActorContext<Self::Msg>) {
+            if let Signal::Started = signal {
+                let socket = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+                let addr = socket.local_addr().unwrap();
+
+                let options = UdpOptions::new()
+                    .with_broadcast(true)
+                    .with_ttl(32)
+                    .with_recv_buffer_size(64 * 1024)
+                    .with_send_buffer_size(64 * 1024);
+
+                let (_, result) =
+                    ctx.spawn(Source::udp_with_options(&socket, options).to(Sink::first()));
+
+                ctx.watch_result(result, expect_value);
+
+                let _ = ctx.spawn(
+                    Source::single(Datagram::new(b"12345".to_vec(), addr)).to(Sink::udp(&socket)),
+                );
+
+                self.socket = Some(socket);
+            }
+        }
+    }
+
+    assert!(ActorSystem::new()
+        .spawn(TestReaper { socket: None })
+        .is_ok());
+}
@@ -0,0 +1,268 @@
+use super::expect_value;
+use crate::actor::*;
+use crate::stream::source::uds::UdsConnection;
+use crate::stream::{Flow, Sink, Source, UdsDatagram};
+use std::fs;
+use std::io::{Read, Write};
+use std::net::Shutdown;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::{process, thread};
+
+fn socket_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("pantomime-{}-{}.sock", process::id(), name));
+
+    let _ = fs::remove_file(&path);
+
+    path
+}
+
+#[test]
+fn test_uds_datagram() {
+    struct TestReaper {
+        client_path: PathBuf,
+    }
+
+    impl Actor for TestReaper {
+        type Msg = Option<UdsDatagram>;
+
+        fn receive(&mut self, datagram: Option<UdsDatagram>, ctx: &mut ActorContext<Self::Msg>) {
+            let datagram = datagram.unwrap();
+
+            assert_eq!(datagram.data, b"hello".to_vec());
+            assert_eq!(datagram.path, Some(self.client_path.clone()));
+
+            ctx.stop();
+        }
+
+        fn receive_signal(&mut self, signal: Signal, ctx: &mut ActorContext<Self::Msg>) {
+            if let Signal::Started = signal {
+                let server_path = socket_path("datagram-server");
+                let server = mio_uds::UnixDatagram::bind(&server_path).unwrap();
+                let client = mio_uds::UnixDatagram::bind(&self.client_path).unwrap();
+
+                let (_, result) = ctx.spawn(Source::uds(&server).to(Sink::first()));
+
+                ctx.watch_result(result, expect_value);
+
+                ctx.spawn(
+                    Source::single(UdsDatagram::new(b"hello".to_vec(), Some(server_path)))
+                        .to(Sink::uds(&client)),
+                );
+            }
+        }
+    }
+
+    assert!(ActorSystem::new()
+        .spawn(TestReaper {
+            client_path: socket_path("datagram-client"),
+        })
+        .is_ok());
+}
+
+#[test]
+fn test_uds_datagram_truncation() {
+    use crate::stream::datagram::{DatagramErrorPolicy, UdsOptions};
+
+    struct TestReaper {
+        policy: DatagramErrorPolicy,
+        name: &'static str,
+    }
+
+    impl Actor for TestReaper {
+        type Msg = Result<Option<UdsDatagram>, FailureReason>;
+
+        fn receive(&mut self, datagram: Self::Msg, ctx: &mut ActorContext<Self::Msg>) {
+            match self.policy {
+                DatagramErrorPolicy::LogAndContinue => {
+                    // the oversized datagram is discarded
+
+                    assert_eq!(expect_value(datagram).unwrap().data, b"123".to_vec());
+                }
+
+                DatagramErrorPolicy::Fail => {
+                    assert!(datagram.is_err());
+                }
+            }
+
+            ctx.stop();
+        }
+
+        fn receive_signal(&mut self, signal: Signal, ctx: &mut ActorContext<Self::Msg>) {
+            if let Signal::Started = signal {
+                let path = socket_path(self.name);
+                let server = mio_uds::UnixDatagram::bind(&path).unwrap();
+                let client = mio_uds::UnixDatagram::unbound().unwrap();
+
+                let options = UdsOptions::new()
+                    .with_max_datagram_size(4)
+                    .with_error_policy(self.policy);
+
+                let (_, result) =
+                    ctx.spawn(Source::uds_with_options(&server, options).to(Sink::first()));
+
+                ctx.watch_result(result, |result| result);
+
+                ctx.spawn(
+                    Source::iterator(
+                        vec![
+                            UdsDatagram::new(b"12345".to_vec(), Some(path.clone())),
+                            UdsDatagram::new(b"123".to_vec(), Some(path)),
+                        ]
+                        .into_iter(),
+                    )
+                    .to(Sink::uds(&client)),
+                );
+            }
+        }
+    }
+
+    assert!(ActorSystem::new()
+        .spawn(TestReaper {
+            policy: DatagramErrorPolicy::LogAndContinue,
+            name: "truncation-continue",
+        })
+        .is_ok());
+
+    assert!(ActorSystem::new()
+        .spawn(TestReaper {
+            policy: DatagramErrorPolicy::Fail,
+            name: "truncation-fail",
+        })
+        .is_ok());
+}
+
+#[test]
+fn test_uds_bind() {
+    enum TestReaperMsg {
+        Connection(Box<UdsConnection>),
+        Received(Vec<Vec<u8>>),
+    }
+
+    struct TestReaper;
+
+    impl Actor for TestReaper {
+        type Msg = TestReaperMsg;
+
+        fn receive(&mut self, msg: TestReaperMsg, ctx: &mut ActorContext<TestReaperMsg>) {
+            match msg {
+                TestReaperMsg::Connection(connection) => {
+                    let (_, result) = ctx.spawn(
+                        Source::single(b"hello".to_vec())
+                            .via(connection.flow())
+                            .to(Sink::collect()),
+                    );
+
+                    ctx.watch_result(result, |result| {
+                        TestReaperMsg::Received(expect_value(result))
+                    });
+                }
+
+                TestReaperMsg::Received(chunks) => {
+                    assert_eq!(chunks.concat(), b"world".to_vec());
+
+                    ctx.stop();
+                }
+            }
+        }
+
+        fn receive_signal(&mut self, signal: Signal, ctx: &mut ActorContext<TestReaperMsg>) {
+            if let Signal::Started = signal {
+                let path = socket_path("bind");
+
+                let actor_ref = ctx.actor_ref().clone();
+
+                let (source, _) = Source::uds_bind(&path).unwrap();
+
+                ctx.spawn(source.to(Sink::for_each(move |c| {
+                    actor_ref.tell(TestReaperMsg::Connection(Box::new(c)))
+                })));
+
+                thread::spawn(move || {
+                    let mut stream = loop {
+                        if let Ok(stream) = UnixStream::connect(&path) {
+                            break stream;
+                        }
+
+                        thread::yield_now();
+                    };
+
+                    let mut greeting = Vec::new();
+
+                    stream.read_to_end(&mut greeting).unwrap();
+
+                    assert_eq!(greeting, b"hello".to_vec());
+
+                    stream.write_all(b"world").unwrap();
+                    stream.shutdown(Shutdown::Write).unwrap();
+                });
+            }
+        }
+    }
+
+    assert!(ActorSystem::new().spawn(TestReaper).is_ok());
+}
+
+#[test]
+fn test_uds_connect() {
+    struct TestReaper;
+
+    impl Actor for TestReaper {
+        type Msg = Vec<Vec<u8>>;
+
+        fn receive(&mut self, chunks: Vec<Vec<u8>>, ctx: &mut ActorContext<Vec<Vec<u8>>>) {
+            assert_eq!(chunks.concat(), b"hello world".to_vec());
+
+            ctx.stop();
+        }
+
+        fn receive_signal(&mut self, signal: Signal, ctx: &mut ActorContext<Vec<Vec<u8>>>) {
+            if let Signal::Started = signal {
+                let path = socket_path("connect");
+                let listener = UnixListener::bind(&path).unwrap();
+
+                thread::spawn(move || {
+                    let (mut stream, _) = listener.accept().unwrap();
+
+                    let mut data = Vec::new();
+
+                    stream.read_to_end(&mut data).unwrap();
+                    stream.write_all(&data).unwrap();
+                });
+
+                let (_, result) = ctx.spawn(
+                    Source::iterator(vec![b"hello ".to_vec(), b"world".to_vec()].into_iter())
+                        .via(Flow::uds_connect(&path))
+                        .to(Sink::collect()),
+                );
+
+                ctx.watch_result(result, expect_value);
+            }
+        }
+    }
+
+    assert!(ActorSystem::new().spawn(TestReaper).is_ok());
+}
+
+#[test]
+fn test_uds_connect_deferred() {
+    use std::io::ErrorKind;
+    use std::time::Duration;
+
+    // no connection is made until the flow is run
+
+    let path = socket_path("connect-deferred");
+    let listener = UnixListener::bind(&path).unwrap();
+
+    listener.set_nonblocking(true).unwrap();
+
+    let flow: Flow<Vec<u8>, Vec<u8>> = Flow::uds_connect(&path);
+
+    thread::sleep(Duration::from_millis(50));
+
+    assert_eq!(listener.accept().unwrap_err().kind(), ErrorKind::WouldBlock);
+
+    drop(flow);
+
+    let _ = fs::remove_file(&path);
+}
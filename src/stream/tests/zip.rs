@@ -0,0 +1,57 @@
+use super::assert_stream;
+use crate::stream::{FanInCompletion, Flow, Sink, Source};
+
+#[test]
+fn test_zip() {
+    let source =
+        Source::iterator(1..=3).zip(Source::iterator(vec!["a", "b", "c", "d"].into_iter()));
+
+    assert_stream(source.to(Sink::collect()), |values: Vec<(usize, &str)>| {
+        assert_eq!(values, vec![(1, "a"), (2, "b"), (3, "c")]);
+    });
+}
+
+#[test]
+fn test_zip_with() {
+    // the repeated source never completes, so this completes
+    // because the other one did
+
+    let source = Source::iterator(1..=5).zip_with(Source::repeat(10), |a, b| a * b);
+
+    assert_stream(source.to(Sink::collect()), |values: Vec<usize>| {
+        assert_eq!(values, vec![10, 20, 30, 40, 50]);
+    });
+}
+
+#[test]
+fn test_zip_flow() {
+    let flow = Flow::new()
+        .map(|n: usize| n * 2)
+        .zip(Source::iterator(4..=10));
+
+    assert_stream(
+        Source::iterator(1..=3).via(flow).to(Sink::collect()),
+        |values: Vec<(usize, usize)>| {
+            assert_eq!(values, vec![(2, 4), (4, 5), (6, 6)]);
+        },
+    );
+}
+
+#[test]
+fn test_zip_latest() {
+    let source = Source::single(1).zip_latest(Source::iterator(1..=3), FanInCompletion::Lazy);
+
+    assert_stream(source.to(Sink::collect()), |values: Vec<(usize, usize)>| {
+        assert!(values.iter().all(|(left, _)| *left == 1));
+        assert_eq!(values.last(), Some(&(1, 3)));
+    });
+}
+
+#[test]
+fn test_zip_latest_eager() {
+    let source = Source::iterator(1..=3).zip_latest(Source::repeat(0), FanInCompletion::Eager);
+
+    assert_stream(source.to(Sink::collect()), |values: Vec<(usize, usize)>| {
+        assert!(values.iter().all(|(_, right)| *right == 0));
+    });
+}
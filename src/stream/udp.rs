@@ -0,0 +1,177 @@
+use crate::stream::datagram::{DatagramErrorPolicy, DatagramOptions, MAX_DATAGRAM_SIZE};
+use mio::net::UdpSocket;
+use socket2::SockRef;
+use std::io::Result as IoResult;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+enum MulticastGroup {
+    V4(Ipv4Addr, Ipv4Addr),
+    V6(Ipv6Addr, u32),
+}
+
+/// Configures the UDP socket that is used by `Source::udp_with_options`
+/// and `Sink::udp_with_options`.
+///
+/// Socket options are applied when the stage starts, and since the stage
+/// operates on a clone of the supplied socket, they also apply to the
+/// original. Multicast groups are joined when the stage starts, and left
+/// when it stops.
+pub struct UdpOptions {
+    max_datagram_size: usize,
+    error_policy: DatagramErrorPolicy,
+    recv_buffer_size: Option<usize>,
+    send_buffer_size: Option<usize>,
+    broadcast: Option<bool>,
+    ttl: Option<u32>,
+    multicast_ttl_v4: Option<u32>,
+    multicast_groups: Vec<MulticastGroup>,
+}
+
+impl UdpOptions {
+    /// Creates options with a maximum datagram size of `MAX_DATAGRAM_SIZE`,
+    /// so each source allocates a buffer of that size, and an error
+    /// policy of `LogAndContinue`.
+    pub fn new() -> Self {
+        Self {
+            max_datagram_size: MAX_DATAGRAM_SIZE,
+            error_policy: DatagramErrorPolicy::LogAndContinue,
+            recv_buffer_size: None,
+            send_buffer_size: None,
+            broadcast: None,
+            ttl: None,
+            multicast_ttl_v4: None,
+            multicast_groups: Vec::new(),
+        }
+    }
+
+    /// The largest datagram that can be received. Larger datagrams are
+    /// truncated by the operating system, which is detected and handled
+    /// according to the error policy.
+    pub fn with_max_datagram_size(mut self, size: usize) -> Self {
+        self.max_datagram_size = size;
+        self
+    }
+
+    pub fn with_error_policy(mut self, policy: DatagramErrorPolicy) -> Self {
+        self.error_policy = policy;
+        self
+    }
+
+    /// Set the size of the socket's receive buffer, i.e. `SO_RCVBUF`.
+    pub fn with_recv_buffer_size(mut self, size: usize) -> Self {
+        self.recv_buffer_size = Some(size);
+        self
+    }
+
+    /// Set the size of the socket's send buffer, i.e. `SO_SNDBUF`.
+    pub fn with_send_buffer_size(mut self, size: usize) -> Self {
+        self.send_buffer_size = Some(size);
+        self
+    }
+
+    pub fn with_broadcast(mut self, broadcast: bool) -> Self {
+        self.broadcast = Some(broadcast);
+        self
+    }
+
+    pub fn with_ttl(mut self, ttl: u32) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    pub fn with_multicast_ttl_v4(mut self, ttl: u32) -> Self {
+        self.multicast_ttl_v4 = Some(ttl);
+        self
+    }
+
+    /// Join the IPv4 multicast group `multiaddr` on the interface with
+    /// the address `interface`, or any suitable interface if it is
+    /// unspecified.
+    pub fn with_multicast_group_v4(mut self, multiaddr: Ipv4Addr, interface: Ipv4Addr) -> Self {
+        self.multicast_groups
+            .push(MulticastGroup::V4(multiaddr, interface));
+        self
+    }
+
+    /// Join the IPv6 multicast group `multiaddr` on the interface with
+    /// the index `interface`, or any suitable interface if it is 0.
+    pub fn with_multicast_group_v6(mut self, multiaddr: Ipv6Addr, interface: u32) -> Self {
+        self.multicast_groups
+            .push(MulticastGroup::V6(multiaddr, interface));
+        self
+    }
+}
+
+impl DatagramOptions<UdpSocket> for UdpOptions {
+    fn max_datagram_size(&self) -> usize {
+        self.max_datagram_size
+    }
+
+    fn error_policy(&self) -> DatagramErrorPolicy {
+        self.error_policy
+    }
+
+    /// Apply the socket options and join the multicast groups.
+    fn apply(&self, socket: &UdpSocket) -> IoResult<()> {
+        let socket = SockRef::from(socket);
+
+        if let Some(size) = self.recv_buffer_size {
+            socket.set_recv_buffer_size(size)?;
+        }
+
+        if let Some(size) = self.send_buffer_size {
+            socket.set_send_buffer_size(size)?;
+        }
+
+        if let Some(broadcast) = self.broadcast {
+            socket.set_broadcast(broadcast)?;
+        }
+
+        if let Some(ttl) = self.ttl {
+            socket.set_ttl(ttl)?;
+        }
+
+        if let Some(ttl) = self.multicast_ttl_v4 {
+            socket.set_multicast_ttl_v4(ttl)?;
+        }
+
+        for group in self.multicast_groups.iter() {
+            match *group {
+                MulticastGroup::V4(ref multiaddr, ref interface) => {
+                    socket.join_multicast_v4(multiaddr, interface)?
+                }
+
+                MulticastGroup::V6(ref multiaddr, interface) => {
+                    socket.join_multicast_v6(multiaddr, interface)?
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Leave the multicast groups that were joined by `apply`.
+    fn unapply(&self, socket: &UdpSocket) -> IoResult<()> {
+        let socket = SockRef::from(socket);
+
+        for group in self.multicast_groups.iter() {
+            match *group {
+                MulticastGroup::V4(ref multiaddr, ref interface) => {
+                    socket.leave_multicast_v4(multiaddr, interface)?
+                }
+
+                MulticastGroup::V6(ref multiaddr, interface) => {
+                    socket.leave_multicast_v6(multiaddr, interface)?
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for UdpOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,3 +1,6 @@
+use std::future::Future;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
 use std::thread;
 use std::time::{Duration, Instant};
 
@@ -21,3 +24,35 @@ pub fn eventually<F: FnMut() -> bool>(limit: Duration, mut f: F) {
         thread::sleep(Duration::from_millis(30));
     }
 }
+
+/// Block the current thread until the provided future
+/// has completed, returning its output.
+///
+/// This is useful for testing code that returns futures,
+/// e.g. a `JoinHandle` from `Dispatcher::spawn_future`.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    struct ThreadWaker(thread::Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let mut future = Box::pin(future);
+
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut context = Context::from_waker(&waker);
+
+    loop {
+        match future.as_mut().poll(&mut context) {
+            Poll::Ready(output) => {
+                return output;
+            }
+
+            Poll::Pending => {
+                thread::park();
+            }
+        }
+    }
+}